@@ -1,24 +1,22 @@
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime};
 use ina219::address::Address;
 use ina219::SyncIna219;
 use rppal::i2c::I2c;
 use chrono::Utc;
-use std::net::TcpStream;
 use chrono::{DateTime, Local};
+use log::{debug, error, info};
 
-use crate::{database::Database, control::{Control, socket::{self, notifications::APINotification, MAX_CONNECTED}}, sqlite, network::api, screen::CharacterDisplay, notifier};
+use crate::{database::{self, Database}, objects::system_event, control::{Control, socket::notifications::APINotification}, events::{self, EventBus}, sqlite, network::api, screen::CharacterDisplay, notifier};
 
 pub struct Checker {
     keepalive: Arc<Mutex<bool>>,
     control: Arc<Mutex<Control>>,
     screen: Arc<Mutex<Option<CharacterDisplay>>>,
     notifier: notifier::Notifier,
-    control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
+    event_bus: EventBus,
     sqlite: Arc<Mutex<sqlite::SQLite>>,
-    last_low: u64,
-    last_crit: u64,
 }
 
 impl Checker {
@@ -27,7 +25,7 @@ impl Checker {
         control: Arc<Mutex<Control>>,
         screen: Arc<Mutex<Option<CharacterDisplay>>>,
         notifier: notifier::Notifier,
-        control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
+        event_bus: EventBus,
         sqlite: Arc<Mutex<sqlite::SQLite>>,
     ) -> Self {
         Self {
@@ -35,34 +33,32 @@ impl Checker {
             control,
             screen,
             notifier,
-            control_sockets,
+            event_bus,
             sqlite,
-            last_low: 0,
-            last_crit: 0,
         }
     }
 
     pub fn run(&mut self) {
-        println!("Starting battery checker thread.");
+        info!("Starting battery checker thread.");
         if let Ok(device) = I2c::with_bus(1) {
-            println!("I2C initialized.");
+            info!("I2C initialized.");
             if let Ok(mut ina) = SyncIna219::new(device, Address::from_byte(0x40).unwrap()) {
-                println!("ina219 initiailized.");
+                info!("ina219 initiailized.");
                 if let Ok(config) = ina.configuration() {
-                    println!("Configuration pulled.");
+                    debug!("Configuration pulled.");
                     if let Some(time) = config.conversion_time_us() {
-                        println!("Conversion time gathered.");
+                        debug!("Conversion time gathered.");
                         let conversion_time = Duration::from_micros(time as u64);
                         thread::sleep(conversion_time);
-                        println!("Getting measurement.");
+                        debug!("Getting measurement.");
                         if let Ok(Some(_)) = ina.next_measurement() {
                             if let Ok(voltage) = ina.bus_voltage() {
                                 self.set_percentage(voltage.voltage_mv());
                             } else {
-                                println!("Error checking voltage on startup.");
+                                error!("Error checking voltage on startup.");
                             }
                         } else {
-                            println!("Error checking for measurement on startup.");
+                            error!("Error checking for measurement on startup.");
                         }
                         
                         loop {
@@ -71,7 +67,7 @@ impl Checker {
                                 if let Ok(voltage) = ina.bus_voltage() {
                                     self.set_percentage(voltage.voltage_mv());
                                 } else {
-                                    println!("Error checking voltage.");
+                                    error!("Error checking voltage.");
                                 }
                             }
                             thread::sleep(Duration::from_secs(5));
@@ -82,16 +78,16 @@ impl Checker {
                             }
                         }
                     } else {
-                        println!("Error getting conversion time for ina219 device.");
+                        error!("Error getting conversion time for ina219 device.");
                     }
                 } else {
-                    println!("Error setting configuration for ina219 device.");
+                    error!("Error setting configuration for ina219 device.");
                 }
             } else {
-                println!("Error connecting to ina219 device.")
+                error!("Error connecting to ina219 device.")
             }
         } else {
-            println!("Error initializing i2c for ina219 device.")
+            error!("Error initializing i2c for ina219 device.")
         }
     }
 
@@ -136,24 +132,33 @@ impl Checker {
         } else {
             0
         };
-        let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
-            Ok(t) => { t.as_secs() }
-            Err(_) => { 0 }
-        };
+        let mut level: Option<(&str, &str)> = None;
         if let Ok(mut control) = self.control.lock() {
-            if control.battery > 30 && percentage <= 30 && now > self.last_low + 60 {
+            // the notifier's rules keep these from repeating while the reading bounces around
+            if control.battery > 30 && percentage <= 30 {
+                level = Some((system_event::SEVERITY_WARNING, "low"));
                 let date_time: DateTime<Local> = SystemTime::now().into();
-                self.notifier.send_notification(notifier::Notification::BatteryLow, format!("{}", date_time.format("%Y/%m/%d %T")));
-                self.send_notification(APINotification::BatteryLow);
-                self.last_low = now;
-            } else if control.battery > 15 && percentage <= 15 && now > self.last_crit + 60 {
+                if self.notifier.send_notification(notifier::Notification::BatteryLow, format!("{}", date_time.format("%Y/%m/%d %T"))) {
+                    self.send_notification(APINotification::BatteryLow);
+                }
+            } else if control.battery > 15 && percentage <= 15 {
+                level = Some((system_event::SEVERITY_ERROR, "critical"));
                 let date_time: DateTime<Local> = SystemTime::now().into();
-                self.notifier.send_notification(notifier::Notification::BatteryCritical, format!("{}", date_time.format("%Y/%m/%d %T")));
-                self.send_notification(APINotification::BatteryCritical);
-                self.last_crit = now;
+                if self.notifier.send_notification(notifier::Notification::BatteryCritical, format!("{}", date_time.format("%Y/%m/%d %T"))) {
+                    self.send_notification(APINotification::BatteryCritical);
+                }
             }
             control.battery = percentage;
         }
+        if let Some((severity, name)) = level {
+            database::log_event(
+                &self.sqlite,
+                severity,
+                system_event::SOURCE_BATTERY,
+                system_event::SYSTEM_EVENT_BATTERY,
+                serde_json::json!({ "percentage": percentage, "voltage": voltage, "level": name }),
+            );
+        }
         if let Ok(mut screen_opt) = self.screen.lock() {
             if let Some(screen) = &mut *screen_opt {
                 screen.update_battery();
@@ -163,14 +168,11 @@ impl Checker {
 
     fn send_notification(&self, notification: APINotification) {
         let time = Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string();
-        if let Ok(c_socks) = self.control_sockets.lock() {
-            println!("notifying connected sockets");
-            for sock in c_socks.iter() {
-                if let Some(s) = sock {
-                    _ = socket::write_notification(&s, &notification, &time);
-                }
-            }
-        }
+        debug!("notifying connected sockets");
+        self.event_bus.publish(events::Event::Notification {
+            notification: notification.clone(),
+            time,
+        });
         if let Ok(control) = self.control.lock() {
             if control.auto_remote {
                 if let Ok(sq) = self.sqlite.lock() {
@@ -184,7 +186,7 @@ impl Checker {
                             }
                         },
                         Err(e) => {
-                            println!("Error trying to get apis: {e}");
+                            error!("Error trying to get apis: {e}");
                         }
                     }
                 }
@@ -4,6 +4,8 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 #[cfg(target_os = "linux")]
 use rppal::gpio::{Gpio, Trigger};
+#[cfg(target_os = "linux")]
+use log::{debug, info, warn};
 
 #[cfg(target_os = "linux")]
 use crate::screen::ButtonPress;
@@ -27,7 +29,7 @@ impl Buttons {
         _screen: Arc<Mutex<Option<CharacterDisplay>>>,
         keepalive: Arc<Mutex<bool>>
     ) -> Self {
-        println!("Checking if there are buttons we should be reading from.");
+        debug!("Checking if there are buttons we should be reading from.");
         let mut up_button: u8 = 0;
         if let Ok(btn) = std::env::var("PORTAL_UP_BUTTON") {
             up_button = btn.parse().unwrap_or(0);
@@ -88,11 +90,11 @@ impl Buttons {
                 &right,
                 &enter,
             ];
-            println!("Entering button thread loop.");
+            info!("Entering button thread loop.");
             loop {
                 if let Ok(keepalive) = self.keepalive.try_lock() {
                     if *keepalive == false {
-                        println!("Button thread stopping.");
+                        info!("Button thread stopping.");
                         break;
                     }
                 }
@@ -113,7 +115,7 @@ impl Buttons {
                                     } else if p == self.enter_button {
                                         screen.register_button(ButtonPress::Enter);
                                     } else {
-                                        println!("Unknown button pressed. GPIO {p}");
+                                        warn!("Unknown button pressed. GPIO {p}");
                                     }
                                 }
                             }
@@ -123,9 +125,9 @@ impl Buttons {
                 }
             }
         } else {
-            println!("Unable to get buttons.");
+            warn!("Unable to get buttons.");
         }
-        println!("Button thread terminated.");
+        info!("Button thread terminated.");
     }
 
     pub fn stop(&self) {
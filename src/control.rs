@@ -1,5 +1,6 @@
 use crate::{database::{self, sqlite, DBError, Database}, defaults, objects::setting, sound_board::{SoundBoard, Voice}};
 use rand::prelude::random;
+use log::info;
 
 pub mod socket;
 pub mod zero_conf;
@@ -19,6 +20,41 @@ pub const SETTING_NTFY_USER: &str = "SETTING_NTFY_USER";
 pub const SETTING_NTFY_PASS: &str = "SETTING_NTFY_PASS";
 pub const SETTING_NTFY_TOPIC: &str = "SETTING_NTFY_TOPIC";
 pub const SETTING_ENABLE_NTFY: &str = "SETTING_ENABLE_NTFY";
+pub const SETTING_MQTT_URL: &str = "SETTING_MQTT_URL";
+pub const SETTING_MQTT_USER: &str = "SETTING_MQTT_USER";
+pub const SETTING_MQTT_PASS: &str = "SETTING_MQTT_PASS";
+pub const SETTING_MQTT_TOPIC: &str = "SETTING_MQTT_TOPIC";
+pub const SETTING_MQTT_QOS: &str = "SETTING_MQTT_QOS";
+pub const SETTING_ENABLE_MQTT: &str = "SETTING_ENABLE_MQTT";
+pub const SETTING_ENABLE_OUTPUT: &str = "SETTING_ENABLE_OUTPUT";
+pub const SETTING_OUTPUT_PORT: &str = "SETTING_OUTPUT_PORT";
+pub const SETTING_OUTPUT_FORMAT: &str = "SETTING_OUTPUT_FORMAT";
+pub const SETTING_ENABLE_RELAY: &str = "SETTING_ENABLE_RELAY";
+pub const SETTING_RELAY_PORT: &str = "SETTING_RELAY_PORT";
+pub const SETTING_UPLOAD_BATCH_SIZE: &str = "SETTING_UPLOAD_BATCH_SIZE";
+pub const SETTING_CONNECTIVITY_CHECK_URL: &str = "SETTING_CONNECTIVITY_CHECK_URL";
+pub const SETTING_ENABLE_PARTICIPANT_SYNC: &str = "SETTING_ENABLE_PARTICIPANT_SYNC";
+pub const SETTING_PARTICIPANT_SYNC_API: &str = "SETTING_PARTICIPANT_SYNC_API";
+pub const SETTING_PARTICIPANT_SYNC_SLUG: &str = "SETTING_PARTICIPANT_SYNC_SLUG";
+pub const SETTING_PARTICIPANT_SYNC_YEAR: &str = "SETTING_PARTICIPANT_SYNC_YEAR";
+pub const SETTING_PARTICIPANT_SYNC_INTERVAL: &str = "SETTING_PARTICIPANT_SYNC_INTERVAL";
+pub const SETTING_HTTP_PROXY_URL: &str = "SETTING_HTTP_PROXY_URL";
+pub const SETTING_HTTP_PROXY_USER: &str = "SETTING_HTTP_PROXY_USER";
+pub const SETTING_HTTP_PROXY_PASS: &str = "SETTING_HTTP_PROXY_PASS";
+pub const SETTING_HTTP_CA_BUNDLE: &str = "SETTING_HTTP_CA_BUNDLE";
+pub const SETTING_HTTP_TIMEOUT: &str = "SETTING_HTTP_TIMEOUT";
+pub const SETTING_HTTP_CONNECT_TIMEOUT: &str = "SETTING_HTTP_CONNECT_TIMEOUT";
+pub const SETTING_ENABLE_SIGHTING_UPLOAD: &str = "SETTING_ENABLE_SIGHTING_UPLOAD";
+pub const SETTING_SILENT_ANTENNA_SECONDS: &str = "SETTING_SILENT_ANTENNA_SECONDS";
+pub const SETTING_LOG_LEVEL: &str = "SETTING_LOG_LEVEL";
+pub const SETTING_ENABLE_SMS: &str = "SETTING_ENABLE_SMS";
+pub const SETTING_SMS_URL: &str = "SETTING_SMS_URL";
+pub const SETTING_SMS_TOKEN: &str = "SETTING_SMS_TOKEN";
+pub const SETTING_SMS_FROM: &str = "SETTING_SMS_FROM";
+pub const SETTING_SMS_TEMPLATE: &str = "SETTING_SMS_TEMPLATE";
+pub const SETTING_SMS_INTERVAL: &str = "SETTING_SMS_INTERVAL";
+pub const SETTING_SMS_HOURLY_LIMIT: &str = "SETTING_SMS_HOURLY_LIMIT";
+pub const SETTING_UNKNOWN_CHIP_POLICY: &str = "SETTING_UNKNOWN_CHIP_POLICY";
 
 pub struct Control {
     pub name: String,
@@ -35,6 +71,41 @@ pub struct Control {
     pub ntfy_pass: String,
     pub ntfy_topic: String,
     pub enable_ntfy: bool,
+    pub mqtt_url: String,
+    pub mqtt_user: String,
+    pub mqtt_pass: String,
+    pub mqtt_topic: String,
+    pub mqtt_qos: u8,
+    pub enable_mqtt: bool,
+    pub enable_output: bool,
+    pub output_port: u16,
+    pub output_format: String,
+    pub enable_relay: bool,
+    pub relay_port: u16,
+    pub upload_batch_size: usize,
+    pub connectivity_check_url: String,
+    pub enable_participant_sync: bool,
+    pub participant_sync_api: i64,
+    pub participant_sync_slug: String,
+    pub participant_sync_year: String,
+    pub participant_sync_interval: u64,
+    pub http_proxy_url: String,
+    pub http_proxy_user: String,
+    pub http_proxy_pass: String,
+    pub http_ca_bundle: String,
+    pub http_timeout: u64,
+    pub http_connect_timeout: u64,
+    pub enable_sighting_upload: bool,
+    pub silent_antenna_seconds: u64,
+    pub log_level: String,
+    pub enable_sms: bool,
+    pub sms_url: String,
+    pub sms_token: String,
+    pub sms_from: String,
+    pub sms_template: String,
+    pub sms_interval: u64,
+    pub sms_hourly_limit: u32,
+    pub unknown_chip_policy: String,
     pub battery: u8,
 }
 
@@ -79,6 +150,111 @@ impl Control {
         if self.enable_ntfy != new_control.enable_ntfy {
             self.enable_ntfy = new_control.enable_ntfy
         }
+        if self.mqtt_url != new_control.mqtt_url {
+            self.mqtt_url = new_control.mqtt_url
+        }
+        if self.mqtt_user != new_control.mqtt_user {
+            self.mqtt_user = new_control.mqtt_user
+        }
+        if self.mqtt_pass != new_control.mqtt_pass {
+            self.mqtt_pass = new_control.mqtt_pass
+        }
+        if self.mqtt_topic != new_control.mqtt_topic {
+            self.mqtt_topic = new_control.mqtt_topic
+        }
+        if self.mqtt_qos != new_control.mqtt_qos {
+            self.mqtt_qos = new_control.mqtt_qos
+        }
+        if self.enable_mqtt != new_control.enable_mqtt {
+            self.enable_mqtt = new_control.enable_mqtt
+        }
+        if self.enable_output != new_control.enable_output {
+            self.enable_output = new_control.enable_output
+        }
+        if self.output_port != new_control.output_port {
+            self.output_port = new_control.output_port
+        }
+        if self.output_format != new_control.output_format {
+            self.output_format = new_control.output_format
+        }
+        if self.enable_relay != new_control.enable_relay {
+            self.enable_relay = new_control.enable_relay
+        }
+        if self.relay_port != new_control.relay_port {
+            self.relay_port = new_control.relay_port
+        }
+        if self.upload_batch_size != new_control.upload_batch_size {
+            self.upload_batch_size = new_control.upload_batch_size
+        }
+        if self.connectivity_check_url != new_control.connectivity_check_url {
+            self.connectivity_check_url = new_control.connectivity_check_url
+        }
+        if self.enable_participant_sync != new_control.enable_participant_sync {
+            self.enable_participant_sync = new_control.enable_participant_sync
+        }
+        if self.participant_sync_api != new_control.participant_sync_api {
+            self.participant_sync_api = new_control.participant_sync_api
+        }
+        if self.participant_sync_slug != new_control.participant_sync_slug {
+            self.participant_sync_slug = new_control.participant_sync_slug
+        }
+        if self.participant_sync_year != new_control.participant_sync_year {
+            self.participant_sync_year = new_control.participant_sync_year
+        }
+        if self.participant_sync_interval != new_control.participant_sync_interval {
+            self.participant_sync_interval = new_control.participant_sync_interval
+        }
+        if self.http_proxy_url != new_control.http_proxy_url {
+            self.http_proxy_url = new_control.http_proxy_url
+        }
+        if self.http_proxy_user != new_control.http_proxy_user {
+            self.http_proxy_user = new_control.http_proxy_user
+        }
+        if self.http_proxy_pass != new_control.http_proxy_pass {
+            self.http_proxy_pass = new_control.http_proxy_pass
+        }
+        if self.http_ca_bundle != new_control.http_ca_bundle {
+            self.http_ca_bundle = new_control.http_ca_bundle
+        }
+        if self.http_timeout != new_control.http_timeout {
+            self.http_timeout = new_control.http_timeout
+        }
+        if self.http_connect_timeout != new_control.http_connect_timeout {
+            self.http_connect_timeout = new_control.http_connect_timeout
+        }
+        if self.enable_sighting_upload != new_control.enable_sighting_upload {
+            self.enable_sighting_upload = new_control.enable_sighting_upload
+        }
+        if self.silent_antenna_seconds != new_control.silent_antenna_seconds {
+            self.silent_antenna_seconds = new_control.silent_antenna_seconds
+        }
+        if self.log_level != new_control.log_level {
+            self.log_level = new_control.log_level
+        }
+        if self.enable_sms != new_control.enable_sms {
+            self.enable_sms = new_control.enable_sms
+        }
+        if self.sms_url != new_control.sms_url {
+            self.sms_url = new_control.sms_url
+        }
+        if self.sms_token != new_control.sms_token {
+            self.sms_token = new_control.sms_token
+        }
+        if self.sms_from != new_control.sms_from {
+            self.sms_from = new_control.sms_from
+        }
+        if self.sms_template != new_control.sms_template {
+            self.sms_template = new_control.sms_template
+        }
+        if self.sms_interval != new_control.sms_interval {
+            self.sms_interval = new_control.sms_interval
+        }
+        if self.sms_hourly_limit != new_control.sms_hourly_limit {
+            self.sms_hourly_limit = new_control.sms_hourly_limit
+        }
+        if self.unknown_chip_policy != new_control.unknown_chip_policy {
+            self.unknown_chip_policy = new_control.unknown_chip_policy
+        }
         if self.sound_board.get_voice() != new_control.sound_board.get_voice() {
             return self.sound_board.change_voice(new_control.sound_board.get_voice())
         }
@@ -101,6 +277,41 @@ impl Control {
             ntfy_pass: String::from(""),
             ntfy_topic: String::from(""),
             enable_ntfy: defaults::DEFAULT_ENABLE_NTFY,
+            mqtt_url: String::from(""),
+            mqtt_user: String::from(""),
+            mqtt_pass: String::from(""),
+            mqtt_topic: String::from(defaults::DEFAULT_MQTT_TOPIC),
+            mqtt_qos: defaults::DEFAULT_MQTT_QOS,
+            enable_mqtt: defaults::DEFAULT_ENABLE_MQTT,
+            enable_output: defaults::DEFAULT_ENABLE_OUTPUT,
+            output_port: defaults::DEFAULT_OUTPUT_PORT,
+            output_format: String::from(defaults::DEFAULT_OUTPUT_FORMAT),
+            enable_relay: defaults::DEFAULT_ENABLE_RELAY,
+            relay_port: defaults::DEFAULT_RELAY_PORT,
+            upload_batch_size: defaults::DEFAULT_UPLOAD_BATCH_SIZE,
+            connectivity_check_url: String::from(defaults::DEFAULT_CONNECTIVITY_CHECK_URL),
+            enable_participant_sync: defaults::DEFAULT_ENABLE_PARTICIPANT_SYNC,
+            participant_sync_api: defaults::DEFAULT_PARTICIPANT_SYNC_API,
+            participant_sync_slug: String::from(defaults::DEFAULT_PARTICIPANT_SYNC_SLUG),
+            participant_sync_year: String::from(defaults::DEFAULT_PARTICIPANT_SYNC_YEAR),
+            participant_sync_interval: defaults::DEFAULT_PARTICIPANT_SYNC_INTERVAL,
+            http_proxy_url: String::from(defaults::DEFAULT_HTTP_PROXY_URL),
+            http_proxy_user: String::from(defaults::DEFAULT_HTTP_PROXY_USER),
+            http_proxy_pass: String::from(defaults::DEFAULT_HTTP_PROXY_PASS),
+            http_ca_bundle: String::from(defaults::DEFAULT_HTTP_CA_BUNDLE),
+            http_timeout: defaults::DEFAULT_HTTP_TIMEOUT,
+            http_connect_timeout: defaults::DEFAULT_HTTP_CONNECT_TIMEOUT,
+            enable_sighting_upload: defaults::DEFAULT_ENABLE_SIGHTING_UPLOAD,
+            silent_antenna_seconds: defaults::DEFAULT_SILENT_ANTENNA_SECONDS,
+            log_level: String::from(defaults::DEFAULT_LOG_LEVEL),
+            enable_sms: defaults::DEFAULT_ENABLE_SMS,
+            sms_url: String::from(""),
+            sms_token: String::from(""),
+            sms_from: String::from(""),
+            sms_template: String::from(defaults::DEFAULT_SMS_TEMPLATE),
+            sms_interval: defaults::DEFAULT_SMS_INTERVAL,
+            sms_hourly_limit: defaults::DEFAULT_SMS_HOURLY_LIMIT,
+            unknown_chip_policy: String::from(defaults::DEFAULT_UNKNOWN_CHIP_POLICY),
             battery: 0
         };
         match sqlite.get_setting(SETTING_SIGHTING_PERIOD) {
@@ -133,7 +344,7 @@ impl Control {
                 )) {
                     Ok(s) => {
                         output.name = String::from(s.value());
-                        println!("Name successfully set to '{}'.", s.value());
+                        info!("Name successfully set to '{}'.", s.value());
                     }
                     Err(e) => return Err(e)
                 }
@@ -153,7 +364,7 @@ impl Control {
                 )) {
                     Ok(s) => {
                         output.chip_type = String::from(s.value());
-                        println!("Chip type successfully set to '{}'.", s.value());
+                        info!("Chip type successfully set to '{}'.", s.value());
                     },
                     Err(e) => return Err(e)
                 }
@@ -175,7 +386,7 @@ impl Control {
                     Ok(s) => {
                         let rw: u8 = s.value().parse().unwrap();
                         output.read_window = rw;
-                        println!("Read window successfully set to '{}'.", s.value());
+                        info!("Read window successfully set to '{}'.", s.value());
                     },
                     Err(e) => return Err(e)
                 }
@@ -197,7 +408,7 @@ impl Control {
                     Ok(s) => {
                         let ps: bool = s.value().eq_ignore_ascii_case("true");
                         output.play_sound = ps;
-                        println!("Play sound successfully set to '{}'.", s.value());
+                        info!("Play sound successfully set to '{}'.", s.value());
                     },
                     Err(e) => return Err(e)
                 }
@@ -219,7 +430,7 @@ impl Control {
                     Ok(s) => {
                         let vol: f32 = s.value().parse().unwrap();
                         output.volume = vol;
-                        println!("Volume successfully set to '{}'.", s.value());
+                        info!("Volume successfully set to '{}'.", s.value());
                     },
                     Err(e) => return Err(e)
                 }
@@ -239,7 +450,7 @@ impl Control {
                 )) {
                     Ok(s) => {
                         if let Ok(_) = output.sound_board.change_voice(Voice::from_str(s.value())){
-                            println!("Voice successfully set as '{}'.", s.value());
+                            info!("Voice successfully set as '{}'.", s.value());
                         }
                     },
                     Err(e) => return Err(e)
@@ -262,7 +473,7 @@ impl Control {
                     Ok(s) => {
                         let ar: bool = s.value().eq_ignore_ascii_case("true");
                         output.auto_remote = ar;
-                        println!("Auto remote successfully set to '{}'.", s.value());
+                        info!("Auto remote successfully set to '{}'.", s.value());
                     },
                     Err(e) => return Err(e)
                 }
@@ -283,7 +494,7 @@ impl Control {
                         Ok(s) => {
                             let up: u64 = s.value().parse().unwrap();
                             output.upload_interval = up;
-                            println!("Upload interval successfully set to '{}'.", s.value());
+                            info!("Upload interval successfully set to '{}'.", s.value());
                         },
                         Err(e) => return Err(e)
                     }
@@ -303,7 +514,7 @@ impl Control {
                 )) {
                     Ok(s) => {
                         output.ntfy_url = String::from(s.value());
-                        println!("NTFY url successfully set to '{}'.", s.value());
+                        info!("NTFY url successfully set to '{}'.", s.value());
                     },
                     Err(e) => return Err(e)
                 }
@@ -323,7 +534,7 @@ impl Control {
                 )) {
                     Ok(s) => {
                         output.ntfy_user = String::from(s.value());
-                        println!("NTFY user successfully set to '{}'.", s.value());
+                        info!("NTFY user successfully set to '{}'.", s.value());
                     },
                     Err(e) => return Err(e)
                 }
@@ -343,7 +554,7 @@ impl Control {
                 )) {
                     Ok(s) => {
                         output.ntfy_pass = String::from(s.value());
-                        println!("NTFY pass successfully set to '{}'.", s.value());
+                        info!("NTFY pass successfully set to '{}'.", s.value());
                     },
                     Err(e) => return Err(e)
                 }
@@ -363,7 +574,7 @@ impl Control {
                 )) {
                     Ok(s) => {
                         output.ntfy_topic = String::from(s.value());
-                        println!("NTFY topic successfully set to '{}'.", s.value());
+                        info!("NTFY topic successfully set to '{}'.", s.value());
                     },
                     Err(e) => return Err(e)
                 }
@@ -385,7 +596,742 @@ impl Control {
                     Ok(s) => {
                         let e_ntfy: bool = s.value().eq_ignore_ascii_case("true");
                         output.enable_ntfy = e_ntfy;
-                        println!("Enable ntfy successfully set to '{}'.", s.value());
+                        info!("Enable ntfy successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_MQTT_URL) {
+            Ok(s) => {
+                output.mqtt_url = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_MQTT_URL),
+                    String::new(),
+                )) {
+                    Ok(s) => {
+                        output.mqtt_url = String::from(s.value());
+                        info!("MQTT url successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            }
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_MQTT_USER) {
+            Ok(s) => {
+                output.mqtt_user = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_MQTT_USER),
+                    String::new(),
+                )) {
+                    Ok(s) => {
+                        output.mqtt_user = String::from(s.value());
+                        info!("MQTT user successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            }
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_MQTT_PASS) {
+            Ok(s) => {
+                output.mqtt_pass = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_MQTT_PASS),
+                    String::new(),
+                )) {
+                    Ok(s) => {
+                        output.mqtt_pass = String::from(s.value());
+                        info!("MQTT pass successfully set.");
+                    },
+                    Err(e) => return Err(e)
+                }
+            }
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_MQTT_TOPIC) {
+            Ok(s) => {
+                output.mqtt_topic = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_MQTT_TOPIC),
+                    String::from(defaults::DEFAULT_MQTT_TOPIC),
+                )) {
+                    Ok(s) => {
+                        output.mqtt_topic = String::from(s.value());
+                        info!("MQTT topic successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            }
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_MQTT_QOS) {
+            Ok(s) => {
+                // QoS levels above 2 don't exist
+                let qos: u8 = s.value().parse().unwrap_or(defaults::DEFAULT_MQTT_QOS);
+                output.mqtt_qos = qos.min(2);
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_MQTT_QOS),
+                    format!("{}", defaults::DEFAULT_MQTT_QOS),
+                )) {
+                    Ok(s) => {
+                        let qos: u8 = s.value().parse().unwrap_or(defaults::DEFAULT_MQTT_QOS);
+                        output.mqtt_qos = qos.min(2);
+                        info!("MQTT QoS successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            }
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_ENABLE_MQTT) {
+            Ok(s) => {
+                let e_mqtt: bool = s.value().eq_ignore_ascii_case("true");
+                output.enable_mqtt = e_mqtt;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_ENABLE_MQTT),
+                    format!("{}", defaults::DEFAULT_ENABLE_MQTT),
+                )) {
+                    Ok(s) => {
+                        let e_mqtt: bool = s.value().eq_ignore_ascii_case("true");
+                        output.enable_mqtt = e_mqtt;
+                        info!("Enable mqtt successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_ENABLE_OUTPUT) {
+            Ok(s) => {
+                let v: bool = s.value().eq_ignore_ascii_case("true");
+                output.enable_output = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_ENABLE_OUTPUT),
+                    format!("{}", defaults::DEFAULT_ENABLE_OUTPUT),
+                )) {
+                    Ok(s) => {
+                        let v: bool = s.value().eq_ignore_ascii_case("true");
+                        output.enable_output = v;
+                        info!("Enable output successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_OUTPUT_PORT) {
+            Ok(s) => {
+                let v: u16 = s.value().parse().unwrap_or(defaults::DEFAULT_OUTPUT_PORT);
+                output.output_port = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_OUTPUT_PORT),
+                    format!("{}", defaults::DEFAULT_OUTPUT_PORT),
+                )) {
+                    Ok(s) => {
+                        let v: u16 = s.value().parse().unwrap_or(defaults::DEFAULT_OUTPUT_PORT);
+                        output.output_port = v;
+                        info!("Output port successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_OUTPUT_FORMAT) {
+            Ok(s) => {
+                output.output_format = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_OUTPUT_FORMAT),
+                    String::from(defaults::DEFAULT_OUTPUT_FORMAT),
+                )) {
+                    Ok(s) => {
+                        output.output_format = String::from(s.value());
+                        info!("Output format successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_ENABLE_RELAY) {
+            Ok(s) => {
+                let v: bool = s.value().eq_ignore_ascii_case("true");
+                output.enable_relay = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_ENABLE_RELAY),
+                    format!("{}", defaults::DEFAULT_ENABLE_RELAY),
+                )) {
+                    Ok(s) => {
+                        let v: bool = s.value().eq_ignore_ascii_case("true");
+                        output.enable_relay = v;
+                        info!("Enable relay successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_RELAY_PORT) {
+            Ok(s) => {
+                let v: u16 = s.value().parse().unwrap_or(defaults::DEFAULT_RELAY_PORT);
+                output.relay_port = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_RELAY_PORT),
+                    format!("{}", defaults::DEFAULT_RELAY_PORT),
+                )) {
+                    Ok(s) => {
+                        let v: u16 = s.value().parse().unwrap_or(defaults::DEFAULT_RELAY_PORT);
+                        output.relay_port = v;
+                        info!("Relay port successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_UPLOAD_BATCH_SIZE) {
+            Ok(s) => {
+                let v: usize = s.value().parse().unwrap_or(defaults::DEFAULT_UPLOAD_BATCH_SIZE);
+                output.upload_batch_size = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_UPLOAD_BATCH_SIZE),
+                    format!("{}", defaults::DEFAULT_UPLOAD_BATCH_SIZE),
+                )) {
+                    Ok(s) => {
+                        let v: usize = s.value().parse().unwrap_or(defaults::DEFAULT_UPLOAD_BATCH_SIZE);
+                        output.upload_batch_size = v;
+                        info!("Upload batch size successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_CONNECTIVITY_CHECK_URL) {
+            Ok(s) => {
+                output.connectivity_check_url = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_CONNECTIVITY_CHECK_URL),
+                    String::from(defaults::DEFAULT_CONNECTIVITY_CHECK_URL),
+                )) {
+                    Ok(s) => {
+                        output.connectivity_check_url = String::from(s.value());
+                        info!("Connectivity check url successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_ENABLE_PARTICIPANT_SYNC) {
+            Ok(s) => {
+                let v: bool = s.value().eq_ignore_ascii_case("true");
+                output.enable_participant_sync = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_ENABLE_PARTICIPANT_SYNC),
+                    format!("{}", defaults::DEFAULT_ENABLE_PARTICIPANT_SYNC),
+                )) {
+                    Ok(s) => {
+                        let v: bool = s.value().eq_ignore_ascii_case("true");
+                        output.enable_participant_sync = v;
+                        info!("Enable participant sync successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_PARTICIPANT_SYNC_API) {
+            Ok(s) => {
+                let v: i64 = s.value().parse().unwrap_or(defaults::DEFAULT_PARTICIPANT_SYNC_API);
+                output.participant_sync_api = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_PARTICIPANT_SYNC_API),
+                    format!("{}", defaults::DEFAULT_PARTICIPANT_SYNC_API),
+                )) {
+                    Ok(s) => {
+                        let v: i64 = s.value().parse().unwrap_or(defaults::DEFAULT_PARTICIPANT_SYNC_API);
+                        output.participant_sync_api = v;
+                        info!("Participant sync api successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_PARTICIPANT_SYNC_SLUG) {
+            Ok(s) => {
+                output.participant_sync_slug = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_PARTICIPANT_SYNC_SLUG),
+                    String::from(defaults::DEFAULT_PARTICIPANT_SYNC_SLUG),
+                )) {
+                    Ok(s) => {
+                        output.participant_sync_slug = String::from(s.value());
+                        info!("Participant sync slug successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_PARTICIPANT_SYNC_YEAR) {
+            Ok(s) => {
+                output.participant_sync_year = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_PARTICIPANT_SYNC_YEAR),
+                    String::from(defaults::DEFAULT_PARTICIPANT_SYNC_YEAR),
+                )) {
+                    Ok(s) => {
+                        output.participant_sync_year = String::from(s.value());
+                        info!("Participant sync year successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_PARTICIPANT_SYNC_INTERVAL) {
+            Ok(s) => {
+                let v: u64 = s.value().parse().unwrap_or(defaults::DEFAULT_PARTICIPANT_SYNC_INTERVAL);
+                output.participant_sync_interval = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_PARTICIPANT_SYNC_INTERVAL),
+                    format!("{}", defaults::DEFAULT_PARTICIPANT_SYNC_INTERVAL),
+                )) {
+                    Ok(s) => {
+                        let v: u64 = s.value().parse().unwrap_or(defaults::DEFAULT_PARTICIPANT_SYNC_INTERVAL);
+                        output.participant_sync_interval = v;
+                        info!("Participant sync interval successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_HTTP_PROXY_URL) {
+            Ok(s) => {
+                output.http_proxy_url = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_HTTP_PROXY_URL),
+                    String::from(defaults::DEFAULT_HTTP_PROXY_URL),
+                )) {
+                    Ok(s) => {
+                        output.http_proxy_url = String::from(s.value());
+                        info!("Http proxy url successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_HTTP_PROXY_USER) {
+            Ok(s) => {
+                output.http_proxy_user = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_HTTP_PROXY_USER),
+                    String::from(defaults::DEFAULT_HTTP_PROXY_USER),
+                )) {
+                    Ok(s) => {
+                        output.http_proxy_user = String::from(s.value());
+                        info!("Http proxy user successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_HTTP_PROXY_PASS) {
+            Ok(s) => {
+                output.http_proxy_pass = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_HTTP_PROXY_PASS),
+                    String::from(defaults::DEFAULT_HTTP_PROXY_PASS),
+                )) {
+                    Ok(s) => {
+                        output.http_proxy_pass = String::from(s.value());
+                        info!("Http proxy password successfully set.");
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_HTTP_CA_BUNDLE) {
+            Ok(s) => {
+                output.http_ca_bundle = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_HTTP_CA_BUNDLE),
+                    String::from(defaults::DEFAULT_HTTP_CA_BUNDLE),
+                )) {
+                    Ok(s) => {
+                        output.http_ca_bundle = String::from(s.value());
+                        info!("Http CA bundle successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_HTTP_TIMEOUT) {
+            Ok(s) => {
+                let v: u64 = s.value().parse().unwrap_or(defaults::DEFAULT_HTTP_TIMEOUT);
+                output.http_timeout = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_HTTP_TIMEOUT),
+                    format!("{}", defaults::DEFAULT_HTTP_TIMEOUT),
+                )) {
+                    Ok(s) => {
+                        let v: u64 = s.value().parse().unwrap_or(defaults::DEFAULT_HTTP_TIMEOUT);
+                        output.http_timeout = v;
+                        info!("Http timeout successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_HTTP_CONNECT_TIMEOUT) {
+            Ok(s) => {
+                let v: u64 = s.value().parse().unwrap_or(defaults::DEFAULT_HTTP_CONNECT_TIMEOUT);
+                output.http_connect_timeout = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_HTTP_CONNECT_TIMEOUT),
+                    format!("{}", defaults::DEFAULT_HTTP_CONNECT_TIMEOUT),
+                )) {
+                    Ok(s) => {
+                        let v: u64 = s.value().parse().unwrap_or(defaults::DEFAULT_HTTP_CONNECT_TIMEOUT);
+                        output.http_connect_timeout = v;
+                        info!("Http connect timeout successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_ENABLE_SIGHTING_UPLOAD) {
+            Ok(s) => {
+                let v: bool = s.value().eq_ignore_ascii_case("true");
+                output.enable_sighting_upload = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_ENABLE_SIGHTING_UPLOAD),
+                    format!("{}", defaults::DEFAULT_ENABLE_SIGHTING_UPLOAD),
+                )) {
+                    Ok(s) => {
+                        let v: bool = s.value().eq_ignore_ascii_case("true");
+                        output.enable_sighting_upload = v;
+                        info!("Enable sighting upload successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_SILENT_ANTENNA_SECONDS) {
+            Ok(s) => {
+                let v: u64 = s.value().parse().unwrap_or(defaults::DEFAULT_SILENT_ANTENNA_SECONDS);
+                output.silent_antenna_seconds = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_SILENT_ANTENNA_SECONDS),
+                    format!("{}", defaults::DEFAULT_SILENT_ANTENNA_SECONDS),
+                )) {
+                    Ok(s) => {
+                        let v: u64 = s.value().parse().unwrap_or(defaults::DEFAULT_SILENT_ANTENNA_SECONDS);
+                        output.silent_antenna_seconds = v;
+                        info!("Silent antenna seconds successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_LOG_LEVEL) {
+            Ok(s) => {
+                output.log_level = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_LOG_LEVEL),
+                    String::from(defaults::DEFAULT_LOG_LEVEL),
+                )) {
+                    Ok(s) => {
+                        output.log_level = String::from(s.value());
+                        info!("Log level successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_ENABLE_SMS) {
+            Ok(s) => {
+                let v: bool = s.value().eq_ignore_ascii_case("true");
+                output.enable_sms = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_ENABLE_SMS),
+                    format!("{}", defaults::DEFAULT_ENABLE_SMS),
+                )) {
+                    Ok(s) => {
+                        let v: bool = s.value().eq_ignore_ascii_case("true");
+                        output.enable_sms = v;
+                        info!("Enable SMS successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_SMS_URL) {
+            Ok(s) => {
+                output.sms_url = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_SMS_URL),
+                    String::from(""),
+                )) {
+                    Ok(s) => {
+                        output.sms_url = String::from(s.value());
+                        info!("SMS url successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_SMS_TOKEN) {
+            Ok(s) => {
+                output.sms_token = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_SMS_TOKEN),
+                    String::from(""),
+                )) {
+                    Ok(s) => {
+                        output.sms_token = String::from(s.value());
+                        info!("SMS token successfully set.");
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_SMS_FROM) {
+            Ok(s) => {
+                output.sms_from = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_SMS_FROM),
+                    String::from(""),
+                )) {
+                    Ok(s) => {
+                        output.sms_from = String::from(s.value());
+                        info!("SMS from successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_SMS_TEMPLATE) {
+            Ok(s) => {
+                output.sms_template = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_SMS_TEMPLATE),
+                    String::from(defaults::DEFAULT_SMS_TEMPLATE),
+                )) {
+                    Ok(s) => {
+                        output.sms_template = String::from(s.value());
+                        info!("SMS template successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_SMS_INTERVAL) {
+            Ok(s) => {
+                let v: u64 = s.value().parse().unwrap_or(defaults::DEFAULT_SMS_INTERVAL);
+                output.sms_interval = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_SMS_INTERVAL),
+                    format!("{}", defaults::DEFAULT_SMS_INTERVAL),
+                )) {
+                    Ok(s) => {
+                        let v: u64 = s.value().parse().unwrap_or(defaults::DEFAULT_SMS_INTERVAL);
+                        output.sms_interval = v;
+                        info!("SMS interval successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_SMS_HOURLY_LIMIT) {
+            Ok(s) => {
+                let v: u32 = s.value().parse().unwrap_or(defaults::DEFAULT_SMS_HOURLY_LIMIT);
+                output.sms_hourly_limit = v;
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_SMS_HOURLY_LIMIT),
+                    format!("{}", defaults::DEFAULT_SMS_HOURLY_LIMIT),
+                )) {
+                    Ok(s) => {
+                        let v: u32 = s.value().parse().unwrap_or(defaults::DEFAULT_SMS_HOURLY_LIMIT);
+                        output.sms_hourly_limit = v;
+                        info!("SMS hourly limit successfully set to '{}'.", s.value());
+                    },
+                    Err(e) => return Err(e)
+                }
+            },
+            Err(e) => {
+                return Err(e)
+            }
+        }
+        match sqlite.get_setting(SETTING_UNKNOWN_CHIP_POLICY) {
+            Ok(s) => {
+                output.unknown_chip_policy = String::from(s.value());
+            },
+            Err(DBError::NotFound) => {
+                match sqlite.set_setting(&setting::Setting::new(
+                    String::from(SETTING_UNKNOWN_CHIP_POLICY),
+                    String::from(defaults::DEFAULT_UNKNOWN_CHIP_POLICY),
+                )) {
+                    Ok(s) => {
+                        output.unknown_chip_policy = String::from(s.value());
+                        info!("Unknown chip policy successfully set to '{}'.", s.value());
                     },
                     Err(e) => return Err(e)
                 }
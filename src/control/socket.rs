@@ -1,14 +1,15 @@
-use std::{env, io::{ErrorKind, Read, Write}, net::{Shutdown, SocketAddr, TcpListener, TcpStream}, sync::{Arc, Mutex, MutexGuard}, thread::{self, JoinHandle}, time::{Duration, SystemTime, UNIX_EPOCH}};
+use std::{env, io::{ErrorKind, Read, Write}, net::{Shutdown, SocketAddr, TcpListener, TcpStream}, ops::Deref, sync::{Arc, Mutex, MutexGuard}, thread::{self, JoinHandle}, time::{Duration, SystemTime, UNIX_EPOCH}};
 #[cfg(target_os = "linux")]
 use crate::buttons::Buttons;
 #[cfg(target_os = "linux")]
 use crate::battery;
 
 use chrono::{DateTime, Local, TimeZone, Utc};
-use reqwest::header::{HeaderMap, CONTENT_TYPE, AUTHORIZATION};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE, AUTHORIZATION};
 use socket2::{Socket, Type, Protocol, Domain};
+use log::{debug, error, info, warn};
 
-use crate::{control::{socket::requests::AutoUploadQuery, sound::{self, SoundType}, SETTING_AUTO_REMOTE, SETTING_PORTAL_NAME}, database::{sqlite, Database}, network::api::{self, Api}, notifier::{self, Notifier}, objects::{bibchip, event::Event, participant, read, setting::{self, Setting}, sighting}, processor, reader::{self, auto_connect, reconnector::Reconnector, zebra, MAX_ANTENNAS}, remote::{self, remote_util, uploader::{self, Uploader}}, results, screen::CharacterDisplay, sound_board::Voice};
+use crate::{control::{socket::requests::AutoUploadQuery, sound::{self, SoundType}, SETTING_AUTO_REMOTE, SETTING_PORTAL_NAME}, database::{self, sqlite, Database, DBError}, defaults, events::{self, EventBus}, logging, mqtt, output, sms, network::{api::{self, Api}, connectivity::{self, Connectivity}, http::ClientFactory}, notifier::{self, Notifier}, objects::{bibchip, event::Event, notification_channel, notification_rule, participant, read, setting::{self, Setting}, sighting, sms_delivery, sms_supporter, system_event, unknown_chip, watched_bib}, processor::{self, watch::BibWatcher}, reader::{self, auto_connect, monitor::{self, AntennaMonitor}, reconnector::Reconnector, zebra, MAX_ANTENNAS}, remote::{self, relay, remote_util, uploader::{self, Uploader}, webhook}, results::{self, sync, upload}, screen::CharacterDisplay, sound_board::Voice};
 
 use self::notifications::APINotification;
 
@@ -19,9 +20,128 @@ pub mod responses;
 pub mod errors;
 pub mod notifications;
 
+#[cfg(test)]
+pub mod test;
+
 pub const MAX_CONNECTED: usize = 4;
 pub const CONNECTION_TYPE: &str = "chrono_portal";
-pub const CONNECTION_VERS: usize = 1;
+// Newest and oldest protocol versions we can speak.
+pub const CONNECTION_VERS: usize = 6;
+pub const CONNECTION_VERS_MIN: usize = 1;
+// Version request ids were added in, older clients don't get them or events marked.
+pub const CONNECTION_VERS_REQUEST_IDS: usize = 3;
+
+// Capabilities we advertise to clients along with the protocol version they were added in.
+pub const CAPABILITY_READS: &str = "reads";
+pub const CAPABILITY_SIGHTINGS: &str = "sightings";
+pub const CAPABILITY_AUTO_UPLOAD: &str = "auto_upload";
+pub const CAPABILITY_NOTIFICATIONS: &str = "notifications";
+pub const CAPABILITY_SUBSCRIPTION_FILTERS: &str = "subscription_filters";
+pub const CAPABILITY_REQUEST_IDS: &str = "request_ids";
+pub const CAPABILITY_CONNECTIVITY: &str = "connectivity";
+pub const CAPABILITY_ANTENNA_ALERTS: &str = "antenna_alerts";
+pub const CAPABILITY_WATCHED_BIBS: &str = "watched_bibs";
+pub const CAPABILITY_PARTICIPANT_SYNC_HISTORY: &str = "participant_sync_history";
+pub const CAPABILITY_NOTIFICATION_CHANNELS: &str = "notification_channels";
+pub const CAPABILITY_NOTIFICATION_RULES: &str = "notification_rules";
+pub const CAPABILITY_EVENT_LOG: &str = "event_log";
+pub const CAPABILITY_LOG: &str = "log";
+pub const CAPABILITY_SMS: &str = "sms";
+pub const CAPABILITY_UNKNOWN_CHIPS: &str = "unknown_chips";
+pub const CAPABILITIES: [(&str, usize);16] = [
+    (CAPABILITY_READS, 1),
+    (CAPABILITY_SIGHTINGS, 1),
+    (CAPABILITY_AUTO_UPLOAD, 1),
+    (CAPABILITY_NOTIFICATIONS, 1),
+    (CAPABILITY_SUBSCRIPTION_FILTERS, 2),
+    (CAPABILITY_REQUEST_IDS, CONNECTION_VERS_REQUEST_IDS),
+    (CAPABILITY_CONNECTIVITY, 4),
+    (CAPABILITY_PARTICIPANT_SYNC_HISTORY, 4),
+    (CAPABILITY_NOTIFICATION_CHANNELS, 4),
+    (CAPABILITY_NOTIFICATION_RULES, 4),
+    (CAPABILITY_ANTENNA_ALERTS, 5),
+    (CAPABILITY_EVENT_LOG, 5),
+    (CAPABILITY_LOG, 5),
+    (CAPABILITY_WATCHED_BIBS, 6),
+    (CAPABILITY_SMS, 6),
+    (CAPABILITY_UNKNOWN_CHIPS, 6),
+];
+
+// Who a message written to a client is for.  Direct responses echo the request_id the client
+// sent with the request, anything we push on our own (reads, sightings, keepalives, changes made
+// by other clients) is an event.  Clients from before request ids see neither.
+#[derive(Clone, Debug, Default)]
+pub struct Reply {
+    request_id: Option<serde_json::Value>,
+    event: bool,
+}
+
+impl Reply {
+    pub fn response(request_id: Option<serde_json::Value>, version: usize) -> Self {
+        if version < CONNECTION_VERS_REQUEST_IDS {
+            return Self::default()
+        }
+        Self {
+            request_id,
+            event: false,
+        }
+    }
+
+    pub fn event(version: usize) -> Self {
+        Self {
+            request_id: None,
+            event: version >= CONNECTION_VERS_REQUEST_IDS,
+        }
+    }
+
+    pub fn envelope(&self, response: responses::Responses) -> responses::Envelope {
+        responses::Envelope {
+            response,
+            request_id: self.request_id.clone(),
+            event: self.event,
+        }
+    }
+}
+
+// A connected client's socket.  The thread handling its requests and its event writer thread both
+// write to it, so each message is written while holding the lock to keep them from interleaving.
+#[derive(Clone)]
+pub struct ClientStream {
+    stream: Arc<TcpStream>,
+    writing: Arc<Mutex<()>>,
+}
+
+impl ClientStream {
+    pub fn new(stream: TcpStream) -> Self {
+        Self {
+            stream: Arc::new(stream),
+            writing: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, ()> {
+        // nothing is guarded, a writer panicking doesn't leave anything to clean up
+        self.writing.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Deref for ClientStream {
+    type Target = TcpStream;
+
+    fn deref(&self) -> &TcpStream {
+        &self.stream
+    }
+}
+
+impl Write for &ClientStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        (&*self.stream).write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        (&*self.stream).flush()
+    }
+}
 
 pub const CONNECTION_CHANGE_PAUSE: u64 = 500;
 
@@ -43,13 +163,23 @@ pub fn control_loop(
     // Readers are chip readers that are saved.  They may be connected or reading as well.
     let readers: Arc<Mutex<Vec<reader::Reader>>> = Arc::new(Mutex::new(Vec::new()));
 
-    // Control sockets are sockets that are connected and should be relayed any changes in settings / readers / apis
-    // when another socket changes/deletes/adds something. -- Last spot is reserved for localhost to send shutdown command
-    let control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>> = Arc::new(Mutex::new(Default::default()));
+    // Control sockets are sockets that are connected, changes one of them makes are relayed to the others through the
+    // event bus. -- Last spot is reserved for localhost to send shutdown command
+    let control_sockets: Arc<Mutex<[Option<ClientStream>;MAX_CONNECTED + 1]>> = Arc::new(Mutex::new(Default::default()));
     // Read repeaters are sockets that want reads to be sent to them as they're being saved.
     let read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>> = Arc::new(Mutex::new([false;MAX_CONNECTED]));
     // Sighting repeaters are sockets that want sightings to be sent to them as they're being saved.
     let sighting_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>> = Arc::new(Mutex::new([false;MAX_CONNECTED]));
+    // The event bus is how reads, sightings, antenna/uploader updates and notifications are pushed to
+    // the connected sockets. Each socket has its own writer thread so a slow client can't stall the others.
+    let event_bus = EventBus::new(read_repeaters.clone(), sighting_repeaters.clone(), keepalive.clone());
+    // Filters need to know who is who from the start, not just once the sightings processor has run.
+    if let Ok(sq) = sqlite.lock() {
+        match events::filter::ParticipantLookup::load(&sq) {
+            Ok(lookup) => event_bus.set_participant_lookup(lookup),
+            Err(e) => error!("Error loading participants for subscription filters. {e}"),
+        }
+    }
     
     // Our control port will be semi-random at the start to try to ensure we don't try to get a port in use.
     let control_port = get_available_port();
@@ -57,7 +187,7 @@ pub fn control_loop(
     let socket = match Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::TCP)) {
         Ok(sock) => sock,
         Err(e) => {
-            println!("Error creating socket to listen to: {e}");
+            error!("Error creating socket to listen to: {e}");
             return
         }
     };
@@ -65,7 +195,7 @@ pub fn control_loop(
     let address: SocketAddr = match format!("0.0.0.0:{control_port}").parse() {
         Ok(addr) => addr,
         Err(e) => {
-            println!("Error getting address: {e}");
+            error!("Error getting address: {e}");
             return
         }
     };
@@ -76,23 +206,23 @@ pub fn control_loop(
     match socket.set_reuse_address(true) {
         Ok(_) => {}
         Err(e) => {
-            println!("Unable to set SO_REUSEADDR to true: {e}");
+            warn!("Unable to set SO_REUSEADDR to true: {e}");
             return
         }
     }
     match socket.bind(&address) {
         Ok(_) => {
-            println!("Control socket successfully bound.");
+            info!("Control socket successfully bound.");
         }
         Err(e) => {
-            println!("Error binding control socket: {e}");
+            error!("Error binding control socket: {e}");
             return
         }
     }
     match socket.listen(512) {
         Ok(_) => {},
         Err(e) => {
-            println!("Socket Listen call failed: {e}");
+            warn!("Socket Listen call failed: {e}");
             return
         }
     }
@@ -107,7 +237,7 @@ pub fn control_loop(
     ) {
         Ok(zc) => zc,
         Err(e) => {
-            println!("Error getting zero conf: {e}");
+            error!("Error getting zero conf: {e}");
             return
         }
     };
@@ -119,13 +249,12 @@ pub fn control_loop(
     if let Ok(mut j) = joiners.lock() {
         j.push(z_joiner);
     } else {
-        println!("Unable to get joiners lock.");
+        warn!("Unable to get joiners lock.");
     }
 
     // create our sightings processing thread
     let sight_processor = Arc::new(processor::SightingsProcessor::new(
-        control_sockets.clone(),
-        sighting_repeaters.clone(),
+        event_bus.clone(),
         sqlite.clone(),
         keepalive.clone()
     ));
@@ -137,13 +266,13 @@ pub fn control_loop(
     if let Ok(mut j) = joiners.lock() {
         j.push(s_joiner);
     } else {
-        println!("Unable to get joiners lock.");
+        warn!("Unable to get joiners lock.");
     }
 
     // Reset reads status on load and re-process sightings.
     if let Ok(sq) = sqlite.lock() {
         if let Err(e) = sq.reset_reads_status() {
-            println!("Error trying to reset reads statuses: {e}");
+            error!("Error trying to reset reads statuses: {e}");
         };
     }
     sight_processor.notify();
@@ -158,13 +287,13 @@ pub fn control_loop(
                     }
                 },
                 Err(e) => {
-                    println!("Error getting readers mutex. {e}");
+                    error!("Error getting readers mutex. {e}");
                     return
                 },
             }
         }
         Err(e) => {
-            println!("Error getting database mutex. {e}");
+            error!("Error getting database mutex. {e}");
             return
         },
     }
@@ -192,11 +321,27 @@ pub fn control_loop(
     if let Ok(mut j) = joiners.lock() {
         j.push(rs_joiner);
     } else {
-        println!("Unable to get joiners lock.");
+        warn!("Unable to get joiners lock.");
+    }
+
+    // Every http client we use comes from here so proxy and certificate settings apply everywhere.
+    let http = Arc::new(ClientFactory::new(control.clone()));
+
+    // The screen is set up further down if there is one, but the connectivity checker needs it now.
+    let screen: Arc<Mutex<Option<CharacterDisplay>>> = Arc::new(Mutex::new(None));
+
+    // Start a thread to keep track of whether we can reach our remote apis.
+    let connectivity = Arc::new(Connectivity::new(keepalive.clone(), sqlite.clone(), control.clone(), event_bus.clone(), screen.clone(), http.clone()));
+    let t_connectivity = connectivity.clone();
+    let c_joiner = thread::spawn(move|| {
+        t_connectivity.run();
+    });
+    if let Ok(mut j) = joiners.lock() {
+        j.push(c_joiner);
     }
 
     // Start a thread to enable notifications.
-    let notifier = Notifier::new(keepalive.clone(), control.clone());
+    let notifier = Notifier::new(keepalive.clone(), control.clone(), sqlite.clone(), connectivity.clone(), http.clone());
     let mut t_notifier = notifier.clone();
     let n_joiner = thread::spawn(move|| {
         t_notifier.run();
@@ -205,14 +350,94 @@ pub fn control_loop(
         j.push(n_joiner);
     }
 
+    // Start a thread to publish to an MQTT broker if the user has one set up.
+    let mut mqtt_publisher = mqtt::Publisher::new(keepalive.clone(), control.clone(), &event_bus);
+    let m_joiner = thread::spawn(move|| {
+        mqtt_publisher.run();
+    });
+    if let Ok(mut j) = joiners.lock() {
+        j.push(m_joiner);
+    }
+
+    // Start a thread to text participants and their supporters when they're seen.
+    let mut texter = sms::Texter::new(keepalive.clone(), control.clone(), sqlite.clone(), &event_bus, http.clone());
+    let sms_joiner = thread::spawn(move|| {
+        texter.run();
+    });
+    if let Ok(mut j) = joiners.lock() {
+        j.push(sms_joiner);
+    }
+
+    // Start a thread to deliver to any webhooks the user has set up.
+    let mut webhooks = webhook::Webhooks::new(keepalive.clone(), sqlite.clone(), control.clone(), &event_bus, connectivity.clone(), http.clone());
+    let w_joiner = thread::spawn(move|| {
+        webhooks.run();
+    });
+    if let Ok(mut j) = joiners.lock() {
+        j.push(w_joiner);
+    }
+
+    // Start a thread to watch for antennas that stop reading.
+    let antenna_monitor = AntennaMonitor::new(keepalive.clone(), control.clone(), event_bus.clone(), notifier.clone(), sound_notifier.clone(), screen.clone());
+    let am_joiner = thread::spawn(move|| {
+        antenna_monitor.run();
+    });
+    if let Ok(mut j) = joiners.lock() {
+        j.push(am_joiner);
+    }
+
+    // Start a thread to let everyone know when a watched bib is seen.
+    let bib_watcher = BibWatcher::new(keepalive.clone(), sqlite.clone(), event_bus.clone(), notifier.clone(), sound_notifier.clone());
+    let bw_joiner = thread::spawn(move|| {
+        bib_watcher.run();
+    });
+    if let Ok(mut j) = joiners.lock() {
+        j.push(bw_joiner);
+    }
+
+    // Start a thread to stream reads to third party timing software if the user wants it.
+    let output_server = output::OutputServer::new(keepalive.clone(), sqlite.clone(), control.clone(), event_bus.clone());
+    let o_joiner = thread::spawn(move|| {
+        output_server.run();
+    });
+    if let Ok(mut j) = joiners.lock() {
+        j.push(o_joiner);
+    }
+
+    // Start a thread to accept reads relayed from other portals if the user wants it.
+    let relay = relay::Relay::new(keepalive.clone(), sqlite.clone(), control.clone(), event_bus.clone(), sight_processor.clone());
+    let r_joiner = thread::spawn(move|| {
+        relay.run();
+    });
+    if let Ok(mut j) = joiners.lock() {
+        j.push(r_joiner);
+    }
+
+    // Start a thread to keep participants up to date with the results api if the user wants it.
+    let participant_sync = sync::ParticipantSync::new(keepalive.clone(), sqlite.clone(), control.clone(), sight_processor.clone(), connectivity.clone(), http.clone());
+    let p_joiner = thread::spawn(move|| {
+        participant_sync.run();
+    });
+    if let Ok(mut j) = joiners.lock() {
+        j.push(p_joiner);
+    }
+
+    // Start a thread to send sightings to the same event on the results api if the user wants it.
+    let sighting_uploader = upload::SightingUploader::new(keepalive.clone(), sqlite.clone(), control.clone(), &event_bus, connectivity.clone(), http.clone());
+    let su_joiner = thread::spawn(move|| {
+        sighting_uploader.run();
+    });
+    if let Ok(mut j) = joiners.lock() {
+        j.push(su_joiner);
+    }
+
     // create the auto connector for automatically connecting to readers
     let ac_state = Arc::new(Mutex::new(auto_connect::State::Unknown));
     let mut auto_connector = auto_connect::AutoConnector::new(
         ac_state.clone(),
         readers.clone(),
         joiners.clone(),
-        control_sockets.clone(),
-        read_repeaters.clone(),
+        event_bus.clone(),
         sight_processor.clone(),
         control.clone(),
         sqlite.clone(),
@@ -226,23 +451,21 @@ pub fn control_loop(
     });
     
     // Check if we can start a screen
-    let screen: Arc<Mutex<Option<CharacterDisplay>>> = Arc::new(Mutex::new(None));
     // Check for screen information
     #[cfg(target_os = "linux")]
     {
-        println!("Checking if there's a screen to display information on.");
+        debug!("Checking if there's a screen to display information on.");
         if let Ok(screen_bus) = std::env::var("PORTAL_SCREEN_BUS") {
             let bus: u8 = screen_bus.parse().unwrap_or(255);
             if bus < 50 {
-                println!("Screen bus is {bus}.");
+                info!("Screen bus is {bus}.");
                 if let Ok(mut screen) = screen.lock() {
                     let mut new_screen = CharacterDisplay::new(
                         keepalive.clone(),
                         control.clone(),
                         readers.clone(),
                         sqlite.clone(),
-                        control_sockets.clone(),
-                        read_repeaters.clone(),
+                        event_bus.clone(),
                         sight_processor.clone(),
                         ac_state.clone(),
                         read_saver.clone(),
@@ -257,7 +480,7 @@ pub fn control_loop(
                     });
                 }
                 // Start buttons
-                println!("Starting button thread.");
+                info!("Starting button thread.");
                 let btns = Buttons::new(
                     screen.clone(), 
                     keepalive.clone()
@@ -270,10 +493,10 @@ pub fn control_loop(
     }
 
     // create our reads uploader struct for auto uploading if the user wants to
-    let uploader = Arc::new(uploader::Uploader::new(keepalive.clone(), sqlite.clone(), control_sockets.clone(), control.clone(), screen.clone()));
+    let uploader = Arc::new(uploader::Uploader::new(keepalive.clone(), sqlite.clone(), event_bus.clone(), control.clone(), screen.clone(), connectivity.clone(), http.clone()));
     if let Ok(control) = control.lock() {
         if control.auto_remote == true {
-            println!("Starting auto upload thread.");
+            info!("Starting auto upload thread.");
             let t_uploader = uploader.clone();
             let t_joiner = thread::spawn(move|| {
                 t_uploader.run();
@@ -292,7 +515,7 @@ pub fn control_loop(
 
     // Start our code to check the battery level.
     #[cfg(target_os = "linux")]
-    let mut bat_check = battery::Checker::new(keepalive.clone(), control.clone(), screen.clone(), notifier.clone(), control_sockets.clone(), sqlite.clone());
+    let mut bat_check = battery::Checker::new(keepalive.clone(), control.clone(), screen.clone(), notifier.clone(), event_bus.clone(), sqlite.clone());
     #[cfg(target_os = "linux")]
     let b_joiner = thread::spawn(move|| {
         #[cfg(target_os = "linux")]
@@ -307,7 +530,7 @@ pub fn control_loop(
     if let Ok(mut u_readers) = readers.lock() {
         for reader in u_readers.iter_mut() {
             reader.set_screen(screen.clone());
-            reader.set_control_sockets(control_sockets.clone());
+            reader.set_event_bus(event_bus.clone());
             reader.set_readers(readers.clone());
         }
     }
@@ -328,7 +551,7 @@ pub fn control_loop(
                 break;
             }
         } else {
-            println!("Error getting keep alive mutex. Exiting.");
+            error!("Error getting keep alive mutex. Exiting.");
             break;
         }
         match listener.accept() {
@@ -337,17 +560,12 @@ pub fn control_loop(
                 match stream.set_read_timeout(Some(Duration::from_secs(READ_TIMEOUT_SECONDS))) {
                     Ok(_) => (),
                     Err(e) => {
-                        println!("Error setting read timeout: {e}");
+                        error!("Error setting read timeout: {e}");
                     }
                 }
-                println!("New connection: {}", addr);
-                let t_stream = match stream.try_clone() {
-                    Ok(st) => st,
-                    Err(e) => {
-                        println!("Error cloning stream. {e}");
-                        continue
-                    }
-                };
+                info!("New connection: {}", addr);
+                let stream = ClientStream::new(stream);
+                let t_stream = stream.clone();
                 let t_keepalive = keepalive.clone();
                 let t_control = control.clone();
                 let t_readers = readers.clone();
@@ -356,6 +574,7 @@ pub fn control_loop(
                 let t_sighting_repeaters = sighting_repeaters.clone();
                 let t_sqlite = sqlite.clone();
                 let t_control_sockets = control_sockets.clone();
+                let t_event_bus = event_bus.clone();
                 let t_sight_processor = sight_processor.clone();
                 let t_uploader = uploader.clone();
                 let t_ac_state = ac_state.clone();
@@ -363,89 +582,101 @@ pub fn control_loop(
                 let t_read_saver = read_saver.clone();
                 let t_screen = screen.clone();
                 let t_notifier = notifier.clone();
+                let t_connectivity = connectivity.clone();
+                let t_http = http.clone();
 
                 let mut placed = MAX_CONNECTED + 2;
-                if let Ok(c_sock) = stream.try_clone() {
-                    if let Ok(mut c_sockets) = control_sockets.lock() {
-                        for i in 0..(MAX_CONNECTED + 1) {
-                            if c_sockets[i].is_none() && i < MAX_CONNECTED {
-                                c_sockets[i] = Some(c_sock);
-                                placed = i;
-                                break;
-                            // Index MAX_CONNECTED is reserved for the system to tell itself to stop running in case of 
-                            // power failure or some other reason the system needs to shut itself off.
-                            } else if i == MAX_CONNECTED && addr.ip().is_loopback() && c_sockets[MAX_CONNECTED].is_none() {
-                                c_sockets[i] = Some(c_sock);
-                                placed = i;
-                                break;
-                            }
+                let c_sock = stream.clone();
+                if let Ok(mut c_sockets) = control_sockets.lock() {
+                    for i in 0..(MAX_CONNECTED + 1) {
+                        if c_sockets[i].is_none() && i < MAX_CONNECTED {
+                            c_sockets[i] = Some(c_sock);
+                            placed = i;
+                            break;
+                        // Index MAX_CONNECTED is reserved for the system to tell itself to stop running in case of 
+                        // power failure or some other reason the system needs to shut itself off.
+                        } else if i == MAX_CONNECTED && addr.ip().is_loopback() && c_sockets[MAX_CONNECTED].is_none() {
+                            c_sockets[i] = Some(c_sock);
+                            placed = i;
+                            break;
                         }
                     }
-                    if placed <= MAX_CONNECTED {
-                        let l_joiner = thread::spawn(move|| {
-                            handle_stream(
-                                placed,
-                                t_stream,
-                                t_keepalive,
-                                t_control,
-                                &control_port,
-                                t_readers,
-                                t_joiners,
-                                t_read_repeaters,
-                                t_sighting_repeaters,
-                                t_control_sockets,
-                                t_sight_processor,
-                                t_sqlite,
-                                t_uploader,
-                                t_ac_state,
-                                t_read_saver,
-                                t_sound_notifier,
-                                t_screen,
-                                t_notifier,
-                            );
-                        });
-                        if let Ok(mut j) = joiners.lock() {
-                            j.push(l_joiner);
-                        } else {
-                            println!("Unable to get joiners lock.");
+                }
+                if placed <= MAX_CONNECTED {
+                    match event_bus.subscribe(placed, &stream) {
+                        Ok(w_joiner) => {
+                            if let Ok(mut j) = joiners.lock() {
+                                j.push(w_joiner);
+                            } else {
+                                warn!("Unable to get joiners lock.");
+                            }
+                        },
+                        Err(e) => {
+                            error!("Error subscribing socket to events. {e}");
                         }
+                    }
+                    let l_joiner = thread::spawn(move|| {
+                        handle_stream(
+                            placed,
+                            t_stream,
+                            t_keepalive,
+                            t_control,
+                            &control_port,
+                            t_readers,
+                            t_joiners,
+                            t_read_repeaters,
+                            t_sighting_repeaters,
+                            t_control_sockets,
+                            t_event_bus,
+                            t_sight_processor,
+                            t_sqlite,
+                            t_uploader,
+                            t_ac_state,
+                            t_read_saver,
+                            t_sound_notifier,
+                            t_screen,
+                            t_notifier,
+                            t_connectivity,
+                            t_http,
+                        );
+                    });
+                    if let Ok(mut j) = joiners.lock() {
+                        j.push(l_joiner);
                     } else {
-                        _ = write_error(&stream, errors::Errors::TooManyConnections);
+                        warn!("Unable to get joiners lock.");
                     }
                 } else {
-                    _ = write_error(&stream, errors::Errors::ServerError{
-                            message: String::from("unable to clone stream")
-                    });
+                    _ = write_error(&stream, &Reply::event(CONNECTION_VERS_MIN), errors::Errors::TooManyConnections);
                 }
             },
             Err(e) => {
                 if e.kind() != ErrorKind::WouldBlock {
-                    println!("Connection failed. {e}")
+                    warn!("Connection failed. {e}")
                 }
             }
         }
     }
     let date_time: DateTime<Local> = SystemTime::now().into();
     notifier.send_notification(notifier::Notification::Shutdown, format!("{}", date_time.format("%Y/%m/%d %T")));
-    println!("Shutting down control thread.");
-    println!("Stopping readers.");
+    info!("Shutting down control thread.");
+    info!("Stopping readers.");
     if let Ok(mut r) = readers.lock() {
         for reader in r.iter_mut() {
             _ = reader.disconnect();
         }
     }
     if let Ok(c_socks) = control_sockets.lock() {
-        for sock in c_socks.iter() {
+        for (ix, sock) in c_socks.iter().enumerate() {
             if let Some(sock) = sock {
-                _ = write_notification(&sock, &APINotification::ShuttingDown, &format!("now"));
-                _ = write_disconnect(&sock)
+                _ = write_notification(&sock, &Reply::event(event_bus.version(ix)), &APINotification::ShuttingDown, &format!("now"));
+                _ = write_disconnect(&sock, &Reply::event(event_bus.version(ix)))
             }
         }
     }
-    println!("Stopping sightings processor.");
+    info!("Stopping sightings processor.");
     sight_processor.stop();
     sight_processor.notify();
-    println!("Finished control thread shutdown.");
+    info!("Finished control thread shutdown.");
     if let Ok(control) = control.lock() {
         if control.auto_remote {
             if let Ok(sq) = sqlite.lock() {
@@ -459,7 +690,7 @@ pub fn control_loop(
                         }
                     },
                     Err(e) => {
-                        println!("Error trying to get apis: {e}");
+                        error!("Error trying to get apis: {e}");
                     }
                 }
             }
@@ -467,6 +698,41 @@ pub fn control_loop(
     }
 }
 
+// The protocol version a client negotiated and the capabilities they get with it.
+pub struct NegotiatedProtocol {
+    pub version: usize,
+    pub capabilities: Option<Vec<String>>,
+}
+
+// Picks the newest version both sides support, clients that don't tell us what they support are version 1.
+pub fn negotiate_version(min_version: Option<usize>, max_version: Option<usize>) -> Option<usize> {
+    let client_max = max_version.or(min_version).unwrap_or(CONNECTION_VERS_MIN);
+    let client_min = min_version.unwrap_or(client_max.min(CONNECTION_VERS_MIN));
+    let version = client_max.min(CONNECTION_VERS);
+    if version < client_min || version < CONNECTION_VERS_MIN {
+        return None
+    }
+    Some(version)
+}
+
+// Capabilities available at the given version.  If the client told us what features it wants
+// then only those we both know about are returned.
+pub fn get_capabilities(version: usize, features: &Option<Vec<String>>) -> Vec<String> {
+    let mut output: Vec<String> = Vec::new();
+    for (name, added) in CAPABILITIES {
+        if added > version {
+            continue;
+        }
+        if let Some(feats) = features {
+            if !feats.iter().any(|f| f == name) {
+                continue;
+            }
+        }
+        output.push(String::from(name));
+    }
+    output
+}
+
 fn find_json_end(buffer: &String) -> usize {
     let chars = buffer.as_bytes();
     let mut start_count = 0;
@@ -502,7 +768,7 @@ fn find_json_end(buffer: &String) -> usize {
 
 fn handle_stream(
     index: usize,
-    mut stream: TcpStream,
+    stream: ClientStream,
     keepalive: Arc<Mutex<bool>>,
     control: Arc<Mutex<super::Control>>,
     control_port: &u16,
@@ -510,7 +776,8 @@ fn handle_stream(
     joiners: Arc<Mutex<Vec<JoinHandle<()>>>>,
     read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
     sighting_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
-    control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
+    control_sockets: Arc<Mutex<[Option<ClientStream>;MAX_CONNECTED + 1]>>,
+    event_bus: EventBus,
     sight_processor: Arc<processor::SightingsProcessor>,
     sqlite: Arc<Mutex<sqlite::SQLite>>,
     uploader: Arc<uploader::Uploader>,
@@ -519,25 +786,26 @@ fn handle_stream(
     sound: Arc<SoundNotifier>,
     screen: Arc<Mutex<Option<CharacterDisplay>>>,
     notifier: notifier::Notifier,
+    connectivity: Arc<Connectivity>,
+    http: Arc<ClientFactory>,
 ) {
-    println!("Starting control loop for index {index}");
+    info!("Starting control loop for index {index}");
     let mut data = [0 as u8; 51200];
     let mut buffer = String::new();
     let mut no_error = true;
     let mut last_received_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-    let http_client = reqwest::blocking::ClientBuilder::new().timeout(Duration::from_secs(30))
-                                .connect_timeout(Duration::from_secs(30)).build()
-                                .unwrap_or(reqwest::blocking::Client::new());
+    // Clients are assumed to speak the oldest protocol until they tell us otherwise on Connect.
+    let mut protocol_version = CONNECTION_VERS_MIN;
     loop {
         if let Ok(ka) = keepalive.lock() {
             if *ka == false {
                 break;
             }
         } else {
-            println!("Error getting keep alive mutex. Exiting.");
+            error!("Error getting keep alive mutex. Exiting.");
             break;
         }
-        let size = match stream.read(&mut data) {
+        let size = match (&*stream).read(&mut data) {
             Ok(size) => size,
             Err(e) => {
                 match e.kind() {
@@ -546,7 +814,7 @@ fn handle_stream(
                         0
                     },
                     _ => {
-                        println!("Error reading from socket. {e}");
+                        error!("Error reading from socket. {e}");
                         break;
                     }
                 }
@@ -558,14 +826,14 @@ fn handle_stream(
                     time.as_secs()
                 },
                 Err(e) => {
-                    println!("Error getting time: {e}");
+                    error!("Error getting time: {e}");
                     0
                 }
             };
             let s = match std::str::from_utf8(&data[0..size]) {
                 Ok(buf) => buf,
                 Err(e) => {
-                    println!("Error parsing data received: {e}");
+                    error!("Error parsing data received: {e}");
                     ""
                 },
             };
@@ -585,7 +853,7 @@ fn handle_stream(
                         requests::Request::KeepaliveAck => {},
                         requests::Request::TimeGet => {},
                         _ => {
-                            println!("Received message: {:?}", data);
+                            debug!("Received message: {:?}", data);
                         }
                     }
                     data
@@ -595,35 +863,70 @@ fn handle_stream(
                         buffer.push_str(&single_line);
                         break;
                     } else {
-                        println!("Error deserializing request. {e}");
+                        error!("Error deserializing request. {e}");
                     }
                     requests::Request::Unknown
                 },
             };
+            let request_id = match serde_json::from_str::<requests::RequestId>(&single_line) {
+                Ok(r) => r.request_id,
+                Err(_) => None,
+            };
+            let reply = Reply::response(request_id.clone(), protocol_version);
+            // asked for each request so settings changes are picked up
+            let http_client = match http.client() {
+                Ok(client) => client,
+                Err(e) => {
+                    error!("Error getting http client: {e}");
+                    reqwest::blocking::Client::new()
+                }
+            };
             match cmd {
                 requests::Request::Disconnect => {
                     // client requested to close the connection
-                    _ = write_disconnect(&mut stream);
+                    _ = write_disconnect(&stream, &reply);
                     // tell then to close it and then break the loop to exit the thread
                     break;
                 },
-                requests::Request::Connect { reads, sightings } => {
-                    let mut name = String::from("Unknown");
-                    if let Ok(sq) = sqlite.lock() {
-                        if let Ok(set) = sq.get_setting(SETTING_PORTAL_NAME) {
-                            name = String::from(set.value())
+                requests::Request::Connect { reads, sightings, filter, min_version, max_version, features } => {
+                    if let Some(version) = negotiate_version(min_version, max_version) {
+                        protocol_version = version;
+                        event_bus.set_version(index, version);
+                        // the response is in the version we just agreed on
+                        let reply = Reply::response(request_id, protocol_version);
+                        let mut name = String::from("Unknown");
+                        if let Ok(sq) = sqlite.lock() {
+                            if let Ok(set) = sq.get_setting(SETTING_PORTAL_NAME) {
+                                name = String::from(set.value())
+                            }
+                        }
+                        if let Ok(mut repeaters) = read_repeaters.lock() {
+                            repeaters[index] = reads;
+                        }
+                        if let Ok(mut repeaters) = sighting_repeaters.lock() {
+                            repeaters[index] = sightings;
+                        }
+                        event_bus.set_filter(index, filter);
+                        // version 1 clients get the same response they always have
+                        let capabilities = if min_version.is_some() || max_version.is_some() || features.is_some() {
+                            Some(get_capabilities(protocol_version, &features))
+                        } else {
+                            None
+                        };
+                        if let Ok(u_readers) = readers.try_lock() {
+                            no_error = write_connection_successful(&stream, &reply, name, reads, sightings, &*u_readers, &uploader, NegotiatedProtocol {
+                            version: protocol_version,
+                            capabilities,
+                        });
+                        } else {
+                            no_error = write_error(&stream, &reply, errors::Errors::ServerError { message: String::from("unable to get readers mutex") })
                         }
-                    }
-                    if let Ok(mut repeaters) = read_repeaters.lock() {
-                        repeaters[index] = reads;
-                    }
-                    if let Ok(mut repeaters) = sighting_repeaters.lock() {
-                        repeaters[index] = sightings;
-                    }
-                    if let Ok(u_readers) = readers.try_lock() {
-                        no_error = write_connection_successful(&mut stream, name, reads, sightings, &*u_readers, &uploader);
                     } else {
-                        no_error = write_error(&mut stream, errors::Errors::ServerError { message: String::from("unable to get readers mutex") })
+                        no_error = write_error(&stream, &reply, errors::Errors::UnsupportedVersion {
+                            message: String::from("no protocol version in common"),
+                            min_version: CONNECTION_VERS_MIN,
+                            max_version: CONNECTION_VERS,
+                        });
                     }
                 },
                 requests::Request::KeepaliveAck => { },
@@ -636,19 +939,19 @@ fn handle_stream(
                             // for readers is not finished (or before it's started)
                             auto_connect::State::Waiting => {
                                 if let Ok(u_readers) = readers.lock() {
-                                    no_error = write_reader_list(&mut stream, &*u_readers);
+                                    no_error = write_reader_list(&stream, &reply, &*u_readers);
                                 }
                             }
                             _ => {
-                                println!("Auto connect is working right now.");
+                                info!("Auto connect is working right now.");
                                 sound.notify_custom(SoundType::StartupInProgress);
-                                no_error = write_error(&stream, errors::Errors::StartingUp)
+                                no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                             }
                         }
                     } else {
-                        println!("Auto connect is working right now.");
+                        info!("Auto connect is working right now.");
                         sound.notify_custom(SoundType::StartupInProgress);
-                        no_error = write_error(&stream, errors::Errors::StartingUp)
+                        no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                     }
                 },
                 requests::Request::ReaderAdd { id, name, kind, ip_address, port, auto_connect } => {
@@ -690,29 +993,20 @@ fn handle_stream(
                                                                 u_readers.push(tmp);
                                                             }
                                                         }
-                                                        if let Ok(c_socks) = control_sockets.lock() {
-                                                            for sock in c_socks.iter() {
-                                                                if let Some(sock) = sock {
-                                                                    // we might be writing to other sockets
-                                                                    // so errors here shouldn't close our connection
-                                                                    _ = write_reader_list(&sock, &*u_readers);
-                                                                }
-                                                            }
-                                                        } else {
-                                                            no_error = write_reader_list(&stream, &*u_readers);
-                                                        }
+                                                        event_bus.publish_except(index, events::Event::ReaderList(get_reader_list(&u_readers)));
+                                                        no_error = write_reader_list(&stream, &reply, &*u_readers);
                                                     }
                                                 },
                                                 Err(e) => {
-                                                    println!("Error saving reader to database: {e}");
-                                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                                    error!("Error saving reader to database: {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                         message: format!("unexpected error saving reader to database: {e}"),
                                                     });
                                                 },
                                             };
                                         },
                                         Err(e) => {
-                                            no_error = write_error(&stream, errors::Errors::InvalidReaderType {
+                                            no_error = write_error(&stream, &reply, errors::Errors::InvalidReaderType {
                                                 message: e.to_string()
                                              });
                                         },
@@ -720,15 +1014,15 @@ fn handle_stream(
                                 }
                             }
                             _ => {
-                                println!("Auto connect is working right now.");
+                                info!("Auto connect is working right now.");
                                 sound.notify_custom(SoundType::StartupInProgress);
-                                no_error = write_error(&stream, errors::Errors::StartingUp)
+                                no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                             }
                         }
                     } else {
-                        println!("Auto connect is working right now.");
+                        info!("Auto connect is working right now.");
                         sound.notify_custom(SoundType::StartupInProgress);
-                        no_error = write_error(&stream, errors::Errors::StartingUp)
+                        no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                     }
                 },
                 requests::Request::ReaderRemove { id } => {
@@ -750,37 +1044,28 @@ fn handle_stream(
                                             }
                                         },
                                         Err(e) => {
-                                            println!("Error removing database from reader: {e}");
-                                            no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                            error!("Error removing database from reader: {e}");
+                                            no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                 message: format!("unexpected error removing reader from database: {e}")
                                             });
                                         },
                                     }
                                 }
                                 if let Ok(u_readers) = readers.lock() {
-                                    if let Ok(c_socks) = control_sockets.lock() {
-                                        for sock in c_socks.iter() {
-                                            if let Some(sock) = sock {
-                                                // we might be writing to other sockets
-                                                // so errors here shouldn't close our connection
-                                                _ = write_reader_list(&sock, &*u_readers);
-                                            }
-                                        }
-                                    } else {
-                                        no_error = write_reader_list(&stream, &*u_readers);
-                                    }
+                                    event_bus.publish_except(index, events::Event::ReaderList(get_reader_list(&u_readers)));
+                                    no_error = write_reader_list(&stream, &reply, &*u_readers);
                                 }
                             }
                             _ => {
-                                println!("Auto connect is working right now.");
+                                info!("Auto connect is working right now.");
                                 sound.notify_custom(SoundType::StartupInProgress);
-                                no_error = write_error(&stream, errors::Errors::StartingUp)
+                                no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                             }
                         }
                     } else {
-                        println!("Auto connect is working right now.");
+                        info!("Auto connect is working right now.");
                         sound.notify_custom(SoundType::StartupInProgress);
-                        no_error = write_error(&stream, errors::Errors::StartingUp)
+                        no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                     }
                 },
                 requests::Request::ReaderConnect { id } | requests::Request::ReaderStart { id } => {
@@ -800,8 +1085,7 @@ fn handle_stream(
                                                     String::from(old_reader.ip_address()),
                                                     old_reader.port(),
                                                     old_reader.auto_connect(),
-                                                    control_sockets.clone(),
-                                                    read_repeaters.clone(),
+                                                    event_bus.clone(),
                                                     sight_processor.clone(),
                                                     screen.clone(),
                                                     readers.clone(),
@@ -810,8 +1094,7 @@ fn handle_stream(
                                                         let reconnector = Reconnector::new(
                                                             readers.clone(),
                                                             joiners.clone(),
-                                                            control_sockets.clone(),
-                                                            read_repeaters.clone(),
+                                                            event_bus.clone(),
                                                             sight_processor.clone(),
                                                             control.clone(),
                                                             sqlite.clone(),
@@ -835,8 +1118,8 @@ fn handle_stream(
                                                                 }
                                                             },
                                                             Err(e) => {
-                                                                println!("Error connecting to reader: {e}");
-                                                                no_error = write_error(&stream, errors::Errors::ReaderConnection {
+                                                                error!("Error connecting to reader: {e}");
+                                                                no_error = write_error(&stream, &reply, errors::Errors::ReaderConnection {
                                                                     message: format!("error connecting to reader: {e}")
                                                                 });
                                                             }
@@ -845,29 +1128,29 @@ fn handle_stream(
                                                     },
                                                     Err(e) => {
                                                         u_readers.push(old_reader);
-                                                        no_error = write_error(&stream, errors::Errors::InvalidReaderType { message: e.to_string() });
+                                                        no_error = write_error(&stream, &reply, errors::Errors::InvalidReaderType { message: e.to_string() });
                                                     }
                                                 };
                                             } else {
-                                                no_error = write_error(&stream, errors::Errors::AlreadyRunning);
+                                                no_error = write_error(&stream, &reply, errors::Errors::AlreadyRunning);
                                             }
                                         },
                                         None => {
-                                            no_error = write_error(&stream, errors::Errors::NotFound);
+                                            no_error = write_error(&stream, &reply, errors::Errors::NotFound);
                                         }
                                     };
                                 }
                             }
                             _ => {
-                                println!("Auto connect is working right now.");
+                                info!("Auto connect is working right now.");
                                 sound.notify_custom(SoundType::StartupInProgress);
-                                no_error = write_error(&stream, errors::Errors::StartingUp)
+                                no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                             }
                         }
                     } else {
-                        println!("Auto connect is working right now.");
+                        info!("Auto connect is working right now.");
                         sound.notify_custom(SoundType::StartupInProgress);
-                        no_error = write_error(&stream, errors::Errors::StartingUp)
+                        no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                     }
                 },
                 requests::Request::ReaderDisconnect { id } | requests::Request::ReaderStop { id }  => {
@@ -882,8 +1165,8 @@ fn handle_stream(
                                             match reader.stop() {
                                                 Ok(_) => {},
                                                 Err(e) => {
-                                                    println!("Error connecting to reader: {e}");
-                                                    no_error = write_error(&stream, errors::Errors::ReaderConnection {
+                                                    error!("Error connecting to reader: {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::ReaderConnection {
                                                         message: format!("error stopping reader: {e}")
                                                     });
                                                 }
@@ -891,8 +1174,8 @@ fn handle_stream(
                                             match reader.disconnect() {
                                                 Ok(_) => {},
                                                 Err(e) => {
-                                                    println!("Error connecting to reader: {e}");
-                                                    no_error = write_error(&stream, errors::Errors::ReaderConnection {
+                                                    error!("Error connecting to reader: {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::ReaderConnection {
                                                         message: format!("error disconnecting reader: {e}")
                                                     });
                                                 }
@@ -900,31 +1183,24 @@ fn handle_stream(
                                             u_readers.push(reader);
                                         },
                                         None => {
-                                            no_error = write_error(&stream, errors::Errors::NotFound);
+                                            no_error = write_error(&stream, &reply, errors::Errors::NotFound);
                                         }
                                     };
                                     thread::sleep(Duration::from_millis(CONNECTION_CHANGE_PAUSE));
-                                    if let Ok(c_socks) = control_sockets.lock() {
-                                        for sock in c_socks.iter() {
-                                            if let Some(sock) = sock {
-                                                no_error = write_reader_list(&sock, &*u_readers) && no_error;
-                                            }
-                                        }
-                                    } else {
-                                        no_error = write_reader_list(&stream, &*u_readers) && no_error;
-                                    }
+                                    event_bus.publish_except(index, events::Event::ReaderList(get_reader_list(&u_readers)));
+                                    no_error = write_reader_list(&stream, &reply, &*u_readers) && no_error;
                                 }
                             }
                             _ => {
-                                println!("Auto connect is working right now.");
+                                info!("Auto connect is working right now.");
                                 sound.notify_custom(SoundType::StartupInProgress);
-                                no_error = write_error(&stream, errors::Errors::StartingUp)
+                                no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                             }
                         }
                     } else {
-                        println!("Auto connect is working right now.");
+                        info!("Auto connect is working right now.");
                         sound.notify_custom(SoundType::StartupInProgress);
-                        no_error = write_error(&stream, errors::Errors::StartingUp)
+                        no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                     }
                 },
                 requests::Request::ReaderStartAll => { // START ALL
@@ -937,16 +1213,14 @@ fn handle_stream(
                                     for ix in (0..u_readers.len()).rev() {
                                         let mut reader = u_readers.remove(ix);
                                         if reader.is_connected() != Some(true) {
-                                            reader.set_control_sockets(control_sockets.clone());
+                                            reader.set_event_bus(event_bus.clone());
                                             reader.set_readers(readers.clone());
-                                            reader.set_read_repeaters(read_repeaters.clone());
                                             reader.set_sight_processor(sight_processor.clone());
                                             reader.set_screen(screen.clone());
                                             let reconnector = Reconnector::new(
                                                 readers.clone(),
                                                 joiners.clone(),
-                                                control_sockets.clone(),
-                                                read_repeaters.clone(),
+                                                event_bus.clone(),
                                                 sight_processor.clone(),
                                                 control.clone(),
                                                 sqlite.clone(),
@@ -970,8 +1244,8 @@ fn handle_stream(
                                                     }
                                                 },
                                                 Err(e) => {
-                                                    println!("Error connecting to reader: {e}");
-                                                    no_error = write_error(&stream, errors::Errors::ReaderConnection {
+                                                    error!("Error connecting to reader: {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::ReaderConnection {
                                                         message: format!("error connecting to reader: {e}")
                                                     });
                                                 }
@@ -980,27 +1254,20 @@ fn handle_stream(
                                         u_readers.push(reader);
                                     }
                                     thread::sleep(Duration::from_millis(CONNECTION_CHANGE_PAUSE));
-                                    if let Ok(c_socks) = control_sockets.lock() {
-                                        for sock in c_socks.iter() {
-                                            if let Some(sock) = sock {
-                                                no_error = write_reader_list(&sock, &*u_readers) && no_error;
-                                            }
-                                        }
-                                    } else {
-                                        no_error = write_reader_list(&stream, &*u_readers) && no_error;
-                                    }
+                                    event_bus.publish_except(index, events::Event::ReaderList(get_reader_list(&u_readers)));
+                                    no_error = write_reader_list(&stream, &reply, &*u_readers) && no_error;
                                 }
                             },
                             _ => {
-                                println!("Auto connect is working right now.");
+                                info!("Auto connect is working right now.");
                                 sound.notify_custom(SoundType::StartupInProgress);
-                                no_error = write_error(&stream, errors::Errors::StartingUp);
+                                no_error = write_error(&stream, &reply, errors::Errors::StartingUp);
                             }
                         }
                     } else {
-                        println!("Auto connect is working right now.");
+                        info!("Auto connect is working right now.");
                         sound.notify_custom(SoundType::StartupInProgress);
-                        no_error = write_error(&stream, errors::Errors::StartingUp)
+                        no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                     }
                 },
                 requests::Request::ReaderStopAll => {  // STOP ALL
@@ -1015,8 +1282,8 @@ fn handle_stream(
                                             match reader.stop() {
                                                 Ok(_) => {},
                                                 Err(e) => {
-                                                    println!("Error connecting to reader: {e}");
-                                                    no_error = write_error(&stream, errors::Errors::ReaderConnection {
+                                                    error!("Error connecting to reader: {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::ReaderConnection {
                                                         message: format!("error stopping reader: {e}")
                                                     });
                                                 }
@@ -1026,8 +1293,8 @@ fn handle_stream(
                                             match reader.disconnect() {
                                                 Ok(_) => {},
                                                 Err(e) => {
-                                                    println!("Error connecting to reader: {e}");
-                                                    no_error = write_error(&stream, errors::Errors::ReaderConnection {
+                                                    error!("Error connecting to reader: {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::ReaderConnection {
                                                         message: format!("error discconnecting reader: {e}")
                                                     });
                                                 }
@@ -1036,37 +1303,30 @@ fn handle_stream(
                                         u_readers.push(reader);
                                     }
                                     thread::sleep(Duration::from_millis(CONNECTION_CHANGE_PAUSE));
-                                    if let Ok(c_socks) = control_sockets.lock() {
-                                        for sock in c_socks.iter() {
-                                            if let Some(sock) = sock {
-                                                no_error = write_reader_list(&sock, &*u_readers) && no_error;
-                                            }
-                                        }
-                                    } else {
-                                        no_error = write_reader_list(&stream, &*u_readers) && no_error;
-                                    }
+                                    event_bus.publish_except(index, events::Event::ReaderList(get_reader_list(&u_readers)));
+                                    no_error = write_reader_list(&stream, &reply, &*u_readers) && no_error;
                                 }
                             },
                             _ => {
-                                println!("Auto connect is working right now.");
+                                info!("Auto connect is working right now.");
                                 sound.notify_custom(SoundType::StartupInProgress);
-                                no_error = write_error(&stream, errors::Errors::StartingUp)
+                                no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                             },
                         }
                     } else {
-                        println!("Auto connect is working right now.");
+                        info!("Auto connect is working right now.");
                         sound.notify_custom(SoundType::StartupInProgress);
-                        no_error = write_error(&stream, errors::Errors::StartingUp)
+                        no_error = write_error(&stream, &reply, errors::Errors::StartingUp)
                     }
                 },
                 requests::Request::ReaderGetAll => {
                     if let Ok(u_readers) = readers.lock() {
-                        no_error = write_reader_list(&stream, &*u_readers) && no_error;
+                        no_error = write_reader_list(&stream, &reply, &*u_readers) && no_error;
                     }
                 }
                 requests::Request::SettingsGet => {
                     if let Ok(sq) = sqlite.lock() {
-                        no_error = write_settings(&stream, &get_settings(&sq));
+                        no_error = write_settings(&stream, &reply, &get_settings(&sq));
                     }
                 },
                 requests::Request::SettingsGetAll => {
@@ -1075,14 +1335,14 @@ fn handle_stream(
                         match sq.get_apis() {
                             Ok(apis) => {
                                 if let Ok(u_readers) = readers.lock() {
-                                    no_error = write_all_settings(&stream, &settings, &*u_readers, &apis, uploader.status());
+                                    no_error = write_all_settings(&stream, &reply, &settings, &*u_readers, &apis, uploader.status());
                                 } else {
-                                    no_error = write_error(&stream, errors::Errors::ServerError { message: String::from("error getting the readers mutex") });
+                                    no_error = write_error(&stream, &reply, errors::Errors::ServerError { message: String::from("error getting the readers mutex") });
                                 }
                             },
                             Err(e) => {
-                                println!("error getting api list. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("error getting api list. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting api list: {e}")
                                 });
                             }
@@ -1094,13 +1354,14 @@ fn handle_stream(
                         let old_volume = control.volume;
                         let old_play_sound = control.play_sound;
                         let old_voice = control.sound_board.get_voice();
+                        let old_log_level = control.log_level.clone();
                         let mut custom_error = false;
                         for setting in settings {
                             match setting.name() {
                                 super::SETTING_VOICE => {
                                     let new_voice = Voice::from_str(setting.value());
                                     if new_voice == Voice::Custom && !control.sound_board.custom_available() {
-                                        println!("Custom voice selected but not available.");
+                                        warn!("Custom voice selected but not available.");
                                         custom_error = true;
                                     } else {
                                         if let Ok(sq) = sqlite.lock() {
@@ -1110,12 +1371,12 @@ fn handle_stream(
                                                         _ = control.update(new_control);
                                                     } else {
                                                         let settings = get_settings(&sq);
-                                                        no_error = write_settings(&stream, &settings);
+                                                        no_error = write_settings(&stream, &reply, &settings);
                                                     }
                                                 },
                                                 Err(e) => {
-                                                    println!("Error saving setting. {e}");
-                                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                                    error!("Error saving setting. {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                         message: format!("error saving setting: {e}")
                                                     });
                                                 }
@@ -1123,6 +1384,17 @@ fn handle_stream(
                                         }
                                     }
                                 },
+                                super::SETTING_UNKNOWN_CHIP_POLICY if !processor::is_unknown_chip_policy(setting.value()) => {
+                                    no_error = write_error(&stream, &reply, errors::Errors::InvalidSetting {
+                                        message: format!("invalid unknown chip policy: {}", setting.value())
+                                    });
+                                },
+                                super::SETTING_LOG_LEVEL if logging::parse_spec(setting.value()).is_err() => {
+                                    let message = logging::parse_spec(setting.value()).err().unwrap_or_default();
+                                    no_error = write_error(&stream, &reply, errors::Errors::InvalidSetting {
+                                        message: format!("invalid log level: {message}")
+                                    });
+                                },
                                 super::SETTING_CHIP_TYPE |
                                 super::SETTING_PORTAL_NAME |
                                 super::SETTING_READ_WINDOW |
@@ -1134,7 +1406,42 @@ fn handle_stream(
                                 super::SETTING_NTFY_USER |
                                 super::SETTING_NTFY_PASS |
                                 super::SETTING_NTFY_TOPIC | 
-                                super::SETTING_ENABLE_NTFY => {
+                                super::SETTING_ENABLE_NTFY |
+                                super::SETTING_MQTT_URL |
+                                super::SETTING_MQTT_USER |
+                                super::SETTING_MQTT_PASS |
+                                super::SETTING_MQTT_TOPIC |
+                                super::SETTING_MQTT_QOS |
+                                super::SETTING_ENABLE_MQTT |
+                                super::SETTING_ENABLE_OUTPUT |
+                                super::SETTING_OUTPUT_PORT |
+                                super::SETTING_OUTPUT_FORMAT |
+                                super::SETTING_ENABLE_RELAY |
+                                super::SETTING_RELAY_PORT |
+                                super::SETTING_UPLOAD_BATCH_SIZE |
+                                super::SETTING_CONNECTIVITY_CHECK_URL |
+                                super::SETTING_ENABLE_PARTICIPANT_SYNC |
+                                super::SETTING_PARTICIPANT_SYNC_API |
+                                super::SETTING_PARTICIPANT_SYNC_SLUG |
+                                super::SETTING_PARTICIPANT_SYNC_YEAR |
+                                super::SETTING_PARTICIPANT_SYNC_INTERVAL |
+                                super::SETTING_HTTP_PROXY_URL |
+                                super::SETTING_HTTP_PROXY_USER |
+                                super::SETTING_HTTP_PROXY_PASS |
+                                super::SETTING_HTTP_CA_BUNDLE |
+                                super::SETTING_HTTP_TIMEOUT |
+                                super::SETTING_HTTP_CONNECT_TIMEOUT |
+                                super::SETTING_ENABLE_SIGHTING_UPLOAD |
+                                super::SETTING_SILENT_ANTENNA_SECONDS |
+                                super::SETTING_LOG_LEVEL |
+                                super::SETTING_ENABLE_SMS |
+                                super::SETTING_SMS_URL |
+                                super::SETTING_SMS_TOKEN |
+                                super::SETTING_SMS_FROM |
+                                super::SETTING_SMS_TEMPLATE |
+                                super::SETTING_SMS_INTERVAL |
+                                super::SETTING_SMS_HOURLY_LIMIT |
+                                super::SETTING_UNKNOWN_CHIP_POLICY => {
                                     if let Ok(sq) = sqlite.lock() {
                                         match sq.set_setting(&setting) {
                                             Ok(_) => {
@@ -1143,12 +1450,12 @@ fn handle_stream(
                                                     
                                                 } else {
                                                     let settings = get_settings(&sq);
-                                                    no_error = write_settings(&stream, &settings);
+                                                    no_error = write_settings(&stream, &reply, &settings);
                                                 }
                                             },
                                             Err(e) => {
-                                                println!("Error saving setting. {e}");
-                                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                                error!("Error saving setting. {e}");
+                                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                     message: format!("error saving setting: {e}")
                                                 });
                                             }
@@ -1156,8 +1463,8 @@ fn handle_stream(
                                     }
                                 },
                                 other => {
-                                    println!("'{other}' is not a valid setting");
-                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                    warn!("'{other}' is not a valid setting");
+                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                         message: format!("'{other}' is not a valid setting")
                                     });
                                 }
@@ -1165,14 +1472,12 @@ fn handle_stream(
                         }
                         if let Ok(sq) = sqlite.lock() {
                             let settings = get_settings(&sq);
-                            if let Ok(c_socks) = control_sockets.lock() {
-                                for sock in c_socks.iter() {
-                                    if let Some(sock) = sock {
-                                        // we might be writing to other sockets
-                                        // so errors here shouldn't close our connection
-                                        _ = write_settings(&sock, &settings);
-                                    }
-                                }
+                            event_bus.publish_except(index, events::Event::Settings(settings.clone()));
+                            no_error = write_settings(&stream, &reply, &settings) && no_error;
+                        }
+                        if old_log_level != control.log_level {
+                            if let Err(e) = logging::set_level(&control.log_level) {
+                                warn!("Unable to change the log level. {e}");
                             }
                         }
                         if custom_error && control.play_sound {
@@ -1186,21 +1491,35 @@ fn handle_stream(
                     } else {
                         if let Ok(sq) = sqlite.lock() {
                             let settings = get_settings(&sq);
-                            no_error = write_settings(&stream, &settings);
+                            no_error = write_settings(&stream, &reply, &settings);
                         }
                     }
                 },
                 requests::Request::Quit => {
+                    database::log_event(
+                        &sqlite,
+                        system_event::SEVERITY_INFO,
+                        system_event::SOURCE_CONTROL,
+                        system_event::SYSTEM_EVENT_SHUTDOWN,
+                        serde_json::json!({ "reason": "quit" }),
+                    );
                     if let Ok(mut ka) = keepalive.lock() {
-                        println!("Starting program stop sequence.");
+                        info!("Starting program stop sequence.");
                         *ka = false;
                     }
                     // connect to ensure the spawning thread will exit the accept call
                     _ = TcpStream::connect(format!("127.0.0.1:{}", control_port));
                 },
                 requests::Request::Shutdown => {
+                    database::log_event(
+                        &sqlite,
+                        system_event::SEVERITY_INFO,
+                        system_event::SOURCE_CONTROL,
+                        system_event::SYSTEM_EVENT_SHUTDOWN,
+                        serde_json::json!({ "reason": "shutdown" }),
+                    );
                     if let Ok(mut ka) = keepalive.lock() {
-                        println!("Starting program stop sequence.");
+                        info!("Starting program stop sequence.");
                         *ka = false;
                     }
                     // play a shutdown command since the shutdown 
@@ -1210,28 +1529,35 @@ fn handle_stream(
                         }
                     }
                     // send shutdown command to the OS
-                    println!("Sending OS shutdown command if on Linux.");
+                    info!("Sending OS shutdown command if on Linux.");
                     match std::env::consts::OS {
                         "linux" => {
                             match std::process::Command::new("sudo").arg("shutdown").arg("-h").arg("now").spawn() {
                                 Ok(_) => {
-                                    println!("Shutdown command sent to OS successfully.");
+                                    info!("Shutdown command sent to OS successfully.");
                                 },
                                 Err(e) => {
-                                    println!("Error sending shutdown command: {e}");
+                                    error!("Error sending shutdown command: {e}");
                                 }
                             }
                         },
                         other => {
-                            println!("Shutdown not supported on this platform ({other})");
+                            warn!("Shutdown not supported on this platform ({other})");
                         }
                     }
                     // connect to ensure the spawning thread will exit the accept call
                     _ = TcpStream::connect(format!("127.0.0.1:{}", control_port));
                 },
                 requests::Request::Restart => {
+                    database::log_event(
+                        &sqlite,
+                        system_event::SEVERITY_INFO,
+                        system_event::SOURCE_CONTROL,
+                        system_event::SYSTEM_EVENT_SHUTDOWN,
+                        serde_json::json!({ "reason": "restart" }),
+                    );
                     if let Ok(mut ka) = keepalive.lock() {
-                        println!("Starting program stop sequence.");
+                        info!("Starting program stop sequence.");
                         *ka = false;
                     }
                     if let Ok(control) = control.lock() {
@@ -1239,20 +1565,20 @@ fn handle_stream(
                             control.sound_board.play_shutdown(control.volume);
                         }
                     }
-                    println!("Sending restart command if on Linux.");
+                    info!("Sending restart command if on Linux.");
                     match std::env::consts::OS {
                         "linux" => {
                             match std::process::Command::new("sudo").arg("systemctl").arg("restart").arg("portal").spawn() {
                                 Ok(_) => {
-                                    println!("Restart command sent to OS successfully.");
+                                    info!("Restart command sent to OS successfully.");
                                 },
                                 Err(e) => {
-                                    println!("Error sending restart command: {e}");
+                                    error!("Error sending restart command: {e}");
                                 }
                             }
                         },
                         other => {
-                            println!("Restart not supported on this platform ({other})");
+                            warn!("Restart not supported on this platform ({other})");
                         }
                     }
                     // connect to ensure the spawning thread will exit the accept call
@@ -1262,11 +1588,11 @@ fn handle_stream(
                     if let Ok(sq) = sqlite.lock() {
                         match sq.get_apis() {
                             Ok(apis) => {
-                                no_error = write_api_list(&stream, &apis);
+                                no_error = write_api_list(&stream, &reply, &apis);
                             },
                             Err(e) => {
-                                println!("error getting api list. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("error getting api list. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting api list: {e}")
                                 });
                             }
@@ -1291,16 +1617,9 @@ fn handle_stream(
                                 }
                                 match sq.get_apis() {
                                     Ok(apis) => {
-                                        let mut remote_exists = false;
-                                        for api in apis {
-                                            if (api.kind() == api::API_TYPE_CHRONOKEEP_REMOTE || api.kind() == api::API_TYPE_CHRONOKEEP_REMOTE_SELF) && api.id() != id  {
-                                                remote_exists = true;
-                                                break;
-                                            }
-                                        }
-                                        if remote_exists {
-                                            println!("Remote api already exists.");
-                                            no_error = write_error(&stream, errors::Errors::TooManyRemoteApi)
+                                        if remote_conflicts(&apis, id, &t_uri, &token) {
+                                            warn!("Remote api already exists.");
+                                            no_error = write_error(&stream, &reply, errors::Errors::TooManyRemoteApi)
                                         } else {
                                             match sq.save_api(&api::Api::new(
                                                 id,
@@ -1312,29 +1631,20 @@ fn handle_stream(
                                                 Ok(_) => {
                                                     match sq.get_apis() {
                                                         Ok(apis) => {
-                                                            if let Ok(c_socks) = control_sockets.lock() {
-                                                                for sock in c_socks.iter() {
-                                                                    if let Some(sock) = sock {
-                                                                        // we might be writing to other sockets
-                                                                        // so errors here shouldn't close our connection
-                                                                        _ = write_api_list(&sock, &apis);
-                                                                    }
-                                                                }
-                                                            } else {
-                                                                no_error = write_api_list(&stream, &apis);
-                                                            }
+                                                            event_bus.publish_except(index, events::Event::ApiList(apis.clone()));
+                                                            no_error = write_api_list(&stream, &reply, &apis);
                                                         },
                                                         Err(e) => {
-                                                            println!("error getting api list. {e}");
-                                                            no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                                            error!("error getting api list. {e}");
+                                                            no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                                 message: format!("error getting api list: {e}")
                                                             });
                                                         }
                                                     }
                                                 },
                                                 Err(e) => {
-                                                    println!("Error saving api {e}");
-                                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                                    error!("Error saving api {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                         message: format!("error saving api: {e}")
                                                     });
                                                 }
@@ -1342,8 +1652,8 @@ fn handle_stream(
                                         }
                                     }
                                     Err(e) => {
-                                        println!("error getting api list. {e}");
-                                        no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                        error!("error getting api list. {e}");
+                                        no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                             message: format!("error getting apis: {e}")
                                         })
                                     }
@@ -1351,7 +1661,9 @@ fn handle_stream(
                             }
                         },
                         api::API_TYPE_CHRONOKEEP_RESULTS |
-                        api::API_TYPE_CHRONOKEEP_RESULTS_SELF => {
+                        api::API_TYPE_CHRONOKEEP_RESULTS_SELF |
+                        api::API_TYPE_WEBHOOK |
+                        api::API_TYPE_RELAY_SOURCE => {
                             if let Ok(sq) = sqlite.lock() {
                                 let mut t_uri = match kind.as_str() {
                                     api::API_TYPE_CHRONOKEEP_RESULTS => {
@@ -1361,7 +1673,8 @@ fn handle_stream(
                                         uri
                                     }
                                 };
-                                if !t_uri.ends_with("/") {
+                                // webhooks get posted to exactly what they've given us, relay sources aren't posted to
+                                if !t_uri.ends_with("/") && kind != api::API_TYPE_WEBHOOK && kind != api::API_TYPE_RELAY_SOURCE {
                                     t_uri = format!("{t_uri}/")
                                 }
                                 match sq.save_api(&api::Api::new(
@@ -1374,29 +1687,20 @@ fn handle_stream(
                                     Ok(_) => {
                                         match sq.get_apis() {
                                             Ok(apis) => {
-                                                if let Ok(c_socks) = control_sockets.lock() {
-                                                    for sock in c_socks.iter() {
-                                                        if let Some(sock) = sock {
-                                                            // we might be writing to other sockets
-                                                            // so errors here shouldn't close our connection
-                                                            _ = write_api_list(&sock, &apis);
-                                                        }
-                                                    }
-                                                } else {
-                                                    no_error = write_api_list(&stream, &apis);
-                                                }
+                                                event_bus.publish_except(index, events::Event::ApiList(apis.clone()));
+                                                no_error = write_api_list(&stream, &reply, &apis);
                                             },
                                             Err(e) => {
-                                                println!("error getting api list. {e}");
-                                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                                error!("error getting api list. {e}");
+                                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                     message: format!("error getting api list: {e}")
                                                 });
                                             }
                                         }
                                     },
                                     Err(e) => {
-                                        println!("Error saving api {e}");
-                                        no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                        error!("Error saving api {e}");
+                                        no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                             message: format!("error saving api {e}")
                                         });
                                     }
@@ -1404,8 +1708,8 @@ fn handle_stream(
                             }
                         },
                         other => {
-                            println!("'{other}' is not a valid api type");
-                            no_error = write_error(&stream, errors::Errors::InvalidApiType {
+                            warn!("'{other}' is not a valid api type");
+                            no_error = write_error(&stream, &reply, errors::Errors::InvalidApiType {
                                 message: format!("'{other}' is not a valid api type")
                             });
                         }
@@ -1413,39 +1717,39 @@ fn handle_stream(
                 },
                 requests::Request::ApiSaveAll { list } => {
                     if let Ok(sq) = sqlite.lock() {
-                        let mut remote_api: Option<Api> = None;
-                        // check if we have a remote api already set, there can only be one
+                        let mut remote_apis: Vec<Api> = Vec::new();
+                        // check if we have any remote apis already set so we don't add one twice
                         match sq.get_apis() {
                             Ok(apis) => {
                                 for api in apis {
                                     if api.kind() == api::API_TYPE_CHRONOKEEP_REMOTE || api.kind() == api::API_TYPE_CHRONOKEEP_REMOTE_SELF {
-                                        remote_api = Some(api);
-                                        break;
+                                        remote_apis.push(api);
                                     }
                                 }
                             },
                             Err(e) => {
-                                println!("error getting api list. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("error getting api list. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting apis: {e}")
                                 })
                             }
                         }
-                        // if we've got a remote api set we need to check if there is another
-                        if let Some(remote) = remote_api {
+                        // if we've got remote apis set we need to check they aren't being added again
+                        if !remote_apis.is_empty() {
                             let mut remote_exists = false;
                             let mut invalid_type = false;
                             for api in &list {
                                 match api.kind() {
                                     api::API_TYPE_CHRONOKEEP_REMOTE |
                                     api::API_TYPE_CHRONOKEEP_REMOTE_SELF => {
-                                        // if the id's don't match then they're trying to save a second
-                                        if remote.id() != api.id() {
+                                        if remote_conflicts(&remote_apis, api.id(), api.uri(), api.token()) {
                                             remote_exists = true;
                                         }
                                     },
                                     api::API_TYPE_CHRONOKEEP_RESULTS |
-                                    api::API_TYPE_CHRONOKEEP_RESULTS_SELF => {},
+                                    api::API_TYPE_CHRONOKEEP_RESULTS_SELF |
+                                    api::API_TYPE_WEBHOOK |
+                                    api::API_TYPE_RELAY_SOURCE => {},
                                     _ => {
                                         invalid_type = true;
                                     }
@@ -1453,12 +1757,12 @@ fn handle_stream(
                             }
                             // if we found a duplicate remote, don't save and write error
                             if remote_exists {
-                                println!("Remote api already exists.");
-                                no_error = write_error(&stream, errors::Errors::TooManyRemoteApi);
+                                warn!("Remote api already exists.");
+                                no_error = write_error(&stream, &reply, errors::Errors::TooManyRemoteApi);
                             // if there's an invalid type, don't save and write error
                             } else if invalid_type {
-                                println!("One or more invalid api types found.");
-                                no_error = write_error(&stream, errors::Errors::InvalidApiType { message: String::from("one or more invalid api types found") });
+                                warn!("One or more invalid api types found.");
+                                no_error = write_error(&stream, &reply, errors::Errors::InvalidApiType { message: String::from("one or more invalid api types found") });
                             // all are saveable
                             } else {
                                 let mut error_saving = false;
@@ -1472,7 +1776,7 @@ fn handle_stream(
                                             String::from(api.uri())
                                         }
                                     };
-                                    if !t_uri.ends_with("/") {
+                                    if !t_uri.ends_with("/") && api.kind() != api::API_TYPE_WEBHOOK && api.kind() != api::API_TYPE_RELAY_SOURCE {
                                         t_uri = format!("{t_uri}/")
                                     }
                                     match sq.save_api(&api::Api::new(
@@ -1490,29 +1794,20 @@ fn handle_stream(
                                 }
                                 // write an error message if we had an issue
                                 if error_saving {
-                                    println!("Error saving one or more apis");
-                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                    error!("Error saving one or more apis");
+                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                         message: String::from("error saving one or more apis")
                                     });
                                 // otherwise send everyone connected the updated list of apis
                                 } else {
                                     match sq.get_apis() {
                                         Ok(apis) => {
-                                            if let Ok(c_socks) = control_sockets.lock() {
-                                                for sock in c_socks.iter() {
-                                                    if let Some(sock) = sock {
-                                                        // we might be writing to other sockets
-                                                        // so errors here shouldn't close our connection
-                                                        _ = write_api_list(&sock, &apis);
-                                                    }
-                                                }
-                                            } else {
-                                                no_error = write_api_list(&stream, &apis);
-                                            }
+                                            event_bus.publish_except(index, events::Event::ApiList(apis.clone()));
+                                            no_error = write_api_list(&stream, &reply, &apis) && no_error;
                                         },
                                         Err(e) => {
-                                            println!("error getting api list. {e}");
-                                            no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                            error!("error getting api list. {e}");
+                                            no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                 message: format!("error getting api list: {e}")
                                             });
                                         }
@@ -1521,29 +1816,33 @@ fn handle_stream(
                             }
                         // no previous remote api found
                         } else {
-                            let mut remote_count = 0;
+                            let mut remote_exists = false;
                             let mut invalid_type = false;
-                            for api in &list {
+                            for (ix, api) in list.iter().enumerate() {
                                 match api.kind() {
                                     api::API_TYPE_CHRONOKEEP_REMOTE |
                                     api::API_TYPE_CHRONOKEEP_REMOTE_SELF => {
-                                        remote_count += 1
+                                        if remote_conflicts(&list[..ix], 0, api.uri(), api.token()) {
+                                            remote_exists = true;
+                                        }
                                     },
                                     api::API_TYPE_CHRONOKEEP_RESULTS |
-                                    api::API_TYPE_CHRONOKEEP_RESULTS_SELF => {},
+                                    api::API_TYPE_CHRONOKEEP_RESULTS_SELF |
+                                    api::API_TYPE_WEBHOOK |
+                                    api::API_TYPE_RELAY_SOURCE => {},
                                     _ => {
                                         invalid_type = true;
                                     }
                                 }
                             }
                             // if we found a duplicate remote, don't save and write error
-                            if remote_count > 1 {
-                                println!("Remote api already exists.");
-                                no_error = write_error(&stream, errors::Errors::TooManyRemoteApi);
+                            if remote_exists {
+                                warn!("Remote api already exists.");
+                                no_error = write_error(&stream, &reply, errors::Errors::TooManyRemoteApi);
                             // if there's an invalid type, don't save and write error
                             } else if invalid_type {
-                                println!("One or more invalid api types found.");
-                                no_error = write_error(&stream, errors::Errors::InvalidApiType { message: String::from("one or more invalid api types found") });
+                                warn!("One or more invalid api types found.");
+                                no_error = write_error(&stream, &reply, errors::Errors::InvalidApiType { message: String::from("one or more invalid api types found") });
                             // all are saveable
                             } else {
                                 let mut error_saving = false;
@@ -1558,29 +1857,20 @@ fn handle_stream(
                                 }
                                 // write an error message if we had an issue
                                 if error_saving {
-                                    println!("Error saving one or more apis");
-                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                    error!("Error saving one or more apis");
+                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                         message: String::from("error saving one or more apis")
                                     });
                                 // otherwise send everyone connected the updated list of apis
                                 } else {
                                     match sq.get_apis() {
                                         Ok(apis) => {
-                                            if let Ok(c_socks) = control_sockets.lock() {
-                                                for sock in c_socks.iter() {
-                                                    if let Some(sock) = sock {
-                                                        // we might be writing to other sockets
-                                                        // so errors here shouldn't close our connection
-                                                        _ = write_api_list(&sock, &apis);
-                                                    }
-                                                }
-                                            } else {
-                                                no_error = write_api_list(&stream, &apis);
-                                            }
+                                            event_bus.publish_except(index, events::Event::ApiList(apis.clone()));
+                                            no_error = write_api_list(&stream, &reply, &apis);
                                         },
                                         Err(e) => {
-                                            println!("error getting api list. {e}");
-                                            no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                            error!("error getting api list. {e}");
+                                            no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                 message: format!("error getting api list: {e}")
                                             });
                                         }
@@ -1596,29 +1886,20 @@ fn handle_stream(
                             Ok(_) => {
                                 match sq.get_apis() {
                                     Ok(apis) => {
-                                        if let Ok(c_socks) = control_sockets.lock() {
-                                            for sock in c_socks.iter() {
-                                                if let Some(sock) = sock {
-                                                    // we might be writing to other sockets
-                                                    // so errors here shouldn't close our connection
-                                                    _ = write_api_list(&sock, &apis);
-                                                }
-                                            }
-                                        } else {
-                                            no_error = write_api_list(&stream, &apis);
-                                        }
+                                        event_bus.publish_except(index, events::Event::ApiList(apis.clone()));
+                                        no_error = write_api_list(&stream, &reply, &apis);
                                     },
                                     Err(e) => {
-                                        println!("error getting api list. {e}");
-                                        no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                        error!("error getting api list. {e}");
+                                        no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                             message: format!("error getting api list: {e}")
                                         });
                                     }
                                 }
                             },
                             Err(e) => {
-                                println!("Error deleting api {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("Error deleting api {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error deleting api: {e}")
                                 });
                             }
@@ -1627,52 +1908,49 @@ fn handle_stream(
                 },
                 requests::Request::ApiRemoteManualUpload => {
                     let mut to_upload: Vec<read::Read> = Vec::new();
-                    let mut upload_api: Option<api::Api> = None;
+                    let mut upload_apis: Vec<api::Api> = Vec::new();
                     if let Ok(sq) = sqlite.lock() {
                         match sq.get_apis() {
                             Ok(apis) => {
-                                let mut found = false;
-                                for api in apis {
-                                    if api.kind() == api::API_TYPE_CHRONOKEEP_REMOTE || api.kind() == api::API_TYPE_CHRONOKEEP_REMOTE_SELF {
-                                        found = true;
-                                        upload_api = Some(api.clone());
-                                        //println!("Uploading reads to {}", api.nickname());
-                                        // this request will upload all reads regardless of whether or not they've been uploaded previously
-                                        match sq.get_all_reads() {
-                                            Ok(mut reads) => {
-                                                to_upload.append(&mut reads);
-                                            },
-                                            Err(e) => {
-                                                println!("Error geting reads to upload. {e}");
-                                                no_error = write_error(&stream, errors::Errors::DatabaseError { message: format!("error getting reads to upload: {e}") });
-                                                break;
-                                            }
-                                        };
-                                        // remote api found, so break the loop looking for it
-                                        break;
-                                    }
-                                }
-                                if found == false {
-                                    no_error = write_error(&stream, errors::Errors::NoRemoteApi);
+                                upload_apis = apis.into_iter()
+                                    .filter(|a| a.kind() == api::API_TYPE_CHRONOKEEP_REMOTE || a.kind() == api::API_TYPE_CHRONOKEEP_REMOTE_SELF)
+                                    .collect();
+                                if upload_apis.is_empty() {
+                                    no_error = write_error(&stream, &reply, errors::Errors::NoRemoteApi);
+                                } else {
+                                    // this request will upload all reads regardless of whether or not they've been uploaded previously
+                                    match sq.get_all_reads() {
+                                        Ok(mut reads) => {
+                                            to_upload.append(&mut reads);
+                                        },
+                                        Err(e) => {
+                                            error!("Error geting reads to upload. {e}");
+                                            no_error = write_error(&stream, &reply, errors::Errors::DatabaseError { message: format!("error getting reads to upload: {e}") });
+                                        }
+                                    };
                                 }
                             },
                             Err(e) => {
-                                println!("error getting apis: {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("error getting apis: {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting apis: {e}")
                                 });
                             }
                         }
                     }
-                    // upload any reads we found in the database if we found our remote API
-                    if let Some(api) = upload_api {
-                        if to_upload.len() > 0 {
-                            let (modified_reads, _) = remote_util::upload_all_reads(&http_client, &api, to_upload);
+                    // upload any reads we found in the database to every remote API
+                    if to_upload.len() > 0 {
+                        let batch_size = match control.lock() {
+                            Ok(c) => c.upload_batch_size,
+                            Err(_) => defaults::DEFAULT_UPLOAD_BATCH_SIZE,
+                        };
+                        for api in upload_apis {
+                            let (modified_reads, _) = remote_util::upload_all_reads(&http_client, &api, to_upload.clone(), batch_size);
                             if let Ok(mut sq) = sqlite.lock() {
-                                match sq.update_reads_status(&modified_reads) {
+                                match sq.save_uploaded(api.id(), &modified_reads) {
                                     Ok(_) => {},
                                     Err(e) => {
-                                        println!("Error updating uploaded reads: {e}");
+                                        error!("Error updating uploaded reads: {e}");
                                     }
                                 }
                             }
@@ -1683,7 +1961,7 @@ fn handle_stream(
                     match query {
                         AutoUploadQuery::Start => {
                             if uploader.running() {
-                                no_error = write_error(&stream, errors::Errors::AlreadyRunning);
+                                no_error = write_error(&stream, &reply, errors::Errors::AlreadyRunning);
                             } else {
                                 let t_uploader = uploader.clone();
                                 let t_joiner = thread::spawn(move|| {
@@ -1701,8 +1979,8 @@ fn handle_stream(
                                         };
                                     },
                                     Err(e) => {
-                                        println!("Error saving auto upload setting: {:?}", e);
-                                        no_error = write_error(&stream, errors::Errors::ServerError { message: String::from("error saving auto upload setting") });
+                                        error!("Error saving auto upload setting: {:?}", e);
+                                        no_error = write_error(&stream, &reply, errors::Errors::ServerError { message: String::from("error saving auto upload setting") });
                                     }
                                 }
                             };
@@ -1711,7 +1989,7 @@ fn handle_stream(
                             if uploader.running() {
                                 uploader.stop();
                             } else {
-                                no_error = write_error(&stream, errors::Errors::NotRunning);
+                                no_error = write_error(&stream, &reply, errors::Errors::NotRunning);
                             }
                             if let Ok(sq) = sqlite.lock() {
                                 match sq.set_setting(&Setting::new(String::from(SETTING_AUTO_REMOTE), String::from("false"))) {
@@ -1721,14 +1999,14 @@ fn handle_stream(
                                         };
                                     },
                                     Err(e) => {
-                                        println!("Error saving auto upload setting: {:?}", e);
-                                        no_error = write_error(&stream, errors::Errors::ServerError { message: String::from("error saving auto upload setting") });
+                                        error!("Error saving auto upload setting: {:?}", e);
+                                        no_error = write_error(&stream, &reply, errors::Errors::ServerError { message: String::from("error saving auto upload setting") });
                                     }
                                 }
                             };
                         }
                         AutoUploadQuery::Status => {
-                            no_error = write_uploader_status(&stream, uploader.status());
+                            no_error = write_uploader_status(&stream, &reply, uploader.status(), &uploader.destinations());
                         }
                     }
                 },
@@ -1741,25 +2019,25 @@ fn handle_stream(
                                         if api.kind() == api::API_TYPE_CHRONOKEEP_RESULTS || api.kind() == api::API_TYPE_CHRONOKEEP_RESULTS_SELF {
                                             no_error = match get_events(&http_client, api) {
                                                 Ok(events) => {
-                                                    write_event_list(&stream, events)
+                                                    write_event_list(&stream, &reply, events)
                                                 },
                                                 Err(e) => {
-                                                    println!("error getting events: {:?}", e);
-                                                    write_error(&stream, e)
+                                                    error!("error getting events: {:?}", e);
+                                                    write_error(&stream, &reply, e)
                                                 }
                                             };
                                         } else {
                                             let kind = api.kind();
-                                            println!("invalid api type specified: {kind}");
-                                            no_error = write_error(&stream, errors::Errors::InvalidApiType { message: String::from("expected Chronokeep results type") })
+                                            warn!("invalid api type specified: {kind}");
+                                            no_error = write_error(&stream, &reply, errors::Errors::InvalidApiType { message: String::from("expected Chronokeep results type") })
                                         }
                                         break;
                                     }
                                 }
                             },
                             Err(e) => {
-                                println!("error getting apis from database: {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("error getting apis from database: {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting participants from database: {e}")
                                 });
                             }
@@ -1775,25 +2053,25 @@ fn handle_stream(
                                         if api.kind() == api::API_TYPE_CHRONOKEEP_RESULTS || api.kind() == api::API_TYPE_CHRONOKEEP_RESULTS_SELF {
                                             no_error = match get_event_years(&http_client, api, event_slug) {
                                                 Ok(years) => {
-                                                    write_event_years(&stream, years)
+                                                    write_event_years(&stream, &reply, years)
                                                 },
                                                 Err(e) => {
-                                                    println!("error getting event years: {:?}", e);
-                                                    write_error(&stream, e)
+                                                    error!("error getting event years: {:?}", e);
+                                                    write_error(&stream, &reply, e)
                                                 }
                                             };
                                         } else {
                                             let kind = api.kind();
-                                            println!("invalid api type specified: {kind}");
-                                            no_error = write_error(&stream, errors::Errors::InvalidApiType { message: String::from("expected Chronokeep results type") })
+                                            warn!("invalid api type specified: {kind}");
+                                            no_error = write_error(&stream, &reply, errors::Errors::InvalidApiType { message: String::from("expected Chronokeep results type") })
                                         }
                                         break;
                                     }
                                 }
                             },
                             Err(e) => {
-                                println!("error getting apis from database: {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("error getting apis from database: {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting participants from database: {e}")
                                 })
                             }
@@ -1813,8 +2091,8 @@ fn handle_stream(
                                                     new_parts
                                                 },
                                                 Err(e) => {
-                                                    println!("error getting participants from api: {:?}", e);
-                                                    no_error = write_error(&stream, e);
+                                                    error!("error getting participants from api: {:?}", e);
+                                                    no_error = write_error(&stream, &reply, e);
                                                     break;
                                                 }
                                             };
@@ -1823,8 +2101,8 @@ fn handle_stream(
                                                     new_bibchips
                                                 },
                                                 Err(e) => {
-                                                    println!("error getting bibchips from api: {:?}", e);
-                                                    no_error = write_error(&stream, e);
+                                                    error!("error getting bibchips from api: {:?}", e);
+                                                    no_error = write_error(&stream, &reply, e);
                                                     break;
                                                 }
                                             };
@@ -1832,8 +2110,8 @@ fn handle_stream(
                                             match sq.delete_participants() {
                                                 Ok(_) => { },
                                                 Err(e) => {
-                                                    println!("error deleting participants: {e}");
-                                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                                    error!("error deleting participants: {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                         message: format!("error deleting participants: {e}")
                                                     });
                                                     break;
@@ -1848,18 +2126,26 @@ fn handle_stream(
                                             match sq.add_participants(&parts) {
                                                 Ok(_) => { },
                                                 Err(e) => {
-                                                    println!("error adding participants: {e}");
-                                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                                    error!("error adding participants: {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                         message: format!("error adding participants: {e}")
                                                     });
                                                     break;
                                                 },
                                             }
                                             match sq.add_bibchips(&new_bibchips) {
-                                                Ok(_) => { },
+                                                Ok(_) => {
+                                                    // anything held waiting for these chips can be made into sightings now
+                                                    let chips: Vec<String> = new_bibchips.iter().map(|bc| String::from(bc.chip())).collect();
+                                                    match sq.release_held_reads(&chips) {
+                                                        Ok(0) => {},
+                                                        Ok(_) => sight_processor.notify(),
+                                                        Err(e) => error!("Error releasing held reads. {e}"),
+                                                    }
+                                                },
                                                 Err(e) => {
-                                                    println!("error adding bibchips: {e}");
-                                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                                    error!("error adding bibchips: {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                         message: format!("error adding bibchips: {e}")
                                                     });
                                                     break;
@@ -1868,195 +2154,445 @@ fn handle_stream(
                                             // get participants and send them to the connection that had us update participants
                                             match sq.get_participants() {
                                                 Ok(parts) => {
-                                                    no_error = write_participants(&stream, &parts)
+                                                    no_error = write_participants(&stream, &reply, &parts)
                                                 },
                                                 Err(e) => {
-                                                    println!("error getting participants: {e}");
-                                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                                    error!("error getting participants: {e}");
+                                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                                         message: format!("error getting participants: {e}")
                                                     });
                                                 }
                                             };
                                         } else {
                                             let kind = api.kind();
-                                            println!("invalid api type specified: {kind}");
-                                            no_error = write_error(&stream, errors::Errors::InvalidApiType { message: String::from("expected Chronokeep results type") })
+                                            warn!("invalid api type specified: {kind}");
+                                            no_error = write_error(&stream, &reply, errors::Errors::InvalidApiType { message: String::from("expected Chronokeep results type") })
                                         }
                                         break;
                                     }
                                 }
                             },
                             Err(e) => {
-                                println!("error getting apis from database: {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("error getting apis from database: {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting participants from database: {e}")
                                 })
                             }
                         }
                     }
                 },
-                requests::Request::ParticipantsGet => {
+                requests::Request::ParticipantSyncHistoryGet => {
                     if let Ok(sq) = sqlite.lock() {
-                        match sq.get_participants() {
-                            Ok(parts) => {
-                                no_error = write_participants(&stream, &parts);
+                        match sq.get_system_events(system_event::SYSTEM_EVENT_PARTICIPANT_SYNC, sync::HISTORY_LIMIT) {
+                            Ok(events) => {
+                                let history: Vec<sync::SyncResult> = events.into_iter()
+                                    .filter_map(|e| serde_json::from_value(e.detail().clone()).ok())
+                                    .collect();
+                                no_error = write_participant_sync_history(&stream, &reply, history);
                             },
                             Err(e) => {
-                                println!("error getting participants from database. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
-                                    message: format!("error getting participants from database: {e}")
+                                error!("error getting participant sync history from database. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error getting participant sync history from database: {e}")
                                 });
                             }
                         }
                     }
                 },
-                requests::Request::ParticipantsRemove => {
+                requests::Request::NotificationChannelsGet => {
                     if let Ok(sq) = sqlite.lock() {
-                        match sq.delete_participants() {
-                            Ok(_) => {
-                                match sq.get_participants() {
-                                    Ok(parts) => {
-                                        if let Ok(c_socks) = control_sockets.lock() {
-                                            for sock in c_socks.iter() {
-                                                if let Some(sock) = sock {
-                                                    // we might be writing to other sockets
-                                                    // so errors here shouldn't close our connection
-                                                    _ = write_participants(&sock, &parts);
-                                                }
-                                            }
-                                        } else {
-                                            no_error = write_participants(&stream, &parts);
-                                        }
-                                    },
-                                    Err(e) => {
-                                        println!("error getting participants. {e}");
-                                        no_error = write_error(&stream, errors::Errors::DatabaseError {
-                                            message: format!("error getting participants: {e}")
-                                        });
-                                    }
-                                }
-                            },
+                        no_error = write_notification_channels_from(&stream, &reply, &sq);
+                    }
+                },
+                requests::Request::NotificationChannelSave { channel } => {
+                    if let Err(e) = channel.config().validate() {
+                        no_error = write_error(&stream, &reply, errors::Errors::InvalidNotificationChannel {
+                            message: e
+                        });
+                    } else if let Ok(sq) = sqlite.lock() {
+                        match sq.save_notification_channel(&channel) {
+                            Ok(_) => no_error = write_notification_channels_from(&stream, &reply, &sq),
+                            Err(DBError::NotFound) => no_error = write_error(&stream, &reply, errors::Errors::NotFound),
                             Err(e) => {
-                                println!("Error deleting participants. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
-                                    message: format!("error deleting participants: {e}")
+                                error!("error saving notification channel. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error saving notification channel: {e}")
                                 });
                             }
                         }
                     }
                 },
-                requests::Request::ParticipantsAdd { participants } => {
-                    if let Ok(mut sq) = sqlite.lock() {
-                        let mut parts: Vec<participant::Participant> = Vec::new();
-                        for p in participants {
-                            parts.push(p.get_participant());
-                        }
-                        match sq.add_participants(&parts) {
-                            Ok(_) => {
-                                match sq.get_participants() {
-                                    Ok(parts) => {
-                                        if let Ok(c_socks) = control_sockets.lock() {
-                                            for sock in c_socks.iter() {
-                                                if let Some(sock) = sock {
-                                                    // we might be writing to other sockets
-                                                    // so errors here shouldn't close our connection
-                                                    _ = write_participants(&sock, &parts);
-                                                }
-                                            }
-                                        } else {
-                                            no_error = write_participants(&stream, &parts);
-                                        }
-                                    },
-                                    Err(e) => {
-                                        println!("error getting participants. {e}");
-                                        no_error = write_error(&stream, errors::Errors::DatabaseError {
-                                            message: format!("error getting participants: {e}")
-                                        });
-                                    }
-                                }
-                            },
+                requests::Request::NotificationChannelRemove { id } => {
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.delete_notification_channel(id) {
+                            Ok(_) => no_error = write_notification_channels_from(&stream, &reply, &sq),
                             Err(e) => {
-                                println!("Error adding participants. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
-                                    message: format!("error adding participants: {e}")
+                                error!("error removing notification channel. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error removing notification channel: {e}")
                                 });
                             }
                         }
                     }
                 },
-                requests::Request::BibChipsGet => {
+                requests::Request::EventLogGet { query } => {
                     if let Ok(sq) = sqlite.lock() {
-                        match sq.get_bibchips() {
-                            Ok(bib_chips) => {
-                                no_error = write_bibchips(&stream, &bib_chips);
+                        match sq.get_event_log(&query) {
+                            Ok(events) => {
+                                no_error = write_event_log(&stream, &reply, events);
                             },
                             Err(e) => {
-                                println!("error getting bibchips from database. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
-                                    message: format!("error getting bibchips from database: {e}")
+                                error!("error getting event log from database. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error getting event log from database: {e}")
                                 });
                             }
                         }
                     }
                 },
-                requests::Request::BibChipsRemove => {
+                requests::Request::LogGet { lines } => {
+                    no_error = write_log(&stream, &reply, logging::tail(lines.unwrap_or(logging::DEFAULT_TAIL_LINES)));
+                },
+                requests::Request::WatchedBibsGet => {
                     if let Ok(sq) = sqlite.lock() {
-                        match sq.delete_all_bibchips() {
-                            Ok(num) => {
-                                no_error = write_success(&stream, num);
+                        match sq.get_watched_bibs() {
+                            Ok(watched_bibs) => {
+                                no_error = write_watched_bibs(&stream, &reply, watched_bibs);
                             },
                             Err(e) => {
-                                println!("Error deleting bibchips. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
-                                    message: format!("error deleting bibchips: {e}")
+                                error!("error getting watched bibs from database. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error getting watched bibs from database: {e}")
                                 });
                             }
                         }
                     }
                 },
-                requests::Request::BibChipsAdd { bib_chips } => {
-                    if let Ok(mut sq) = sqlite.lock() {
-                        match sq.add_bibchips(&bib_chips) {
+                requests::Request::WatchedBibSave { watched_bib } => {
+                    let bib = watched_bib.bib().trim();
+                    if bib.is_empty() {
+                        no_error = write_error(&stream, &reply, errors::Errors::InvalidWatchedBib {
+                            message: String::from("bib cannot be empty")
+                        });
+                    } else if let Ok(sq) = sqlite.lock() {
+                        let watched_bib = watched_bib::WatchedBib::new(String::from(bib), String::from(watched_bib.note().trim()));
+                        match sq.save_watched_bib(&watched_bib).and_then(|_| sq.get_watched_bibs()) {
+                            Ok(watched_bibs) => {
+                                no_error = write_watched_bibs(&stream, &reply, watched_bibs);
+                            },
+                            Err(e) => {
+                                error!("error saving watched bib. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error saving watched bib: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::WatchedBibRemove { bib } => {
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.delete_watched_bib(bib.trim()).and_then(|_| sq.get_watched_bibs()) {
+                            Ok(watched_bibs) => {
+                                no_error = write_watched_bibs(&stream, &reply, watched_bibs);
+                            },
+                            Err(e) => {
+                                error!("error removing watched bib. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error removing watched bib: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::SmsSupportersGet => {
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.get_sms_supporters() {
+                            Ok(supporters) => {
+                                no_error = write_sms_supporters(&stream, &reply, supporters);
+                            },
+                            Err(e) => {
+                                error!("error getting sms supporters from database. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error getting sms supporters from database: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::SmsSupporterAdd { supporter } => {
+                    let supporter = sms_supporter::SmsSupporter::new(String::from(supporter.bib().trim()), String::from(supporter.mobile().trim()));
+                    if supporter.bib().is_empty() || supporter.mobile().is_empty() {
+                        no_error = write_error(&stream, &reply, errors::Errors::InvalidSmsSupporter {
+                            message: String::from("bib and mobile are required")
+                        });
+                    } else if let Ok(sq) = sqlite.lock() {
+                        match sq.save_sms_supporter(&supporter).and_then(|_| sq.get_sms_supporters()) {
+                            Ok(supporters) => {
+                                no_error = write_sms_supporters(&stream, &reply, supporters);
+                            },
+                            Err(e) => {
+                                error!("error saving sms supporter. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error saving sms supporter: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::SmsSupporterRemove { supporter } => {
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.delete_sms_supporter(&supporter).and_then(|_| sq.get_sms_supporters()) {
+                            Ok(supporters) => {
+                                no_error = write_sms_supporters(&stream, &reply, supporters);
+                            },
+                            Err(e) => {
+                                error!("error removing sms supporter. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error removing sms supporter: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::SmsLogGet { bib, limit } => {
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.get_sms_deliveries(bib.as_deref(), limit) {
+                            Ok(deliveries) => {
+                                no_error = write_sms_log(&stream, &reply, deliveries);
+                            },
+                            Err(e) => {
+                                error!("error getting sms log from database. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error getting sms log from database: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::NotificationRulesGet => {
+                    no_error = write_notification_rules(&stream, &reply, notifier.rules());
+                },
+                requests::Request::NotificationRuleSave { rule } => {
+                    if let Err(e) = notifier::rules::validate(&rule) {
+                        no_error = write_error(&stream, &reply, errors::Errors::InvalidNotificationRule {
+                            message: e
+                        });
+                    } else {
+                        let result = match sqlite.lock() {
+                            Ok(sq) => sq.save_notification_rule(&rule).map(|_| ()),
+                            Err(_) => Ok(()),
+                        };
+                        match result {
+                            Ok(_) => {
+                                notifier.reload_rules();
+                                no_error = write_notification_rules(&stream, &reply, notifier.rules());
+                            },
+                            Err(e) => {
+                                error!("error saving notification rule. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error saving notification rule: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::NotificationRuleReset { kind } => {
+                    let result = match sqlite.lock() {
+                        Ok(sq) => sq.delete_notification_rule(&kind).map(|_| ()),
+                        Err(_) => Ok(()),
+                    };
+                    match result {
+                        Ok(_) => {
+                            notifier.reload_rules();
+                            no_error = write_notification_rules(&stream, &reply, notifier.rules());
+                        },
+                        Err(e) => {
+                            error!("error removing notification rule. {e}");
+                            no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                message: format!("error removing notification rule: {e}")
+                            });
+                        }
+                    }
+                },
+                requests::Request::SightingUploadGet => {
+                    let (enabled, api_id, slug, year) = match control.lock() {
+                        Ok(c) => (c.enable_sighting_upload, c.participant_sync_api, c.participant_sync_slug.clone(), c.participant_sync_year.clone()),
+                        Err(_) => (false, 0, String::new(), String::new()),
+                    };
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.get_sighting_upload_counts(api_id, &slug, &year) {
+                            Ok((pending, uploaded)) => {
+                                no_error = write_sighting_upload(&stream, &reply, &upload::Status {
+                                    enabled,
+                                    api_id,
+                                    slug,
+                                    year,
+                                    pending,
+                                    uploaded,
+                                });
+                            },
+                            Err(e) => {
+                                error!("error getting sighting upload counts from database. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error getting sighting upload counts from database: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::ParticipantsGet => {
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.get_participants() {
+                            Ok(parts) => {
+                                no_error = write_participants(&stream, &reply, &parts);
+                            },
+                            Err(e) => {
+                                error!("error getting participants from database. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error getting participants from database: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::ParticipantsRemove => {
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.delete_participants() {
+                            Ok(_) => {
+                                match sq.get_participants() {
+                                    Ok(parts) => {
+                                        event_bus.publish_except(index, events::Event::Participants(parts.clone()));
+                                        no_error = write_participants(&stream, &reply, &parts);
+                                    },
+                                    Err(e) => {
+                                        error!("error getting participants. {e}");
+                                        no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                            message: format!("error getting participants: {e}")
+                                        });
+                                    }
+                                }
+                            },
+                            Err(e) => {
+                                error!("Error deleting participants. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error deleting participants: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::ParticipantsAdd { participants } => {
+                    if let Ok(mut sq) = sqlite.lock() {
+                        let mut parts: Vec<participant::Participant> = Vec::new();
+                        for p in participants {
+                            parts.push(p.get_participant());
+                        }
+                        match sq.add_participants(&parts) {
+                            Ok(_) => {
+                                match sq.get_participants() {
+                                    Ok(parts) => {
+                                        event_bus.publish_except(index, events::Event::Participants(parts.clone()));
+                                        no_error = write_participants(&stream, &reply, &parts);
+                                    },
+                                    Err(e) => {
+                                        error!("error getting participants. {e}");
+                                        no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                            message: format!("error getting participants: {e}")
+                                        });
+                                    }
+                                }
+                            },
+                            Err(e) => {
+                                error!("Error adding participants. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error adding participants: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::BibChipsGet => {
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.get_bibchips() {
+                            Ok(bib_chips) => {
+                                no_error = write_bibchips(&stream, &reply, &bib_chips);
+                            },
+                            Err(e) => {
+                                error!("error getting bibchips from database. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error getting bibchips from database: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::BibChipsRemove => {
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.delete_all_bibchips() {
                             Ok(num) => {
-                                no_error = write_success(&stream, num);
+                                no_error = write_success(&stream, &reply, num);
                             },
                             Err(e) => {
-                                println!("Error adding bibchips. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("Error deleting bibchips. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error deleting bibchips: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
+                requests::Request::BibChipsAdd { bib_chips } => {
+                    if let Ok(mut sq) = sqlite.lock() {
+                        match sq.add_bibchips(&bib_chips) {
+                            Ok(num) => {
+                                // anything held waiting for these chips can be made into sightings now
+                                let chips: Vec<String> = bib_chips.iter().map(|bc| String::from(bc.chip())).collect();
+                                match sq.release_held_reads(&chips) {
+                                    Ok(0) => {},
+                                    Ok(_) => sight_processor.notify(),
+                                    Err(e) => error!("Error releasing held reads. {e}"),
+                                }
+                                no_error = write_success(&stream, &reply, num);
+                            },
+                            Err(e) => {
+                                error!("Error adding bibchips. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error adding bibchips: {e}")
                                 });
                             }
                         }
                     }
                 },
+                requests::Request::UnknownChipsGet => {
+                    if let Ok(sq) = sqlite.lock() {
+                        match sq.get_unknown_chips() {
+                            Ok(unknown_chips) => {
+                                no_error = write_unknown_chips(&stream, &reply, unknown_chips);
+                            },
+                            Err(e) => {
+                                error!("error getting unknown chips from database. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
+                                    message: format!("error getting unknown chips from database: {e}")
+                                });
+                            }
+                        }
+                    }
+                },
                 requests::Request::ReadsAdd { read } => {
                     if read.is_valid() == false {
-                        no_error = write_error(&stream, errors::Errors::InvalidRead)
+                        no_error = write_error(&stream, &reply, errors::Errors::InvalidRead)
                     } else {
                         if let Ok(mut sq) = sqlite.lock() {
                             let mut reads: Vec<read::Read> = Vec::new();
                             reads.push(read);
                             match sq.save_reads(&reads) {
                                 Ok(_) => {
-                                    if let Ok(sockets) = control_sockets.lock() {
-                                        if let Ok(repeaters) = read_repeaters.lock() {
-                                            for ix in 0..MAX_CONNECTED {
-                                                match &sockets[ix] {
-                                                    Some(sock) => {
-                                                        if repeaters[ix] == true {
-                                                            no_error = no_error && write_reads(&sock, &reads);
-                                                        }
-                                                    },
-                                                    None => {}
-                                                }
-                                            }
-                                        }
-                                    }
+                                    event_bus.publish(events::Event::Reads(reads));
                                     sight_processor.notify();
                                 },
                                 Err(e) => {
-                                    println!("Error saving manual read: {e}");
-                                    no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                    error!("Error saving manual read: {e}");
+                                    no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                         message: format!("error saving manual read: {e}")
                                     });
                                 }
@@ -2068,11 +2604,11 @@ fn handle_stream(
                     if let Ok(sq) = sqlite.lock() {
                         match sq.get_reads(start_seconds, end_seconds) {
                             Ok(reads) => {
-                                no_error = write_reads(&stream, &reads);
+                                no_error = write_reads(&stream, &reply, &reads);
                             },
                             Err(e) => {
-                                println!("Error getting reads. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("Error getting reads. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting reads: {e}")
                                 });
                             }
@@ -2083,11 +2619,11 @@ fn handle_stream(
                     if let Ok(sq) = sqlite.lock() {
                         match sq.get_all_reads() {
                             Ok(reads) => {
-                                no_error = write_reads(&stream, &reads);
+                                no_error = write_reads(&stream, &reply, &reads);
                             },
                             Err(e) => {
-                                println!("Error getting reads. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("Error getting reads. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting reads: {e}")
                                 });
                             }
@@ -2100,19 +2636,19 @@ fn handle_stream(
                             Ok(sightings) => {
                                 match sq.get_bibchips() {
                                     Ok(bibchips) => {
-                                        no_error = write_sightings(&stream, &sightings, &bibchips);
+                                        no_error = write_sightings(&stream, &reply, &sightings, &bibchips);
                                     },
                                     Err(e) => {
-                                        println!("Error getting bibchips. {e}");
-                                        no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                        error!("Error getting bibchips. {e}");
+                                        no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                             message: format!("error getting bibchips: {e}")
                                         });
                                     }
                                 }
                             },
                             Err(e) => {
-                                println!("Error getting sightings. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("Error getting sightings. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting sightings: {e}")
                                 });
                             }
@@ -2125,19 +2661,19 @@ fn handle_stream(
                             Ok(sightings) => {
                                 match sq.get_bibchips() {
                                     Ok(bibchips) => {
-                                        no_error = write_sightings(&stream, &sightings, &bibchips);
+                                        no_error = write_sightings(&stream, &reply, &sightings, &bibchips);
                                     },
                                     Err(e) => {
-                                        println!("Error getting bibchips. {e}");
-                                        no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                        error!("Error getting bibchips. {e}");
+                                        no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                             message: format!("error getting bibchips: {e}")
                                         });
                                     }
                                 }
                             },
                             Err(e) => {
-                                println!("Error getting sightings. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("Error getting sightings. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error getting sightings: {e}")
                                 })
                             }
@@ -2148,11 +2684,11 @@ fn handle_stream(
                     if let Ok(sq) = sqlite.lock() {
                         match sq.delete_reads(start_seconds, end_seconds) {
                             Ok(count) => {
-                                no_error = write_success(&stream, count);
+                                no_error = write_success(&stream, &reply, count);
                             },
                             Err(e) => {
-                                println!("Error deleting reads. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("Error deleting reads. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error deleting reads: {e}")
                                 });
                             }
@@ -2163,11 +2699,11 @@ fn handle_stream(
                     if let Ok(sq) = sqlite.lock() {
                         match sq.delete_all_reads() {
                             Ok(count) => {
-                                no_error = write_success(&stream, count);
+                                no_error = write_success(&stream, &reply, count);
                             },
                             Err(e) => {
-                                println!("Error deleting reads. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("Error deleting reads. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error deleting reads: {e}")
                                 });
                             }
@@ -2178,11 +2714,11 @@ fn handle_stream(
                     if let Ok(sq) = sqlite.lock() {
                         match sq.delete_sightings() {
                             Ok(count) => {
-                                no_error = write_success(&stream, count);
+                                no_error = write_success(&stream, &reply, count);
                             }
                             Err(e) => {
-                                println!("Error deleting sightings. {e}");
-                                no_error = write_error(&stream, errors::Errors::DatabaseError {
+                                error!("Error deleting sightings. {e}");
+                                no_error = write_error(&stream, &reply, errors::Errors::DatabaseError {
                                     message: format!("error deleting reads: {e}")
                                 });
                             }
@@ -2190,7 +2726,10 @@ fn handle_stream(
                     }
                 },
                 requests::Request::TimeGet => {
-                    no_error = write_time(&stream);
+                    no_error = write_time(&stream, &reply);
+                },
+                requests::Request::ConnectivityGet => {
+                    no_error = write_connectivity(&stream, &reply, &connectivity.status());
                 },
                 requests::Request::TimeSet { time } => {
                     let mut allowed = true;
@@ -2198,8 +2737,8 @@ fn handle_stream(
                         for reader in readers.iter() {
                             if let Some(val) = reader.is_connected() {
                                 if val {
-                                    println!("User attempted to set the time while a reader is connected.");
-                                    no_error = write_error(&stream, errors::Errors::NotAllowed { message: format!("setting time not allowed with a reader connected") });
+                                    warn!("User attempted to set the time while a reader is connected.");
+                                    no_error = write_error(&stream, &reply, errors::Errors::NotAllowed { message: format!("setting time not allowed with a reader connected") });
                                     allowed = false;
                                     break;
                                 }
@@ -2207,6 +2746,7 @@ fn handle_stream(
                         }
                     }
                     if allowed {
+                        let previous = Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string();
                         match std::env::consts::OS {
                             "linux" => {
                                 match std::process::Command::new("sudo").arg("date").arg(format!("--set={time}")).status() {
@@ -2215,29 +2755,41 @@ fn handle_stream(
                                             Ok(_) => {
                                                 // Update last_received_at so the socket doesn't auto close if we jump to the future.
                                                 last_received_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                                                no_error = write_time(&stream)
+                                                database::log_event(
+                                                    &sqlite,
+                                                    system_event::SEVERITY_WARNING,
+                                                    system_event::SOURCE_CONTROL,
+                                                    system_event::SYSTEM_EVENT_TIME_CHANGED,
+                                                    serde_json::json!({ "from": previous, "to": time }),
+                                                );
+                                                no_error = write_time(&stream, &reply)
                                             },
                                             Err(e) => {
-                                                println!("error setting time: {e}");
-                                                no_error = write_error(&stream, errors::Errors::ServerError { message: format!("error setting time: {e}") })
+                                                error!("error setting time: {e}");
+                                                no_error = write_error(&stream, &reply, errors::Errors::ServerError { message: format!("error setting time: {e}") })
                                             }
                                         }
                                     },
                                     Err(e) => {
-                                        println!("error setting time: {e}");
-                                        no_error = write_error(&stream, errors::Errors::ServerError { message: format!("error setting time: {e}") })
+                                        error!("error setting time: {e}");
+                                        no_error = write_error(&stream, &reply, errors::Errors::ServerError { message: format!("error setting time: {e}") })
                                     }
                                 }
                             },
                             other => {
-                                println!("not supported on this platform ({other})");
-                                no_error = write_error(&stream, errors::Errors::ServerError { message: format!("not supported on this platform ({other})") })
+                                warn!("not supported on this platform ({other})");
+                                no_error = write_error(&stream, &reply, errors::Errors::ServerError { message: format!("not supported on this platform ({other})") })
                             }
                         }
                     }
                 },
-                requests::Request::Subscribe { reads, sightings } => {
+                requests::Request::Subscribe { reads, sightings, filter } => {
                     let mut message:String = String::from("");
+                    // changing the filter is a valid reason to subscribe again
+                    let filter_changed = filter.is_some();
+                    if filter_changed {
+                        event_bus.set_filter(index, filter);
+                    }
                     if let Ok(mut repeaters) = read_repeaters.lock() {
                         if (repeaters[index] == true && reads == true)
                         || (repeaters[index] == false && reads == false) {
@@ -2254,8 +2806,8 @@ fn handle_stream(
                             repeaters[index] = sightings
                         }
                     }
-                    if message.len() > 0 {
-                        no_error = write_error(&stream, errors::Errors::AlreadySubscribed { message: message });
+                    if message.len() > 0 && !filter_changed {
+                        no_error = write_error(&stream, &reply, errors::Errors::AlreadySubscribed { message: message });
                     }
                 },
                 requests::Request::Update => {
@@ -2264,21 +2816,21 @@ fn handle_stream(
                             if let Ok(update_path) = env::var(UPDATE_SCRIPT_ENV) {
                                 match std::process::Command::new(update_path).spawn() {
                                     Ok(_) => {
-                                        no_error = write_success(&stream, 0);
+                                        no_error = write_success(&stream, &reply, 0);
                                     },
                                     Err(e) => {
-                                        println!("error updating time: {e}");
-                                        no_error = write_error(&stream, errors::Errors::ServerError { message: format!("error updating: {e}") })
+                                        error!("error updating time: {e}");
+                                        no_error = write_error(&stream, &reply, errors::Errors::ServerError { message: format!("error updating: {e}") })
                                     }
                                 }
                             } else {
-                                println!("update script environment variable not set");
-                                no_error = write_error(&stream, errors::Errors::ServerError { message: String::from("update script environment variable not set") })
+                                info!("update script environment variable not set");
+                                no_error = write_error(&stream, &reply, errors::Errors::ServerError { message: String::from("update script environment variable not set") })
                             }
                         },
                         other => {
-                            println!("not supported on this platform ({other})");
-                            no_error = write_error(&stream, errors::Errors::ServerError { message: format!("not supported on this platform ({other})") })
+                            warn!("not supported on this platform ({other})");
+                            no_error = write_error(&stream, &reply, errors::Errors::ServerError { message: format!("not supported on this platform ({other})") })
                         }
                     }
                 },
@@ -2288,14 +2840,11 @@ fn handle_stream(
                         if sock.ip().is_loopback() {
                             //println!("sock is loopback");
                             let time = Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string();
-                            if let Ok(c_socks) = control_sockets.lock() {
-                                println!("notifying connected sockets");
-                                for sock in c_socks.iter() {
-                                    if let Some(s) = sock {
-                                        _ = write_notification(&s, &notification, &time);
-                                    }
-                                }
-                            }
+                            debug!("notifying connected sockets");
+                            event_bus.publish(events::Event::Notification {
+                                notification: notification.clone(),
+                                time,
+                            });
                             if let Ok(control) = control.lock() {
                                 if control.auto_remote {
                                     if let Ok(sq) = sqlite.lock() {
@@ -2309,7 +2858,7 @@ fn handle_stream(
                                                 }
                                             },
                                             Err(e) => {
-                                                println!("Error trying to get apis: {e}");
+                                                error!("Error trying to get apis: {e}");
                                             }
                                         }
                                     }
@@ -2319,8 +2868,19 @@ fn handle_stream(
                     }
                 },
                 _ => {
-                    println!("Unknown command received - line was {:?}", single_line);
-                    no_error = write_error(&stream, errors::Errors::UnknownCommand)
+                    warn!("Unknown command received - line was {:?}", single_line);
+                    if protocol_version >= 2 {
+                        // newer clients are told which command we didn't understand and what we do understand
+                        let command = serde_json::from_str::<serde_json::Value>(&single_line).ok()
+                            .and_then(|v| v.get("command").and_then(|c| c.as_str()).map(String::from))
+                            .unwrap_or_default();
+                        no_error = write_error(&stream, &reply, errors::Errors::UnsupportedCommand {
+                            command,
+                            capabilities: get_capabilities(protocol_version, &None),
+                        })
+                    } else {
+                        no_error = write_error(&stream, &reply, errors::Errors::UnknownCommand)
+                    }
                 },
             }
             if no_error == false {
@@ -2333,12 +2893,12 @@ fn handle_stream(
             if last_received_at + (2*KEEPALIVE_INTERVAL_SECONDS) < time.as_secs() {
                 // write disconnect to tell the client what's going on if they're still
                 // actually listening
-                _ = write_disconnect(&stream);
+                _ = write_disconnect(&stream, &Reply::event(protocol_version));
                 // and we can exit the loop because we're definitely disconnecting
                 break;
             // send a keepalive message if we haven't heard from the socket in KEEPALIVE_INTERVAL_SECONDS
             } else if last_received_at + KEEPALIVE_INTERVAL_SECONDS < time.as_secs() {
-                no_error = write_keepalive(&stream) && no_error;
+                no_error = write_keepalive(&stream, &Reply::event(protocol_version)) && no_error;
             }
         }
         // check if we've encountered an error
@@ -2348,7 +2908,7 @@ fn handle_stream(
         }
     }
     // if we've exited the loop we should ensure the program knows we can close this stream
-    println!("Closing socket for index {index}.");
+    info!("Closing socket for index {index}.");
     // unsubscribe to notifications
     if let Ok(mut repeaters) = read_repeaters.lock() {
         if index < MAX_CONNECTED {
@@ -2360,7 +2920,8 @@ fn handle_stream(
             repeaters[index] = false;
         }
     }
-    write_disconnect(&stream);
+    event_bus.unsubscribe(index);
+    write_disconnect(&stream, &Reply::event(protocol_version));
     _ = stream.shutdown(Shutdown::Both);
     if let Ok(mut c_socks) = control_sockets.lock() {
         c_socks[index] = None;
@@ -2380,14 +2941,16 @@ fn get_available_port() -> u16 {
 }
 
 pub fn write_notification(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     notification: &notifications::APINotification,
     time: &String
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::Notification {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Notification {
         kind: notification.clone(),
         time: String::from(time)
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2397,7 +2960,7 @@ pub fn write_notification(
                     return false;
                 },
                 _ => {
-                    println!("17/ Something went wrong writing to the socket. {e}");
+                    error!("17/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2414,7 +2977,7 @@ pub fn write_notification(
                     return false;
                 },
                 _ => {
-                    println!("17/ Something went wrong writing to the socket. {e}");
+                    error!("17/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2424,12 +2987,14 @@ pub fn write_notification(
 }
 
 fn write_error(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     error: errors::Errors
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::Error{
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Error{
         error,
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2439,7 +3004,7 @@ fn write_error(
                     return false;
                 },
                 _ => {
-                    println!("1/ Something went wrong writing to the socket. {e}");
+                    error!("1/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2456,7 +3021,7 @@ fn write_error(
                     return false;
                 },
                 _ => {
-                    println!("1/ Something went wrong writing to the socket. {e}");
+                    error!("1/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2466,16 +3031,18 @@ fn write_error(
 }
 
 fn write_time(
-    stream: &TcpStream
+    stream: &ClientStream,
+    reply: &Reply
 ) -> bool {
     let time = Utc::now();
     let utc = time.naive_utc();
     let local = Local.from_utc_datetime(&utc).format("%Y-%m-%d %H:%M:%S").to_string();
     let utc = utc.format("%Y-%m-%d %H:%M:%S").to_string();
-    match serde_json::to_writer(stream, &responses::Responses::Time{
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Time{
         local,
         utc,
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2485,7 +3052,7 @@ fn write_time(
                     return false;
                 },
                 _ => {
-                    println!("2/ Something went wrong writing to the socket. {e}");
+                    error!("2/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2502,7 +3069,7 @@ fn write_time(
                     return false;
                 },
                 _ => {
-                    println!("2/ Something went wrong writing to the socket. {e}");
+                    error!("2/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2526,6 +3093,41 @@ pub(crate) fn get_settings(sqlite: &MutexGuard<sqlite::SQLite>) -> Vec<setting::
         super::SETTING_NTFY_PASS,
         super::SETTING_NTFY_TOPIC,
         super::SETTING_ENABLE_NTFY,
+        super::SETTING_MQTT_URL,
+        super::SETTING_MQTT_USER,
+        super::SETTING_MQTT_PASS,
+        super::SETTING_MQTT_TOPIC,
+        super::SETTING_MQTT_QOS,
+        super::SETTING_ENABLE_MQTT,
+        super::SETTING_ENABLE_OUTPUT,
+        super::SETTING_OUTPUT_PORT,
+        super::SETTING_OUTPUT_FORMAT,
+        super::SETTING_ENABLE_RELAY,
+        super::SETTING_RELAY_PORT,
+        super::SETTING_UPLOAD_BATCH_SIZE,
+        super::SETTING_CONNECTIVITY_CHECK_URL,
+        super::SETTING_ENABLE_PARTICIPANT_SYNC,
+        super::SETTING_PARTICIPANT_SYNC_API,
+        super::SETTING_PARTICIPANT_SYNC_SLUG,
+        super::SETTING_PARTICIPANT_SYNC_YEAR,
+        super::SETTING_PARTICIPANT_SYNC_INTERVAL,
+        super::SETTING_HTTP_PROXY_URL,
+        super::SETTING_HTTP_PROXY_USER,
+        super::SETTING_HTTP_PROXY_PASS,
+        super::SETTING_HTTP_CA_BUNDLE,
+        super::SETTING_HTTP_TIMEOUT,
+        super::SETTING_HTTP_CONNECT_TIMEOUT,
+        super::SETTING_ENABLE_SIGHTING_UPLOAD,
+        super::SETTING_SILENT_ANTENNA_SECONDS,
+        super::SETTING_LOG_LEVEL,
+        super::SETTING_ENABLE_SMS,
+        super::SETTING_SMS_URL,
+        super::SETTING_SMS_TOKEN,
+        super::SETTING_SMS_FROM,
+        super::SETTING_SMS_TEMPLATE,
+        super::SETTING_SMS_INTERVAL,
+        super::SETTING_SMS_HOURLY_LIMIT,
+        super::SETTING_UNKNOWN_CHIP_POLICY,
     ];
     let mut settings: Vec<setting::Setting> = Vec::new();
     for name in setting_names {
@@ -2540,12 +3142,14 @@ pub(crate) fn get_settings(sqlite: &MutexGuard<sqlite::SQLite>) -> Vec<setting::
 }
 
 pub(crate) fn write_settings(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     settings: &Vec<setting::Setting>
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::Settings{
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Settings{
         settings: settings.to_vec(),
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2555,7 +3159,7 @@ pub(crate) fn write_settings(
                     return false;
                 },
                 _ => {
-                    println!("3/ Something went wrong writing to the socket. {e}");
+                    error!("3/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2572,7 +3176,7 @@ pub(crate) fn write_settings(
                     return false;
                 },
                 _ => {
-                    println!("3/ Something went wrong writing to the socket. {e}");
+                    error!("3/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2582,7 +3186,8 @@ pub(crate) fn write_settings(
 }
 
 fn write_all_settings(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     settings: &Vec<setting::Setting>,
     u_readers: &Vec<reader::Reader>,
     apis: &Vec<Api>,
@@ -2608,13 +3213,14 @@ fn write_all_settings(
             antennas
         })
     };
-    match serde_json::to_writer(stream, &responses::Responses::SettingsAll {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::SettingsAll {
         settings: settings.to_vec(),
         readers: list,
         apis: apis.to_vec(),
         auto_upload: status,
         portal_version: env!("CARGO_PKG_VERSION")
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2624,7 +3230,7 @@ fn write_all_settings(
                     return false;
                 },
                 _ => {
-            println!("16/ Something went wrong writing to the socket. {e}");
+            error!("16/ Something went wrong writing to the socket. {e}");
             return false;
         }
     }
@@ -2641,7 +3247,7 @@ fn write_all_settings(
                     return false;
                 },
                 _ => {
-                    println!("16/ Something went wrong writing to the socket. {e}");
+                    error!("16/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2651,9 +3257,16 @@ fn write_all_settings(
 }
 
 pub fn write_reader_list(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     u_readers: &Vec<reader::Reader>
 ) -> bool {
+    write_readers(stream, reply, &get_reader_list(u_readers))
+}
+
+pub fn get_reader_list(
+    u_readers: &Vec<reader::Reader>
+) -> Vec<responses::Reader> {
     let mut list: Vec<responses::Reader> = Vec::new();
     for r in u_readers.iter() {
         let mut antennas: [u8;MAX_ANTENNAS] = [0;MAX_ANTENNAS];
@@ -2674,9 +3287,18 @@ pub fn write_reader_list(
             antennas
         })
     };
-    match serde_json::to_writer(stream, &responses::Responses::Readers{
-        readers: list,
-    }) {
+    list
+}
+
+pub fn write_readers(
+    stream: &ClientStream,
+    reply: &Reply,
+    list: &Vec<responses::Reader>
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Readers{
+        readers: list.to_vec(),
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2686,7 +3308,7 @@ pub fn write_reader_list(
                     return false;
                 },
                 _ => {
-            println!("4/ Something went wrong writing to the socket. {e}");
+            error!("4/ Something went wrong writing to the socket. {e}");
             return false;
         }
     }
@@ -2703,7 +3325,7 @@ pub fn write_reader_list(
                     return false;
                 },
                 _ => {
-                    println!("4/ Something went wrong writing to the socket. {e}");
+                    error!("4/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2712,13 +3334,15 @@ pub fn write_reader_list(
     true
 }
 
-fn write_api_list(
-    stream: &TcpStream,
+pub fn write_api_list(
+    stream: &ClientStream,
+    reply: &Reply,
     apis: &Vec<api::Api>
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::ApiList{
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::ApiList{
         apis: apis.to_vec()
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2728,7 +3352,7 @@ fn write_api_list(
                     return false;
                 },
                 _ => {
-                    println!("5/ Something went wrong writing to the socket. {e}");
+                    error!("5/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2745,7 +3369,7 @@ fn write_api_list(
                     return false;
                 },
                 _ => {
-                    println!("5/ Something went wrong writing to the socket. {e}");
+                    error!("5/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2755,14 +3379,16 @@ fn write_api_list(
 }
 
 pub fn write_reader_antennas(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     reader_name: String,
     antennas: &[u8;MAX_ANTENNAS]
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::ReaderAntennas{
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::ReaderAntennas{
         reader_name,
         antennas: antennas.clone()
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2772,7 +3398,7 @@ pub fn write_reader_antennas(
                     return false;
                 },
                 _ => {
-                    println!("16/ Something went wrong writing to the socket. {e}");
+                    error!("16/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2789,7 +3415,7 @@ pub fn write_reader_antennas(
                     return false;
                 },
                 _ => {
-                    println!("13/ Something went wrong writing to the socket. {e}");
+                    error!("13/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2799,12 +3425,14 @@ pub fn write_reader_antennas(
 }
 
 pub fn write_reads(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     reads: &Vec<read::Read>
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::Reads{
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Reads{
         list: reads.to_vec(),
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2814,7 +3442,7 @@ pub fn write_reads(
                     return false;
                 },
                 _ => {
-                    println!("6/ Something went wrong writing to the socket. {e}");
+                    error!("6/ Something went wrong writing to the socket. {e}");
                 }
             }
         }
@@ -2830,7 +3458,7 @@ pub fn write_reads(
                     return false;
                 },
                 _ => {
-                    println!("6/ Something went wrong writing to the socket. {e}");
+                    error!("6/ Something went wrong writing to the socket. {e}");
                 }
             }
         }
@@ -2839,14 +3467,16 @@ pub fn write_reads(
 }
 
 pub fn write_sightings(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     sightings: &Vec<sighting::Sighting>,
     bibchips: &Vec<bibchip::BibChip>
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::Sightings {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Sightings {
         list: sightings.to_vec(),
         bib_chips: bibchips.to_vec()
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2856,7 +3486,7 @@ pub fn write_sightings(
                     return false;
                 },
                 _ => {
-                    println!("14/ Something went wrong writing to the socket. {e}");
+                    error!("14/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2873,7 +3503,7 @@ pub fn write_sightings(
                     return false;
                 },
                 _ => {
-                    println!("14/ Something went wrong writing to the socket. {e}");
+                    error!("14/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2883,12 +3513,14 @@ pub fn write_sightings(
 }
 
 fn write_success(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     count: usize
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::Success {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Success {
         count
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2898,7 +3530,7 @@ fn write_success(
                     return false;
                 },
                 _ => {
-                    println!("7/ Something went wrong writing to the socket. {e}");
+                    error!("7/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2915,7 +3547,7 @@ fn write_success(
                     return false;
                 },
                 _ => {
-                    println!("7/ Something went wrong writing to the socket. {e}");
+                    error!("7/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2925,12 +3557,14 @@ fn write_success(
 }
 
 fn write_bibchips(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     bibchips: &Vec<bibchip::BibChip>
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::BibChips {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::BibChips {
         bib_chips: bibchips.to_vec(),
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2940,7 +3574,7 @@ fn write_bibchips(
                     return false;
                 },
                 _ => {
-                    println!("16/ Something went wrong writing to the socket. {e}");
+                    error!("16/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2957,7 +3591,7 @@ fn write_bibchips(
                     return false;
                 },
                 _ => {
-                    println!("16/ Something went wrong writing to the socket. {e}");
+                    error!("16/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2966,13 +3600,15 @@ fn write_bibchips(
     true
 }
 
-fn write_participants(
-    stream: &TcpStream,
+pub fn write_participants(
+    stream: &ClientStream,
+    reply: &Reply,
     parts: &Vec<participant::Participant>
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::Participants {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Participants {
         participants: parts.to_vec(),
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -2982,7 +3618,7 @@ fn write_participants(
                     return false;
                 },
                 _ => {
-                    println!("8/ Something went wrong writing to the socket. {e}");
+                    error!("8/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -2999,7 +3635,7 @@ fn write_participants(
                     return false;
                 },
                 _ => {
-                    println!("8/ Something went wrong writing to the socket. {e}");
+                    error!("8/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3009,12 +3645,14 @@ fn write_participants(
 }
 
 fn write_connection_successful(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     name: String,
     reads: bool,
     sightings: bool,
     u_readers: &Vec<reader::Reader>,
-    uploader: &Arc<Uploader>
+    uploader: &Arc<Uploader>,
+    protocol: NegotiatedProtocol,
 ) -> bool {
     let mut list: Vec<responses::Reader> = Vec::new();
     for r in u_readers.iter() {
@@ -3042,17 +3680,21 @@ fn write_connection_successful(
             updatable = true;
         }
     }
-    match serde_json::to_writer(stream, &responses::Responses::ConnectionSuccessful{
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::ConnectionSuccessful{
         name,
         kind: String::from(CONNECTION_TYPE),
-        version: CONNECTION_VERS,
+        version: protocol.version,
         reads_subscribed: reads,
         sightings_subscribed: sightings,
         readers: list,
         updatable: updatable,
         auto_upload: uploader.status(),
         portal_version: env!("CARGO_PKG_VERSION"),
-    }) {
+        min_version: protocol.capabilities.as_ref().map(|_| CONNECTION_VERS_MIN),
+        max_version: protocol.capabilities.as_ref().map(|_| CONNECTION_VERS),
+        capabilities: protocol.capabilities,
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -3062,7 +3704,7 @@ fn write_connection_successful(
                     return false;
                 },
                 _ => {
-                    println!("9/ Something went wrong writing to the socket. {e}");
+                    error!("9/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3079,7 +3721,7 @@ fn write_connection_successful(
                     return false;
                 },
                 _ => {
-                    println!("9/ Something went wrong writing to the socket. {e}");
+                    error!("9/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3089,9 +3731,11 @@ fn write_connection_successful(
 }
 
 pub fn write_keepalive(
-    stream: &TcpStream
+    stream: &ClientStream,
+    reply: &Reply
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::Keepalive) {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Keepalive)) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -3101,7 +3745,7 @@ pub fn write_keepalive(
                     return false;
                 },
                 _ => {
-                    println!("10/ Something went wrong writing to the socket. {e}");
+                    error!("10/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3118,7 +3762,7 @@ pub fn write_keepalive(
                     return false;
                 },
                 _ => {
-                    println!("10/ Something went wrong writing to the socket. {e}");
+                    error!("10/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3128,9 +3772,11 @@ pub fn write_keepalive(
 }
 
 pub fn write_disconnect(
-    stream: &TcpStream
+    stream: &ClientStream,
+    reply: &Reply
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::Disconnect) {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Disconnect)) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -3140,7 +3786,7 @@ pub fn write_disconnect(
                     return false;
                 },
                 _ => {
-                    println!("11/ Something went wrong writing to the socket. {e}");
+                    error!("11/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3157,7 +3803,7 @@ pub fn write_disconnect(
                     return false;
                 },
                 _ => {
-                    println!("11/ Something went wrong writing to the socket. {e}");
+                    error!("11/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3167,12 +3813,14 @@ pub fn write_disconnect(
 }
 
 pub fn write_event_list(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     events: Vec<Event>
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::Events {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Events {
         events
-    }) {
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -3182,7 +3830,7 @@ pub fn write_event_list(
                     return false;
                 },
                 _ => {
-                    println!("12/ Something went wrong writing to the socket. {e}");
+                    error!("12/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3199,7 +3847,7 @@ pub fn write_event_list(
                     return false;
                 },
                 _ => {
-                    println!("12/ Something went wrong writing to the socket. {e}");
+                    error!("12/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3209,10 +3857,12 @@ pub fn write_event_list(
 }
 
 pub fn write_event_years(
-    stream: &TcpStream,
+    stream: &ClientStream,
+    reply: &Reply,
     years: Vec<String>
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::EventYears { years }) {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::EventYears { years })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -3222,7 +3872,7 @@ pub fn write_event_years(
                     return false;
                 },
                 _ => {
-                    println!("13/ Something went wrong writing to the socket. {e}");
+                    error!("13/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3239,7 +3889,7 @@ pub fn write_event_years(
                     return false;
                 },
                 _ => {
-                    println!("13/ Something went wrong writing to the socket. {e}");
+                    error!("13/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3248,13 +3898,15 @@ pub fn write_event_years(
     true
 }
 
-pub fn write_uploader_status(
-    stream: &TcpStream,
-    status: uploader::Status
+pub fn write_participant_sync_history(
+    stream: &ClientStream,
+    reply: &Reply,
+    history: Vec<sync::SyncResult>,
 ) -> bool {
-    match serde_json::to_writer(stream, &responses::Responses::ReadAutoUpload {
-        status
-    }) {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::ParticipantSyncHistory {
+        history,
+    })) {
         Ok(_) => {},
         Err(e) => {
             match e.io_error_kind() {
@@ -3264,7 +3916,7 @@ pub fn write_uploader_status(
                     return false;
                 },
                 _ => {
-                    println!("15/ Something went wrong writing to the socket. {e}");
+                    error!("19/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3281,7 +3933,7 @@ pub fn write_uploader_status(
                     return false;
                 },
                 _ => {
-                    println!("15/ Something went wrong writing to the socket. {e}");
+                    error!("19/ Something went wrong writing to the socket. {e}");
                     return false;
                 }
             }
@@ -3290,61 +3942,668 @@ pub fn write_uploader_status(
     true
 }
 
-fn construct_headers(key: &str) -> HeaderMap {
-    let mut headers = HeaderMap::new();
-    headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
-    headers.insert(AUTHORIZATION, format!("Bearer {key}").parse().unwrap());
-    headers
-}
-
-pub fn upload_reads(
-    http_client: &reqwest::blocking::Client,
-    api: &Api,
-    reads: &[read::Read]
-) -> Result<usize, errors::Errors> {
-    let url = api.uri();
-    let response = match http_client.post(format!("{url}reads/add"))
-        .headers(construct_headers(api.token()))
-        .json(&remote::requests::UploadReadsRequest {
-            reads: reads.to_vec()
-        })
-        .send() {
-            Ok(resp) => resp,
-            Err(e) => {
-                println!("error trying to talk to api: {e}");
-                return Err(errors::Errors::ServerError { message: format!("error trying to talk to api: {e}") })
-            }
-        };
-    let output = match response.status() {
-        reqwest::StatusCode::OK => {
-            let resp_body: remote::responses::UploadReadsResponse = match response.json() {
-                Ok(it) => it,
-                Err(e) => {
-                    println!("error trying to parse response from api: {e}");
-                    return Err(errors::Errors::ServerError { message: format!("error trying to parse response from api: {e}") })
+pub fn write_connectivity(
+    stream: &ClientStream,
+    reply: &Reply,
+    status: &connectivity::Status,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Connectivity {
+        connectivity: status.clone(),
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("18/ Something went wrong writing to the socket. {e}");
+                    return false;
                 }
-            };
-            resp_body.count
-        },
-        other => {
-            println!("invalid status code: {other}");
-            return Err(errors::Errors::ServerError { message: format!("invalid status code: {other}") })
+            }
         }
     };
-    Ok(output)
-}
-
-fn get_events(
-    http_client: &reqwest::blocking::Client,
-    api: Api
-) -> Result<Vec<Event>, errors::Errors> {
-    let url = api.uri();
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("18/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_antenna_alert(
+    stream: &ClientStream,
+    reply: &Reply,
+    alert: &monitor::AntennaAlert,
+    time: &str,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::AntennaAlert {
+        alert: alert.clone(),
+        time: String::from(time),
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("23/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("23/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_event_log(
+    stream: &ClientStream,
+    reply: &Reply,
+    events: Vec<system_event::SystemEvent>,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::EventLog {
+        events,
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("24/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("24/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_log(
+    stream: &ClientStream,
+    reply: &Reply,
+    lines: Vec<String>,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::Log {
+        lines,
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("25/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("25/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_sighting_upload(
+    stream: &ClientStream,
+    reply: &Reply,
+    status: &upload::Status,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::SightingUpload {
+        sighting_upload: status.clone(),
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("20/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("20/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_notification_channels(
+    stream: &ClientStream,
+    reply: &Reply,
+    channels: Vec<notification_channel::NotificationChannel>,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::NotificationChannels {
+        channels,
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("21/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("21/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_notification_rules(
+    stream: &ClientStream,
+    reply: &Reply,
+    rules: Vec<notification_rule::NotificationRule>,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::NotificationRules {
+        rules,
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("22/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("22/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_watched_bibs(
+    stream: &ClientStream,
+    reply: &Reply,
+    watched_bibs: Vec<watched_bib::WatchedBib>,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::WatchedBibs {
+        watched_bibs,
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("26/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("26/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_watched_bib_sighting(
+    stream: &ClientStream,
+    reply: &Reply,
+    sighting: &sighting::Sighting,
+    note: &str,
+    time: &str,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::WatchedBibSighting {
+        sighting: Box::new(sighting.clone()),
+        note: String::from(note),
+        time: String::from(time),
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("27/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("27/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_sms_supporters(
+    stream: &ClientStream,
+    reply: &Reply,
+    supporters: Vec<sms_supporter::SmsSupporter>,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::SmsSupporters {
+        supporters,
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("28/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("28/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_sms_log(
+    stream: &ClientStream,
+    reply: &Reply,
+    deliveries: Vec<sms_delivery::SmsDelivery>,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::SmsLog {
+        deliveries,
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("29/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("29/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+pub fn write_unknown_chips(
+    stream: &ClientStream,
+    reply: &Reply,
+    unknown_chips: Vec<unknown_chip::UnknownChip>,
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::UnknownChips {
+        unknown_chips,
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("30/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("30/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+fn write_notification_channels_from(stream: &ClientStream, reply: &Reply, sq: &MutexGuard<sqlite::SQLite>) -> bool {
+    match sq.get_notification_channels() {
+        Ok(channels) => write_notification_channels(stream, reply, channels),
+        Err(e) => {
+            error!("error getting notification channels from database. {e}");
+            write_error(stream, reply, errors::Errors::DatabaseError {
+                message: format!("error getting notification channels from database: {e}")
+            })
+        }
+    }
+}
+
+pub fn write_uploader_status(
+    stream: &ClientStream,
+    reply: &Reply,
+    status: uploader::Status,
+    destinations: &[uploader::Destination],
+) -> bool {
+    let _writing = stream.lock();
+    match serde_json::to_writer(stream, &reply.envelope(responses::Responses::ReadAutoUpload {
+        status,
+        destinations: destinations.to_vec(),
+    })) {
+        Ok(_) => {},
+        Err(e) => {
+            match e.io_error_kind() {
+                Some(ErrorKind::BrokenPipe) |
+                Some(ErrorKind::ConnectionReset) |
+                Some(ErrorKind::ConnectionAborted) => {
+                    return false;
+                },
+                _ => {
+                    error!("15/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    let mut writer = stream;
+    match writer.write_all(b"\n") {
+        Ok(_) => {},
+        Err(e) => {
+            match e.kind() {
+                ErrorKind::BrokenPipe |
+                ErrorKind::ConnectionReset |
+                ErrorKind::ConnectionAborted => {
+                    return false;
+                },
+                _ => {
+                    error!("15/ Something went wrong writing to the socket. {e}");
+                    return false;
+                }
+            }
+        }
+    };
+    true
+}
+
+// Any number of remote apis can be uploaded to, but the same one can't be added twice.
+fn remote_conflicts(apis: &[Api], id: i64, uri: &str, token: &str) -> bool {
+    apis.iter().any(|a| {
+        (a.kind() == api::API_TYPE_CHRONOKEEP_REMOTE || a.kind() == api::API_TYPE_CHRONOKEEP_REMOTE_SELF)
+            && (id == 0 || a.id() != id)
+            && a.uri().trim_end_matches('/') == uri.trim_end_matches('/')
+            && a.token() == token
+    })
+}
+
+fn construct_headers(key: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+    headers.insert(AUTHORIZATION, format!("Bearer {key}").parse().unwrap());
+    headers
+}
+
+pub fn upload_reads(
+    http_client: &reqwest::blocking::Client,
+    api: &Api,
+    reads: &[read::Read],
+    batch_id: Option<&str>,
+) -> Result<remote::responses::UploadReadsResponse, errors::Errors> {
+    let url = api.uri();
+    let mut headers = construct_headers(api.token());
+    if let Some(Ok(id)) = batch_id.map(HeaderValue::from_str) {
+        headers.insert(remote_util::HEADER_IDEMPOTENCY_KEY, id);
+    }
+    let response = match http_client.post(format!("{url}reads/add"))
+        .headers(headers)
+        .json(&remote::requests::UploadReadsRequest {
+            reads: reads.to_vec(),
+            ids: reads.iter().map(|r| r.id()).collect(),
+        })
+        .send() {
+            Ok(resp) => resp,
+            Err(e) => {
+                error!("error trying to talk to api: {e}");
+                return Err(errors::Errors::ServerError { message: format!("error trying to talk to api: {e}") })
+            }
+        };
+    let output = match response.status() {
+        reqwest::StatusCode::OK => {
+            match response.json::<remote::responses::UploadReadsResponse>() {
+                Ok(it) => it,
+                Err(e) => {
+                    error!("error trying to parse response from api: {e}");
+                    return Err(errors::Errors::ServerError { message: format!("error trying to parse response from api: {e}") })
+                }
+            }
+        },
+        other => {
+            warn!("invalid status code: {other}");
+            return Err(errors::Errors::ServerError { message: format!("invalid status code: {other}") })
+        }
+    };
+    Ok(output)
+}
+
+fn get_events(
+    http_client: &reqwest::blocking::Client,
+    api: Api
+) -> Result<Vec<Event>, errors::Errors> {
+    let url = api.uri();
     let response = match http_client.get(format!("{url}event/all"))
         .headers(construct_headers(api.token()))
         .send() {
             Ok(resp) => resp,
             Err(e) => {
-                println!("error trying to talk to api: {e}");
+                error!("error trying to talk to api: {e}");
                 return Err(errors::Errors::ServerError { message: format!("error trying to talk to api: {e}") })
             }
         };
@@ -3353,18 +4612,18 @@ fn get_events(
             let resp_body: results::responses::GetEventsResponse = match response.json() {
                 Ok(it) => it,
                 Err(e) => {
-                    println!("error trying to parse response from api: {e}");
+                    error!("error trying to parse response from api: {e}");
                     return Err(errors::Errors::ServerError { message: format!("error trying to parse response from api: {e}") })
                 }
             };
             resp_body.events
         },
         reqwest::StatusCode::NOT_FOUND => {
-            println!("event not found");
+            warn!("event not found");
             return Err(errors::Errors::NotFound);
         }
         other => {
-            println!("invalid status code: {other}");
+            warn!("invalid status code: {other}");
             return Err(errors::Errors::ServerError { message: format!("invalid status code: {other}") })
         }
     };
@@ -3385,7 +4644,7 @@ fn get_event_years(
         .send() {
             Ok(resp) => resp,
             Err(e) => {
-                println!("error trying to talk to api: {e}");
+                error!("error trying to talk to api: {e}");
                 return Err(errors::Errors::ServerError { message: format!("error trying to talk to api: {e}") })
             }
         };
@@ -3394,7 +4653,7 @@ fn get_event_years(
             let resp_body: results::responses::GetEventResponse = match response.json() {
                 Ok(it) => it,
                 Err(e) => {
-                    println!("error trying to parse response from api: {e}");
+                    error!("error trying to parse response from api: {e}");
                     return Err(errors::Errors::ServerError { message: format!("error trying to parse response from api: {e}") })
                 },
             };
@@ -3405,18 +4664,18 @@ fn get_event_years(
             years
         },
         reqwest::StatusCode::NOT_FOUND => {
-            println!("event not found");
+            warn!("event not found");
             return Err(errors::Errors::NotFound);
         }
         other => {
-            println!("invalid status code: {other}");
+            warn!("invalid status code: {other}");
             return Err(errors::Errors::ServerError { message: String::from("invalid status code") })
         }
     };
     Ok(output)
 }
 
-fn get_participants(
+pub fn get_participants(
     http_client: &reqwest::blocking::Client,
     api: &Api,
     slug: &str,
@@ -3432,7 +4691,7 @@ fn get_participants(
         .send() {
             Ok(resp) => resp,
             Err(e) => {
-                println!("error trying to talk to api: {e}");
+                error!("error trying to talk to api: {e}");
                 return Err(errors::Errors::ServerError { message: format!("error trying to talk to api: {e}") })
             }
         };
@@ -3441,25 +4700,25 @@ fn get_participants(
             let resp_body: results::responses::GetParticipantsResponse = match response.json() {
                 Ok(it) => it,
                 Err(e) => {
-                    println!("error trying to parse response from api: {e}");
+                    error!("error trying to parse response from api: {e}");
                     return Err(errors::Errors::ServerError { message: format!("error trying to parse response from api: {e}") })
                 }
             };
             resp_body.participants
         },
         reqwest::StatusCode::NOT_FOUND => {
-            println!("event not found");
+            warn!("event not found");
             return Err(errors::Errors::NotFound);
         }
         other => {
-            println!("invalid status code: {other}");
+            warn!("invalid status code: {other}");
             return Err(errors::Errors::ServerError { message: String::from("invalid status code") })
         }
     };
     Ok(output)
 }
 
-fn get_bibchips(
+pub fn get_bibchips(
     http_client: &reqwest::blocking::Client,
     api: &Api,
     slug: &str,
@@ -3475,7 +4734,7 @@ fn get_bibchips(
         .send() {
             Ok(resp) => resp,
             Err(e) => {
-                println!("error trying to talk to api: {e}");
+                error!("error trying to talk to api: {e}");
                 return Err(errors::Errors::ServerError { message: format!("error trying to talk to api: {e}") })
             }
         };
@@ -3484,20 +4743,65 @@ fn get_bibchips(
             let resp_body: results::responses::GetBibChipsResponse = match response.json() {
                 Ok(it) => it,
                 Err(e) => {
-                    println!("error trying to parse response from api: {e}");
+                    error!("error trying to parse response from api: {e}");
                     return Err(errors::Errors::ServerError { message: format!("error trying to parse response from api: {e}") })
                 }
             };
             resp_body.bib_chips
         },
         reqwest::StatusCode::NOT_FOUND => {
-            println!("event not found");
+            warn!("event not found");
             return Err(errors::Errors::NotFound);
         }
         other => {
-            println!("invalid status code: {other}");
+            warn!("invalid status code: {other}");
             return Err(errors::Errors::ServerError { message: String::from("invalid status code") })
         }
     };
     Ok(output)
+}
+
+pub fn upload_sightings(
+    http_client: &reqwest::blocking::Client,
+    api: &Api,
+    slug: &str,
+    year: &str,
+    sightings: Vec<results::requests::UploadSighting>
+) -> Result<usize, errors::Errors> {
+    let url = api.uri();
+    let response = match http_client.post(format!("{url}sightings/add"))
+        .headers(construct_headers(api.token()))
+        .json(&results::requests::AddSightingsRequest{
+            slug: String::from(slug),
+            year: String::from(year),
+            sightings
+        })
+        .send() {
+            Ok(resp) => resp,
+            Err(e) => {
+                error!("error trying to talk to api: {e}");
+                return Err(errors::Errors::ServerError { message: format!("error trying to talk to api: {e}") })
+            }
+        };
+    let output = match response.status() {
+        reqwest::StatusCode::OK => {
+            let resp_body: results::responses::AddSightingsResponse = match response.json() {
+                Ok(it) => it,
+                Err(e) => {
+                    error!("error trying to parse response from api: {e}");
+                    return Err(errors::Errors::ServerError { message: format!("error trying to parse response from api: {e}") })
+                }
+            };
+            resp_body.count
+        },
+        reqwest::StatusCode::NOT_FOUND => {
+            warn!("event not found");
+            return Err(errors::Errors::NotFound);
+        }
+        other => {
+            warn!("invalid status code: {other}");
+            return Err(errors::Errors::ServerError { message: format!("invalid status code: {other}") })
+        }
+    };
+    Ok(output)
 }
\ No newline at end of file
@@ -25,6 +25,18 @@ pub enum Errors {
     InvalidApiType {
         message: String,
     },
+    InvalidNotificationChannel {
+        message: String,
+    },
+    InvalidNotificationRule {
+        message: String,
+    },
+    InvalidWatchedBib {
+        message: String,
+    },
+    InvalidSmsSupporter {
+        message: String,
+    },
     AlreadySubscribed {
         message: String,
     },
@@ -36,4 +48,13 @@ pub enum Errors {
     NotAllowed {
         message: String,
     },
+    UnsupportedCommand {
+        command: String,
+        capabilities: Vec<String>,
+    },
+    UnsupportedVersion {
+        message: String,
+        min_version: usize,
+        max_version: usize,
+    },
 }
\ No newline at end of file
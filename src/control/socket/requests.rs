@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{network::api, objects::{bibchip::BibChip, participant, read, setting::Setting}};
+use crate::{events::filter::SubscriptionFilter, network::api, objects::{bibchip::BibChip, notification_channel, notification_rule, participant, read, setting::Setting, sms_supporter, system_event::EventLogQuery, watched_bib}};
 
 use super::notifications;
 
@@ -43,14 +43,70 @@ pub enum Request {
     Connect {
         reads: bool,
         sightings: bool,
+        filter: Option<SubscriptionFilter>,
+        // Protocol versions the client supports, clients that don't send these are version 1.
+        min_version: Option<usize>,
+        max_version: Option<usize>,
+        features: Option<Vec<String>>,
     },
     Disconnect,
     KeepaliveAck,
     Quit,
     Shutdown,
     Restart,
+    // Notification channel related requests, an id of 0 adds a new channel.
+    NotificationChannelsGet,
+    NotificationChannelSave {
+        channel: notification_channel::NotificationChannel,
+    },
+    NotificationChannelRemove {
+        id: i64,
+    },
+    // Notification rule related requests, resetting a rule goes back to the defaults for that kind.
+    NotificationRulesGet,
+    NotificationRuleSave {
+        rule: notification_rule::NotificationRule,
+    },
+    NotificationRuleReset {
+        kind: String,
+    },
+    // Watched bib related requests, saving a bib that's already watched replaces its note.
+    WatchedBibsGet,
+    WatchedBibSave {
+        watched_bib: watched_bib::WatchedBib,
+    },
+    WatchedBibRemove {
+        bib: String,
+    },
+    // SMS related requests, the log is newest first and can be limited to a single bib.
+    SmsSupportersGet,
+    SmsSupporterAdd {
+        supporter: sms_supporter::SmsSupporter,
+    },
+    SmsSupporterRemove {
+        supporter: sms_supporter::SmsSupporter,
+    },
+    SmsLogGet {
+        #[serde(default)]
+        bib: Option<String>,
+        #[serde(default)]
+        limit: Option<u32>,
+    },
+    // Event log related requests, every filter is optional and the newest entries come first.
+    EventLogGet {
+        #[serde(flatten)]
+        query: EventLogQuery,
+    },
+    // The newest lines from the log file.
+    LogGet {
+        #[serde(default)]
+        lines: Option<usize>,
+    },
     // Participants related requests
     ParticipantsGet,
+    ParticipantSyncHistoryGet,
+    // Whether sightings are being sent to the results api and how many are left.
+    SightingUploadGet,
     ParticipantsRemove,
     ParticipantsAdd {
         participants: Vec<RequestParticipant>,
@@ -61,6 +117,8 @@ pub enum Request {
     BibChipsAdd {
         bib_chips: Vec<BibChip>,
     },
+    // Chips we've seen that nobody has, assigning one with BibChipsAdd turns held reads into sightings.
+    UnknownChipsGet,
     // Reader related requests
     ReaderAdd {
         id: i64,
@@ -119,7 +177,10 @@ pub enum Request {
     Subscribe {
         reads: bool,
         sightings: bool,
+        filter: Option<SubscriptionFilter>,
     },
+    // Whether we can reach the remote apis.
+    ConnectivityGet,
     // Time related requests
     TimeGet,
     TimeSet {
@@ -132,6 +193,12 @@ pub enum Request {
     },
 }
 
+// Any request can carry a request_id which is echoed back on the responses to it.
+#[derive(Deserialize, Debug)]
+pub struct RequestId {
+    pub request_id: Option<serde_json::Value>,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all="snake_case")]
 pub enum AutoUploadQuery {
@@ -169,6 +236,7 @@ impl RequestParticipant {
             self.age_group.clone(),
             self.distance.clone(),
             self.anonymous
-        )
+        ).with_sms(self.sms_enabled, self.mobile.clone())
+        .with_registration(self.id.clone(), self.apparel.clone())
     }
 }
\ No newline at end of file
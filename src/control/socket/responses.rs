@@ -1,9 +1,21 @@
 use serde::Serialize;
 
-use crate::{network::api, objects::{bibchip::{self, BibChip}, event::Event, participant::Participant, read, setting, sighting::Sighting}, reader::MAX_ANTENNAS, remote::uploader};
+use crate::{network::{api, connectivity}, objects::{bibchip::{self, BibChip}, event::Event, notification_channel, notification_rule, participant::Participant, read, setting, sighting::Sighting, sms_delivery::SmsDelivery, sms_supporter::SmsSupporter, system_event::SystemEvent, unknown_chip::UnknownChip, watched_bib::WatchedBib}, reader::{monitor, MAX_ANTENNAS}, remote::uploader, results::{sync, upload}};
 
 use super::{errors, notifications};
 
+// Everything written to a client is wrapped in this.  Direct responses echo the request_id
+// the client sent with the request, anything we push on our own is marked as an event.
+#[derive(Serialize, Debug)]
+pub struct Envelope {
+    #[serde(flatten)]
+    pub response: Responses,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub request_id: Option<serde_json::Value>,
+    #[serde(skip_serializing_if="std::ops::Not::not")]
+    pub event: bool,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(tag="command", rename_all="snake_case")]
 pub enum Responses {
@@ -62,6 +74,49 @@ pub enum Responses {
     },
     ReadAutoUpload {
         status: uploader::Status,
+        destinations: Vec<uploader::Destination>,
+    },
+    Connectivity {
+        connectivity: connectivity::Status,
+    },
+    AntennaAlert {
+        alert: monitor::AntennaAlert,
+        time: String,
+    },
+    WatchedBibSighting {
+        sighting: Box<Sighting>,
+        note: String,
+        time: String,
+    },
+    WatchedBibs {
+        watched_bibs: Vec<WatchedBib>,
+    },
+    SmsSupporters {
+        supporters: Vec<SmsSupporter>,
+    },
+    SmsLog {
+        deliveries: Vec<SmsDelivery>,
+    },
+    UnknownChips {
+        unknown_chips: Vec<UnknownChip>,
+    },
+    ParticipantSyncHistory {
+        history: Vec<sync::SyncResult>,
+    },
+    SightingUpload {
+        sighting_upload: upload::Status,
+    },
+    NotificationChannels {
+        channels: Vec<notification_channel::NotificationChannel>,
+    },
+    NotificationRules {
+        rules: Vec<notification_rule::NotificationRule>,
+    },
+    EventLog {
+        events: Vec<SystemEvent>,
+    },
+    Log {
+        lines: Vec<String>,
     },
     ConnectionSuccessful {
         name: String,
@@ -73,12 +128,19 @@ pub enum Responses {
         updatable: bool,
         auto_upload: uploader::Status,
         portal_version: &'static str,
+        // Only sent to clients that negotiated a version, older clients don't know about them.
+        #[serde(skip_serializing_if="Option::is_none")]
+        min_version: Option<usize>,
+        #[serde(skip_serializing_if="Option::is_none")]
+        max_version: Option<usize>,
+        #[serde(skip_serializing_if="Option::is_none")]
+        capabilities: Option<Vec<String>>,
     },
     Keepalive,
     Disconnect,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all="snake_case")]
 pub struct Reader {
     pub id: i64,
@@ -0,0 +1,99 @@
+use serde_json::json;
+
+use super::{get_capabilities, negotiate_version, responses::Responses, Reply, CAPABILITIES, CAPABILITY_CONNECTIVITY, CAPABILITY_READS, CAPABILITY_REQUEST_IDS, CAPABILITY_SMS, CAPABILITY_SUBSCRIPTION_FILTERS, CAPABILITY_UNKNOWN_CHIPS, CAPABILITY_WATCHED_BIBS, CONNECTION_VERS, CONNECTION_VERS_MIN, CONNECTION_VERS_REQUEST_IDS};
+
+#[test]
+fn test_capabilities() {
+    for (ix, (name, added)) in CAPABILITIES.iter().enumerate() {
+        assert!(*added >= CONNECTION_VERS_MIN && *added <= CONNECTION_VERS, "{name} added in unknown version {added}");
+        assert!(!CAPABILITIES[ix + 1..].iter().any(|(other, _)| other == name), "{name} listed twice");
+    }
+    // every version we speak has to have given clients something new
+    for version in CONNECTION_VERS_MIN..=CONNECTION_VERS {
+        assert!(CAPABILITIES.iter().any(|(_, added)| *added == version), "nothing added in version {version}");
+    }
+}
+
+#[test]
+fn test_negotiate_version() {
+    // version 1 clients don't send versions at all
+    assert_eq!(Some(CONNECTION_VERS_MIN), negotiate_version(None, None));
+    assert_eq!(Some(CONNECTION_VERS), negotiate_version(Some(CONNECTION_VERS_MIN), Some(CONNECTION_VERS)));
+    assert_eq!(Some(CONNECTION_VERS), negotiate_version(Some(CONNECTION_VERS), Some(CONNECTION_VERS)));
+    assert_eq!(Some(CONNECTION_VERS_MIN), negotiate_version(Some(CONNECTION_VERS_MIN), Some(CONNECTION_VERS_MIN)));
+    // newer clients get the newest version we know
+    assert_eq!(Some(CONNECTION_VERS), negotiate_version(Some(CONNECTION_VERS_MIN), Some(CONNECTION_VERS + 1)));
+    assert_eq!(Some(CONNECTION_VERS), negotiate_version(Some(CONNECTION_VERS), Some(CONNECTION_VERS + 5)));
+    assert_eq!(None, negotiate_version(Some(CONNECTION_VERS + 1), Some(CONNECTION_VERS + 2)));
+    // a single version means that version exactly
+    assert_eq!(Some(3), negotiate_version(Some(3), None));
+    assert_eq!(Some(3), negotiate_version(None, Some(3)));
+    assert_eq!(Some(CONNECTION_VERS), negotiate_version(None, Some(CONNECTION_VERS + 1)));
+    assert_eq!(None, negotiate_version(Some(CONNECTION_VERS + 1), None));
+    // nothing below the oldest version we speak
+    assert_eq!(None, negotiate_version(Some(0), Some(0)));
+    assert_eq!(None, negotiate_version(None, Some(0)));
+    assert_eq!(Some(CONNECTION_VERS_MIN), negotiate_version(Some(0), Some(CONNECTION_VERS_MIN)));
+    // a client can't want a newer version than it speaks
+    assert_eq!(None, negotiate_version(Some(3), Some(2)));
+}
+
+#[test]
+fn test_get_capabilities() {
+    let v1 = get_capabilities(1, &None);
+    assert!(v1.iter().any(|c| c == CAPABILITY_READS));
+    assert!(!v1.iter().any(|c| c == CAPABILITY_SUBSCRIPTION_FILTERS));
+    let v2 = get_capabilities(2, &None);
+    assert!(v2.iter().any(|c| c == CAPABILITY_SUBSCRIPTION_FILTERS));
+    assert!(!v2.iter().any(|c| c == CAPABILITY_REQUEST_IDS));
+    let v3 = get_capabilities(3, &None);
+    assert!(v3.iter().any(|c| c == CAPABILITY_REQUEST_IDS));
+    assert!(!v3.iter().any(|c| c == CAPABILITY_CONNECTIVITY));
+    let v5 = get_capabilities(5, &None);
+    assert!(!v5.iter().any(|c| c == CAPABILITY_WATCHED_BIBS || c == CAPABILITY_SMS || c == CAPABILITY_UNKNOWN_CHIPS));
+    let newest = get_capabilities(CONNECTION_VERS, &None);
+    assert_eq!(CAPABILITIES.len(), newest.len());
+    assert_eq!(newest, get_capabilities(CONNECTION_VERS + 1, &None));
+    // each version has everything the one before it did
+    for version in CONNECTION_VERS_MIN..CONNECTION_VERS {
+        let older = get_capabilities(version, &None);
+        let newer = get_capabilities(version + 1, &None);
+        assert!(older.len() < newer.len());
+        assert!(older.iter().all(|c| newer.contains(c)));
+    }
+    // only the features asked for that we know about
+    let features = Some(vec![String::from(CAPABILITY_READS), String::from(CAPABILITY_SMS), String::from("teleport")]);
+    assert_eq!(vec![String::from(CAPABILITY_READS), String::from(CAPABILITY_SMS)], get_capabilities(CONNECTION_VERS, &features));
+    assert_eq!(vec![String::from(CAPABILITY_READS)], get_capabilities(5, &features));
+    assert!(get_capabilities(CONNECTION_VERS, &Some(Vec::new())).is_empty());
+}
+
+fn envelope(reply: &Reply, response: Responses) -> String {
+    serde_json::to_string(&reply.envelope(response)).unwrap()
+}
+
+#[test]
+fn test_envelope_old_versions() {
+    // clients from before request ids get exactly what they always have
+    for version in CONNECTION_VERS_MIN..CONNECTION_VERS_REQUEST_IDS {
+        let reply = Reply::response(Some(json!(12)), version);
+        assert_eq!(r#"{"command":"keepalive"}"#, envelope(&reply, Responses::Keepalive));
+        assert_eq!(r#"{"command":"success","count":3}"#, envelope(&reply, Responses::Success { count: 3 }));
+        assert_eq!(r#"{"command":"keepalive"}"#, envelope(&Reply::event(version), Responses::Keepalive));
+    }
+}
+
+#[test]
+fn test_envelope_request_ids() {
+    for version in CONNECTION_VERS_REQUEST_IDS..=CONNECTION_VERS {
+        let reply = Reply::response(Some(json!(12)), version);
+        assert_eq!(r#"{"command":"success","count":3,"request_id":12}"#, envelope(&reply, Responses::Success { count: 3 }));
+        let reply = Reply::response(Some(json!("abc")), version);
+        assert_eq!(r#"{"command":"keepalive","request_id":"abc"}"#, envelope(&reply, Responses::Keepalive));
+        // a request without an id gets a plain response, never one marked as an event
+        let reply = Reply::response(None, version);
+        assert_eq!(r#"{"command":"keepalive"}"#, envelope(&reply, Responses::Keepalive));
+        assert_eq!(r#"{"command":"keepalive","event":true}"#, envelope(&Reply::event(version), Responses::Keepalive));
+    }
+}
+
@@ -1,6 +1,7 @@
 use std::{sync::{Arc, Mutex, Condvar}, time::{Duration, Instant}};
 
 use rand::Rng;
+use log::error;
 
 use crate::sound_board;
 
@@ -22,7 +23,9 @@ pub enum SoundType {
     Introduction,
     StartupFinished,
     StartupInProgress,
-    CustomNotAvailable
+    CustomNotAvailable,
+    Alarm,
+    WatchedBib,
 }
 
 impl SoundNotifier {
@@ -103,6 +106,8 @@ impl Sounds {
                                     SoundType::StartupFinished => control.sound_board.play_startup_finished(control.volume),
                                     SoundType::StartupInProgress => control.sound_board.play_startup_in_progress(control.volume),
                                     SoundType::CustomNotAvailable => control.sound_board.play_custom_not_available(control.volume),
+                                    SoundType::Alarm => control.sound_board.play_alarm(control.volume),
+                                    SoundType::WatchedBib => control.sound_board.play_watched_bib(control.volume),
                                 }
                             }
                         };
@@ -111,7 +116,7 @@ impl Sounds {
                     }
                 }
             } else {
-                println!("Error waiting to play a sound.");
+                error!("Error waiting to play a sound.");
             }
         }
     }
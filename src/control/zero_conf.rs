@@ -2,6 +2,7 @@ use std::{net::{UdpSocket, Ipv4Addr, SocketAddr}, sync::{Arc, Mutex}, time::Dura
 
 use rand::{thread_rng, Rng};
 use socket2::{Socket, Domain, Type, Protocol};
+use log::{debug, error, info, warn};
 
 use crate::database::{Database, sqlite};
 
@@ -27,18 +28,18 @@ impl ZeroConf {
         for _ in 0..10 {
             server_id.push(chars[rng.gen_range(0..chars.len())])
         }
-        println!("Zero Conf Server id is {}, port is {}", server_id, ZERO_CONF_PORT);
+        info!("Zero Conf Server id is {}, port is {}", server_id, ZERO_CONF_PORT);
         let socket = match Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP)) {
             Ok(sock) => sock,
             Err(e) => {
-                println!("Something went wrong trying to create our socket: {e}");
+                error!("Something went wrong trying to create our socket: {e}");
                 return Err("unable to create udp socket")
             }
         };
         let address: SocketAddr = match format!("0.0.0.0:{ZERO_CONF_PORT}").parse() {
             Ok(a) => a,
             Err(e) => {
-                println!("Error creating SockAddr: {e}");
+                error!("Error creating SockAddr: {e}");
                 return Err("unable to create sock address")
             }
         };
@@ -48,16 +49,16 @@ impl ZeroConf {
         match socket.set_reuse_address(true) {
             Ok(_) => {}
             Err(e) => {
-                println!("Unable to set SO_REUSEADDR to true: {e}");
+                warn!("Unable to set SO_REUSEADDR to true: {e}");
                 return Err("error setting SO_REUSEADDR to true")
             }
         }
         match socket.bind(&address) {
             Ok(_) => {
-                println!("Zero conf socket successfully bound.");
+                info!("Zero conf socket successfully bound.");
             }
             Err(e) => {
-                println!("Error binding zero conf socket: {e}");
+                error!("Error binding zero conf socket: {e}");
                 return Err("error binding socket")
             }
         }
@@ -65,7 +66,7 @@ impl ZeroConf {
         match socket.set_read_timeout(Some(Duration::new(2,0))) {
             Ok(_) => {},
             Err(e) => {
-                println!("Unable to set read timeout on socket: {e}");
+                warn!("Unable to set read timeout on socket: {e}");
                 return Err("unable to set read timeout")
             }
         }
@@ -73,7 +74,7 @@ impl ZeroConf {
         let addresses = match if_addrs::get_if_addrs() {
             Ok(addrs) => addrs,
             Err(e) => {
-                println!("Error getting network interfaces: {e}");
+                error!("Error getting network interfaces: {e}");
                 return Err("error getting network interfaces")
             }
         };
@@ -85,10 +86,10 @@ impl ZeroConf {
                         &addr.ip
                     ) {
                         Ok(_) => {
-                            println!("Successfully joined multicast group on ip {}.", addr.ip);
+                            info!("Successfully joined multicast group on ip {}.", addr.ip);
                         },
                         Err(e) => {
-                            println!("Unable to join multicast group. {e}");
+                            warn!("Unable to join multicast group. {e}");
                             return Err("unable to join multicast group")
                         },
                     }
@@ -124,7 +125,7 @@ impl ZeroConf {
                         ErrorKind::TimedOut |
                         ErrorKind::WouldBlock => {},
                         _ => {
-                            println!("Zero Conf - Error receiving: {e}");
+                            error!("Zero Conf - Error receiving: {e}");
                         }
                     }
                     continue
@@ -141,26 +142,26 @@ impl ZeroConf {
                                         response = format!("[{}|{}|{}]", name.value(), self.server_id, self.control_port)
                                     }
                                     Err(e) => {
-                                        println!("Error getting server name: {e}")
+                                        error!("Error getting server name: {e}")
                                     }
                                 }
                             }
                             match self.socket.send_to(response.as_bytes(), src) {
                                 Ok(num) => {
-                                    println!("Sent {response} -- {src} -- {num} bytes.");
+                                    debug!("Sent {response} -- {src} -- {num} bytes.");
                                 },
                                 Err(e) => {
-                                    println!("Error sending response: {e}");
+                                    error!("Error sending response: {e}");
                                 }
                             };
                         },
                         u => {
-                            println!("Unknown request received: {u}");
+                            warn!("Unknown request received: {u}");
                         }
                     };
                 },
                 Err(e) => {
-                    println!("Error translating value received: {e}");
+                    error!("Error translating value received: {e}");
                 }
             };
         }
@@ -175,10 +176,10 @@ impl ZeroConf {
                                 &addr.ip
                             ) {
                                 Ok(_) => {
-                                    println!("Successfully left multicast group on ip {}.", addr.ip);
+                                    info!("Successfully left multicast group on ip {}.", addr.ip);
                                 },
                                 Err(e) => {
-                                    println!("Unable to leave multicast group. {e}");
+                                    warn!("Unable to leave multicast group. {e}");
                                 },
                             }
                         }
@@ -186,9 +187,9 @@ impl ZeroConf {
                 }
             },
             Err(e) => {
-                println!("Error getting network interfaces: {e}");
+                error!("Error getting network interfaces: {e}");
             }
         };
-        println!("Zero Conf Server has shut down.");
+        info!("Zero Conf Server has shut down.");
     }
 }
\ No newline at end of file
@@ -1,10 +1,20 @@
-use crate::objects::{bibchip, participant, read, setting, sighting};
+use crate::objects::{bibchip, notification_channel, notification_rule, participant, read, setting, sighting, system_event, upload_batch, watched_bib, sms_supporter, sms_delivery, unknown_chip};
 use crate::network::api;
 use crate::reader;
-use std::fmt;
+use std::{fmt, sync::Mutex};
+use log::error;
 
 pub mod sqlite;
 
+// Saves an entry to the event log.  Don't call it while holding the database lock.
+pub fn log_event(sqlite: &Mutex<sqlite::SQLite>, severity: &str, source: &str, kind: &str, detail: serde_json::Value) {
+    if let Ok(sq) = sqlite.lock() {
+        if let Err(e) = sq.save_system_event(&system_event::SystemEvent::log(severity, source, kind, detail)) {
+            error!("Error saving {kind} to the event log. {e}");
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum DBError {
     ConnectionError(String),
@@ -56,8 +66,15 @@ pub trait Database {
     fn delete_reads(&self, start: i64, end: i64) -> Result<usize, DBError>;
     fn delete_all_reads(&self) -> Result<usize, DBError>;
     fn reset_reads_status(&self) -> Result<usize, DBError>;
+    // Only resets reads for the given chips (or bibs), so their sightings can be processed again.
+    fn reset_reads_status_for(&mut self, chips: &[String]) -> Result<usize, DBError>;
     fn reset_reads_upload(&self) -> Result<usize, DBError>;
     fn get_useful_reads(&self) -> Result<Vec<read::Read>, DBError>;
+    // Unused reads saved after the given read id, oldest first.
+    fn get_unused_reads_after(&self, id: u64) -> Result<Vec<read::Read>, DBError>;
+    // Puts held reads for the given chips back in line to be made into sightings.
+    fn release_held_reads(&mut self, chips: &[String]) -> Result<usize, DBError>;
+    fn get_unknown_chips(&self) -> Result<Vec<unknown_chip::UnknownChip>, DBError>;
     fn get_not_uploaded_reads(&self) -> Result<Vec<read::Read>, DBError>;
     fn update_reads_status(&mut self, reads: &Vec<read::Read>) -> Result<usize, DBError>;
     // Participant information
@@ -69,10 +86,61 @@ pub trait Database {
     fn add_bibchips(&mut self, bibchips: &Vec<bibchip::BibChip>) -> Result<usize, DBError>;
     fn delete_all_bibchips(&self) -> Result<usize, DBError>;
     fn delete_bibchips(&self, bib: &str) -> Result<usize, DBError>;
+    fn delete_bibchip(&self, chip: &str) -> Result<usize, DBError>;
     fn get_bibchips(&self) -> Result<Vec<bibchip::BibChip>, DBError>;
     // Sighting information
     fn save_sightings(&mut self, sightings: &Vec<sighting::Sighting>) -> Result<usize, DBError>;
     fn get_sightings(&self, start: i64, end: i64) -> Result<Vec<sighting::Sighting>, DBError>;
     fn get_all_sightings(&self) -> Result<Vec<sighting::Sighting>, DBError>;
     fn delete_sightings(&self) -> Result<usize, DBError>;
+    // System events
+    fn save_system_event(&self, event: &system_event::SystemEvent) -> Result<i64, DBError>;
+    fn get_system_events_after(&self, id: i64, limit: u32) -> Result<Vec<system_event::SystemEvent>, DBError>;
+    // Newest first.
+    fn get_system_events(&self, kind: &str, limit: u32) -> Result<Vec<system_event::SystemEvent>, DBError>;
+    fn get_event_log(&self, query: &system_event::EventLogQuery) -> Result<Vec<system_event::SystemEvent>, DBError>;
+    // Webhook delivery, ids are chip_reads ids for reads and sighting ids for sightings
+    fn get_reads_after(&self, id: i64, limit: u32) -> Result<Vec<read::Read>, DBError>;
+    fn get_sightings_after(&self, id: i64, limit: u32) -> Result<Vec<(i64, sighting::Sighting)>, DBError>;
+    // Which sightings have been sent to which event on a results api
+    fn get_sightings_to_upload(&self, api_id: i64, slug: &str, year: &str, limit: u32) -> Result<Vec<sighting::Sighting>, DBError>;
+    fn save_uploaded_sightings(&mut self, api_id: i64, slug: &str, year: &str, sightings: &[sighting::Sighting]) -> Result<usize, DBError>;
+    // Returns the number of sightings still to upload and the number uploaded.
+    fn get_sighting_upload_counts(&self, api_id: i64, slug: &str, year: &str) -> Result<(usize, usize), DBError>;
+    // Places notifications are sent, an id of 0 or less adds a new channel.
+    fn save_notification_channel(&self, channel: &notification_channel::NotificationChannel) -> Result<i64, DBError>;
+    fn get_notification_channels(&self) -> Result<Vec<notification_channel::NotificationChannel>, DBError>;
+    fn delete_notification_channel(&self, id: i64) -> Result<usize, DBError>;
+    // Saved rules for how each kind of notification is sent, saving a kind replaces its rule.
+    fn save_notification_rule(&self, rule: &notification_rule::NotificationRule) -> Result<usize, DBError>;
+    fn get_notification_rules(&self) -> Result<Vec<notification_rule::NotificationRule>, DBError>;
+    fn delete_notification_rule(&self, kind: &str) -> Result<usize, DBError>;
+    // Bibs to alert on when they're seen, saving a bib that's already watched replaces its note.
+    fn save_watched_bib(&self, watched: &watched_bib::WatchedBib) -> Result<usize, DBError>;
+    fn get_watched_bibs(&self) -> Result<Vec<watched_bib::WatchedBib>, DBError>;
+    fn delete_watched_bib(&self, bib: &str) -> Result<usize, DBError>;
+    // Returns false if we've already alerted for this bib and read.
+    fn save_watched_bib_alert(&self, bib: &str, read_id: u64) -> Result<bool, DBError>;
+    // People texted when a participant is seen, on top of the participant's own number.
+    fn save_sms_supporter(&self, supporter: &sms_supporter::SmsSupporter) -> Result<usize, DBError>;
+    fn get_sms_supporters(&self) -> Result<Vec<sms_supporter::SmsSupporter>, DBError>;
+    fn delete_sms_supporter(&self, supporter: &sms_supporter::SmsSupporter) -> Result<usize, DBError>;
+    // Texts we've tried to send, newest first.
+    fn save_sms_delivery(&self, delivery: &sms_delivery::SmsDelivery) -> Result<i64, DBError>;
+    fn get_sms_deliveries(&self, bib: Option<&str>, limit: Option<u32>) -> Result<Vec<sms_delivery::SmsDelivery>, DBError>;
+    fn sms_delivered(&self, bib: &str, mobile: &str, read_seconds: i64, read_milliseconds: u32) -> Result<bool, DBError>;
+    fn get_webhook_cursor(&self, api_id: i64, stream: &str) -> Result<i64, DBError>;
+    fn set_webhook_cursor(&self, api_id: i64, stream: &str, cursor: i64) -> Result<usize, DBError>;
+    // Which reads have been uploaded to which remote apis
+    fn get_reads_to_upload(&self, api_id: i64, limit: u32) -> Result<Vec<read::Read>, DBError>;
+    fn save_uploaded(&mut self, api_id: i64, reads: &[read::Read]) -> Result<usize, DBError>;
+    // Returns the number of reads still to upload, the number uploaded and the number rejected.
+    fn get_upload_counts(&self, api_id: i64) -> Result<(usize, usize, usize), DBError>;
+    // Batches of reads waiting to be uploaded, only batches that haven't been rejected are returned.
+    fn save_upload_batch(&mut self, batch: &upload_batch::UploadBatch) -> Result<usize, DBError>;
+    fn get_upload_batches(&self, api_id: i64) -> Result<Vec<upload_batch::UploadBatch>, DBError>;
+    fn set_upload_batch_attempts(&self, batch_id: &str, attempts: u32) -> Result<usize, DBError>;
+    fn complete_upload_batch(&mut self, batch: &upload_batch::UploadBatch) -> Result<usize, DBError>;
+    fn reject_upload_batch(&self, batch_id: &str) -> Result<usize, DBError>;
+    fn accept_upload_batch_reads(&mut self, batch: &upload_batch::UploadBatch, read_ids: &[u64]) -> Result<usize, DBError>;
 }
\ No newline at end of file
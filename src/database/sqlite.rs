@@ -1,8 +1,9 @@
-use crate::objects::{bibchip, setting, participant, read, sighting};
+use crate::objects::{bibchip, setting, participant, read, sighting, system_event, upload_batch, notification_channel, notification_rule, watched_bib, sms_supporter, sms_delivery, unknown_chip};
 use crate::network::api;
 use crate::database::DBError;
 use crate::reader;
 
+use std::cell::Cell;
 use std::env;
 use std::path::Path;
 use std::str::FromStr;
@@ -13,12 +14,20 @@ mod tests;
 const DATABASE_URI: &str = "./chronokeep-portal.sqlite";
 
 const DATABASE_VERSION_SETTING: &str = "PORTAL_DATABASE_VERSION";
-const DATABASE_VERSION: u16 = 4;
+const DATABASE_VERSION: u16 = 18;
+const EVENT_LOG_DEFAULT_LIMIT: u32 = 100;
+const EVENT_LOG_MAX_LIMIT: u32 = 1000;
+const SMS_LOG_DEFAULT_LIMIT: u32 = 100;
+const SMS_LOG_MAX_LIMIT: u32 = 1000;
 
 const DATABASE_PATH_ENV: &str = "PORTAL_DATABASE_PATH";
 
 pub struct SQLite {
     conn: rusqlite::Connection,
+    // Bumped whenever participants or bibchips change, or reads are put back in line to be
+    // processed, so the sightings processor knows when what it has in memory is stale.
+    participants_generation: Cell<u64>,
+    reads_generation: Cell<u64>,
 }
 
 struct TempReader {
@@ -35,24 +44,42 @@ impl SQLite {
         if let Ok(db_path) = env::var(DATABASE_PATH_ENV) {
             let new_conn = rusqlite::Connection::open(db_path);
             match new_conn {
-                Ok(c) => 
-                    Ok(SQLite {
-                        conn: c,
-                    }),
+                Ok(c) => Ok(SQLite::from_connection(c)),
                 Err(e) => Err(DBError::ConnectionError(e.to_string()))
             }
         } else {
             let new_conn = rusqlite::Connection::open(DATABASE_URI);
             match new_conn {
-                Ok(c) => 
-                    Ok(SQLite {
-                        conn: c,
-                    }),
+                Ok(c) => Ok(SQLite::from_connection(c)),
                 Err(e) => Err(DBError::ConnectionError(e.to_string()))
             }
         }
     }
 
+    fn from_connection(conn: rusqlite::Connection) -> SQLite {
+        SQLite {
+            conn,
+            participants_generation: Cell::new(0),
+            reads_generation: Cell::new(0),
+        }
+    }
+
+    pub fn participants_generation(&self) -> u64 {
+        self.participants_generation.get()
+    }
+
+    pub fn reads_generation(&self) -> u64 {
+        self.reads_generation.get()
+    }
+
+    fn participants_changed(&self) {
+        self.participants_generation.set(self.participants_generation.get() + 1);
+    }
+
+    fn reads_changed(&self) {
+        self.reads_generation.set(self.reads_generation.get() + 1);
+    }
+
     pub fn already_exists() -> bool {
         match Path::try_exists(Path::new(DATABASE_URI)) {
             Ok(val) => val,
@@ -77,17 +104,94 @@ impl SQLite {
                     return Err(e)
                 }
             }
+            if old_version < 5 {
+                if let Err(e) = self.update_to_v5() {
+                    return Err(e)
+                }
+            }
+            if old_version < 6 {
+                if let Err(e) = self.update_to_v6() {
+                    return Err(e)
+                }
+            }
+            if old_version < 7 {
+                if let Err(e) = self.update_to_v7() {
+                    return Err(e)
+                }
+            }
+            if old_version < 8 {
+                if let Err(e) = self.update_to_v8() {
+                    return Err(e)
+                }
+            }
+            if old_version < 9 {
+                if let Err(e) = self.update_to_v9() {
+                    return Err(e)
+                }
+            }
+            if old_version < 10 {
+                if let Err(e) = self.update_to_v10() {
+                    return Err(e)
+                }
+            }
+            if old_version < 11 {
+                if let Err(e) = self.update_to_v11() {
+                    return Err(e)
+                }
+            }
+            if old_version < 12 {
+                if let Err(e) = self.update_to_v12() {
+                    return Err(e)
+                }
+            }
+            if old_version < 13 {
+                if let Err(e) = self.update_to_v13() {
+                    return Err(e)
+                }
+            }
+            if old_version < 14 {
+                if let Err(e) = self.update_to_v14() {
+                    return Err(e)
+                }
+            }
+            if old_version < 15 {
+                if let Err(e) = self.update_to_v15() {
+                    return Err(e)
+                }
+            }
+            if old_version < 16 {
+                if let Err(e) = self.update_to_v16() {
+                    return Err(e)
+                }
+            }
+            if old_version < 17 {
+                if let Err(e) = self.update_to_v17() {
+                    return Err(e)
+                }
+            }
+            if old_version < 18 {
+                if let Err(e) = self.update_to_v18() {
+                    return Err(e)
+                }
+            }
         } else if new_version < old_version {
             return Err(DBError::DatabaseTooNew(String::from("database version is newer than our known version")))
         }
         return Ok(())
     }
 
-    fn update_to_v4(&mut self) -> Result<(), DBError> {
+    fn update_to_v18(&mut self) -> Result<(), DBError> {
         if let Ok(tx) = self.conn.transaction() {
+            // remember which read each text and watched bib alert was for so sightings worked out
+            // again don't send them a second time
             let updates = [
-                "ALTER TABLE participants DROP COLUMN age;",
-                "ALTER TABLE participants ADD COLUMN birthdate VARCHAR(50) NOT NULL DEFAULT '';",
+                "ALTER TABLE sms_deliveries ADD COLUMN read_seconds BIGINT NOT NULL DEFAULT 0;",
+                "ALTER TABLE sms_deliveries ADD COLUMN read_milliseconds INTEGER NOT NULL DEFAULT 0;",
+                "CREATE TABLE IF NOT EXISTS watched_bib_alerts (
+                    bib VARCHAR(50) NOT NULL,
+                    chip_id INTEGER NOT NULL,
+                    UNIQUE (bib, chip_id) ON CONFLICT IGNORE
+                );",
             ];
             for table in updates {
                 if let Err(e) = tx.execute(table, ()) {
@@ -96,7 +200,7 @@ impl SQLite {
             }
             if let Err(e) = tx.execute(
                 "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
-                (DATABASE_VERSION_SETTING, "4")
+                (DATABASE_VERSION_SETTING, "18")
             ) {
                 return Err(DBError::DataInsertionError(e.to_string()))
             }
@@ -108,31 +212,29 @@ impl SQLite {
         Err(DBError::ConnectionError(String::from("unable to start transaction")))
     }
 
-    fn update_to_v3(&mut self) -> Result<(), DBError> {
+    fn update_to_v17(&mut self) -> Result<(), DBError> {
         if let Ok(tx) = self.conn.transaction() {
+            // sightings get their own id so a sighting made later for an older read still comes
+            // after everything webhooks have already been sent.  The ids are kept in sighting_keys
+            // so a sighting worked out again, on boot for example, gets the id it had before.
             let updates = [
-                "CREATE TABLE IF NOT EXISTS bibchip (
-                    bib VARCHAR(50),
-                    chip VARCHAR(100),
-                    UNIQUE (bib, chip) ON CONFLICT REPLACE,
-                    UNIQUE (chip) ON CONFLICT REPLACE
+                "CREATE TABLE IF NOT EXISTS sighting_keys (
+                    sighting_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    chip_id INTEGER NOT NULL,
+                    part_id INTEGER NOT NULL,
+                    UNIQUE (chip_id, part_id) ON CONFLICT IGNORE
                 );",
-                "CREATE TABLE IF NOT EXISTS participants_new (
-                    part_id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    bib VARCHAR(50) NOT NULL,
-                    first VARCHAR(50) NOT NULL,
-                    last VARCHAR(75) NOT NULL,
-                    age INTEGER NOT NULL DEFAULT 0,
-                    gender VARCHAR(10) NOT NULL DEFAULT 'u',
-                    age_group VARCHAR(100) NOT NULL,
-                    distance VARCHAR(75) NOT NULL,
-                    anonymous SMALLINT NOT NULL DEFAULT 0,
-                    UNIQUE (bib) ON CONFLICT REPLACE
+                "INSERT INTO sighting_keys (chip_id, part_id) SELECT chip_id, part_id FROM sightings ORDER BY chip_id, part_id;",
+                "CREATE TABLE IF NOT EXISTS sightings_new (
+                    sighting_id INTEGER PRIMARY KEY,
+                    chip_id INTEGER REFERENCES chip_reads(chip_id) ON DELETE CASCADE,
+                    part_id INTEGER REFERENCES participants(part_id) ON DELETE CASCADE,
+                    UNIQUE (chip_id, part_id) ON CONFLICT IGNORE
                 );",
-                "INSERT INTO bibchip SELECT bib, part_chip FROM participants;",
-                "INSERT INTO participants_new SELECT part_id, bib, first, last, age, gender, age_group, distance, anonymous FROM participants;",
-                "DROP TABLE participants;",
-                "ALTER TABLE participants_new RENAME TO participants;"
+                "INSERT INTO sightings_new (sighting_id, chip_id, part_id) SELECT sighting_id, chip_id, part_id FROM sighting_keys;",
+                "DROP TABLE sightings;",
+                "ALTER TABLE sightings_new RENAME TO sightings;",
+                "UPDATE webhook_cursors SET cursor=(SELECT COALESCE(MAX(sighting_id), 0) FROM sightings WHERE chip_id <= webhook_cursors.cursor) WHERE stream='sightings';",
             ];
             for table in updates {
                 if let Err(e) = tx.execute(table, ()) {
@@ -141,7 +243,7 @@ impl SQLite {
             }
             if let Err(e) = tx.execute(
                 "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
-                (DATABASE_VERSION_SETTING, "3")
+                (DATABASE_VERSION_SETTING, "17")
             ) {
                 return Err(DBError::DataInsertionError(e.to_string()))
             }
@@ -153,11 +255,20 @@ impl SQLite {
         Err(DBError::ConnectionError(String::from("unable to start transaction")))
     }
 
-    fn update_to_v2(&mut self) -> Result<(), DBError> {
+    fn update_to_v16(&mut self) -> Result<(), DBError> {
         if let Ok(tx) = self.conn.transaction() {
+            // a chip used to belong to one bib forever, now it can move with a time it moved at
             let updates = [
-                "ALTER TABLE chip_reads ADD COLUMN reader_seconds BIGINT NOT NULL DEFAULT 0;",
-                "ALTER TABLE chip_reads ADD COLUMN reader_milliseconds INTEGER NOT NULL DEFAULT 0;"
+                "CREATE TABLE IF NOT EXISTS bibchip_new (
+                    chip VARCHAR(100),
+                    bib VARCHAR(50),
+                    effective_from BIGINT NOT NULL DEFAULT 0,
+                    effective_to BIGINT NOT NULL DEFAULT 0,
+                    UNIQUE (chip, bib, effective_from) ON CONFLICT REPLACE
+                );",
+                "INSERT INTO bibchip_new (chip, bib) SELECT chip, bib FROM bibchip;",
+                "DROP TABLE bibchip;",
+                "ALTER TABLE bibchip_new RENAME TO bibchip;",
             ];
             for table in updates {
                 if let Err(e) = tx.execute(table, ()) {
@@ -166,7 +277,7 @@ impl SQLite {
             }
             if let Err(e) = tx.execute(
                 "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
-                (DATABASE_VERSION_SETTING, "2")
+                (DATABASE_VERSION_SETTING, "16")
             ) {
                 return Err(DBError::DataInsertionError(e.to_string()))
             }
@@ -178,72 +289,59 @@ impl SQLite {
         Err(DBError::ConnectionError(String::from("unable to start transaction")))
     }
 
-    fn make_tables(&mut self) -> Result<(), DBError> {
+    fn update_to_v15(&mut self) -> Result<(), DBError> {
         if let Ok(tx) = self.conn.transaction() {
-            let database_tables = [
-                "CREATE TABLE IF NOT EXISTS results_api (
-                    api_id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    nickname VARCHAR(75),
-                    kind VARCHAR(50),
-                    token VARCHAR(100),
-                    uri VARCHAR(150),
-                    UNIQUE (nickname) ON CONFLICT REPLACE,
-                    UNIQUE (uri, token) ON CONFLICT REPLACE
-                );",
-                "CREATE TABLE IF NOT EXISTS participants (
-                    part_id INTEGER PRIMARY KEY AUTOINCREMENT,
+            let updates = [
+                "ALTER TABLE participants ADD COLUMN external_id VARCHAR(100) NOT NULL DEFAULT '';",
+                "ALTER TABLE participants ADD COLUMN apparel VARCHAR(100) NOT NULL DEFAULT '';",
+            ];
+            for table in updates {
+                if let Err(e) = tx.execute(table, ()) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "15")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+
+    fn update_to_v14(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let updates = [
+                "ALTER TABLE participants ADD COLUMN sms_enabled SMALLINT NOT NULL DEFAULT 0;",
+                "ALTER TABLE participants ADD COLUMN mobile VARCHAR(20) NOT NULL DEFAULT '';",
+                "CREATE TABLE IF NOT EXISTS sms_supporters (
                     bib VARCHAR(50) NOT NULL,
-                    first VARCHAR(50) NOT NULL,
-                    last VARCHAR(75) NOT NULL,
-                    birthdate VARCHAR(50) NOT NULL DEFAULT '',
-                    gender VARCHAR(10) NOT NULL DEFAULT 'u',
-                    age_group VARCHAR(100) NOT NULL,
-                    distance VARCHAR(75) NOT NULL,
-                    anonymous SMALLINT NOT NULL DEFAULT 0,
-                    UNIQUE (bib) ON CONFLICT REPLACE
-                );",
-                "CREATE TABLE IF NOT EXISTS bibchip (
-                    chip VARCHAR(100),
-                    bib VARCHAR(50),
-                    UNIQUE (chip) ON CONFLICT REPLACE
-                );",
-                "CREATE TABLE IF NOT EXISTS readers (
-                    reader_id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    nickname VARCHAR(75) NOT NULL,
-                    kind VARCHAR(50) NOT NULL,
-                    ip_address VARCHAR(100) NOT NULL,
-                    port INTEGER NOT NULL,
-                    auto_connect INTEGER NOT NULL DEFAULT 0,
-                    UNIQUE (nickname) ON CONFLICT REPLACE
-                );",
-                "CREATE TABLE IF NOT EXISTS chip_reads (
-                    chip_id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    chip VARCHAR(100) NOT NULL,
-                    seconds BIGINT NOT NULL,
-                    milliseconds INTEGER NOT NULL,
-                    reader_seconds BIGINT NOT NULL,
-                    reader_milliseconds INTEGER NOT NULL,
-                    antenna INTEGER,
-                    reader VARCHAR(75),
-                    rssi VARCHAR(10),
-                    status SMALLINT NOT NULL DEFAULT 0,
-                    uploaded SMALLINT NOT NULL DEFAULT 0,
-                    UNIQUE (chip, seconds, milliseconds) ON CONFLICT IGNORE
+                    mobile VARCHAR(20) NOT NULL,
+                    UNIQUE (bib, mobile) ON CONFLICT REPLACE
                 );",
-                "CREATE TABLE IF NOT EXISTS sightings (
-                    chip_id INTEGER REFERENCES chip_reads(chip_id) ON DELETE CASCADE,
-                    part_id INTEGER REFERENCES participants(part_id) ON DELETE CASCADE,
-                    UNIQUE (chip_id, part_id) ON CONFLICT IGNORE
+                "CREATE TABLE IF NOT EXISTS sms_deliveries (
+                    delivery_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    bib VARCHAR(50) NOT NULL,
+                    mobile VARCHAR(20) NOT NULL,
+                    message TEXT NOT NULL,
+                    time BIGINT NOT NULL,
+                    status VARCHAR(10) NOT NULL,
+                    error TEXT NOT NULL DEFAULT ''
                 );"
             ];
-            for table in database_tables {
+            for table in updates {
                 if let Err(e) = tx.execute(table, ()) {
                     return Err(DBError::DataInsertionError(e.to_string()))
                 }
             }
             if let Err(e) = tx.execute(
                 "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
-                (DATABASE_VERSION_SETTING, DATABASE_VERSION.to_string())
+                (DATABASE_VERSION_SETTING, "14")
             ) {
                 return Err(DBError::DataInsertionError(e.to_string()))
             }
@@ -254,346 +352,1774 @@ impl SQLite {
         }
         Err(DBError::ConnectionError(String::from("unable to start transaction")))
     }
-}
 
-impl super::Database for SQLite {
-    // Setup
-    fn setup(&mut self) -> Result<(), DBError> {
-        // If our settings table doesn't exist we run into an error we
-        // can't check for when we try to retrieve the database version value.
-        match self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS settings (
-                setting VARCHAR NOT NULL,
-                value VARCHAR NOT NULL,
-                UNIQUE (setting) ON CONFLICT REPLACE
-            );",
-            []
-        ) {
-            Ok(_) => {},
-            Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+    fn update_to_v13(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            if let Err(e) = tx.execute(
+                "CREATE TABLE IF NOT EXISTS watched_bibs (
+                    bib VARCHAR(50) NOT NULL,
+                    note VARCHAR(200) NOT NULL DEFAULT '',
+                    UNIQUE (bib) ON CONFLICT REPLACE
+                );",
+                ()
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "13")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
         }
-        // Get the results of the version check.
-        // This could cause issues if the UNIQUE trait on settings.setting fails.
-        match self.conn.query_row("SELECT setting, value FROM settings WHERE setting=?1;",
-            [DATABASE_VERSION_SETTING],
-            |row| {
-                Ok(setting::Setting::new(row.get(0)?, row.get(1)?))
-        }) {
-            Ok(it) => {
-                if let Ok(v) = u16::from_str(&it.value()) {
-                    return self.update(v, DATABASE_VERSION)
-                }
-                return Err(DBError::DataRetrievalError(String::from("error parsing version value")))
-            },
-            Err(rusqlite::Error::QueryReturnedNoRows) => {
-                return self.make_tables()
-            },
-            Err(err) => return Err(DBError::DataRetrievalError(format!("{}",err)))
-        };
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
     }
 
-    // Settings
-    fn set_setting(&self, setting: &setting::Setting) -> Result<setting::Setting, DBError> {
-        // Block until we can do something.
-        let res = self.conn.execute(
-            "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
-            (setting.name(), setting.value()),
-        );
-        match res {
-            Ok(_) => return Ok(setting::Setting::new(
-                String::from(setting.name()),
-                String::from(setting.value()))),
-            Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+    fn update_to_v12(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let updates = [
+                "ALTER TABLE system_events ADD COLUMN severity VARCHAR(10) NOT NULL DEFAULT 'info';",
+                "ALTER TABLE system_events ADD COLUMN source VARCHAR(50) NOT NULL DEFAULT '';",
+            ];
+            for table in updates {
+                if let Err(e) = tx.execute(table, ()) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "12")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
         }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
     }
 
-    fn get_setting(&self, name: &str) -> Result<setting::Setting, DBError> {
-        match self.conn.query_row("SELECT setting, value FROM settings WHERE setting=?1;",
-            [name],
-            |row| {
-                Ok(setting::Setting::new(row.get(0)?, row.get(1)?))
-        }) {
-            Ok(it) => return Ok(it),
-            Err(rusqlite::Error::QueryReturnedNoRows) => return Err(DBError::NotFound),
-            Err(err) => return Err(DBError::DataRetrievalError(err.to_string())),
-        };
+    fn update_to_v11(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            if let Err(e) = tx.execute(
+                "CREATE TABLE IF NOT EXISTS notification_rules (
+                    kind VARCHAR(50) NOT NULL,
+                    enabled SMALLINT NOT NULL DEFAULT 1,
+                    priority SMALLINT NOT NULL DEFAULT 3,
+                    channels TEXT NOT NULL DEFAULT '[]',
+                    repeat_interval BIGINT NOT NULL DEFAULT 0,
+                    quiet_start VARCHAR(5) NOT NULL DEFAULT '',
+                    quiet_end VARCHAR(5) NOT NULL DEFAULT '',
+                    UNIQUE (kind) ON CONFLICT REPLACE
+                );",
+                ()
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "11")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
     }
 
-    // Readers
-    fn save_reader(&self, reader: &reader::Reader) -> Result<i64, DBError> {
-        match reader.kind() {
-            reader::READER_KIND_ZEBRA => {},
-            reader::READER_KIND_IMPINJ => return Err(DBError::DataInsertionError(String::from("not yet implemented"))),
-            reader::READER_KIND_RFID => return Err(DBError::DataInsertionError(String::from("not yet implemented"))),
-            _ => return Err(DBError::DataInsertionError(String::from("unknown reader kind specified")))
+    fn update_to_v10(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            if let Err(e) = tx.execute(
+                "CREATE TABLE IF NOT EXISTS notification_channels (
+                    channel_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name VARCHAR(100) NOT NULL,
+                    kind VARCHAR(20) NOT NULL,
+                    enabled SMALLINT NOT NULL DEFAULT 1,
+                    config TEXT NOT NULL
+                );",
+                ()
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "10")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
         }
-        // if our id is set to a number greater than 0 we should be updating
-        if reader.id() > 0 {
-            match self.conn.execute(
-                "UPDATE readers SET nickname=?1, kind=?2, ip_address=?3, port=?4, auto_connect=?5 WHERE reader_id=?6;",
-                (reader.nickname(), reader.kind(), reader.ip_address(), reader.port(), reader.auto_connect(), reader.id()),
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+
+    fn update_to_v9(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            if let Err(e) = tx.execute(
+                "CREATE TABLE IF NOT EXISTS sighting_uploads (
+                    chip_id INTEGER NOT NULL,
+                    part_id INTEGER NOT NULL,
+                    api_id INTEGER NOT NULL,
+                    slug VARCHAR(100) NOT NULL,
+                    year VARCHAR(20) NOT NULL,
+                    UNIQUE (chip_id, part_id, api_id, slug, year) ON CONFLICT IGNORE
+                );",
+                ()
             ) {
-                Ok(_) => return Ok(reader.id()),
-                Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                return Err(DBError::DataInsertionError(e.to_string()))
             }
-        // otherwise add a new reader
-        } else {
-            match self.conn.execute(
-                "INSERT INTO readers (nickname, kind, ip_address, port, auto_connect) VALUES (?1, ?2, ?3, ?4, ?5);",
-                (reader.nickname(), reader.kind(), reader.ip_address(), reader.port(), reader.auto_connect()),
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "9")
             ) {
-                Ok(_) => return Ok(self.conn.last_insert_rowid()),
-                Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+
+    fn update_to_v8(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let updates = [
+                "CREATE TABLE IF NOT EXISTS upload_batches (
+                    batch_id VARCHAR(100) PRIMARY KEY,
+                    api_id INTEGER NOT NULL,
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    rejected SMALLINT NOT NULL DEFAULT 0
+                );",
+                "CREATE TABLE IF NOT EXISTS upload_batch_reads (
+                    batch_id VARCHAR(100) NOT NULL,
+                    chip_id INTEGER NOT NULL,
+                    UNIQUE (batch_id, chip_id) ON CONFLICT IGNORE
+                );",
+            ];
+            for table in updates {
+                if let Err(e) = tx.execute(table, ()) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "8")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+
+    fn update_to_v7(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let updates = [
+                "CREATE TABLE IF NOT EXISTS upload_ledger (
+                    chip_id INTEGER NOT NULL,
+                    api_id INTEGER NOT NULL,
+                    UNIQUE (chip_id, api_id) ON CONFLICT IGNORE
+                );",
+                // anything already uploaded went to the first remote api, the only one we used to send to
+                "INSERT INTO upload_ledger (chip_id, api_id)
+                    SELECT chip_id, (SELECT MIN(api_id) FROM results_api WHERE kind IN ('CHRONOKEEP_REMOTE', 'CHRONOKEEP_REMOTE_SELF'))
+                    FROM chip_reads
+                    WHERE uploaded=1 AND EXISTS (SELECT 1 FROM results_api WHERE kind IN ('CHRONOKEEP_REMOTE', 'CHRONOKEEP_REMOTE_SELF'));",
+            ];
+            for table in updates {
+                if let Err(e) = tx.execute(table, ()) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "7")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+
+    fn update_to_v6(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            if let Err(e) = tx.execute("ALTER TABLE chip_reads ADD COLUMN source VARCHAR(100) NOT NULL DEFAULT '';", ()) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "6")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+
+    fn update_to_v5(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let updates = [
+                "CREATE TABLE IF NOT EXISTS system_events (
+                    event_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    kind VARCHAR(50) NOT NULL,
+                    time BIGINT NOT NULL,
+                    detail TEXT NOT NULL DEFAULT ''
+                );",
+                "CREATE TABLE IF NOT EXISTS webhook_cursors (
+                    api_id INTEGER NOT NULL,
+                    stream VARCHAR(20) NOT NULL,
+                    cursor BIGINT NOT NULL DEFAULT 0,
+                    UNIQUE (api_id, stream) ON CONFLICT REPLACE
+                );",
+            ];
+            for table in updates {
+                if let Err(e) = tx.execute(table, ()) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "5")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+
+    fn update_to_v4(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let updates = [
+                "ALTER TABLE participants DROP COLUMN age;",
+                "ALTER TABLE participants ADD COLUMN birthdate VARCHAR(50) NOT NULL DEFAULT '';",
+            ];
+            for table in updates {
+                if let Err(e) = tx.execute(table, ()) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "4")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+
+    fn update_to_v3(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let updates = [
+                "CREATE TABLE IF NOT EXISTS bibchip (
+                    bib VARCHAR(50),
+                    chip VARCHAR(100),
+                    UNIQUE (bib, chip) ON CONFLICT REPLACE,
+                    UNIQUE (chip) ON CONFLICT REPLACE
+                );",
+                "CREATE TABLE IF NOT EXISTS participants_new (
+                    part_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    bib VARCHAR(50) NOT NULL,
+                    first VARCHAR(50) NOT NULL,
+                    last VARCHAR(75) NOT NULL,
+                    age INTEGER NOT NULL DEFAULT 0,
+                    gender VARCHAR(10) NOT NULL DEFAULT 'u',
+                    age_group VARCHAR(100) NOT NULL,
+                    distance VARCHAR(75) NOT NULL,
+                    anonymous SMALLINT NOT NULL DEFAULT 0,
+                    UNIQUE (bib) ON CONFLICT REPLACE
+                );",
+                "INSERT INTO bibchip SELECT bib, part_chip FROM participants;",
+                "INSERT INTO participants_new SELECT part_id, bib, first, last, age, gender, age_group, distance, anonymous FROM participants;",
+                "DROP TABLE participants;",
+                "ALTER TABLE participants_new RENAME TO participants;"
+            ];
+            for table in updates {
+                if let Err(e) = tx.execute(table, ()) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "3")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+
+    fn update_to_v2(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let updates = [
+                "ALTER TABLE chip_reads ADD COLUMN reader_seconds BIGINT NOT NULL DEFAULT 0;",
+                "ALTER TABLE chip_reads ADD COLUMN reader_milliseconds INTEGER NOT NULL DEFAULT 0;"
+            ];
+            for table in updates {
+                if let Err(e) = tx.execute(table, ()) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, "2")
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+
+    fn make_tables(&mut self) -> Result<(), DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let database_tables = [
+                "CREATE TABLE IF NOT EXISTS results_api (
+                    api_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    nickname VARCHAR(75),
+                    kind VARCHAR(50),
+                    token VARCHAR(100),
+                    uri VARCHAR(150),
+                    UNIQUE (nickname) ON CONFLICT REPLACE,
+                    UNIQUE (uri, token) ON CONFLICT REPLACE
+                );",
+                "CREATE TABLE IF NOT EXISTS participants (
+                    part_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    bib VARCHAR(50) NOT NULL,
+                    first VARCHAR(50) NOT NULL,
+                    last VARCHAR(75) NOT NULL,
+                    birthdate VARCHAR(50) NOT NULL DEFAULT '',
+                    gender VARCHAR(10) NOT NULL DEFAULT 'u',
+                    age_group VARCHAR(100) NOT NULL,
+                    distance VARCHAR(75) NOT NULL,
+                    anonymous SMALLINT NOT NULL DEFAULT 0,
+                    sms_enabled SMALLINT NOT NULL DEFAULT 0,
+                    mobile VARCHAR(20) NOT NULL DEFAULT '',
+                    external_id VARCHAR(100) NOT NULL DEFAULT '',
+                    apparel VARCHAR(100) NOT NULL DEFAULT '',
+                    UNIQUE (bib) ON CONFLICT REPLACE
+                );",
+                "CREATE TABLE IF NOT EXISTS bibchip (
+                    chip VARCHAR(100),
+                    bib VARCHAR(50),
+                    effective_from BIGINT NOT NULL DEFAULT 0,
+                    effective_to BIGINT NOT NULL DEFAULT 0,
+                    UNIQUE (chip, bib, effective_from) ON CONFLICT REPLACE
+                );",
+                "CREATE TABLE IF NOT EXISTS readers (
+                    reader_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    nickname VARCHAR(75) NOT NULL,
+                    kind VARCHAR(50) NOT NULL,
+                    ip_address VARCHAR(100) NOT NULL,
+                    port INTEGER NOT NULL,
+                    auto_connect INTEGER NOT NULL DEFAULT 0,
+                    UNIQUE (nickname) ON CONFLICT REPLACE
+                );",
+                "CREATE TABLE IF NOT EXISTS chip_reads (
+                    chip_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    chip VARCHAR(100) NOT NULL,
+                    seconds BIGINT NOT NULL,
+                    milliseconds INTEGER NOT NULL,
+                    reader_seconds BIGINT NOT NULL,
+                    reader_milliseconds INTEGER NOT NULL,
+                    antenna INTEGER,
+                    reader VARCHAR(75),
+                    rssi VARCHAR(10),
+                    status SMALLINT NOT NULL DEFAULT 0,
+                    uploaded SMALLINT NOT NULL DEFAULT 0,
+                    source VARCHAR(100) NOT NULL DEFAULT '',
+                    UNIQUE (chip, seconds, milliseconds) ON CONFLICT IGNORE
+                );",
+                "CREATE TABLE IF NOT EXISTS sighting_keys (
+                    sighting_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    chip_id INTEGER NOT NULL,
+                    part_id INTEGER NOT NULL,
+                    UNIQUE (chip_id, part_id) ON CONFLICT IGNORE
+                );",
+                "CREATE TABLE IF NOT EXISTS sightings (
+                    sighting_id INTEGER PRIMARY KEY,
+                    chip_id INTEGER REFERENCES chip_reads(chip_id) ON DELETE CASCADE,
+                    part_id INTEGER REFERENCES participants(part_id) ON DELETE CASCADE,
+                    UNIQUE (chip_id, part_id) ON CONFLICT IGNORE
+                );",
+                "CREATE TABLE IF NOT EXISTS system_events (
+                    event_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    kind VARCHAR(50) NOT NULL,
+                    time BIGINT NOT NULL,
+                    detail TEXT NOT NULL DEFAULT '',
+                    severity VARCHAR(10) NOT NULL DEFAULT 'info',
+                    source VARCHAR(50) NOT NULL DEFAULT ''
+                );",
+                "CREATE TABLE IF NOT EXISTS webhook_cursors (
+                    api_id INTEGER NOT NULL,
+                    stream VARCHAR(20) NOT NULL,
+                    cursor BIGINT NOT NULL DEFAULT 0,
+                    UNIQUE (api_id, stream) ON CONFLICT REPLACE
+                );",
+                "CREATE TABLE IF NOT EXISTS upload_ledger (
+                    chip_id INTEGER NOT NULL,
+                    api_id INTEGER NOT NULL,
+                    UNIQUE (chip_id, api_id) ON CONFLICT IGNORE
+                );",
+                "CREATE TABLE IF NOT EXISTS upload_batches (
+                    batch_id VARCHAR(100) PRIMARY KEY,
+                    api_id INTEGER NOT NULL,
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    rejected SMALLINT NOT NULL DEFAULT 0
+                );",
+                "CREATE TABLE IF NOT EXISTS upload_batch_reads (
+                    batch_id VARCHAR(100) NOT NULL,
+                    chip_id INTEGER NOT NULL,
+                    UNIQUE (batch_id, chip_id) ON CONFLICT IGNORE
+                );",
+                "CREATE TABLE IF NOT EXISTS sighting_uploads (
+                    chip_id INTEGER NOT NULL,
+                    part_id INTEGER NOT NULL,
+                    api_id INTEGER NOT NULL,
+                    slug VARCHAR(100) NOT NULL,
+                    year VARCHAR(20) NOT NULL,
+                    UNIQUE (chip_id, part_id, api_id, slug, year) ON CONFLICT IGNORE
+                );",
+                "CREATE TABLE IF NOT EXISTS notification_channels (
+                    channel_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name VARCHAR(100) NOT NULL,
+                    kind VARCHAR(20) NOT NULL,
+                    enabled SMALLINT NOT NULL DEFAULT 1,
+                    config TEXT NOT NULL
+                );",
+                "CREATE TABLE IF NOT EXISTS notification_rules (
+                    kind VARCHAR(50) NOT NULL,
+                    enabled SMALLINT NOT NULL DEFAULT 1,
+                    priority SMALLINT NOT NULL DEFAULT 3,
+                    channels TEXT NOT NULL DEFAULT '[]',
+                    repeat_interval BIGINT NOT NULL DEFAULT 0,
+                    quiet_start VARCHAR(5) NOT NULL DEFAULT '',
+                    quiet_end VARCHAR(5) NOT NULL DEFAULT '',
+                    UNIQUE (kind) ON CONFLICT REPLACE
+                );",
+                "CREATE TABLE IF NOT EXISTS watched_bibs (
+                    bib VARCHAR(50) NOT NULL,
+                    note VARCHAR(200) NOT NULL DEFAULT '',
+                    UNIQUE (bib) ON CONFLICT REPLACE
+                );",
+                "CREATE TABLE IF NOT EXISTS sms_supporters (
+                    bib VARCHAR(50) NOT NULL,
+                    mobile VARCHAR(20) NOT NULL,
+                    UNIQUE (bib, mobile) ON CONFLICT REPLACE
+                );",
+                "CREATE TABLE IF NOT EXISTS sms_deliveries (
+                    delivery_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    bib VARCHAR(50) NOT NULL,
+                    mobile VARCHAR(20) NOT NULL,
+                    message TEXT NOT NULL,
+                    time BIGINT NOT NULL,
+                    status VARCHAR(10) NOT NULL,
+                    error TEXT NOT NULL DEFAULT '',
+                    read_seconds BIGINT NOT NULL DEFAULT 0,
+                    read_milliseconds INTEGER NOT NULL DEFAULT 0
+                );",
+                "CREATE TABLE IF NOT EXISTS watched_bib_alerts (
+                    bib VARCHAR(50) NOT NULL,
+                    chip_id INTEGER NOT NULL,
+                    UNIQUE (bib, chip_id) ON CONFLICT IGNORE
+                );"
+            ];
+            for table in database_tables {
+                if let Err(e) = tx.execute(table, ()) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.execute(
+                "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+                (DATABASE_VERSION_SETTING, DATABASE_VERSION.to_string())
+            ) {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(())
+        }
+        Err(DBError::ConnectionError(String::from("unable to start transaction")))
+    }
+}
+
+impl super::Database for SQLite {
+    // Setup
+    fn setup(&mut self) -> Result<(), DBError> {
+        // If our settings table doesn't exist we run into an error we
+        // can't check for when we try to retrieve the database version value.
+        match self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS settings (
+                setting VARCHAR NOT NULL,
+                value VARCHAR NOT NULL,
+                UNIQUE (setting) ON CONFLICT REPLACE
+            );",
+            []
+        ) {
+            Ok(_) => {},
+            Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+        }
+        // Get the results of the version check.
+        // This could cause issues if the UNIQUE trait on settings.setting fails.
+        match self.conn.query_row("SELECT setting, value FROM settings WHERE setting=?1;",
+            [DATABASE_VERSION_SETTING],
+            |row| {
+                Ok(setting::Setting::new(row.get(0)?, row.get(1)?))
+        }) {
+            Ok(it) => {
+                if let Ok(v) = u16::from_str(&it.value()) {
+                    return self.update(v, DATABASE_VERSION)
+                }
+                return Err(DBError::DataRetrievalError(String::from("error parsing version value")))
+            },
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return self.make_tables()
+            },
+            Err(err) => return Err(DBError::DataRetrievalError(format!("{}",err)))
+        };
+    }
+
+    // Settings
+    fn set_setting(&self, setting: &setting::Setting) -> Result<setting::Setting, DBError> {
+        // Block until we can do something.
+        let res = self.conn.execute(
+            "INSERT INTO settings (setting, value) VALUES (?1, ?2);",
+            (setting.name(), setting.value()),
+        );
+        match res {
+            Ok(_) => return Ok(setting::Setting::new(
+                String::from(setting.name()),
+                String::from(setting.value()))),
+            Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+        }
+    }
+
+    fn get_setting(&self, name: &str) -> Result<setting::Setting, DBError> {
+        match self.conn.query_row("SELECT setting, value FROM settings WHERE setting=?1;",
+            [name],
+            |row| {
+                Ok(setting::Setting::new(row.get(0)?, row.get(1)?))
+        }) {
+            Ok(it) => return Ok(it),
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Err(DBError::NotFound),
+            Err(err) => return Err(DBError::DataRetrievalError(err.to_string())),
+        };
+    }
+
+    // Readers
+    fn save_reader(&self, reader: &reader::Reader) -> Result<i64, DBError> {
+        match reader.kind() {
+            reader::READER_KIND_ZEBRA => {},
+            reader::READER_KIND_IMPINJ => return Err(DBError::DataInsertionError(String::from("not yet implemented"))),
+            reader::READER_KIND_RFID => return Err(DBError::DataInsertionError(String::from("not yet implemented"))),
+            _ => return Err(DBError::DataInsertionError(String::from("unknown reader kind specified")))
+        }
+        // if our id is set to a number greater than 0 we should be updating
+        if reader.id() > 0 {
+            match self.conn.execute(
+                "UPDATE readers SET nickname=?1, kind=?2, ip_address=?3, port=?4, auto_connect=?5 WHERE reader_id=?6;",
+                (reader.nickname(), reader.kind(), reader.ip_address(), reader.port(), reader.auto_connect(), reader.id()),
+            ) {
+                Ok(_) => return Ok(reader.id()),
+                Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+            }
+        // otherwise add a new reader
+        } else {
+            match self.conn.execute(
+                "INSERT INTO readers (nickname, kind, ip_address, port, auto_connect) VALUES (?1, ?2, ?3, ?4, ?5);",
+                (reader.nickname(), reader.kind(), reader.ip_address(), reader.port(), reader.auto_connect()),
+            ) {
+                Ok(_) => return Ok(self.conn.last_insert_rowid()),
+                Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+            }
+        }
+    }
+
+    fn get_reader(&self, id: &i64) -> Result<reader::Reader, DBError> {
+        match self.conn.query_row("SELECT reader_id, nickname, kind, ip_address, port, auto_connect FROM readers WHERE reader_id=?1;",
+            [id],
+            |row| {
+                Ok(TempReader {
+                    id: row.get(0)?,
+                    nickname: row.get(1)?,
+                    kind: row.get(2)?,
+                    ip_address: row.get(3)?,
+                    port: row.get(4)?,
+                    auto_connect: row.get(5)?,
+                })
+        }) {
+            Ok(r) => {
+                match reader::Reader::new_no_repeaters(
+                    r.id,
+                    r.kind,
+                    r.nickname,
+                    r.ip_address,
+                    r.port,
+                    r.auto_connect
+                ) {
+                    Ok(output) => return Ok(output),
+                    Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+                }
+            },
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Err(DBError::NotFound),
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string())),
+        };
+    }
+
+    fn get_readers(&self) -> Result<Vec<reader::Reader>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT reader_id, nickname, kind, ip_address, port, auto_connect FROM readers;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map([],
+            |row| {
+                Ok(TempReader {
+                    id: row.get(0)?,
+                    nickname: row.get(1)?,
+                    kind: row.get(2)?,
+                    ip_address: row.get(3)?,
+                    port: row.get(4)?,
+                    auto_connect: row.get(5)?
+                })
+            }) {
+                Ok(r) => r,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<reader::Reader> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => {
+                    match reader::Reader::new_no_repeaters(
+                        r.id,
+                        r.kind,
+                        r.nickname,
+                        r.ip_address,
+                        r.port,
+                        r.auto_connect
+                    ) {
+                        Ok(reader) => {
+                            output.push(reader);
+                        }
+                        Err(e) => return Err(e)
+                    }
+                },
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        Ok(output)
+    }
+
+    fn delete_reader(&self, id: &i64) -> Result<usize, DBError> {
+        match self.conn.execute("DELETE FROM readers WHERE reader_id=?1", [id]) {
+            Ok(num) => return Ok(num),
+            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+        }
+    }
+
+    // Results API
+    fn save_api(&self, api: &api::Api) -> Result<i64, DBError> {
+        match api.kind() {
+            api::API_TYPE_CHRONOKEEP_RESULTS |
+            api::API_TYPE_CHRONOKEEP_RESULTS_SELF |
+            api::API_TYPE_CHRONOKEEP_REMOTE |
+            api::API_TYPE_CHRONOKEEP_REMOTE_SELF |
+            api::API_TYPE_WEBHOOK |
+            api::API_TYPE_RELAY_SOURCE =>
+            {},
+            _ => return Err(DBError::DataInsertionError(String::from("invalid kind specified")))
+        }
+        if api.id() > 0 {
+            match self.conn.execute(
+                "UPDATE results_api SET 
+                        nickname=?1, 
+                        kind=?2, 
+                        token=?3, 
+                        uri=?4 
+                    WHERE api_id=?5",
+                (api.nickname(), api.kind(), api.token(), api.uri(), api.id()))
+            {
+                Ok(_) => return Ok(api.id()),
+                Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+            }
+        }
+        match self.conn.execute(
+            "INSERT INTO results_api (
+                    nickname,
+                    kind,
+                    token,
+                    uri
+                ) VALUES (?1,?2,?3,?4);",
+            (api.nickname(), api.kind(), api.token(), api.uri())
+        ) {
+            Ok(_) => return Ok(self.conn.last_insert_rowid()),
+            Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+        }
+    }
+
+    fn get_apis(&self) -> Result<Vec<api::Api>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT api_id, nickname, kind, token, uri FROM results_api;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [],
+            |row|{
+                Ok(api::Api::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?
+                ))
+            }) {
+                Ok(r) => r,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<api::Api> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => {
+                    match r.kind() {
+                        api::API_TYPE_CHRONOKEEP_RESULTS |
+                        api::API_TYPE_CHRONOKEEP_RESULTS_SELF |
+                        api::API_TYPE_CHRONOKEEP_REMOTE |
+                        api::API_TYPE_CHRONOKEEP_REMOTE_SELF |
+                        api::API_TYPE_WEBHOOK |
+                        api::API_TYPE_RELAY_SOURCE =>
+                        {
+                            output.push(r)
+                        },
+                        _ => return Err(DBError::DataRetrievalError(String::from("invalid api type")))
+                    }
+                },
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        return Ok(output);
+    }
+
+    fn delete_api(&self, id: &i64) -> Result<usize, DBError> {
+        if let Err(e) = self.conn.execute("DELETE FROM webhook_cursors WHERE api_id=?1", [id]) {
+            return Err(DBError::DataDeletionError(e.to_string()))
+        }
+        if let Err(e) = self.conn.execute("DELETE FROM upload_ledger WHERE api_id=?1", [id]) {
+            return Err(DBError::DataDeletionError(e.to_string()))
+        }
+        if let Err(e) = self.conn.execute("DELETE FROM upload_batch_reads WHERE batch_id IN (SELECT batch_id FROM upload_batches WHERE api_id=?1)", [id]) {
+            return Err(DBError::DataDeletionError(e.to_string()))
+        }
+        if let Err(e) = self.conn.execute("DELETE FROM upload_batches WHERE api_id=?1", [id]) {
+            return Err(DBError::DataDeletionError(e.to_string()))
+        }
+        match self.conn.execute("DELETE FROM results_api WHERE api_id=?1", [id]) {
+            Ok(num) => return Ok(num),
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        }
+    }
+
+    // Reads
+    fn save_reads(&mut self, reads: &Vec<read::Read>) -> Result<usize, DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let mut count = 0;
+            for r in reads {
+                match r.status() {
+                    read::READ_STATUS_TOO_SOON | read::READ_STATUS_UNUSED | read::READ_STATUS_USED | read::READ_STATUS_HELD | read::READ_STATUS_IGNORED => {},
+                    _ => return Err(DBError::DataInsertionError(String::from("invalid chip read status")))
+                }
+                match tx.execute(
+                    "INSERT INTO chip_reads (
+                            chip, 
+                            seconds,
+                            milliseconds,
+                            reader_seconds,
+                            reader_milliseconds,
+                            antenna,
+                            reader,
+                            rssi,
+                            status,
+                            uploaded,
+                            source
+                        ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11);",
+                    (r.chip(), r.seconds(), r.milliseconds(), r.reader_seconds(), r.reader_milliseconds(), r.antenna(), r.reader(), r.rssi(), r.status(), r.uploaded(), r.source())
+                ) {
+                    Ok(val) => count = count + val,
+                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()));
+            }
+            return Ok(count);
+        }
+        return Err(DBError::ConnectionError(String::from("error starting transaction")));
+    }
+
+    fn get_reads(&self, start: i64, end: i64) -> Result<Vec<read::Read>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded, source FROM chip_reads WHERE seconds >= ?1 AND seconds <= ?2;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [start, end],
+            |row| {
+                Ok(read::Read::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                    row.get(8)?,
+                    row.get(9)?,
+                    row.get(10)?,
+                ).with_source(row.get(11)?))
+            }) {
+                Ok(r) => r,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<read::Read> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => {
+                    output.push(r);
+                },
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        return Ok(output);
+    }
+
+    fn get_all_reads(&self) -> Result<Vec<read::Read>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded, source FROM chip_reads;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [],
+            |row| {
+                Ok(read::Read::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                    row.get(8)?,
+                    row.get(9)?,
+                    row.get(10)?,
+                ).with_source(row.get(11)?))
+            }) {
+                Ok(r) => r,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<read::Read> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => {
+                    output.push(r);
+                },
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        return Ok(output);
+    }
+
+    fn delete_reads(&self, start: i64, end: i64) -> Result<usize, DBError> {
+        self.reads_changed();
+        if let Err(e) = self.conn.execute(
+            "DELETE FROM upload_ledger WHERE chip_id IN (SELECT chip_id FROM chip_reads WHERE seconds >= ?1 AND seconds <= ?2);",
+            [start, end]
+        ) {
+            return Err(DBError::DataDeletionError(e.to_string()))
+        }
+        if let Err(e) = self.conn.execute(
+            "DELETE FROM upload_batch_reads WHERE chip_id IN (SELECT chip_id FROM chip_reads WHERE seconds >= ?1 AND seconds <= ?2);",
+            [start, end]
+        ) {
+            return Err(DBError::DataDeletionError(e.to_string()))
+        }
+        match self.conn.execute(
+            "DELETE FROM chip_reads WHERE seconds >= ?1 AND seconds <= ?2;",
+            [start, end]
+        ) {
+            Ok(num) => return Ok(num),
+            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+        }
+    }
+
+    fn delete_all_reads(&self) -> Result<usize, DBError> {
+        self.reads_changed();
+        for table in ["upload_ledger", "upload_batch_reads", "upload_batches"] {
+            if let Err(e) = self.conn.execute(&format!("DELETE FROM {table};"), []) {
+                return Err(DBError::DataDeletionError(e.to_string()))
+            }
+        }
+        match self.conn.execute(
+            "DELETE FROM chip_reads;",
+            []
+        ) {
+            Ok(num) => return Ok(num),
+            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+        }
+    }
+
+    fn get_useful_reads(&self) -> Result<Vec<read::Read>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded, source FROM chip_reads WHERE status=?1 OR status=?2;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [read::READ_STATUS_UNUSED, read::READ_STATUS_USED],
+            |row| {
+                Ok(read::Read::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                    row.get(8)?,
+                    row.get(9)?,
+                    row.get(10)?,
+                ).with_source(row.get(11)?))
+            }) {
+                Ok(r) => r,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<read::Read> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => {
+                    output.push(r);
+                },
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        return Ok(output);
+    }
+    
+    fn get_unused_reads_after(&self, id: u64) -> Result<Vec<read::Read>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded, source FROM chip_reads WHERE chip_id > ?1 AND status=?2 ORDER BY chip_id;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            (id, read::READ_STATUS_UNUSED),
+            |row| {
+                Ok(read::Read::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                    row.get(8)?,
+                    row.get(9)?,
+                    row.get(10)?,
+                ).with_source(row.get(11)?))
+            }) {
+                Ok(r) => r,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<read::Read> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => output.push(r),
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        Ok(output)
+    }
+
+    fn release_held_reads(&mut self, chips: &[String]) -> Result<usize, DBError> {
+        self.reads_changed();
+        if let Ok(tx) = self.conn.transaction() {
+            let mut count = 0;
+            for chip in chips {
+                match tx.execute(
+                    "UPDATE chip_reads SET status=?1 WHERE chip=?2 AND status=?3;",
+                    (read::READ_STATUS_UNUSED, chip, read::READ_STATUS_HELD)
+                ) {
+                    Ok(num) => count += num,
+                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(count)
+        }
+        Err(DBError::ConnectionError(String::from("error starting transaction")))
+    }
+
+    fn get_unknown_chips(&self) -> Result<Vec<unknown_chip::UnknownChip>, DBError> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT chip, MIN(seconds), MAX(seconds), COUNT(*), GROUP_CONCAT(DISTINCT antenna) FROM chip_reads WHERE status=?1 OR status=?2 GROUP BY chip ORDER BY MIN(seconds), chip;"
+        ) {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [read::READ_STATUS_HELD, read::READ_STATUS_IGNORED],
+            |row| {
+                let antennas: String = row.get(4)?;
+                let mut antennas: Vec<u32> = antennas.split(',').filter_map(|a| a.parse().ok()).collect();
+                antennas.sort();
+                Ok(unknown_chip::UnknownChip::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    antennas,
+                ))
+            }) {
+                Ok(c) => c,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<unknown_chip::UnknownChip> = Vec::new();
+        for row in results {
+            match row {
+                Ok(c) => output.push(c),
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        Ok(output)
+    }
+
+    fn get_not_uploaded_reads(&self) -> Result<Vec<read::Read>, DBError> {       
+        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded, source FROM chip_reads WHERE uploaded=?1;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [read::READ_UPLOADED_FALSE],
+            |row| {
+                Ok(read::Read::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                    row.get(8)?,
+                    row.get(9)?,
+                    row.get(10)?,
+                ).with_source(row.get(11)?))
+            }) {
+                Ok(r) => r,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<read::Read> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => {
+                    output.push(r);
+                },
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        return Ok(output);
+    }
+
+    fn reset_reads_status(&self) -> Result<usize, DBError> {
+        self.reads_changed();
+        // uploads are tracked by read and participant, not by sighting, so they're kept
+        if let Err(e) = self.conn.execute("DELETE FROM sightings;", []) {
+            return Err(DBError::DataDeletionError(e.to_string()))
+        }
+        match self.conn.execute(
+            "UPDATE chip_reads SET status=?1;",
+            [read::READ_STATUS_UNUSED]
+        ) {
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
+        }
+    }
+
+    fn reset_reads_status_for(&mut self, chips: &[String]) -> Result<usize, DBError> {
+        self.reads_changed();
+        if let Ok(tx) = self.conn.transaction() {
+            let mut count = 0;
+            for chip in chips {
+                if let Err(e) = tx.execute(
+                    "DELETE FROM sightings WHERE chip_id IN (SELECT chip_id FROM chip_reads WHERE chip=?1);",
+                    [chip]
+                ) {
+                    return Err(DBError::DataDeletionError(e.to_string()))
+                }
+                match tx.execute(
+                    "UPDATE chip_reads SET status=?1 WHERE chip=?2;",
+                    (read::READ_STATUS_UNUSED, chip)
+                ) {
+                    Ok(num) => count += num,
+                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(count)
+        }
+        Err(DBError::ConnectionError(String::from("error starting transaction")))
+    }
+
+    fn reset_reads_upload(&self) -> Result<usize, DBError> {
+        for table in ["upload_ledger", "upload_batch_reads", "upload_batches"] {
+            if let Err(e) = self.conn.execute(&format!("DELETE FROM {table};"), []) {
+                return Err(DBError::DataDeletionError(e.to_string()))
+            }
+        }
+        match self.conn.execute(
+            "UPDATE chip_reads SET uploaded=?1;",
+            [read::READ_UPLOADED_FALSE]
+        ) {
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
+        }
+    }
+
+    fn update_reads_status(&mut self, reads: &Vec<read::Read>) -> Result<usize, DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let mut count = 0;
+            for r in reads {
+                match r.status() {
+                    read::READ_STATUS_TOO_SOON | read::READ_STATUS_UNUSED | read::READ_STATUS_USED | read::READ_STATUS_HELD | read::READ_STATUS_IGNORED => {},
+                    _ => return Err(DBError::DataInsertionError(String::from("invalid chip read status")))
+                }
+                match tx.execute(
+                    "UPDATE chip_reads SET
+                            status=?1,
+                            uploaded=?2
+                            WHERE chip_id=?3;",
+                    (r.status(), r.uploaded(), r.id())
+                ) {
+                    Ok(_) => count += 1,
+                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()));
+            }
+            return Ok(count);
+        }
+        return Err(DBError::ConnectionError(String::from("error starting transaction")));
+    }
+
+    // Participants
+    fn add_participants(&mut self, participants: &Vec<participant::Participant>) -> Result<usize, DBError> {
+        self.participants_changed();
+        let mut count = 0;
+        if let Ok(tx) = self.conn.transaction() {
+            for p in participants {
+                match tx.execute(
+                    "INSERT INTO participants (
+                        bib,
+                        first,
+                        last,
+                        birthdate,
+                        gender,
+                        age_group,
+                        distance,
+                        anonymous,
+                        sms_enabled,
+                        mobile,
+                        external_id,
+                        apparel
+                    ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12)",
+                    (
+                        p.bib(),
+                        p.first(),
+                        p.last(),
+                        p.birthdate(),
+                        p.gender(),
+                        p.age_group(),
+                        p.distance(),
+                        p.anonymous(),
+                        p.sms_enabled(),
+                        p.mobile(),
+                        p.external_id(),
+                        p.apparel()
+                    )
+                ) {
+                    Ok(_) => count = count + 1,
+                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+        }
+        Ok(count)
+    }
+
+    fn delete_participants(&self) -> Result<usize, DBError> {
+        self.participants_changed();
+        match self.conn.execute(
+            "DELETE FROM bibchip;", 
+            []
+        ) {
+            Ok(_) => {},
+            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+        }
+        match self.delete_sightings() {
+            Ok(_) => (),
+            Err(e) => return Err(e)
+        }
+        match self.conn.execute(
+            "DELETE FROM participants;",
+            []
+        ) {
+            Ok(num) => return Ok(num),
+            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+        }
+    }
+
+    fn delete_participant(&self, bib: &str) -> Result<usize, DBError> {
+        self.participants_changed();
+        match self.conn.execute(
+            "DELETE FROM bibchip WHERE bib=?1;", 
+            [bib]
+        ) {
+            Ok(_) => {},
+            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+        }
+        match self.conn.execute(
+            "DELETE FROM participants WHERE bib=?1;",
+            [bib]
+        ) {
+            Ok(num) => return Ok(num),
+            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+        }
+    }
+
+    fn get_participants(&self) -> Result<Vec<participant::Participant>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT part_id, bib, first, last, birthdate, gender, age_group, distance, anonymous, sms_enabled, mobile, external_id, apparel FROM participants;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [],
+            |row| {
+                Ok(participant::Participant::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                    row.get(8)?,
+                ).with_sms(row.get(9)?, row.get(10)?)
+                .with_registration(row.get(11)?, row.get(12)?))
+            }) {
+                Ok(r) => r,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<participant::Participant> = Vec::new();
+        for row in results {
+            match row {
+                Ok(p) => {
+                    output.push(p);
+                },
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        return Ok(output);
+    }
+
+    // BibChips
+    // A chip given to a new bib with a start time is taken off whoever had it from then on, one
+    // without a start time replaces the chip's other untimed entries like it always has.
+    fn add_bibchips(&mut self, bibchips: &Vec<bibchip::BibChip>) -> Result<usize, DBError> {
+        self.participants_changed();
+        let mut count = 0;
+        if let Ok(tx) = self.conn.transaction() {
+            for b in bibchips {
+                // a timed swap closes out whoever had the chip, an untimed one replaces them outright
+                let swapped = if b.effective_from() > 0 {
+                    tx.execute(
+                        "UPDATE bibchip SET effective_to=?3 WHERE chip=?1 AND bib<>?2 AND effective_to=0 AND effective_from<?3;",
+                        (b.chip(), b.bib(), b.effective_from())
+                    )
+                } else {
+                    tx.execute(
+                        "DELETE FROM bibchip WHERE chip=?1 AND bib<>?2;",
+                        (b.chip(), b.bib())
+                    )
+                };
+                if let Err(e) = swapped {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+                match tx.execute(
+                    "INSERT INTO bibchip (
+                        bib,
+                        chip,
+                        effective_from,
+                        effective_to
+                    ) VALUES (?1, ?2, ?3, ?4)",
+                    (
+                        b.bib(),
+                        b.chip(),
+                        b.effective_from(),
+                        b.effective_to()
+                    )
+                ) {
+                    Ok(_) => count = count + 1,
+                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+        }
+        Ok(count)
+    }
+
+    fn delete_all_bibchips(&self) -> Result<usize, DBError> {
+        self.participants_changed();
+        match self.conn.execute(
+            "DELETE FROM bibchip;", 
+            []
+        ) {
+            Ok(num) => return Ok(num),
+            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+        }
+    }
+
+    fn delete_bibchips(&self, bib: &str) -> Result<usize, DBError> {
+        self.participants_changed();
+        match self.conn.execute(
+            "DELETE FROM bibchip WHERE bib=?1;", 
+            [bib]
+        ) {
+            Ok(num) => return Ok(num),
+            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+        }
+    }
+
+    fn delete_bibchip(&self, chip: &str) -> Result<usize, DBError> {
+        self.participants_changed();
+        match self.conn.execute(
+            "DELETE FROM bibchip WHERE chip=?1;",
+            [chip]
+        ) {
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataDeletionError(e.to_string()))
+        }
+    }
+
+    fn get_bibchips(&self) -> Result<Vec<bibchip::BibChip>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT bib, chip, effective_from, effective_to FROM bibchip ORDER BY chip, effective_from;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [],
+            |row| {
+                Ok(bibchip::BibChip::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                ).with_effective(row.get(2)?, row.get(3)?))
+            }) {
+                Ok(b) => b,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<bibchip::BibChip> = Vec::new();
+        for row in results {
+            match row {
+                Ok(b) => {
+                    output.push(b);
+                },
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        return Ok(output)
+    }
+
+    // Sightings
+    fn save_sightings(&mut self, sightings: &Vec<sighting::Sighting>) -> Result<usize, DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let mut count = 0;
+            for s in sightings {
+                // ids are handed out once per read and participant, never again
+                if let Err(e) = tx.execute(
+                    "INSERT INTO sighting_keys (chip_id, part_id) VALUES (?1,?2);",
+                    (s.read.id(), s.participant.id())
+                ) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+                match tx.execute(
+                    "INSERT OR IGNORE INTO sightings (
+                        sighting_id,
+                        chip_id,
+                        part_id
+                    ) SELECT sighting_id, chip_id, part_id FROM sighting_keys WHERE chip_id=?1 AND part_id=?2;",
+                    (s.read.id(), s.participant.id())
+                ) {
+                    Ok(val) => count = count + val,
+                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()));
             }
+            return Ok(count);
         }
+        return Err(DBError::ConnectionError(String::from("error starting transaction")));
     }
 
-    fn get_reader(&self, id: &i64) -> Result<reader::Reader, DBError> {
-        match self.conn.query_row("SELECT reader_id, nickname, kind, ip_address, port, auto_connect FROM readers WHERE reader_id=?1;",
-            [id],
+    fn get_sightings(&self, start: i64, end: i64) -> Result<Vec<sighting::Sighting>, DBError> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT 
+                part_id,
+                bib,
+                first,
+                last,
+                birthdate,
+                gender,
+                age_group,
+                distance,
+                chip,
+                anonymous,
+                chip_id,
+                seconds,
+                milliseconds,
+                reader_seconds,
+                reader_milliseconds,
+                antenna,
+                reader,
+                rssi,
+                status,
+                uploaded,
+                source,
+                sms_enabled,
+                mobile,
+                external_id,
+                apparel
+            FROM participants NATURAL JOIN sightings NATURAL JOIN chip_reads 
+            WHERE seconds >= ?1 AND seconds <= ?2;"
+        ) {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [start, end],
             |row| {
-                Ok(TempReader {
-                    id: row.get(0)?,
-                    nickname: row.get(1)?,
-                    kind: row.get(2)?,
-                    ip_address: row.get(3)?,
-                    port: row.get(4)?,
-                    auto_connect: row.get(5)?,
+                Ok(sighting::Sighting{
+                    participant: participant::Participant::new(
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get(6)?,
+                        row.get(7)?,
+                        row.get(9)?
+                    ).with_sms(row.get(21)?, row.get(22)?)
+                    .with_registration(row.get(23)?, row.get(24)?),
+                    read: read::Read::new(
+                        row.get(10)?,
+                        row.get(8)?,
+                        row.get(11)?,
+                        row.get(12)?,
+                        row.get(13)?,
+                        row.get(14)?,
+                        row.get(15)?,
+                        row.get(16)?,
+                        row.get(17)?,
+                        row.get(18)?,
+                        row.get(19)?,
+                    ).with_source(row.get(20)?)
                 })
-        }) {
-            Ok(r) => {
-                match reader::Reader::new_no_repeaters(
-                    r.id,
-                    r.kind,
-                    r.nickname,
-                    r.ip_address,
-                    r.port,
-                    r.auto_connect
-                ) {
-                    Ok(output) => return Ok(output),
-                    Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
-                }
-            },
-            Err(rusqlite::Error::QueryReturnedNoRows) => return Err(DBError::NotFound),
-            Err(e) => return Err(DBError::DataRetrievalError(e.to_string())),
+            }
+        ) {
+            Ok(r) => r,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
         };
+        let mut output: Vec<sighting::Sighting> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => output.push(r),
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        return Ok(output);
     }
 
-    fn get_readers(&self) -> Result<Vec<reader::Reader>, DBError> {
-        let mut stmt = match self.conn.prepare("SELECT reader_id, nickname, kind, ip_address, port, auto_connect FROM readers;") {
+    fn get_all_sightings(&self) -> Result<Vec<sighting::Sighting>, DBError> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT 
+                part_id,
+                bib,
+                first,
+                last,
+                birthdate,
+                gender,
+                age_group,
+                distance,
+                chip,
+                anonymous,
+                chip_id,
+                seconds,
+                milliseconds,
+                reader_seconds,
+                reader_milliseconds,
+                antenna,
+                reader,
+                rssi,
+                status,
+                uploaded,
+                source,
+                sms_enabled,
+                mobile,
+                external_id,
+                apparel
+            FROM participants NATURAL JOIN sightings NATURAL JOIN chip_reads;"
+        ) {
             Ok(stmt) => stmt,
-            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
         };
         let results = match stmt.query_map([],
             |row| {
-                Ok(TempReader {
-                    id: row.get(0)?,
-                    nickname: row.get(1)?,
-                    kind: row.get(2)?,
-                    ip_address: row.get(3)?,
-                    port: row.get(4)?,
-                    auto_connect: row.get(5)?
+                Ok(sighting::Sighting{
+                    participant: participant::Participant::new(
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get(6)?,
+                        row.get(7)?,
+                        row.get(9)?,
+                    ).with_sms(row.get(21)?, row.get(22)?)
+                    .with_registration(row.get(23)?, row.get(24)?),
+                    read: read::Read::new(
+                        row.get(10)?,
+                        row.get(8)?,
+                        row.get(11)?,
+                        row.get(12)?,
+                        row.get(13)?,
+                        row.get(14)?,
+                        row.get(15)?,
+                        row.get(16)?,
+                        row.get(17)?,
+                        row.get(18)?,
+                        row.get(19)?,
+                    ).with_source(row.get(20)?)
                 })
-            }) {
-                Ok(r) => r,
-                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
-            };
-        let mut output: Vec<reader::Reader> = Vec::new();
+            }
+        ) {
+            Ok(r) => r,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let mut output: Vec<sighting::Sighting> = Vec::new();
         for row in results {
             match row {
-                Ok(r) => {
-                    match reader::Reader::new_no_repeaters(
-                        r.id,
-                        r.kind,
-                        r.nickname,
-                        r.ip_address,
-                        r.port,
-                        r.auto_connect
-                    ) {
-                        Ok(reader) => {
-                            output.push(reader);
-                        }
-                        Err(e) => return Err(e)
-                    }
-                },
+                Ok(r) => output.push(r),
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             }
         }
-        Ok(output)
+        return Ok(output);
     }
 
-    fn delete_reader(&self, id: &i64) -> Result<usize, DBError> {
-        match self.conn.execute("DELETE FROM readers WHERE reader_id=?1", [id]) {
-            Ok(num) => return Ok(num),
+    fn delete_sightings(&self) -> Result<usize, DBError> {
+        self.reads_changed();
+        if let Err(e) = self.conn.execute("DELETE FROM sighting_uploads;", []) {
+            return Err(DBError::DataDeletionError(e.to_string()))
+        }
+        let output = match self.conn.execute("DELETE FROM sightings;", []) {
+            Ok(num) => num,
             Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+        };
+        match self.conn.execute(
+            "UPDATE chip_reads SET status=?1;",
+            [read::READ_STATUS_UNUSED]
+        ) {
+            Ok(_) => (),
+            Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
         }
+        Ok(output)
     }
 
-    // Results API
-    fn save_api(&self, api: &api::Api) -> Result<i64, DBError> {
-        match api.kind() {
-            api::API_TYPE_CHRONOKEEP_RESULTS |
-            api::API_TYPE_CHRONOKEEP_RESULTS_SELF |
-            api::API_TYPE_CHRONOKEEP_REMOTE |
-            api::API_TYPE_CHRONOKEEP_REMOTE_SELF =>
-            {},
-            _ => return Err(DBError::DataInsertionError(String::from("invalid kind specified")))
-        }
-        if api.id() > 0 {
-            match self.conn.execute(
-                "UPDATE results_api SET 
-                        nickname=?1, 
-                        kind=?2, 
-                        token=?3, 
-                        uri=?4 
-                    WHERE api_id=?5",
-                (api.nickname(), api.kind(), api.token(), api.uri(), api.id()))
-            {
-                Ok(_) => return Ok(api.id()),
-                Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
-            }
-        }
+    // System events
+    fn save_system_event(&self, event: &system_event::SystemEvent) -> Result<i64, DBError> {
         match self.conn.execute(
-            "INSERT INTO results_api (
-                    nickname,
+            "INSERT INTO system_events (
                     kind,
-                    token,
-                    uri
-                ) VALUES (?1,?2,?3,?4);",
-            (api.nickname(), api.kind(), api.token(), api.uri())
+                    time,
+                    detail,
+                    severity,
+                    source
+                ) VALUES (?1,?2,?3,?4,?5);",
+            (event.kind(), event.time(), event.detail().to_string(), event.severity(), event.source())
         ) {
-            Ok(_) => return Ok(self.conn.last_insert_rowid()),
-            Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+            Ok(_) => Ok(self.conn.last_insert_rowid()),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
         }
     }
 
-    fn get_apis(&self) -> Result<Vec<api::Api>, DBError> {
-        let mut stmt = match self.conn.prepare("SELECT api_id, nickname, kind, token, uri FROM results_api;") {
+    fn get_system_events_after(&self, id: i64, limit: u32) -> Result<Vec<system_event::SystemEvent>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT event_id, kind, time, detail, severity, source FROM system_events WHERE event_id > ?1 ORDER BY event_id LIMIT ?2;") {
             Ok(stmt) => stmt,
             Err(e) => return Err(DBError::ConnectionError(e.to_string()))
         };
         let results = match stmt.query_map(
-            [],
-            |row|{
-                Ok(api::Api::new(
+            (id, limit),
+            |row| {
+                let detail: String = row.get(3)?;
+                let severity: String = row.get(4)?;
+                let source: String = row.get(5)?;
+                Ok(system_event::SystemEvent::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    serde_json::from_str(&detail).unwrap_or(serde_json::Value::String(detail)),
+                ).with_severity(&severity).with_source(&source))
+            }) {
+                Ok(r) => r,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<system_event::SystemEvent> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => output.push(r),
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        Ok(output)
+    }
+
+    fn get_system_events(&self, kind: &str, limit: u32) -> Result<Vec<system_event::SystemEvent>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT event_id, kind, time, detail, severity, source FROM system_events WHERE kind=?1 ORDER BY event_id DESC LIMIT ?2;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            (kind, limit),
+            |row| {
+                let detail: String = row.get(3)?;
+                let severity: String = row.get(4)?;
+                let source: String = row.get(5)?;
+                Ok(system_event::SystemEvent::new(
                     row.get(0)?,
                     row.get(1)?,
                     row.get(2)?,
-                    row.get(3)?,
-                    row.get(4)?
-                ))
+                    serde_json::from_str(&detail).unwrap_or(serde_json::Value::String(detail)),
+                ).with_severity(&severity).with_source(&source))
             }) {
                 Ok(r) => r,
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             };
-        let mut output: Vec<api::Api> = Vec::new();
+        let mut output: Vec<system_event::SystemEvent> = Vec::new();
         for row in results {
             match row {
-                Ok(r) => {
-                    match r.kind() {
-                        api::API_TYPE_CHRONOKEEP_RESULTS |
-                        api::API_TYPE_CHRONOKEEP_RESULTS_SELF |
-                        api::API_TYPE_CHRONOKEEP_REMOTE |
-                        api::API_TYPE_CHRONOKEEP_REMOTE_SELF =>
-                        {
-                            output.push(r)
-                        },
-                        _ => return Err(DBError::DataRetrievalError(String::from("invalid api type")))
-                    }
-                },
+                Ok(r) => output.push(r),
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             }
         }
-        return Ok(output);
-    }
-
-    fn delete_api(&self, id: &i64) -> Result<usize, DBError> {
-        match self.conn.execute("DELETE FROM results_api WHERE api_id=?1", [id]) {
-            Ok(num) => return Ok(num),
-            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
-        }
-    }
-
-    // Reads
-    fn save_reads(&mut self, reads: &Vec<read::Read>) -> Result<usize, DBError> {
-        if let Ok(tx) = self.conn.transaction() {
-            let mut count = 0;
-            for r in reads {
-                match r.status() {
-                    read::READ_STATUS_TOO_SOON | read::READ_STATUS_UNUSED | read::READ_STATUS_USED => {},
-                    _ => return Err(DBError::DataInsertionError(String::from("invalid chip read status")))
-                }
-                match tx.execute(
-                    "INSERT INTO chip_reads (
-                            chip, 
-                            seconds,
-                            milliseconds,
-                            reader_seconds,
-                            reader_milliseconds,
-                            antenna,
-                            reader,
-                            rssi,
-                            status,
-                            uploaded
-                        ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10);",
-                    (r.chip(), r.seconds(), r.milliseconds(), r.reader_seconds(), r.reader_milliseconds(), r.antenna(), r.reader(), r.rssi(), r.status(), r.uploaded())
-                ) {
-                    Ok(val) => count = count + val,
-                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
-                }
-            }
-            if let Err(e) = tx.commit() {
-                return Err(DBError::DataInsertionError(e.to_string()));
-            }
-            return Ok(count);
-        }
-        return Err(DBError::ConnectionError(String::from("error starting transaction")));
+        Ok(output)
     }
 
-    fn get_reads(&self, start: i64, end: i64) -> Result<Vec<read::Read>, DBError> {
-        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded FROM chip_reads WHERE seconds >= ?1 AND seconds <= ?2;") {
+    fn get_event_log(&self, query: &system_event::EventLogQuery) -> Result<Vec<system_event::SystemEvent>, DBError> {
+        let min_level = query.severity.as_deref().map(|s| system_event::severity_level(s) as i64).unwrap_or(0);
+        let limit = query.limit.unwrap_or(EVENT_LOG_DEFAULT_LIMIT).min(EVENT_LOG_MAX_LIMIT);
+        let mut stmt = match self.conn.prepare(
+            "SELECT event_id, kind, time, detail, severity, source FROM system_events WHERE
+                CASE severity WHEN 'debug' THEN 0 WHEN 'warning' THEN 2 WHEN 'error' THEN 3 ELSE 1 END >= ?1
+                AND (?2 IS NULL OR source=?2)
+                AND (?3 IS NULL OR kind=?3)
+                AND (?4 IS NULL OR time>=?4)
+                AND (?5 IS NULL OR event_id<?5)
+            ORDER BY event_id DESC LIMIT ?6;"
+        ) {
             Ok(stmt) => stmt,
             Err(e) => return Err(DBError::ConnectionError(e.to_string()))
         };
         let results = match stmt.query_map(
-            [start, end],
+            (min_level, &query.source, &query.kind, query.since, query.before, limit),
             |row| {
-                Ok(read::Read::new(
+                let detail: String = row.get(3)?;
+                let severity: String = row.get(4)?;
+                let source: String = row.get(5)?;
+                Ok(system_event::SystemEvent::new(
                     row.get(0)?,
                     row.get(1)?,
                     row.get(2)?,
-                    row.get(3)?,
-                    row.get(4)?,
-                    row.get(5)?,
-                    row.get(6)?,
-                    row.get(7)?,
-                    row.get(8)?,
-                    row.get(9)?,
-                    row.get(10)?,
-                ))
+                    serde_json::from_str(&detail).unwrap_or(serde_json::Value::String(detail)),
+                ).with_severity(&severity).with_source(&source))
             }) {
                 Ok(r) => r,
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             };
-        let mut output: Vec<read::Read> = Vec::new();
+        let mut output: Vec<system_event::SystemEvent> = Vec::new();
         for row in results {
             match row {
-                Ok(r) => {
-                    output.push(r);
-                },
+                Ok(r) => output.push(r),
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             }
         }
-        return Ok(output);
+        Ok(output)
     }
 
-    fn get_all_reads(&self) -> Result<Vec<read::Read>, DBError> {
-        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded FROM chip_reads;") {
+    // Webhooks
+    fn get_reads_after(&self, id: i64, limit: u32) -> Result<Vec<read::Read>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded, source FROM chip_reads WHERE chip_id > ?1 ORDER BY chip_id LIMIT ?2;") {
             Ok(stmt) => stmt,
             Err(e) => return Err(DBError::ConnectionError(e.to_string()))
         };
         let results = match stmt.query_map(
-            [],
+            (id, limit),
             |row| {
                 Ok(read::Read::new(
                     row.get(0)?,
@@ -607,7 +2133,7 @@ impl super::Database for SQLite {
                     row.get(8)?,
                     row.get(9)?,
                     row.get(10)?,
-                ))
+                ).with_source(row.get(11)?))
             }) {
                 Ok(r) => r,
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
@@ -615,383 +2141,614 @@ impl super::Database for SQLite {
         let mut output: Vec<read::Read> = Vec::new();
         for row in results {
             match row {
-                Ok(r) => {
-                    output.push(r);
+                Ok(r) => output.push(r),
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        Ok(output)
+    }
+
+    fn get_sightings_after(&self, id: i64, limit: u32) -> Result<Vec<(i64, sighting::Sighting)>, DBError> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT 
+                part_id,
+                bib,
+                first,
+                last,
+                birthdate,
+                gender,
+                age_group,
+                distance,
+                chip,
+                anonymous,
+                chip_id,
+                seconds,
+                milliseconds,
+                reader_seconds,
+                reader_milliseconds,
+                antenna,
+                reader,
+                rssi,
+                status,
+                uploaded,
+                source,
+                sms_enabled,
+                mobile,
+                external_id,
+                apparel,
+                sighting_id
+            FROM participants NATURAL JOIN sightings NATURAL JOIN chip_reads 
+            WHERE sighting_id > ?1 ORDER BY sighting_id LIMIT ?2;"
+        ) {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            (id, limit),
+            |row| {
+                Ok((row.get(25)?, sighting::Sighting{
+                    participant: participant::Participant::new(
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get(6)?,
+                        row.get(7)?,
+                        row.get(9)?,
+                    ).with_sms(row.get(21)?, row.get(22)?)
+                    .with_registration(row.get(23)?, row.get(24)?),
+                    read: read::Read::new(
+                        row.get(10)?,
+                        row.get(8)?,
+                        row.get(11)?,
+                        row.get(12)?,
+                        row.get(13)?,
+                        row.get(14)?,
+                        row.get(15)?,
+                        row.get(16)?,
+                        row.get(17)?,
+                        row.get(18)?,
+                        row.get(19)?,
+                    ).with_source(row.get(20)?)
+                }))
+            }
+        ) {
+            Ok(r) => r,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let mut output: Vec<(i64, sighting::Sighting)> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => output.push(r),
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        Ok(output)
+    }
+
+    fn get_sightings_to_upload(&self, api_id: i64, slug: &str, year: &str, limit: u32) -> Result<Vec<sighting::Sighting>, DBError> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT 
+                part_id,
+                bib,
+                first,
+                last,
+                birthdate,
+                gender,
+                age_group,
+                distance,
+                chip,
+                anonymous,
+                chip_id,
+                seconds,
+                milliseconds,
+                reader_seconds,
+                reader_milliseconds,
+                antenna,
+                reader,
+                rssi,
+                status,
+                uploaded,
+                source,
+                sms_enabled,
+                mobile,
+                external_id,
+                apparel
+            FROM participants NATURAL JOIN sightings NATURAL JOIN chip_reads 
+            WHERE NOT EXISTS (SELECT 1 FROM sighting_uploads u WHERE u.chip_id=sightings.chip_id AND u.part_id=sightings.part_id AND u.api_id=?1 AND u.slug=?2 AND u.year=?3)
+            ORDER BY chip_id LIMIT ?4;"
+        ) {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            (api_id, slug, year, limit),
+            |row| {
+                Ok(sighting::Sighting{
+                    participant: participant::Participant::new(
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get(6)?,
+                        row.get(7)?,
+                        row.get(9)?,
+                    ).with_sms(row.get(21)?, row.get(22)?)
+                    .with_registration(row.get(23)?, row.get(24)?),
+                    read: read::Read::new(
+                        row.get(10)?,
+                        row.get(8)?,
+                        row.get(11)?,
+                        row.get(12)?,
+                        row.get(13)?,
+                        row.get(14)?,
+                        row.get(15)?,
+                        row.get(16)?,
+                        row.get(17)?,
+                        row.get(18)?,
+                        row.get(19)?,
+                    ).with_source(row.get(20)?)
+                })
+            }
+        ) {
+            Ok(r) => r,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let mut output: Vec<sighting::Sighting> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => output.push(r),
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            }
+        }
+        Ok(output)
+    }
+
+    fn save_uploaded_sightings(&mut self, api_id: i64, slug: &str, year: &str, sightings: &[sighting::Sighting]) -> Result<usize, DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let mut count = 0;
+            for s in sightings {
+                match tx.execute(
+                    "INSERT INTO sighting_uploads (chip_id, part_id, api_id, slug, year) VALUES (?1, ?2, ?3, ?4, ?5);",
+                    (s.read.id(), s.participant.id(), api_id, slug, year)
+                ) {
+                    Ok(val) => count += val,
+                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(count)
+        }
+        Err(DBError::ConnectionError(String::from("error starting transaction")))
+    }
+
+    fn get_sighting_upload_counts(&self, api_id: i64, slug: &str, year: &str) -> Result<(usize, usize), DBError> {
+        match self.conn.query_row(
+            "SELECT
+                (SELECT COUNT(*) FROM sightings WHERE NOT EXISTS (SELECT 1 FROM sighting_uploads u WHERE u.chip_id=sightings.chip_id AND u.part_id=sightings.part_id AND u.api_id=?1 AND u.slug=?2 AND u.year=?3)),
+                (SELECT COUNT(*) FROM sightings WHERE EXISTS (SELECT 1 FROM sighting_uploads u WHERE u.chip_id=sightings.chip_id AND u.part_id=sightings.part_id AND u.api_id=?1 AND u.slug=?2 AND u.year=?3));",
+            (api_id, slug, year),
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ) {
+            Ok(counts) => Ok(counts),
+            Err(e) => Err(DBError::DataRetrievalError(e.to_string())),
+        }
+    }
+
+    fn save_notification_channel(&self, channel: &notification_channel::NotificationChannel) -> Result<i64, DBError> {
+        let config = match serde_json::to_string(channel.config()) {
+            Ok(c) => c,
+            Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+        };
+        if channel.id() > 0 {
+            match self.conn.execute(
+                "UPDATE notification_channels SET name=?1, kind=?2, enabled=?3, config=?4 WHERE channel_id=?5;",
+                (channel.name(), channel.config().kind(), channel.enabled(), &config, channel.id())
+            ) {
+                Ok(0) => return Err(DBError::NotFound),
+                Ok(_) => return Ok(channel.id()),
+                Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+            }
+        }
+        match self.conn.execute(
+            "INSERT INTO notification_channels (name, kind, enabled, config) VALUES (?1, ?2, ?3, ?4);",
+            (channel.name(), channel.config().kind(), channel.enabled(), &config)
+        ) {
+            Ok(_) => Ok(self.conn.last_insert_rowid()),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
+        }
+    }
+
+    fn get_notification_channels(&self) -> Result<Vec<notification_channel::NotificationChannel>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT channel_id, name, enabled, config FROM notification_channels ORDER BY channel_id;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [],
+            |row| Ok((row.get::<usize, i64>(0)?, row.get::<usize, String>(1)?, row.get::<usize, bool>(2)?, row.get::<usize, String>(3)?))
+        ) {
+            Ok(r) => r,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let mut output: Vec<notification_channel::NotificationChannel> = Vec::new();
+        for row in results {
+            match row {
+                Ok((id, name, enabled, config)) => match serde_json::from_str(&config) {
+                    Ok(config) => output.push(notification_channel::NotificationChannel::new(id, name, enabled, config)),
+                    Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
                 },
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             }
         }
-        return Ok(output);
+        Ok(output)
     }
 
-    fn delete_reads(&self, start: i64, end: i64) -> Result<usize, DBError> {
-        match self.conn.execute(
-            "DELETE FROM chip_reads WHERE seconds >= ?1 AND seconds <= ?2;",
-            [start, end]
-        ) {
-            Ok(num) => return Ok(num),
-            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+    fn delete_notification_channel(&self, id: i64) -> Result<usize, DBError> {
+        match self.conn.execute("DELETE FROM notification_channels WHERE channel_id=?1;", [id]) {
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataDeletionError(e.to_string()))
         }
     }
 
-    fn delete_all_reads(&self) -> Result<usize, DBError> {
+    fn save_notification_rule(&self, rule: &notification_rule::NotificationRule) -> Result<usize, DBError> {
+        let channels = match serde_json::to_string(rule.channels()) {
+            Ok(c) => c,
+            Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+        };
         match self.conn.execute(
-            "DELETE FROM chip_reads;",
-            []
+            "INSERT INTO notification_rules (
+                    kind,
+                    enabled,
+                    priority,
+                    channels,
+                    repeat_interval,
+                    quiet_start,
+                    quiet_end
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7);",
+            (rule.kind(), rule.enabled(), rule.priority(), &channels, rule.repeat_interval(), rule.quiet_start(), rule.quiet_end())
         ) {
-            Ok(num) => return Ok(num),
-            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
         }
     }
 
-    fn get_useful_reads(&self) -> Result<Vec<read::Read>, DBError> {
-        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded FROM chip_reads WHERE status <> ?1;") {
+    fn get_notification_rules(&self) -> Result<Vec<notification_rule::NotificationRule>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT kind, enabled, priority, channels, repeat_interval, quiet_start, quiet_end FROM notification_rules ORDER BY kind;") {
             Ok(stmt) => stmt,
             Err(e) => return Err(DBError::ConnectionError(e.to_string()))
         };
         let results = match stmt.query_map(
-            [read::READ_STATUS_TOO_SOON],
+            [],
             |row| {
-                Ok(read::Read::new(
+                let channels: String = row.get(3)?;
+                Ok(notification_rule::NotificationRule::new(
                     row.get(0)?,
                     row.get(1)?,
                     row.get(2)?,
-                    row.get(3)?,
+                    serde_json::from_str(&channels).unwrap_or_default(),
                     row.get(4)?,
                     row.get(5)?,
                     row.get(6)?,
-                    row.get(7)?,
-                    row.get(8)?,
-                    row.get(9)?,
-                    row.get(10)?,
                 ))
-            }) {
-                Ok(r) => r,
-                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
-            };
-        let mut output: Vec<read::Read> = Vec::new();
+            }
+        ) {
+            Ok(r) => r,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let mut output: Vec<notification_rule::NotificationRule> = Vec::new();
         for row in results {
             match row {
-                Ok(r) => {
-                    output.push(r);
-                },
+                Ok(r) => output.push(r),
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             }
         }
-        return Ok(output);
+        Ok(output)
     }
-    
-    fn get_not_uploaded_reads(&self) -> Result<Vec<read::Read>, DBError> {       
-        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded FROM chip_reads WHERE uploaded=?1;") {
+
+    fn delete_notification_rule(&self, kind: &str) -> Result<usize, DBError> {
+        match self.conn.execute("DELETE FROM notification_rules WHERE kind=?1;", [kind]) {
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataDeletionError(e.to_string()))
+        }
+    }
+
+    fn save_watched_bib(&self, watched: &watched_bib::WatchedBib) -> Result<usize, DBError> {
+        match self.conn.execute(
+            "INSERT INTO watched_bibs (bib, note) VALUES (?1, ?2);",
+            (watched.bib(), watched.note())
+        ) {
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
+        }
+    }
+
+    fn get_watched_bibs(&self) -> Result<Vec<watched_bib::WatchedBib>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT bib, note FROM watched_bibs ORDER BY bib;") {
             Ok(stmt) => stmt,
             Err(e) => return Err(DBError::ConnectionError(e.to_string()))
         };
         let results = match stmt.query_map(
-            [read::READ_UPLOADED_FALSE],
+            [],
             |row| {
-                Ok(read::Read::new(
+                Ok(watched_bib::WatchedBib::new(
                     row.get(0)?,
                     row.get(1)?,
-                    row.get(2)?,
-                    row.get(3)?,
-                    row.get(4)?,
-                    row.get(5)?,
-                    row.get(6)?,
-                    row.get(7)?,
-                    row.get(8)?,
-                    row.get(9)?,
-                    row.get(10)?,
                 ))
-            }) {
-                Ok(r) => r,
-                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
-            };
-        let mut output: Vec<read::Read> = Vec::new();
+            }
+        ) {
+            Ok(r) => r,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let mut output: Vec<watched_bib::WatchedBib> = Vec::new();
         for row in results {
             match row {
-                Ok(r) => {
-                    output.push(r);
-                },
+                Ok(r) => output.push(r),
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             }
         }
-        return Ok(output);
+        Ok(output)
     }
 
-    fn reset_reads_status(&self) -> Result<usize, DBError> {
-        match self.conn.execute("DELETE FROM sightings;", []) {
-            Ok(_) => (),
-            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
-        };
-        match self.conn.execute(
-            "UPDATE chip_reads SET status=?1;",
-            [read::READ_STATUS_UNUSED]
-        ) {
+    fn delete_watched_bib(&self, bib: &str) -> Result<usize, DBError> {
+        match self.conn.execute("DELETE FROM watched_bibs WHERE bib=?1;", [bib]) {
             Ok(num) => Ok(num),
-            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
+            Err(e) => Err(DBError::DataDeletionError(e.to_string()))
         }
     }
 
-    fn reset_reads_upload(&self) -> Result<usize, DBError> {
+    fn save_watched_bib_alert(&self, bib: &str, read_id: u64) -> Result<bool, DBError> {
         match self.conn.execute(
-            "UPDATE chip_reads SET uploaded=?1;",
-            [read::READ_UPLOADED_FALSE]
+            "INSERT INTO watched_bib_alerts (bib, chip_id) VALUES (?1, ?2);",
+            (bib, read_id)
         ) {
-            Ok(num) => Ok(num),
+            Ok(num) => Ok(num > 0),
             Err(e) => Err(DBError::DataInsertionError(e.to_string()))
         }
     }
 
-    fn update_reads_status(&mut self, reads: &Vec<read::Read>) -> Result<usize, DBError> {
-        if let Ok(tx) = self.conn.transaction() {
-            let mut count = 0;
-            for r in reads {
-                match r.status() {
-                    read::READ_STATUS_TOO_SOON | read::READ_STATUS_UNUSED | read::READ_STATUS_USED => {},
-                    _ => return Err(DBError::DataInsertionError(String::from("invalid chip read status")))
-                }
-                match tx.execute(
-                    "UPDATE chip_reads SET
-                            status=?1,
-                            uploaded=?2
-                            WHERE chip_id=?3;",
-                    (r.status(), r.uploaded(), r.id())
-                ) {
-                    Ok(_) => count += 1,
-                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
-                }
-            }
-            if let Err(e) = tx.commit() {
-                return Err(DBError::DataInsertionError(e.to_string()));
-            }
-            return Ok(count);
+    fn save_sms_supporter(&self, supporter: &sms_supporter::SmsSupporter) -> Result<usize, DBError> {
+        match self.conn.execute(
+            "INSERT INTO sms_supporters (bib, mobile) VALUES (?1, ?2);",
+            (supporter.bib(), supporter.mobile())
+        ) {
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
         }
-        return Err(DBError::ConnectionError(String::from("error starting transaction")));
     }
 
-    // Participants
-    fn add_participants(&mut self, participants: &Vec<participant::Participant>) -> Result<usize, DBError> {
-        let mut count = 0;
-        if let Ok(tx) = self.conn.transaction() {
-            for p in participants {
-                match tx.execute(
-                    "INSERT INTO participants (
-                        bib,
-                        first,
-                        last,
-                        birthdate,
-                        gender,
-                        age_group,
-                        distance,
-                        anonymous
-                    ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8)",
-                    (
-                        p.bib(),
-                        p.first(),
-                        p.last(),
-                        p.birthdate(),
-                        p.gender(),
-                        p.age_group(),
-                        p.distance(),
-                        p.anonymous()
-                    )
-                ) {
-                    Ok(_) => count = count + 1,
-                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
-                }
+    fn get_sms_supporters(&self) -> Result<Vec<sms_supporter::SmsSupporter>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT bib, mobile FROM sms_supporters ORDER BY bib, mobile;") {
+            Ok(stmt) => stmt,
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
+        };
+        let results = match stmt.query_map(
+            [],
+            |row| {
+                Ok(sms_supporter::SmsSupporter::new(
+                    row.get(0)?,
+                    row.get(1)?,
+                ))
             }
-            if let Err(e) = tx.commit() {
-                return Err(DBError::DataInsertionError(e.to_string()))
+        ) {
+            Ok(r) => r,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let mut output: Vec<sms_supporter::SmsSupporter> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => output.push(r),
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             }
         }
-        Ok(count)
+        Ok(output)
     }
 
-    fn delete_participants(&self) -> Result<usize, DBError> {
-        match self.conn.execute(
-            "DELETE FROM bibchip;", 
-            []
-        ) {
-            Ok(_) => {},
-            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
-        }
-        match self.delete_sightings() {
-            Ok(_) => (),
-            Err(e) => return Err(e)
-        }
+    fn delete_sms_supporter(&self, supporter: &sms_supporter::SmsSupporter) -> Result<usize, DBError> {
         match self.conn.execute(
-            "DELETE FROM participants;",
-            []
+            "DELETE FROM sms_supporters WHERE bib=?1 AND mobile=?2;",
+            (supporter.bib(), supporter.mobile())
         ) {
-            Ok(num) => return Ok(num),
-            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataDeletionError(e.to_string()))
         }
     }
 
-    fn delete_participant(&self, bib: &str) -> Result<usize, DBError> {
-        match self.conn.execute(
-            "DELETE FROM bibchip WHERE bib=?1;", 
-            [bib]
-        ) {
-            Ok(_) => {},
-            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
-        }
+    fn save_sms_delivery(&self, delivery: &sms_delivery::SmsDelivery) -> Result<i64, DBError> {
         match self.conn.execute(
-            "DELETE FROM participants WHERE bib=?1;",
-            [bib]
+            "INSERT INTO sms_deliveries (
+                    bib,
+                    mobile,
+                    message,
+                    time,
+                    status,
+                    error,
+                    read_seconds,
+                    read_milliseconds
+                ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8);",
+            (delivery.bib(), delivery.mobile(), delivery.message(), delivery.time(), delivery.status(), delivery.error(), delivery.read_seconds(), delivery.read_milliseconds())
         ) {
-            Ok(num) => return Ok(num),
-            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+            Ok(_) => Ok(self.conn.last_insert_rowid()),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
         }
     }
 
-    fn get_participants(&self) -> Result<Vec<participant::Participant>, DBError> {
-        let mut stmt = match self.conn.prepare("SELECT part_id, bib, first, last, birthdate, gender, age_group, distance, anonymous FROM participants;") {
+    fn get_sms_deliveries(&self, bib: Option<&str>, limit: Option<u32>) -> Result<Vec<sms_delivery::SmsDelivery>, DBError> {
+        let limit = limit.unwrap_or(SMS_LOG_DEFAULT_LIMIT).min(SMS_LOG_MAX_LIMIT);
+        let mut stmt = match self.conn.prepare(
+            "SELECT delivery_id, bib, mobile, message, time, status, error, read_seconds, read_milliseconds FROM sms_deliveries
+                WHERE (?1 IS NULL OR bib=?1)
+            ORDER BY delivery_id DESC LIMIT ?2;"
+        ) {
             Ok(stmt) => stmt,
             Err(e) => return Err(DBError::ConnectionError(e.to_string()))
         };
         let results = match stmt.query_map(
-            [],
+            (bib, limit),
             |row| {
-                Ok(participant::Participant::new(
+                Ok(sms_delivery::SmsDelivery::new(
                     row.get(0)?,
                     row.get(1)?,
                     row.get(2)?,
                     row.get(3)?,
                     row.get(4)?,
-                    row.get(5)?,
-                    row.get(6)?,
-                    row.get(7)?,
-                    row.get(8)?,
-                ))
-            }) {
-                Ok(r) => r,
-                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
-            };
-        let mut output: Vec<participant::Participant> = Vec::new();
-        for row in results {
-            match row {
-                Ok(p) => {
-                    output.push(p);
-                },
-                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
-            }
-        }
-        return Ok(output);
-    }
-
-    // BibChips
-    fn add_bibchips(&mut self, bibchips: &Vec<bibchip::BibChip>) -> Result<usize, DBError> {
-        let mut count = 0;
-        if let Ok(tx) = self.conn.transaction() {
-            for b in bibchips {
-                match tx.execute(
-                    "INSERT INTO bibchip (
-                        bib,
-                        chip
-                    ) VALUES (?1, ?2)",
-                    (
-                        b.bib(),
-                        b.chip()
-                    )
-                ) {
-                    Ok(_) => count = count + 1,
-                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
-                }
+                    row.get(5)?,
+                    row.get(6)?,
+                ).with_read(row.get(7)?, row.get(8)?))
             }
-            if let Err(e) = tx.commit() {
-                return Err(DBError::DataInsertionError(e.to_string()))
+        ) {
+            Ok(r) => r,
+            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+        };
+        let mut output: Vec<sms_delivery::SmsDelivery> = Vec::new();
+        for row in results {
+            match row {
+                Ok(r) => output.push(r),
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             }
         }
-        Ok(count)
+        Ok(output)
     }
 
-    fn delete_all_bibchips(&self) -> Result<usize, DBError> {
-        match self.conn.execute(
-            "DELETE FROM bibchip;", 
-            []
+    fn sms_delivered(&self, bib: &str, mobile: &str, read_seconds: i64, read_milliseconds: u32) -> Result<bool, DBError> {
+        match self.conn.query_row(
+            "SELECT COUNT(*) FROM sms_deliveries WHERE bib=?1 AND mobile=?2 AND read_seconds=?3 AND read_milliseconds=?4;",
+            (bib, mobile, read_seconds, read_milliseconds),
+            |row| row.get::<usize, i64>(0)
         ) {
-            Ok(num) => return Ok(num),
-            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+            Ok(count) => Ok(count > 0),
+            Err(e) => Err(DBError::DataRetrievalError(e.to_string()))
         }
     }
 
-    fn delete_bibchips(&self, bib: &str) -> Result<usize, DBError> {
+    fn get_webhook_cursor(&self, api_id: i64, stream: &str) -> Result<i64, DBError> {
+        match self.conn.query_row("SELECT cursor FROM webhook_cursors WHERE api_id=?1 AND stream=?2;",
+            (api_id, stream),
+            |row| row.get(0)
+        ) {
+            Ok(it) => Ok(it),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(DBError::NotFound),
+            Err(e) => Err(DBError::DataRetrievalError(e.to_string())),
+        }
+    }
+
+    fn set_webhook_cursor(&self, api_id: i64, stream: &str, cursor: i64) -> Result<usize, DBError> {
         match self.conn.execute(
-            "DELETE FROM bibchip WHERE bib=?1;", 
-            [bib]
+            "INSERT INTO webhook_cursors (api_id, stream, cursor) VALUES (?1,?2,?3);",
+            (api_id, stream, cursor)
         ) {
-            Ok(num) => return Ok(num),
-            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
         }
     }
 
-    fn get_bibchips(&self) -> Result<Vec<bibchip::BibChip>, DBError> {
-        let mut stmt = match self.conn.prepare("SELECT bib, chip FROM bibchip;") {
+    // Upload ledger
+    fn get_reads_to_upload(&self, api_id: i64, limit: u32) -> Result<Vec<read::Read>, DBError> {
+        let mut stmt = match self.conn.prepare("SELECT chip_id, chip, seconds, milliseconds, reader_seconds, reader_milliseconds, antenna, reader, rssi, status, uploaded, source FROM chip_reads WHERE
+                chip_id NOT IN (SELECT chip_id FROM upload_ledger WHERE api_id=?1) AND
+                chip_id NOT IN (SELECT chip_id FROM upload_batch_reads NATURAL JOIN upload_batches WHERE api_id=?1)
+                ORDER BY chip_id LIMIT ?2;") {
             Ok(stmt) => stmt,
             Err(e) => return Err(DBError::ConnectionError(e.to_string()))
         };
         let results = match stmt.query_map(
-            [],
+            (api_id, limit),
             |row| {
-                Ok(bibchip::BibChip::new(
+                Ok(read::Read::new(
                     row.get(0)?,
                     row.get(1)?,
-                ))
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                    row.get(8)?,
+                    row.get(9)?,
+                    row.get(10)?,
+                ).with_source(row.get(11)?))
             }) {
-                Ok(b) => b,
+                Ok(r) => r,
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             };
-        let mut output: Vec<bibchip::BibChip> = Vec::new();
+        let mut output: Vec<read::Read> = Vec::new();
         for row in results {
             match row {
-                Ok(b) => {
-                    output.push(b);
-                },
+                Ok(r) => output.push(r),
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             }
         }
-        return Ok(output)
+        Ok(output)
     }
 
-    // Sightings
-    fn save_sightings(&mut self, sightings: &Vec<sighting::Sighting>) -> Result<usize, DBError> {
+    fn save_uploaded(&mut self, api_id: i64, reads: &[read::Read]) -> Result<usize, DBError> {
         if let Ok(tx) = self.conn.transaction() {
             let mut count = 0;
-            for s in sightings {
+            for r in reads {
                 match tx.execute(
-                    "INSERT INTO sightings (
-                        chip_id,
-                        part_id
-                    ) VALUES (?1,?2);",
-                    (s.read.id(), s.participant.id())
+                    "INSERT INTO upload_ledger (chip_id, api_id) VALUES (?1, ?2);",
+                    (r.id(), api_id)
                 ) {
-                    Ok(val) => count = count + val,
+                    Ok(val) => count += val,
                     Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
                 }
+                // the old flag just says it's gone somewhere
+                if let Err(e) = tx.execute(
+                    "UPDATE chip_reads SET uploaded=?1 WHERE chip_id=?2;",
+                    (read::READ_UPLOADED_TRUE, r.id())
+                ) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
             }
             if let Err(e) = tx.commit() {
-                return Err(DBError::DataInsertionError(e.to_string()));
+                return Err(DBError::DataInsertionError(e.to_string()))
             }
-            return Ok(count);
+            return Ok(count)
         }
-        return Err(DBError::ConnectionError(String::from("error starting transaction")));
+        Err(DBError::ConnectionError(String::from("error starting transaction")))
     }
 
-    fn get_sightings(&self, start: i64, end: i64) -> Result<Vec<sighting::Sighting>, DBError> {
+    fn get_upload_counts(&self, api_id: i64) -> Result<(usize, usize, usize), DBError> {
+        match self.conn.query_row(
+            "SELECT
+                (SELECT COUNT(*) FROM chip_reads WHERE
+                    chip_id NOT IN (SELECT chip_id FROM upload_ledger WHERE api_id=?1) AND
+                    chip_id NOT IN (SELECT chip_id FROM upload_batch_reads NATURAL JOIN upload_batches WHERE api_id=?1 AND rejected=1)),
+                (SELECT COUNT(*) FROM upload_ledger WHERE api_id=?1),
+                (SELECT COUNT(*) FROM upload_batch_reads NATURAL JOIN upload_batches WHERE api_id=?1 AND rejected=1);",
+            [api_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        ) {
+            Ok(it) => Ok(it),
+            Err(e) => Err(DBError::DataRetrievalError(e.to_string())),
+        }
+    }
+
+    // Upload batches
+    fn save_upload_batch(&mut self, batch: &upload_batch::UploadBatch) -> Result<usize, DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            insert_upload_batch(&tx, batch)?;
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(batch.reads().len())
+        }
+        Err(DBError::ConnectionError(String::from("error starting transaction")))
+    }
+
+    fn get_upload_batches(&self, api_id: i64) -> Result<Vec<upload_batch::UploadBatch>, DBError> {
         let mut stmt = match self.conn.prepare(
-            "SELECT 
-                part_id,
-                bib,
-                first,
-                last,
-                birthdate,
-                gender,
-                age_group,
-                distance,
-                chip,
-                anonymous,
+            "SELECT
+                batch_id,
+                attempts,
                 chip_id,
+                chip,
                 seconds,
                 milliseconds,
                 reader_seconds,
@@ -1000,140 +2757,169 @@ impl super::Database for SQLite {
                 reader,
                 rssi,
                 status,
-                uploaded
-            FROM participants NATURAL JOIN sightings NATURAL JOIN chip_reads 
-            WHERE seconds >= ?1 AND seconds <= ?2;"
+                uploaded,
+                source
+            FROM upload_batches NATURAL JOIN upload_batch_reads NATURAL JOIN chip_reads
+            WHERE api_id=?1 AND rejected=0 ORDER BY upload_batches.rowid, chip_id;"
         ) {
             Ok(stmt) => stmt,
-            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            Err(e) => return Err(DBError::ConnectionError(e.to_string()))
         };
         let results = match stmt.query_map(
-            [start, end],
+            [api_id],
             |row| {
-                Ok(sighting::Sighting{
-                    participant: participant::Participant::new(
-                        row.get(0)?,
-                        row.get(1)?,
+                Ok((
+                    row.get::<usize, String>(0)?,
+                    row.get::<usize, u32>(1)?,
+                    read::Read::new(
                         row.get(2)?,
                         row.get(3)?,
                         row.get(4)?,
                         row.get(5)?,
                         row.get(6)?,
                         row.get(7)?,
-                        row.get(9)?
-                    ),
-                    read: read::Read::new(
-                        row.get(10)?,
                         row.get(8)?,
+                        row.get(9)?,
+                        row.get(10)?,
                         row.get(11)?,
                         row.get(12)?,
-                        row.get(13)?,
-                        row.get(14)?,
-                        row.get(15)?,
-                        row.get(16)?,
-                        row.get(17)?,
-                        row.get(18)?,
-                        row.get(19)?,
-                    )
-                })
-            }
-        ) {
-            Ok(r) => r,
-            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
-        };
-        let mut output: Vec<sighting::Sighting> = Vec::new();
+                    ).with_source(row.get(13)?)
+                ))
+            }) {
+                Ok(r) => r,
+                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            };
+        let mut output: Vec<upload_batch::UploadBatch> = Vec::new();
+        let mut reads: Vec<read::Read> = Vec::new();
+        let mut current: Option<(String, u32)> = None;
         for row in results {
             match row {
-                Ok(r) => output.push(r),
+                Ok((batch_id, attempts, read)) => {
+                    if current.as_ref().is_some_and(|(id, _)| *id != batch_id) {
+                        if let Some((id, att)) = current.take() {
+                            output.push(upload_batch::UploadBatch::new(id, api_id, att, std::mem::take(&mut reads)));
+                        }
+                    }
+                    if current.is_none() {
+                        current = Some((batch_id, attempts));
+                    }
+                    reads.push(read);
+                },
                 Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
             }
         }
-        return Ok(output);
+        if let Some((id, att)) = current {
+            output.push(upload_batch::UploadBatch::new(id, api_id, att, reads));
+        }
+        Ok(output)
     }
 
-    fn get_all_sightings(&self) -> Result<Vec<sighting::Sighting>, DBError> {
-        let mut stmt = match self.conn.prepare(
-            "SELECT 
-                part_id,
-                bib,
-                first,
-                last,
-                birthdate,
-                gender,
-                age_group,
-                distance,
-                chip,
-                anonymous,
-                chip_id,
-                seconds,
-                milliseconds,
-                reader_seconds,
-                reader_milliseconds,
-                antenna,
-                reader,
-                rssi,
-                status,
-                uploaded
-            FROM participants NATURAL JOIN sightings NATURAL JOIN chip_reads;"
+    fn set_upload_batch_attempts(&self, batch_id: &str, attempts: u32) -> Result<usize, DBError> {
+        match self.conn.execute(
+            "UPDATE upload_batches SET attempts=?1 WHERE batch_id=?2;",
+            (attempts, batch_id)
         ) {
-            Ok(stmt) => stmt,
-            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
-        };
-        let results = match stmt.query_map([],
-            |row| {
-                Ok(sighting::Sighting{
-                    participant: participant::Participant::new(
-                        row.get(0)?,
-                        row.get(1)?,
-                        row.get(2)?,
-                        row.get(3)?,
-                        row.get(4)?,
-                        row.get(5)?,
-                        row.get(6)?,
-                        row.get(7)?,
-                        row.get(9)?,
-                    ),
-                    read: read::Read::new(
-                        row.get(10)?,
-                        row.get(8)?,
-                        row.get(11)?,
-                        row.get(12)?,
-                        row.get(13)?,
-                        row.get(14)?,
-                        row.get(15)?,
-                        row.get(16)?,
-                        row.get(17)?,
-                        row.get(18)?,
-                        row.get(19)?,
-                    )
-                })
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
+        }
+    }
+
+    fn complete_upload_batch(&mut self, batch: &upload_batch::UploadBatch) -> Result<usize, DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let mut count = 0;
+            for id in batch.read_ids() {
+                match tx.execute(
+                    "INSERT INTO upload_ledger (chip_id, api_id) VALUES (?1, ?2);",
+                    (id, batch.api_id())
+                ) {
+                    Ok(val) => count += val,
+                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                }
+                // the old flag just says it's gone somewhere
+                if let Err(e) = tx.execute(
+                    "UPDATE chip_reads SET uploaded=?1 WHERE chip_id=?2;",
+                    (read::READ_UPLOADED_TRUE, id)
+                ) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
             }
-        ) {
-            Ok(r) => r,
-            Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
-        };
-        let mut output: Vec<sighting::Sighting> = Vec::new();
-        for row in results {
-            match row {
-                Ok(r) => output.push(r),
-                Err(e) => return Err(DBError::DataRetrievalError(e.to_string()))
+            delete_upload_batch(&tx, batch.id())?;
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
             }
+            return Ok(count)
         }
-        return Ok(output);
+        Err(DBError::ConnectionError(String::from("error starting transaction")))
     }
 
-    fn delete_sightings(&self) -> Result<usize, DBError> {
-        let output = match self.conn.execute("DELETE FROM sightings;", []) {
-            Ok(num) => num,
-            Err(e) => return Err(DBError::DataDeletionError(e.to_string()))
-        };
+    fn reject_upload_batch(&self, batch_id: &str) -> Result<usize, DBError> {
         match self.conn.execute(
-            "UPDATE chip_reads SET status=?1;",
-            [read::READ_STATUS_UNUSED]
+            "UPDATE upload_batches SET rejected=1 WHERE batch_id=?1;",
+            [batch_id]
         ) {
-            Ok(_) => (),
-            Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+            Ok(num) => Ok(num),
+            Err(e) => Err(DBError::DataInsertionError(e.to_string()))
+        }
+    }
+
+    // Records the reads the server took out of a batch, the rest stay in it to be sent again.
+    fn accept_upload_batch_reads(&mut self, batch: &upload_batch::UploadBatch, read_ids: &[u64]) -> Result<usize, DBError> {
+        if let Ok(tx) = self.conn.transaction() {
+            let mut count = 0;
+            for id in read_ids {
+                match tx.execute(
+                    "INSERT INTO upload_ledger (chip_id, api_id) VALUES (?1, ?2);",
+                    (id, batch.api_id())
+                ) {
+                    Ok(val) => count += val,
+                    Err(e) => return Err(DBError::DataInsertionError(e.to_string()))
+                }
+                // the old flag just says it's gone somewhere
+                if let Err(e) = tx.execute(
+                    "UPDATE chip_reads SET uploaded=?1 WHERE chip_id=?2;",
+                    (read::READ_UPLOADED_TRUE, id)
+                ) {
+                    return Err(DBError::DataInsertionError(e.to_string()))
+                }
+                if let Err(e) = tx.execute(
+                    "DELETE FROM upload_batch_reads WHERE batch_id=?1 AND chip_id=?2;",
+                    (batch.id(), id)
+                ) {
+                    return Err(DBError::DataDeletionError(e.to_string()))
+                }
+            }
+            if let Err(e) = tx.commit() {
+                return Err(DBError::DataInsertionError(e.to_string()))
+            }
+            return Ok(count)
+        }
+        Err(DBError::ConnectionError(String::from("error starting transaction")))
+    }
+}
+
+fn insert_upload_batch(tx: &rusqlite::Transaction, batch: &upload_batch::UploadBatch) -> Result<(), DBError> {
+    if let Err(e) = tx.execute(
+        "INSERT INTO upload_batches (batch_id, api_id, attempts) VALUES (?1, ?2, ?3);",
+        (batch.id(), batch.api_id(), batch.attempts())
+    ) {
+        return Err(DBError::DataInsertionError(e.to_string()))
+    }
+    for id in batch.read_ids() {
+        if let Err(e) = tx.execute(
+            "INSERT INTO upload_batch_reads (batch_id, chip_id) VALUES (?1, ?2);",
+            (batch.id(), id)
+        ) {
+            return Err(DBError::DataInsertionError(e.to_string()))
+        }
+    }
+    Ok(())
+}
+
+fn delete_upload_batch(tx: &rusqlite::Transaction, batch_id: &str) -> Result<(), DBError> {
+    for statement in ["DELETE FROM upload_batch_reads WHERE batch_id=?1;", "DELETE FROM upload_batches WHERE batch_id=?1;"] {
+        if let Err(e) = tx.execute(statement, [batch_id]) {
+            return Err(DBError::DataDeletionError(e.to_string()))
         }
-        Ok(output)
     }
+    Ok(())
 }
\ No newline at end of file
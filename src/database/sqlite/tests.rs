@@ -6,21 +6,42 @@ use crate::database::DBError;
 use crate::database::Database;
 use crate::network::api;
 use crate::objects::bibchip;
+use crate::objects::notification_channel;
+use crate::objects::notification_rule;
 use crate::objects::participant;
 use crate::objects::read;
 use crate::objects::setting;
 use crate::objects::sighting;
+use crate::objects::upload_batch;
+use crate::objects::system_event;
+use crate::objects::watched_bib;
+use crate::objects::sms_delivery;
+use crate::objects::sms_supporter;
+use crate::objects::unknown_chip;
 use crate::reader::{self, zebra};
 
 fn setup_tests(path: &str) -> SQLite {
     let new_conn = rusqlite::Connection::open(path).unwrap();
     let drop_tables = [
         "DROP TABLE IF EXISTS sightings;",
+        "DROP TABLE IF EXISTS sighting_keys;",
         "DROP TABLE IF EXISTS results_api;",
         "DROP TABLE IF EXISTS participants;",
         "DROP TABLE IF EXISTS readers;",
         "DROP TABLE IF EXISTS chip_reads;",
         "DROP TABLE IF EXISTS settings;",
+        "DROP TABLE IF EXISTS system_events;",
+        "DROP TABLE IF EXISTS webhook_cursors;",
+        "DROP TABLE IF EXISTS upload_ledger;",
+        "DROP TABLE IF EXISTS upload_batches;",
+        "DROP TABLE IF EXISTS upload_batch_reads;",
+        "DROP TABLE IF EXISTS sighting_uploads;",
+        "DROP TABLE IF EXISTS notification_channels;",
+        "DROP TABLE IF EXISTS notification_rules;",
+        "DROP TABLE IF EXISTS watched_bibs;",
+        "DROP TABLE IF EXISTS sms_supporters;",
+        "DROP TABLE IF EXISTS sms_deliveries;",
+        "DROP TABLE IF EXISTS watched_bib_alerts;",
     ];
     for table in drop_tables {
         if let Err(v) = new_conn.execute(table, []) {
@@ -28,9 +49,7 @@ fn setup_tests(path: &str) -> SQLite {
             panic!();
         }
     }
-    let mut output = SQLite {
-        conn: new_conn
-    };
+    let mut output = SQLite::from_connection(new_conn);
     match output.setup() {
         Ok(_) => {},
         Err(e) => {
@@ -119,9 +138,7 @@ fn setup_v1(path: &str) -> SQLite {
             panic!("{}", e)
         }
     }
-    SQLite {
-        conn: new_conn
-    }
+    SQLite::from_connection(new_conn)
 }
 
 #[test]
@@ -130,9 +147,7 @@ fn test_setup() {
     {
         let new_conn = rusqlite::Connection::open(unique_path);
         assert!(new_conn.is_ok());
-        let mut sqlite = SQLite {
-            conn: new_conn.unwrap()
-        };
+        let mut sqlite = SQLite::from_connection(new_conn.unwrap());
         let res = sqlite.setup();
         match res {
             Ok(_) => println!("Everything went ok!"),
@@ -161,6 +176,38 @@ fn test_update() {
     finalize_tests(unique_path);
 }
 
+#[test]
+fn test_update_to_v7_upload_ledger() {
+    let unique_path = "./test_update_to_v7_upload_ledger.sqlite";
+    let mut sqlite = setup_tests(unique_path);
+    let first = sqlite.save_api(&api::Api::new(
+        0,
+        String::from("remote-1"),
+        String::from(api::API_TYPE_CHRONOKEEP_REMOTE),
+        String::from("token-1"),
+        String::from("https://example.com/one"))
+    ).unwrap();
+    let second = sqlite.save_api(&api::Api::new(
+        0,
+        String::from("remote-2"),
+        String::from(api::API_TYPE_CHRONOKEEP_REMOTE_SELF),
+        String::from("token-2"),
+        String::from("https://example.com/two"))
+    ).unwrap();
+    sqlite.save_reads(&make_reads()).unwrap();
+    let all = sqlite.get_all_reads().unwrap();
+    let uploaded = all.iter().filter(|r| r.uploaded() == read::READ_UPLOADED_TRUE).count();
+    assert!(uploaded > 0);
+    // go back to before the ledger existed
+    sqlite.conn.execute("DROP TABLE upload_ledger;", []).unwrap();
+    assert!(sqlite.update_to_v7().is_ok());
+    // only the api the old uploader used has the uploaded reads
+    assert_eq!(all.len() - uploaded, sqlite.get_reads_to_upload(first, 1000).unwrap().len());
+    assert_eq!(all.len(), sqlite.get_reads_to_upload(second, 1000).unwrap().len());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
 #[test]
 fn test_set_setting() {
     let unique_path = "./test_set_setting.sqlite";
@@ -903,6 +950,95 @@ fn test_get_useful_reads() {
     finalize_tests(unique_path);
 }
 
+#[test]
+fn test_get_unused_reads_after() {
+    let unique_path = "./test_get_unused_reads_after.sqlite";
+    let mut sqlite = setup_tests(unique_path);
+    sqlite.save_reads(&make_reads()).unwrap();
+    let unused = sqlite.get_unused_reads_after(0).unwrap();
+    assert_eq!(94, unused.len());
+    assert!(unused.iter().all(|r| r.status() == read::READ_STATUS_UNUSED));
+    assert!(unused.windows(2).all(|w| w[0].id() < w[1].id()));
+    let watermark = unused[49].id();
+    let unused = sqlite.get_unused_reads_after(watermark).unwrap();
+    assert_eq!(44, unused.len());
+    assert!(unused.iter().all(|r| r.id() > watermark));
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_generations() {
+    let unique_path = "./test_generations.sqlite";
+    let mut sqlite = setup_tests(unique_path);
+    let participants = sqlite.participants_generation();
+    let reads = sqlite.reads_generation();
+    // new reads and status updates from the processor don't invalidate anything
+    sqlite.save_reads(&make_reads()).unwrap();
+    let mut all = sqlite.get_all_reads().unwrap();
+    for r in all.iter_mut() {
+        r.set_status(read::READ_STATUS_USED);
+    }
+    sqlite.update_reads_status(&all).unwrap();
+    assert_eq!(participants, sqlite.participants_generation());
+    assert_eq!(reads, sqlite.reads_generation());
+    let _ = sqlite.add_participants(&make_participants().participants);
+    assert!(participants < sqlite.participants_generation());
+    let participants = sqlite.participants_generation();
+    let _ = sqlite.add_bibchips(&make_participants().bibchips);
+    assert!(participants < sqlite.participants_generation());
+    assert_eq!(reads, sqlite.reads_generation());
+    let _ = sqlite.reset_reads_status_for(&[String::from("1005")]);
+    assert!(reads < sqlite.reads_generation());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_unknown_chips() {
+    let unique_path = "./test_unknown_chips.sqlite";
+    let mut sqlite = setup_tests(unique_path);
+    let mut reads: Vec<read::Read> = Vec::new();
+    for (chip, seconds, antenna, status) in [
+        ("3000", 500, 2, read::READ_STATUS_HELD),
+        ("3000", 200, 1, read::READ_STATUS_HELD),
+        ("3000", 300, 2, read::READ_STATUS_HELD),
+        ("3001", 400, 3, read::READ_STATUS_IGNORED),
+        ("3002", 100, 1, read::READ_STATUS_USED),
+    ] {
+        reads.push(read::Read::new(
+            0,
+            String::from(chip),
+            seconds,
+            0,
+            seconds,
+            0,
+            antenna,
+            String::from("reader-1"),
+            String::from("-25dba"),
+            status,
+            read::READ_UPLOADED_FALSE
+        ));
+    }
+    sqlite.save_reads(&reads).unwrap();
+    // held and ignored reads aren't sent to the processor
+    assert_eq!(1, sqlite.get_useful_reads().unwrap().len());
+    let unknown = sqlite.get_unknown_chips().unwrap();
+    assert_eq!(2, unknown.len());
+    assert_eq!(unknown_chip::UnknownChip::new(String::from("3000"), 200, 500, 3, vec![1, 2]), unknown[0]);
+    assert_eq!(unknown_chip::UnknownChip::new(String::from("3001"), 400, 400, 1, vec![3]), unknown[1]);
+    // only held reads come back once the chip is assigned
+    let result = sqlite.release_held_reads(&[String::from("3000"), String::from("3001")]);
+    assert!(result.is_ok());
+    assert_eq!(3, result.unwrap());
+    assert_eq!(4, sqlite.get_useful_reads().unwrap().len());
+    let unknown = sqlite.get_unknown_chips().unwrap();
+    assert_eq!(1, unknown.len());
+    assert_eq!("3001", unknown[0].chip());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
 #[test]
 fn test_get_not_uploaded_reads() {
     let unique_path = "./test_get_not_uploaded_reads.sqlite";
@@ -1226,6 +1362,33 @@ fn test_add_bibchips() {
     finalize_tests(unique_path);
 }
 
+#[test]
+fn test_bibchip_history() {
+    let unique_path = "./test_bibchip_history.sqlite";
+    let mut sqlite = setup_tests(unique_path);
+    let _ = sqlite.add_bibchips(&vec!(bibchip::BibChip::new(String::from("200"), String::from("2000"))));
+    // swap the chip to someone else partway through
+    let result = sqlite.add_bibchips(&vec!(bibchip::BibChip::new(
+        String::from("201"),
+        String::from("2000")
+    ).with_effective(5000, 0)));
+    assert!(result.is_ok());
+    let bcs = sqlite.get_bibchips().unwrap();
+    assert_eq!(2, bcs.len());
+    assert!(bcs[0].equals(&bibchip::BibChip::new(String::from("200"), String::from("2000")).with_effective(0, 5000)));
+    assert!(bcs[1].equals(&bibchip::BibChip::new(String::from("201"), String::from("2000")).with_effective(5000, 0)));
+    assert!(!bcs[0].is_current());
+    assert!(bcs[1].is_current());
+    // an untimed assignment replaces the whole history for the chip
+    let result = sqlite.add_bibchips(&vec!(bibchip::BibChip::new(String::from("202"), String::from("2000"))));
+    assert!(result.is_ok());
+    let bcs = sqlite.get_bibchips().unwrap();
+    assert_eq!(1, bcs.len());
+    assert_eq!("202", bcs[0].bib());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
 #[test]
 fn test_delete_all_bibchips() {
     let unique_path = "./test_delete_all_bibchips.sqlite";
@@ -1465,4 +1628,690 @@ fn test_delete_sightings() {
     assert_eq!(0, sights.len());
     drop(sqlite);
     finalize_tests(unique_path);
-}
\ No newline at end of file
+}
+#[test]
+fn test_save_system_event() {
+    let unique_path = "./test_save_system_event.sqlite";
+    let sqlite = setup_tests(unique_path);
+    let result = sqlite.save_system_event(&system_event::SystemEvent::new(
+        0,
+        String::from(system_event::SYSTEM_EVENT_NOTIFICATION),
+        1000,
+        serde_json::json!({ "kind": "BATTERY_LOW" }),
+    ));
+    assert!(result.is_ok());
+    let first = result.unwrap();
+    let result = sqlite.save_system_event(&system_event::SystemEvent::new(
+        0,
+        String::from(system_event::SYSTEM_EVENT_READER_STATUS),
+        1001,
+        serde_json::json!({ "readers": [] }),
+    ));
+    assert!(result.is_ok());
+    assert!(result.unwrap() > first);
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_get_system_events_after() {
+    let unique_path = "./test_get_system_events_after.sqlite";
+    let sqlite = setup_tests(unique_path);
+    for i in 0..10 {
+        _ = sqlite.save_system_event(&system_event::SystemEvent::new(
+            0,
+            String::from(system_event::SYSTEM_EVENT_NOTIFICATION),
+            1000 + i,
+            serde_json::json!({ "count": i }),
+        ));
+    }
+    let result = sqlite.get_system_events_after(0, 100);
+    assert!(result.is_ok());
+    let events = result.unwrap();
+    assert_eq!(10, events.len());
+    assert_eq!(1000, events[0].time());
+    assert_eq!(system_event::SYSTEM_EVENT_NOTIFICATION, events[0].kind());
+    assert_eq!(&serde_json::json!({ "count": 0 }), events[0].detail());
+    let result = sqlite.get_system_events_after(events[4].id(), 3);
+    assert!(result.is_ok());
+    let after = result.unwrap();
+    assert_eq!(3, after.len());
+    assert_eq!(events[5].id(), after[0].id());
+    let result = sqlite.get_system_events_after(events[9].id(), 100);
+    assert!(result.is_ok());
+    assert_eq!(0, result.unwrap().len());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_get_reads_after() {
+    let unique_path = "./test_get_reads_after.sqlite";
+    let new_reads = make_reads();
+    let mut sqlite = setup_tests(unique_path);
+    _ = sqlite.save_reads(&new_reads);
+    let result = sqlite.get_reads_after(0, 1000);
+    assert!(result.is_ok());
+    let reads = result.unwrap();
+    assert_eq!(new_reads.len() - 1, reads.len());
+    for pair in reads.windows(2) {
+        assert!(pair[0].id() < pair[1].id());
+    }
+    let result = sqlite.get_reads_after(reads[9].id() as i64, 20);
+    assert!(result.is_ok());
+    let after = result.unwrap();
+    assert_eq!(20, after.len());
+    assert!(after[0].equals(&reads[10]));
+    let result = sqlite.get_reads_after(reads.last().unwrap().id() as i64, 20);
+    assert!(result.is_ok());
+    assert_eq!(0, result.unwrap().len());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_read_source() {
+    let unique_path = "./test_read_source.sqlite";
+    let new_reads = make_reads();
+    let mut sqlite = setup_tests(unique_path);
+    let relayed: Vec<read::Read> = new_reads[0..2].iter().map(|r| r.clone().with_source(String::from("Aid Station 2"))).collect();
+    _ = sqlite.save_reads(&relayed);
+    _ = sqlite.save_reads(&new_reads[3..8].to_vec());
+    let result = sqlite.get_all_reads();
+    assert!(result.is_ok());
+    let reads = result.unwrap();
+    assert_eq!(7, reads.len());
+    assert_eq!(2, reads.iter().filter(|r| r.source() == "Aid Station 2").count());
+    assert_eq!(5, reads.iter().filter(|r| r.source().is_empty()).count());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_get_sightings_after() {
+    let unique_path = "./test_get_sightings_after.sqlite";
+    let mut sqlite = setup_tests(unique_path);
+    let sightings = make_sightings(&mut sqlite);
+    _ = sqlite.save_sightings(&sightings);
+    let result = sqlite.get_sightings_after(0, 1000);
+    assert!(result.is_ok());
+    let sights = result.unwrap();
+    assert_eq!(sightings.len(), sights.len());
+    for pair in sights.windows(2) {
+        assert!(pair[0].0 < pair[1].0);
+    }
+    let result = sqlite.get_sightings_after(sights[4].0, 5);
+    assert!(result.is_ok());
+    let after = result.unwrap();
+    assert_eq!(5, after.len());
+    assert!(after[0].1.equals(&sights[5].1));
+    // sightings worked out again keep the id they had
+    let cursor = sights.last().unwrap().0;
+    _ = sqlite.reset_reads_status();
+    _ = sqlite.save_sightings(&sightings);
+    assert!(sqlite.get_sightings_after(cursor, 1000).unwrap().is_empty());
+    let again = sqlite.get_sightings_after(0, 1000).unwrap();
+    assert_eq!(sights.iter().map(|s| s.0).collect::<Vec<i64>>(), again.iter().map(|s| s.0).collect::<Vec<i64>>());
+    let older = sights[0].1.clone();
+    _ = sqlite.reset_reads_status_for(&[String::from(older.read.chip())]);
+    _ = sqlite.save_sightings(&vec![older.clone()]);
+    assert!(sqlite.get_sightings_after(cursor, 1000).unwrap().is_empty());
+    // a sighting made later for an older read comes after the cursor, not before it
+    _ = sqlite.reset_reads_status_for(&[String::from(older.read.chip())]);
+    let moved = sighting::Sighting {
+        participant: sights.iter().find(|s| s.1.participant.id() != older.participant.id()).unwrap().1.participant.clone(),
+        read: older.read.clone(),
+    };
+    _ = sqlite.save_sightings(&vec![moved.clone()]);
+    let after = sqlite.get_sightings_after(cursor, 1000).unwrap();
+    assert_eq!(1, after.len());
+    assert!(after[0].0 > cursor);
+    assert_eq!(older.read.id(), after[0].1.read.id());
+    assert!(after[0].1.participant.equals(&moved.participant));
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_webhook_cursor() {
+    let unique_path = "./test_webhook_cursor.sqlite";
+    let sqlite = setup_tests(unique_path);
+    let api_id = sqlite.save_api(&api::Api::new(
+        0,
+        String::from("webhook"),
+        String::from(api::API_TYPE_WEBHOOK),
+        String::from("secret"),
+        String::from("https://example.com/hook"))
+    ).unwrap();
+    match sqlite.get_webhook_cursor(api_id, "reads") {
+        Err(DBError::NotFound) => {},
+        _ => panic!("expected cursor to not be found"),
+    }
+    assert!(sqlite.set_webhook_cursor(api_id, "reads", 10).is_ok());
+    assert!(sqlite.set_webhook_cursor(api_id, "events", 3).is_ok());
+    assert_eq!(10, sqlite.get_webhook_cursor(api_id, "reads").unwrap());
+    assert_eq!(3, sqlite.get_webhook_cursor(api_id, "events").unwrap());
+    assert!(sqlite.set_webhook_cursor(api_id, "reads", 25).is_ok());
+    assert_eq!(25, sqlite.get_webhook_cursor(api_id, "reads").unwrap());
+    // deleting the api gets rid of its cursors
+    assert!(sqlite.delete_api(&api_id).is_ok());
+    assert!(sqlite.get_webhook_cursor(api_id, "reads").is_err());
+    assert!(sqlite.get_webhook_cursor(api_id, "events").is_err());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_upload_ledger() {
+    let unique_path = "./test_upload_ledger.sqlite";
+    let new_reads = make_reads();
+    let mut sqlite = setup_tests(unique_path);
+    _ = sqlite.save_reads(&new_reads);
+    let total = new_reads.len() - 1;
+    let cloud = sqlite.save_api(&api::Api::new(
+        0,
+        String::from("cloud"),
+        String::from(api::API_TYPE_CHRONOKEEP_REMOTE),
+        String::from("token"),
+        String::from(api::API_URI_CHRONOKEEP_REMOTE))
+    ).unwrap();
+    let local = sqlite.save_api(&api::Api::new(
+        0,
+        String::from("local"),
+        String::from(api::API_TYPE_CHRONOKEEP_REMOTE_SELF),
+        String::from("token"),
+        String::from("http://10.0.0.5:4480/"))
+    ).unwrap();
+    let to_upload = sqlite.get_reads_to_upload(cloud, 1000).unwrap();
+    assert_eq!(total, to_upload.len());
+    assert_eq!((total, 0, 0), sqlite.get_upload_counts(cloud).unwrap());
+    assert_eq!(10, sqlite.save_uploaded(cloud, &to_upload[0..10]).unwrap());
+    // uploading twice doesn't count twice
+    assert_eq!(0, sqlite.save_uploaded(cloud, &to_upload[5..10]).unwrap());
+    assert_eq!((total - 10, 10, 0), sqlite.get_upload_counts(cloud).unwrap());
+    let remaining = sqlite.get_reads_to_upload(cloud, 1000).unwrap();
+    assert_eq!(total - 10, remaining.len());
+    assert!(remaining.iter().all(|r| !to_upload[0..10].iter().any(|u| u.id() == r.id())));
+    // each destination is tracked on its own
+    assert_eq!(total, sqlite.get_reads_to_upload(local, 1000).unwrap().len());
+    assert_eq!((total, 0, 0), sqlite.get_upload_counts(local).unwrap());
+    // removing an api gets rid of its ledger
+    assert!(sqlite.delete_api(&cloud).is_ok());
+    assert_eq!((total, 0, 0), sqlite.get_upload_counts(cloud).unwrap());
+    assert!(sqlite.save_uploaded(local, &to_upload).is_ok());
+    assert!(sqlite.reset_reads_upload().is_ok());
+    assert_eq!((total, 0, 0), sqlite.get_upload_counts(local).unwrap());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_upload_batches() {
+    let unique_path = "./test_upload_batches.sqlite";
+    let new_reads = make_reads();
+    let mut sqlite = setup_tests(unique_path);
+    _ = sqlite.save_reads(&new_reads);
+    let total = new_reads.len() - 1;
+    let cloud = sqlite.save_api(&api::Api::new(
+        0,
+        String::from("cloud"),
+        String::from(api::API_TYPE_CHRONOKEEP_REMOTE),
+        String::from("token"),
+        String::from(api::API_URI_CHRONOKEEP_REMOTE))
+    ).unwrap();
+    let reads = sqlite.get_reads_to_upload(cloud, 4).unwrap();
+    assert_eq!(4, reads.len());
+    let batch = upload_batch::UploadBatch::new(String::from("batch"), cloud, 0, reads.clone());
+    assert_eq!(4, sqlite.save_upload_batch(&batch).unwrap());
+    // reads in a batch aren't picked up again
+    let next = sqlite.get_reads_to_upload(cloud, 1000).unwrap();
+    assert_eq!(total - 4, next.len());
+    assert!(next.iter().all(|r| !reads.iter().any(|b| b.id() == r.id())));
+    assert!(sqlite.set_upload_batch_attempts("batch", 3).is_ok());
+    let batches = sqlite.get_upload_batches(cloud).unwrap();
+    assert_eq!(1, batches.len());
+    assert_eq!("batch", batches[0].id());
+    assert_eq!(3, batches[0].attempts());
+    assert_eq!(batch.read_ids(), batches[0].read_ids());
+    // the reads the server took are recorded and the rest stay in the batch under the same id
+    let accepted: Vec<u64> = reads[..2].iter().map(|r| r.id()).collect();
+    assert_eq!(2, sqlite.accept_upload_batch_reads(&batch, &accepted).unwrap());
+    let batches = sqlite.get_upload_batches(cloud).unwrap();
+    assert_eq!(1, batches.len());
+    assert_eq!("batch", batches[0].id());
+    assert_eq!(3, batches[0].attempts());
+    assert_eq!(reads[2..].iter().map(|r| r.id()).collect::<Vec<u64>>(), batches[0].read_ids());
+    assert_eq!((total - 2, 2, 0), sqlite.get_upload_counts(cloud).unwrap());
+    // what's left can be set aside
+    assert!(sqlite.reject_upload_batch("batch").is_ok());
+    assert!(sqlite.get_upload_batches(cloud).unwrap().is_empty());
+    assert_eq!((total - 4, 2, 2), sqlite.get_upload_counts(cloud).unwrap());
+    assert_eq!(total - 4, sqlite.get_reads_to_upload(cloud, 1000).unwrap().len());
+    // completing a batch records it in the ledger
+    let next = upload_batch::UploadBatch::new(String::from("next"), cloud, 0, sqlite.get_reads_to_upload(cloud, 2).unwrap());
+    assert_eq!(2, sqlite.save_upload_batch(&next).unwrap());
+    assert_eq!(2, sqlite.complete_upload_batch(&next).unwrap());
+    assert!(sqlite.get_upload_batches(cloud).unwrap().is_empty());
+    assert_eq!((total - 6, 4, 2), sqlite.get_upload_counts(cloud).unwrap());
+    // removing the api gets rid of its batches
+    assert!(sqlite.delete_api(&cloud).is_ok());
+    assert!(sqlite.get_upload_batches(cloud).unwrap().is_empty());
+    assert_eq!((total, 0, 0), sqlite.get_upload_counts(cloud).unwrap());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_reset_reads_status_for() {
+    let unique_path = "./test_reset_reads_status_for.sqlite";
+    let new_reads = make_reads();
+    let mut sqlite = setup_tests(unique_path);
+    _ = sqlite.save_reads(&new_reads);
+    let before = sqlite.get_reads_after(0, 1000).unwrap();
+    let chip_count = before.iter().filter(|r| r.chip() == "1005").count();
+    assert_ne!(0, chip_count);
+    let result = sqlite.reset_reads_status_for(&[String::from("1005"), String::from("unknown")]);
+    assert!(result.is_ok());
+    assert_eq!(chip_count, result.unwrap());
+    let reads = sqlite.get_reads_after(0, 1000).unwrap();
+    assert!(reads.iter().filter(|r| r.chip() == "1005").all(|r| r.status() == read::READ_STATUS_UNUSED));
+    // everything else is left alone
+    for (old, new) in before.iter().zip(reads.iter()).filter(|(r, _)| r.chip() != "1005") {
+        assert_eq!(old.status(), new.status());
+    }
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_delete_bibchip() {
+    let unique_path = "./test_delete_bibchip.sqlite";
+    let bibchips = make_participants().bibchips;
+    let mut sqlite = setup_tests(unique_path);
+    let _ = sqlite.add_bibchips(&bibchips);
+    let result = sqlite.delete_bibchip(bibchips.first().unwrap().chip());
+    assert!(result.is_ok());
+    assert_eq!(1, result.unwrap());
+    let bcs = sqlite.get_bibchips().unwrap();
+    assert_eq!(bibchips.len() - 1, bcs.len());
+    assert!(!bcs.iter().any(|bc| bc.chip() == bibchips.first().unwrap().chip()));
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_get_system_events() {
+    let unique_path = "./test_get_system_events.sqlite";
+    let sqlite = setup_tests(unique_path);
+    for i in 0..10 {
+        _ = sqlite.save_system_event(&system_event::SystemEvent::new(
+            0,
+            String::from(if i % 2 == 0 { system_event::SYSTEM_EVENT_PARTICIPANT_SYNC } else { system_event::SYSTEM_EVENT_NOTIFICATION }),
+            1000 + i,
+            serde_json::json!({ "count": i }),
+        ));
+    }
+    let result = sqlite.get_system_events(system_event::SYSTEM_EVENT_PARTICIPANT_SYNC, 3);
+    assert!(result.is_ok());
+    let events = result.unwrap();
+    assert_eq!(vec![1008, 1006, 1004], events.iter().map(|e| e.time()).collect::<Vec<i64>>());
+    assert!(events.iter().all(|e| e.kind() == system_event::SYSTEM_EVENT_PARTICIPANT_SYNC));
+    assert_eq!(5, sqlite.get_system_events(system_event::SYSTEM_EVENT_NOTIFICATION, 100).unwrap().len());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_event_log() {
+    let unique_path = "./test_event_log.sqlite";
+    let sqlite = setup_tests(unique_path);
+    let severities = [system_event::SEVERITY_DEBUG, system_event::SEVERITY_INFO, system_event::SEVERITY_WARNING, system_event::SEVERITY_ERROR];
+    for i in 0..12 {
+        _ = sqlite.save_system_event(&system_event::SystemEvent::new(
+            0,
+            String::from(if i % 2 == 0 { system_event::SYSTEM_EVENT_READER_DISCONNECTED } else { system_event::SYSTEM_EVENT_UPLOAD_FAILED }),
+            1000 + i,
+            serde_json::json!({ "count": i }),
+        ).with_severity(severities[i as usize % 4]).with_source(if i % 2 == 0 { system_event::SOURCE_READER } else { system_event::SOURCE_UPLOADER }));
+    }
+    // events from before severity and source were saved
+    _ = sqlite.save_system_event(&system_event::SystemEvent::new(
+        0,
+        String::from(system_event::SYSTEM_EVENT_NOTIFICATION),
+        1012,
+        serde_json::json!({}),
+    ));
+    let all = sqlite.get_event_log(&system_event::EventLogQuery::default()).unwrap();
+    assert_eq!(13, all.len());
+    assert_eq!(1012, all[0].time());
+    assert_eq!(system_event::SEVERITY_INFO, all[0].severity());
+    assert_eq!(system_event::SEVERITY_ERROR, all[1].severity());
+    assert_eq!(system_event::SOURCE_UPLOADER, all[1].source());
+    let query = system_event::EventLogQuery {
+        severity: Some(String::from(system_event::SEVERITY_WARNING)),
+        ..Default::default()
+    };
+    let events = sqlite.get_event_log(&query).unwrap();
+    assert_eq!(vec![1011, 1010, 1007, 1006, 1003, 1002], events.iter().map(|e| e.time()).collect::<Vec<i64>>());
+    let query = system_event::EventLogQuery {
+        severity: Some(String::from(system_event::SEVERITY_WARNING)),
+        source: Some(String::from(system_event::SOURCE_READER)),
+        ..Default::default()
+    };
+    let events = sqlite.get_event_log(&query).unwrap();
+    assert_eq!(vec![1010, 1006, 1002], events.iter().map(|e| e.time()).collect::<Vec<i64>>());
+    assert!(events.iter().all(|e| e.kind() == system_event::SYSTEM_EVENT_READER_DISCONNECTED));
+    let query = system_event::EventLogQuery {
+        kind: Some(String::from(system_event::SYSTEM_EVENT_UPLOAD_FAILED)),
+        since: Some(1005),
+        ..Default::default()
+    };
+    let events = sqlite.get_event_log(&query).unwrap();
+    assert_eq!(vec![1011, 1009, 1007, 1005], events.iter().map(|e| e.time()).collect::<Vec<i64>>());
+    // paging back through older entries
+    let query = system_event::EventLogQuery {
+        before: Some(all[4].id()),
+        limit: Some(3),
+        ..Default::default()
+    };
+    let events = sqlite.get_event_log(&query).unwrap();
+    assert_eq!(vec![all[5].id(), all[6].id(), all[7].id()], events.iter().map(|e| e.id()).collect::<Vec<i64>>());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_sighting_uploads() {
+    let unique_path = "./test_sighting_uploads.sqlite";
+    let mut sqlite = setup_tests(unique_path);
+    let sightings = make_sightings(&mut sqlite);
+    _ = sqlite.save_sightings(&sightings);
+    let all: Vec<sighting::Sighting> = sqlite.get_sightings_after(0, 1000).unwrap().into_iter().map(|(_, s)| s).collect();
+    let result = sqlite.get_sightings_to_upload(1, "event", "2024", 5);
+    assert!(result.is_ok());
+    let first = result.unwrap();
+    assert_eq!(5, first.len());
+    assert!(first[0].equals(&all[0]));
+    assert_eq!(5, sqlite.save_uploaded_sightings(1, "event", "2024", &first).unwrap());
+    // already uploaded sightings aren't saved again
+    assert_eq!(0, sqlite.save_uploaded_sightings(1, "event", "2024", &first).unwrap());
+    let next = sqlite.get_sightings_to_upload(1, "event", "2024", 1000).unwrap();
+    assert_eq!(all.len() - 5, next.len());
+    assert!(next[0].equals(&all[5]));
+    assert_eq!((all.len() - 5, 5), sqlite.get_sighting_upload_counts(1, "event", "2024").unwrap());
+    // each api and event keeps track of its own uploads
+    assert_eq!(all.len(), sqlite.get_sightings_to_upload(2, "event", "2024", 1000).unwrap().len());
+    assert_eq!(all.len(), sqlite.get_sightings_to_upload(1, "event", "2025", 1000).unwrap().len());
+    assert_eq!(all.len(), sqlite.get_sightings_to_upload(1, "other", "2024", 1000).unwrap().len());
+    // sightings worked out again on boot aren't sent again
+    _ = sqlite.reset_reads_status();
+    _ = sqlite.save_sightings(&sightings);
+    assert_eq!((all.len() - 5, 5), sqlite.get_sighting_upload_counts(1, "event", "2024").unwrap());
+    assert_eq!(all.len() - 5, sqlite.get_sightings_to_upload(1, "event", "2024", 1000).unwrap().len());
+    // deleting the sightings starts the uploads over
+    _ = sqlite.delete_sightings();
+    _ = sqlite.save_sightings(&sightings);
+    assert_eq!((all.len(), 0), sqlite.get_sighting_upload_counts(1, "event", "2024").unwrap());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_notification_channels() {
+    let unique_path = "./test_notification_channels.sqlite";
+    let sqlite = setup_tests(unique_path);
+    let ntfy = notification_channel::NotificationChannel::new(
+        0,
+        String::from("Crew"),
+        true,
+        notification_channel::ChannelConfig::Ntfy(notification_channel::NtfyConfig {
+            url: String::from("https://ntfy.sh/"),
+            topic: String::from("crew"),
+            user: String::new(),
+            pass: String::new(),
+        }),
+    );
+    let gotify = notification_channel::NotificationChannel::new(
+        0,
+        String::from("Director"),
+        false,
+        notification_channel::ChannelConfig::Gotify(notification_channel::GotifyConfig {
+            url: String::from("https://gotify.example.com"),
+            token: String::from("abc"),
+        }),
+    );
+    let ntfy_id = sqlite.save_notification_channel(&ntfy).unwrap();
+    let gotify_id = sqlite.save_notification_channel(&gotify).unwrap();
+    assert!(ntfy_id > 0);
+    assert_ne!(ntfy_id, gotify_id);
+    let channels = sqlite.get_notification_channels().unwrap();
+    assert_eq!(2, channels.len());
+    assert_eq!(ntfy_id, channels[0].id());
+    assert_eq!("Crew", channels[0].name());
+    assert!(channels[0].enabled());
+    assert_eq!(ntfy.config(), channels[0].config());
+    assert_eq!(gotify.config(), channels[1].config());
+    assert!(!channels[1].enabled());
+    // update an existing channel
+    let updated = notification_channel::NotificationChannel::new(
+        gotify_id,
+        String::from("Race Director"),
+        true,
+        gotify.config().clone(),
+    );
+    assert_eq!(gotify_id, sqlite.save_notification_channel(&updated).unwrap());
+    let channels = sqlite.get_notification_channels().unwrap();
+    assert_eq!(2, channels.len());
+    assert_eq!(updated, channels[1]);
+    let missing = notification_channel::NotificationChannel::new(1000, String::from("Missing"), true, gotify.config().clone());
+    assert!(matches!(sqlite.save_notification_channel(&missing), Err(DBError::NotFound)));
+    assert_eq!(1, sqlite.delete_notification_channel(ntfy_id).unwrap());
+    let channels = sqlite.get_notification_channels().unwrap();
+    assert_eq!(1, channels.len());
+    assert_eq!(gotify_id, channels[0].id());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_notification_rules() {
+    let unique_path = "./test_notification_rules.sqlite";
+    let sqlite = setup_tests(unique_path);
+    assert!(sqlite.get_notification_rules().unwrap().is_empty());
+    let battery = notification_rule::NotificationRule::new(
+        String::from("battery_low"),
+        true,
+        5,
+        vec![1, 3],
+        300,
+        String::from("22:00"),
+        String::from("06:00"),
+    );
+    let start = notification_rule::NotificationRule::new(
+        String::from("start"),
+        false,
+        2,
+        Vec::new(),
+        0,
+        String::new(),
+        String::new(),
+    );
+    assert_eq!(1, sqlite.save_notification_rule(&battery).unwrap());
+    assert_eq!(1, sqlite.save_notification_rule(&start).unwrap());
+    let rules = sqlite.get_notification_rules().unwrap();
+    assert_eq!(2, rules.len());
+    assert!(rules.contains(&battery));
+    assert!(rules.contains(&start));
+    // saving the same kind again replaces it
+    let updated = notification_rule::NotificationRule::new(
+        String::from("start"),
+        true,
+        3,
+        vec![2],
+        60,
+        String::new(),
+        String::new(),
+    );
+    assert_eq!(1, sqlite.save_notification_rule(&updated).unwrap());
+    let rules = sqlite.get_notification_rules().unwrap();
+    assert_eq!(2, rules.len());
+    assert!(rules.contains(&updated));
+    assert!(!rules.contains(&start));
+    assert_eq!(1, sqlite.delete_notification_rule("battery_low").unwrap());
+    assert_eq!(0, sqlite.delete_notification_rule("battery_low").unwrap());
+    let rules = sqlite.get_notification_rules().unwrap();
+    assert_eq!(vec![updated], rules);
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_watched_bibs() {
+    let unique_path = "./test_watched_bibs.sqlite";
+    let sqlite = setup_tests(unique_path);
+    assert!(sqlite.get_watched_bibs().unwrap().is_empty());
+    let medical = watched_bib::WatchedBib::new(String::from("104"), String::from("diabetic, carries glucose"));
+    let leader = watched_bib::WatchedBib::new(String::from("1"), String::new());
+    assert_eq!(1, sqlite.save_watched_bib(&medical).unwrap());
+    assert_eq!(1, sqlite.save_watched_bib(&leader).unwrap());
+    let bibs = sqlite.get_watched_bibs().unwrap();
+    assert_eq!(vec![leader.clone(), medical.clone()], bibs);
+    // saving the same bib again replaces the note
+    let updated = watched_bib::WatchedBib::new(String::from("104"), String::from("check in at aid 2"));
+    assert_eq!(1, sqlite.save_watched_bib(&updated).unwrap());
+    let bibs = sqlite.get_watched_bibs().unwrap();
+    assert_eq!(vec![leader.clone(), updated], bibs);
+    assert_eq!(1, sqlite.delete_watched_bib("104").unwrap());
+    assert_eq!(0, sqlite.delete_watched_bib("104").unwrap());
+    assert_eq!(vec![leader], sqlite.get_watched_bibs().unwrap());
+    // alerts are only new the first time for a bib and read
+    assert!(sqlite.save_watched_bib_alert("1", 10).unwrap());
+    assert!(!sqlite.save_watched_bib_alert("1", 10).unwrap());
+    assert!(sqlite.save_watched_bib_alert("1", 11).unwrap());
+    assert!(sqlite.save_watched_bib_alert("104", 10).unwrap());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_sms() {
+    let unique_path = "./test_sms.sqlite";
+    let mut sqlite = setup_tests(unique_path);
+    let participant = participant::Participant::new(
+        0,
+        String::from("104"),
+        String::from("Jane"),
+        String::from("Smith"),
+        String::from("1/1/1990"),
+        String::from("F"),
+        String::from("30-39"),
+        String::from("Marathon"),
+        false,
+    ).with_sms(true, String::from("+15555550100"));
+    assert_eq!(1, sqlite.add_participants(&vec![participant.clone()]).unwrap());
+    let participants = sqlite.get_participants().unwrap();
+    assert_eq!(1, participants.len());
+    assert!(participants[0].equals(&participant));
+    assert!(participants[0].sms_enabled());
+    assert_eq!("+15555550100", participants[0].mobile());
+    // supporters
+    assert!(sqlite.get_sms_supporters().unwrap().is_empty());
+    let first = sms_supporter::SmsSupporter::new(String::from("104"), String::from("+15555550101"));
+    let second = sms_supporter::SmsSupporter::new(String::from("104"), String::from("+15555550102"));
+    assert_eq!(1, sqlite.save_sms_supporter(&first).unwrap());
+    assert_eq!(1, sqlite.save_sms_supporter(&second).unwrap());
+    assert_eq!(1, sqlite.save_sms_supporter(&first).unwrap());
+    assert_eq!(vec![first.clone(), second.clone()], sqlite.get_sms_supporters().unwrap());
+    assert_eq!(1, sqlite.delete_sms_supporter(&first).unwrap());
+    assert_eq!(0, sqlite.delete_sms_supporter(&first).unwrap());
+    assert_eq!(vec![second], sqlite.get_sms_supporters().unwrap());
+    // deliveries
+    for (bib, status) in [("104", sms_delivery::SMS_STATUS_SENT), ("7", sms_delivery::SMS_STATUS_SENT), ("104", sms_delivery::SMS_STATUS_FAILED)] {
+        let id = sqlite.save_sms_delivery(&sms_delivery::SmsDelivery::new(
+            0,
+            String::from(bib),
+            String::from("+15555550100"),
+            String::from("Jane Smith (bib 104) was seen."),
+            1000,
+            String::from(status),
+            String::new(),
+        )).unwrap();
+        assert!(id > 0);
+    }
+    let deliveries = sqlite.get_sms_deliveries(None, None).unwrap();
+    assert_eq!(3, deliveries.len());
+    assert!(deliveries[0].id() > deliveries[1].id());
+    assert_eq!(sms_delivery::SMS_STATUS_FAILED, deliveries[0].status());
+    let deliveries = sqlite.get_sms_deliveries(Some("104"), None).unwrap();
+    assert_eq!(2, deliveries.len());
+    assert!(deliveries.iter().all(|d| d.bib() == "104"));
+    assert_eq!(1, sqlite.get_sms_deliveries(None, Some(1)).unwrap().len());
+    // deliveries remember the read they were about
+    assert!(!sqlite.sms_delivered("104", "+15555550100", 1700000000, 250).unwrap());
+    sqlite.save_sms_delivery(&sms_delivery::SmsDelivery::new(
+        0,
+        String::from("104"),
+        String::from("+15555550100"),
+        String::from("Jane Smith (bib 104) was seen."),
+        1000,
+        String::from(sms_delivery::SMS_STATUS_SENT),
+        String::new(),
+    ).with_read(1700000000, 250)).unwrap();
+    assert!(sqlite.sms_delivered("104", "+15555550100", 1700000000, 250).unwrap());
+    assert!(!sqlite.sms_delivered("104", "+15555550101", 1700000000, 250).unwrap());
+    assert!(!sqlite.sms_delivered("104", "+15555550100", 1700000000, 251).unwrap());
+    let latest = &sqlite.get_sms_deliveries(None, Some(1)).unwrap()[0];
+    assert_eq!(1700000000, latest.read_seconds());
+    assert_eq!(250, latest.read_milliseconds());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
+
+#[test]
+fn test_participant_registration() {
+    let unique_path = "./test_participant_registration.sqlite";
+    let mut sqlite = setup_tests(unique_path);
+    let participant = participant::Participant::new(
+        0,
+        String::from("104"),
+        String::from("Jane"),
+        String::from("Smith"),
+        String::from("1/1/1990"),
+        String::from("F"),
+        String::from("30-39"),
+        String::from("Marathon"),
+        false,
+    ).with_sms(true, String::from("+15555550100"))
+    .with_registration(String::from("8812"), String::from("Women's M"));
+    assert_eq!(1, sqlite.add_participants(&vec![participant.clone()]).unwrap());
+    let participants = sqlite.get_participants().unwrap();
+    assert_eq!(1, participants.len());
+    assert!(participants[0].equals(&participant));
+    assert_eq!("8812", participants[0].external_id());
+    assert_eq!("Women's M", participants[0].apparel());
+    // sightings carry the whole record
+    assert_eq!(1, sqlite.save_reads(&vec![read::Read::new(
+        0,
+        String::from("1000"),
+        100,
+        0,
+        100,
+        0,
+        1,
+        String::from("reader-1"),
+        String::from("-50"),
+        read::READ_STATUS_USED,
+        read::READ_UPLOADED_FALSE,
+    )]).unwrap());
+    let read = sqlite.get_reads(0, 200).unwrap().remove(0);
+    assert_eq!(1, sqlite.save_sightings(&vec![sighting::Sighting {
+        participant: participants[0].clone(),
+        read,
+    }]).unwrap());
+    let sightings = sqlite.get_all_sightings().unwrap();
+    assert_eq!(1, sightings.len());
+    assert!(sightings[0].participant.equals(&participant));
+    assert_eq!("8812", sightings[0].participant.external_id());
+    drop(sqlite);
+    finalize_tests(unique_path);
+}
@@ -1,4 +1,4 @@
-use crate::{sound_board::Voice, types};
+use crate::{output, sound_board::Voice, types};
 
 pub const DEFAULT_SIGHTING_PERIOD: u32 = 5 * 60;
 pub const DEFAULT_CHIP_TYPE: &str = types::TYPE_CHIP_DEC;
@@ -8,4 +8,34 @@ pub const DEFAULT_VOLUME: f32 = 1.0;
 pub const DEFAULT_VOICE: Voice = Voice::Emily;
 pub const DEFAULT_AUTO_REMOTE: bool = false;
 pub const DEFAULT_UPLOAD_INTERVAL: u64 = 10;
-pub const DEFAULT_ENABLE_NTFY: bool = false;
\ No newline at end of file
+pub const DEFAULT_ENABLE_NTFY: bool = false;
+pub const DEFAULT_MQTT_TOPIC: &str = "chronokeep/portal";
+pub const DEFAULT_MQTT_QOS: u8 = 1;
+pub const DEFAULT_ENABLE_MQTT: bool = false;
+pub const DEFAULT_ENABLE_OUTPUT: bool = false;
+// The port Ultra style readers stream reads on.
+pub const DEFAULT_OUTPUT_PORT: u16 = 23;
+pub const DEFAULT_OUTPUT_FORMAT: &str = output::OUTPUT_FORMAT_ULTRA;
+pub const DEFAULT_ENABLE_RELAY: bool = false;
+pub const DEFAULT_RELAY_PORT: u16 = 4480;
+pub const DEFAULT_UPLOAD_BATCH_SIZE: usize = 50;
+pub const DEFAULT_CONNECTIVITY_CHECK_URL: &str = "";
+pub const DEFAULT_ENABLE_PARTICIPANT_SYNC: bool = false;
+pub const DEFAULT_PARTICIPANT_SYNC_API: i64 = 0;
+pub const DEFAULT_PARTICIPANT_SYNC_SLUG: &str = "";
+pub const DEFAULT_PARTICIPANT_SYNC_YEAR: &str = "";
+pub const DEFAULT_PARTICIPANT_SYNC_INTERVAL: u64 = 300;
+pub const DEFAULT_HTTP_PROXY_URL: &str = "";
+pub const DEFAULT_HTTP_PROXY_USER: &str = "";
+pub const DEFAULT_HTTP_PROXY_PASS: &str = "";
+pub const DEFAULT_HTTP_CA_BUNDLE: &str = "";
+pub const DEFAULT_HTTP_TIMEOUT: u64 = 30;
+pub const DEFAULT_HTTP_CONNECT_TIMEOUT: u64 = 10;
+pub const DEFAULT_ENABLE_SIGHTING_UPLOAD: bool = false;
+pub const DEFAULT_SILENT_ANTENNA_SECONDS: u64 = 300;
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+pub const DEFAULT_ENABLE_SMS: bool = false;
+pub const DEFAULT_SMS_TEMPLATE: &str = "{first} {last} (bib {bib}) was seen at {portal} at {time}.";
+pub const DEFAULT_SMS_INTERVAL: u64 = 600;
+pub const DEFAULT_SMS_HOURLY_LIMIT: u32 = 100;
+pub const DEFAULT_UNKNOWN_CHIP_POLICY: &str = "placeholder";
\ No newline at end of file
@@ -0,0 +1,350 @@
+use std::{net::Shutdown, sync::{mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError}, Arc, Mutex}, thread::{self, JoinHandle}, time::Duration};
+use log::{error, warn};
+
+use crate::{control::socket::{self, notifications::APINotification, responses, ClientStream, CONNECTION_VERS_MIN, MAX_CONNECTED}, network::{api, connectivity}, objects::{bibchip, participant, read, setting, sighting}, reader::{monitor, MAX_ANTENNAS}, remote::uploader};
+
+use self::filter::{ParticipantLookup, SubscriptionFilter};
+
+pub mod filter;
+
+#[cfg(test)]
+pub mod test;
+
+// Number of events a client can fall behind before we consider it stalled and drop it.
+pub const CLIENT_QUEUE_SIZE: usize = 256;
+// How often writer threads wake up to check if they should still be running.
+pub const WRITER_WAKE_SECONDS: u64 = 1;
+
+#[derive(Clone, Debug)]
+pub enum Event {
+    Reads(Vec<read::Read>),
+    Sightings {
+        sightings: Vec<sighting::Sighting>,
+        bibchips: Vec<bibchip::BibChip>,
+    },
+    ReaderAntennas {
+        reader_name: String,
+        antennas: [u8;MAX_ANTENNAS],
+    },
+    ReaderList(Vec<responses::Reader>),
+    // Changes one client made that everyone else should know about.
+    Settings(Vec<setting::Setting>),
+    ApiList(Vec<api::Api>),
+    Participants(Vec<participant::Participant>),
+    UploaderStatus {
+        status: uploader::Status,
+        destinations: Vec<uploader::Destination>,
+    },
+    Notification {
+        notification: APINotification,
+        time: String,
+    },
+    Connectivity(connectivity::Status),
+    AntennaAlert {
+        alert: monitor::AntennaAlert,
+        time: String,
+    },
+    WatchedBib {
+        sighting: Box<sighting::Sighting>,
+        note: String,
+        time: String,
+    },
+}
+
+impl Event {
+    // The protocol version a client needs to have negotiated to be sent this event.
+    pub fn min_version(&self) -> usize {
+        match self {
+            Event::Reads(_) |
+            Event::Sightings { .. } |
+            Event::ReaderAntennas { .. } |
+            Event::ReaderList(_) |
+            Event::Settings(_) |
+            Event::ApiList(_) |
+            Event::Participants(_) |
+            Event::UploaderStatus { .. } |
+            Event::Notification { .. } => 1,
+            Event::Connectivity(_) => 4,
+            Event::AntennaAlert { .. } => 5,
+            Event::WatchedBib { .. } => 6,
+        }
+    }
+}
+
+struct Subscriber {
+    index: usize,
+    stream: ClientStream,
+    sender: SyncSender<Arc<Event>>,
+}
+
+// The event bus lets the reader threads, the sightings processor, the uploader and the battery
+// checker publish events without touching the client sockets.  Each connected client gets its
+// own writer thread and queue, so a stalled client can't block anyone publishing.
+#[derive(Clone)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    listeners: Arc<Mutex<Vec<Sender<Arc<Event>>>>>,
+    bounded_listeners: Arc<Mutex<Vec<SyncSender<Arc<Event>>>>>,
+    read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+    sighting_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+    filters: Arc<Mutex<[Option<SubscriptionFilter>;MAX_CONNECTED]>>,
+    versions: Arc<Mutex<[usize;MAX_CONNECTED + 1]>>,
+    lookup: Arc<Mutex<ParticipantLookup>>,
+    keepalive: Arc<Mutex<bool>>,
+}
+
+impl EventBus {
+    pub fn new(
+        read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+        sighting_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+        keepalive: Arc<Mutex<bool>>,
+    ) -> Self {
+        Self {
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            listeners: Arc::new(Mutex::new(Vec::new())),
+            bounded_listeners: Arc::new(Mutex::new(Vec::new())),
+            read_repeaters,
+            sighting_repeaters,
+            filters: Arc::new(Mutex::new(Default::default())),
+            versions: Arc::new(Mutex::new([CONNECTION_VERS_MIN;MAX_CONNECTED + 1])),
+            lookup: Arc::new(Mutex::new(ParticipantLookup::default())),
+            keepalive,
+        }
+    }
+
+    pub fn set_filter(&self, index: usize, filter: Option<SubscriptionFilter>) {
+        if index >= MAX_CONNECTED {
+            return
+        }
+        if let Ok(mut filters) = self.filters.lock() {
+            filters[index] = filter.filter(|f| !f.is_empty());
+        }
+    }
+
+    pub fn set_version(&self, index: usize, version: usize) {
+        if let Ok(mut versions) = self.versions.lock() {
+            if index < versions.len() {
+                versions[index] = version;
+            }
+        }
+    }
+
+    pub fn set_participant_lookup(&self, lookup: ParticipantLookup) {
+        if let Ok(mut l) = self.lookup.lock() {
+            *l = lookup;
+        }
+    }
+
+    pub fn publish(&self, event: Event) {
+        self.send(event, None);
+    }
+
+    // Publishes a change made by the client at index to everyone else, that client is sent
+    // the response to its request instead.
+    pub fn publish_except(&self, index: usize, event: Event) {
+        self.send(event, Some(index));
+    }
+
+    fn send(&self, event: Event, except: Option<usize>) {
+        let event = Arc::new(event);
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|sub| {
+                if except == Some(sub.index) {
+                    return true
+                }
+                match sub.sender.try_send(event.clone()) {
+                    Ok(_) => true,
+                    Err(TrySendError::Full(_)) => {
+                        warn!("Client at index {} is not keeping up with events, disconnecting.", sub.index);
+                        _ = sub.stream.shutdown(Shutdown::Both);
+                        false
+                    },
+                    Err(TrySendError::Disconnected(_)) => false,
+                }
+            });
+        } else {
+            error!("Error getting event bus subscribers mutex.");
+        }
+        if let Ok(mut listeners) = self.listeners.lock() {
+            listeners.retain(|l| l.send(event.clone()).is_ok());
+        }
+        if let Ok(mut listeners) = self.bounded_listeners.lock() {
+            listeners.retain(|l| {
+                match l.try_send(event.clone()) {
+                    Ok(_) => true,
+                    Err(TrySendError::Full(_)) => {
+                        warn!("Listener is not keeping up with events, disconnecting it.");
+                        false
+                    },
+                    Err(TrySendError::Disconnected(_)) => false,
+                }
+            });
+        }
+    }
+
+    // Internal consumers, like the MQTT publisher, get every event published regardless of
+    // repeaters or filters.  Their queue isn't bounded so they never drop anything, dropping
+    // the receiver removes the listener.
+    pub fn listen(&self) -> Receiver<Arc<Event>> {
+        let (sender, receiver) = mpsc::channel();
+        if let Ok(mut listeners) = self.listeners.lock() {
+            listeners.push(sender);
+        }
+        receiver
+    }
+
+    // Like listen, but for consumers that talk to something outside the portal.  Once the
+    // queue is full the listener is dropped, so its receiver sees it's been disconnected.
+    pub fn listen_bounded(&self, size: usize) -> Receiver<Arc<Event>> {
+        let (sender, receiver) = mpsc::sync_channel(size);
+        if let Ok(mut listeners) = self.bounded_listeners.lock() {
+            listeners.push(sender);
+        }
+        receiver
+    }
+
+    pub fn subscribe(&self, index: usize, stream: &ClientStream) -> Result<JoinHandle<()>, &'static str> {
+        let (stream, t_stream) = (stream.clone(), stream.clone());
+        let (sender, receiver) = mpsc::sync_channel(CLIENT_QUEUE_SIZE);
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|sub| sub.index != index);
+            subscribers.push(Subscriber {
+                index,
+                stream,
+                sender,
+            });
+        } else {
+            return Err("unable to get subscribers mutex")
+        }
+        let bus = self.clone();
+        Ok(thread::spawn(move|| {
+            bus.write_events(index, t_stream, receiver);
+        }))
+    }
+
+    pub fn unsubscribe(&self, index: usize) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|sub| sub.index != index);
+        }
+        self.set_filter(index, None);
+        self.set_version(index, CONNECTION_VERS_MIN);
+    }
+
+    fn write_events(&self, index: usize, stream: ClientStream, receiver: Receiver<Arc<Event>>) {
+        loop {
+            if let Ok(ka) = self.keepalive.lock() {
+                if !*ka {
+                    break;
+                }
+            }
+            let event = match receiver.recv_timeout(Duration::from_secs(WRITER_WAKE_SECONDS)) {
+                Ok(ev) => ev,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            // don't send clients events their protocol version doesn't know about
+            let version = self.version(index);
+            if event.min_version() > version {
+                continue;
+            }
+            let reply = socket::Reply::event(version);
+            let no_error = match &*event {
+                Event::Reads(reads) => {
+                    if self.repeating(&self.read_repeaters, index) {
+                        match self.filter(index) {
+                            Some(filter) => {
+                                let reads: Vec<read::Read> = match self.lookup.lock() {
+                                    Ok(lookup) => reads.iter().filter(|r| filter.matches_read(r, &lookup)).cloned().collect(),
+                                    Err(_) => reads.iter().filter(|r| filter.matches_read(r, &ParticipantLookup::default())).cloned().collect(),
+                                };
+                                reads.is_empty() || socket::write_reads(&stream, &reply, &reads)
+                            },
+                            None => socket::write_reads(&stream, &reply, reads),
+                        }
+                    } else {
+                        true
+                    }
+                },
+                Event::Sightings { sightings, bibchips } => {
+                    if self.repeating(&self.sighting_repeaters, index) {
+                        match self.filter(index) {
+                            Some(filter) => {
+                                let sightings: Vec<sighting::Sighting> = sightings.iter().filter(|s| filter.matches_sighting(s)).cloned().collect();
+                                sightings.is_empty() || socket::write_sightings(&stream, &reply, &sightings, bibchips)
+                            },
+                            None => socket::write_sightings(&stream, &reply, sightings, bibchips),
+                        }
+                    } else {
+                        true
+                    }
+                },
+                Event::ReaderAntennas { reader_name, antennas } => {
+                    // the localhost slot only exists to shut the portal down
+                    if index < MAX_CONNECTED {
+                        socket::write_reader_antennas(&stream, &reply, reader_name.clone(), antennas)
+                    } else {
+                        true
+                    }
+                },
+                Event::ReaderList(list) => socket::write_readers(&stream, &reply, list),
+                Event::Settings(settings) => socket::write_settings(&stream, &reply, settings),
+                Event::ApiList(apis) => socket::write_api_list(&stream, &reply, apis),
+                Event::Participants(parts) => socket::write_participants(&stream, &reply, parts),
+                Event::UploaderStatus { status, destinations } => socket::write_uploader_status(&stream, &reply, status.clone(), destinations),
+                Event::Notification { notification, time } => socket::write_notification(&stream, &reply, notification, time),
+                Event::Connectivity(status) => socket::write_connectivity(&stream, &reply, status),
+                Event::AntennaAlert { alert, time } => socket::write_antenna_alert(&stream, &reply, alert, time),
+                Event::WatchedBib { sighting, note, time } => socket::write_watched_bib_sighting(&stream, &reply, sighting, note, time),
+            };
+            if !no_error {
+                error!("Error writing event to socket at index {index}.");
+                if index < MAX_CONNECTED {
+                    if let Ok(mut repeaters) = self.read_repeaters.lock() {
+                        repeaters[index] = false;
+                    }
+                    if let Ok(mut repeaters) = self.sighting_repeaters.lock() {
+                        repeaters[index] = false;
+                    }
+                }
+                if let Err(e) = stream.shutdown(Shutdown::Both) {
+                    error!("Error shutting down closed socket. {e}");
+                }
+                break;
+            }
+        }
+    }
+
+    pub fn version(&self, index: usize) -> usize {
+        if let Ok(versions) = self.versions.lock() {
+            return versions[index]
+        }
+        CONNECTION_VERS_MIN
+    }
+
+    fn filter(&self, index: usize) -> Option<SubscriptionFilter> {
+        if let Ok(filters) = self.filters.lock() {
+            return filters[index].clone()
+        }
+        None
+    }
+
+    fn repeating(&self, repeaters: &Arc<Mutex<[bool;MAX_CONNECTED]>>, index: usize) -> bool {
+        if index >= MAX_CONNECTED {
+            return false
+        }
+        if let Ok(repeaters) = repeaters.lock() {
+            return repeaters[index]
+        }
+        false
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(
+            Arc::new(Mutex::new([false;MAX_CONNECTED])),
+            Arc::new(Mutex::new([false;MAX_CONNECTED])),
+            Arc::new(Mutex::new(true)),
+        )
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{database::{sqlite, DBError, Database}, objects::{bibchip, participant, read, sighting}, processor};
+
+#[cfg(test)]
+pub mod test;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all="snake_case")]
+pub enum KnownFilter {
+    #[default]
+    All,
+    Known,
+    Unknown,
+}
+
+// A filter a client can attach to its reads/sightings subscription.  Empty lists mean
+// anything is allowed for that field.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all="snake_case")]
+pub struct SubscriptionFilter {
+    #[serde(default)]
+    pub readers: Vec<String>,
+    #[serde(default)]
+    pub antennas: Vec<u32>,
+    #[serde(default)]
+    pub chips: Vec<String>,
+    #[serde(default)]
+    pub bibs: Vec<String>,
+    #[serde(default)]
+    pub distances: Vec<String>,
+    #[serde(default)]
+    pub known: KnownFilter,
+}
+
+// Lookup of chips and bibs to the participant information needed to filter raw reads.
+// Only contains participants we actually know about, placeholders for unknown chips are left out.
+#[derive(Debug, Clone, Default)]
+pub struct ParticipantLookup {
+    chips: HashMap<String, (String, String)>,
+    bibs: HashMap<String, String>,
+}
+
+impl ParticipantLookup {
+    pub fn new(
+        bibchips: &[bibchip::BibChip],
+        participants: &HashMap<String, participant::Participant>,
+    ) -> Self {
+        let mut lookup = Self::default();
+        // live reads only need to know who is wearing each chip right now
+        for bc in bibchips.iter().filter(|bc| bc.is_current()) {
+            if let Some(part) = participants.get(bc.bib()) {
+                if !processor::is_placeholder(part) {
+                    lookup.chips.insert(String::from(bc.chip()), (String::from(bc.bib()), String::from(part.distance())));
+                    lookup.bibs.insert(String::from(bc.bib()), String::from(part.distance()));
+                }
+            }
+        }
+        lookup
+    }
+
+    // Builds the lookup straight from the database, used before the sightings processor has
+    // had a chance to build one.
+    pub fn load(sq: &sqlite::SQLite) -> Result<Self, DBError> {
+        let bibchips = sq.get_bibchips()?;
+        let mut participants: HashMap<String, participant::Participant> = HashMap::new();
+        for part in sq.get_participants()? {
+            participants.insert(String::from(part.bib()), part);
+        }
+        Ok(Self::new(&bibchips, &participants))
+    }
+
+    // Returns the bib and distance associated with the read, if there is one.
+    fn find(&self, read: &read::Read) -> Option<(String, String)> {
+        if read.ident_type() == read::READ_IDENT_TYPE_BIB {
+            return self.bibs.get(read.chip()).map(|dist| (String::from(read.chip()), dist.clone()))
+        }
+        self.chips.get(read.chip()).cloned()
+    }
+}
+
+impl SubscriptionFilter {
+    pub fn is_empty(&self) -> bool {
+        self.readers.is_empty()
+        && self.antennas.is_empty()
+        && self.chips.is_empty()
+        && self.bibs.is_empty()
+        && self.distances.is_empty()
+        && self.known == KnownFilter::All
+    }
+
+    fn matches_read_source(&self, read: &read::Read) -> bool {
+        (self.readers.is_empty() || self.readers.iter().any(|r| r == read.reader()))
+        && (self.antennas.is_empty() || self.antennas.contains(&read.antenna()))
+        && (self.chips.is_empty() || self.chips.iter().any(|c| c == read.chip()))
+    }
+
+    fn matches_participant(&self, bib: Option<&str>, distance: Option<&str>) -> bool {
+        let known = bib.is_some();
+        match self.known {
+            KnownFilter::Known if !known => return false,
+            KnownFilter::Unknown if known => return false,
+            _ => {}
+        }
+        (self.bibs.is_empty() || bib.is_some_and(|b| self.bibs.iter().any(|x| x == b)))
+        && (self.distances.is_empty() || distance.is_some_and(|d| self.distances.iter().any(|x| x == d)))
+    }
+
+    pub fn matches_read(&self, read: &read::Read, lookup: &ParticipantLookup) -> bool {
+        if !self.matches_read_source(read) {
+            return false
+        }
+        match lookup.find(read) {
+            Some((bib, distance)) => self.matches_participant(Some(&bib), Some(&distance)),
+            None => self.matches_participant(None, None),
+        }
+    }
+
+    pub fn matches_sighting(&self, sighting: &sighting::Sighting) -> bool {
+        if !self.matches_read_source(&sighting.read) {
+            return false
+        }
+        if processor::is_placeholder(&sighting.participant) {
+            self.matches_participant(None, None)
+        } else {
+            self.matches_participant(Some(sighting.participant.bib()), Some(sighting.participant.distance()))
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::{objects::{bibchip::BibChip, participant::Participant, read::{self, Read}, sighting::Sighting}, processor};
+
+use super::{KnownFilter, ParticipantLookup, SubscriptionFilter};
+
+fn chip_read(chip: &str, reader: &str, antenna: u32) -> Read {
+    Read::new(0, String::from(chip), 100, 0, 100, 0, antenna, String::from(reader), String::from("-50"), read::READ_STATUS_UNUSED, read::READ_UPLOADED_FALSE)
+}
+
+fn bib_read(bib: &str) -> Read {
+    serde_json::from_str(&format!(r#"{{"identifier":"{bib}","seconds":100,"milliseconds":0,"reader_seconds":100,"reader_milliseconds":0,"antenna":1,"reader":"finish","rssi":"","ident_type":"bib","type":"manual"}}"#)).unwrap()
+}
+
+fn participant(bib: &str, distance: &str) -> Participant {
+    Participant::new(0, String::from(bib), String::from("John"), String::from("Smith"), String::from("1/1/1990"), String::from("M"), String::from("30-39"), String::from(distance), false)
+}
+
+fn placeholder(bib: &str) -> Participant {
+    Participant::new(0, String::from(bib), String::from(processor::PLACEHOLDER_FIRST), String::from(processor::PLACEHOLDER_LAST), String::from(processor::PLACEHOLDER_BIRTHDATE), String::from(processor::PLACEHOLDER_GENDER), String::from(processor::PLACEHOLDER_AGE_GROUP), String::from(processor::PLACEHOLDER_DISTANCE), false)
+}
+
+fn lookup() -> ParticipantLookup {
+    let bibchips = vec![
+        BibChip::new(String::from("100"), String::from("chip100")),
+        BibChip::new(String::from("200"), String::from("chip200")),
+        // chip200old used to belong to 200 but it was swapped out
+        BibChip::new(String::from("200"), String::from("chip200old")).with_effective(0, 50),
+        BibChip::new(String::from("999"), String::from("chip999")),
+    ];
+    let mut participants: HashMap<String, Participant> = HashMap::new();
+    participants.insert(String::from("100"), participant("100", "5K"));
+    participants.insert(String::from("200"), participant("200", "10K"));
+    participants.insert(String::from("999"), placeholder("999"));
+    ParticipantLookup::new(&bibchips, &participants)
+}
+
+#[test]
+fn test_is_empty() {
+    assert!(SubscriptionFilter::default().is_empty());
+    let filter = SubscriptionFilter { known: KnownFilter::Known, ..Default::default() };
+    assert!(!filter.is_empty());
+    let filter = SubscriptionFilter { readers: vec![String::from("finish")], ..Default::default() };
+    assert!(!filter.is_empty());
+}
+
+#[test]
+fn test_matches_read_source() {
+    let lookup = lookup();
+    let filter = SubscriptionFilter {
+        readers: vec![String::from("finish")],
+        antennas: vec![1, 2],
+        ..Default::default()
+    };
+    assert!(filter.matches_read(&chip_read("chip100", "finish", 1), &lookup));
+    assert!(filter.matches_read(&chip_read("unknown", "finish", 2), &lookup));
+    assert!(!filter.matches_read(&chip_read("chip100", "start", 1), &lookup));
+    assert!(!filter.matches_read(&chip_read("chip100", "finish", 3), &lookup));
+    let filter = SubscriptionFilter { chips: vec![String::from("chip200")], ..Default::default() };
+    assert!(filter.matches_read(&chip_read("chip200", "finish", 1), &lookup));
+    assert!(!filter.matches_read(&chip_read("chip100", "finish", 1), &lookup));
+}
+
+#[test]
+fn test_matches_read_participant() {
+    let lookup = lookup();
+    let filter = SubscriptionFilter { bibs: vec![String::from("100")], ..Default::default() };
+    assert!(filter.matches_read(&chip_read("chip100", "finish", 1), &lookup));
+    assert!(!filter.matches_read(&chip_read("chip200", "finish", 1), &lookup));
+    assert!(!filter.matches_read(&chip_read("unknown", "finish", 1), &lookup));
+    // manual reads carry the bib instead of a chip
+    assert!(filter.matches_read(&bib_read("100"), &lookup));
+    assert!(!filter.matches_read(&bib_read("200"), &lookup));
+    let filter = SubscriptionFilter { distances: vec![String::from("10K")], ..Default::default() };
+    assert!(filter.matches_read(&chip_read("chip200", "finish", 1), &lookup));
+    assert!(!filter.matches_read(&chip_read("chip100", "finish", 1), &lookup));
+    // only the chip someone is wearing now counts for live reads
+    assert!(!filter.matches_read(&chip_read("chip200old", "finish", 1), &lookup));
+}
+
+#[test]
+fn test_matches_read_known() {
+    let lookup = lookup();
+    let known = SubscriptionFilter { known: KnownFilter::Known, ..Default::default() };
+    let unknown = SubscriptionFilter { known: KnownFilter::Unknown, ..Default::default() };
+    assert!(known.matches_read(&chip_read("chip100", "finish", 1), &lookup));
+    assert!(!unknown.matches_read(&chip_read("chip100", "finish", 1), &lookup));
+    assert!(!known.matches_read(&chip_read("unknown", "finish", 1), &lookup));
+    assert!(unknown.matches_read(&chip_read("unknown", "finish", 1), &lookup));
+    // placeholders are what we create for unknown chips so they aren't known
+    assert!(!known.matches_read(&chip_read("chip999", "finish", 1), &lookup));
+    assert!(unknown.matches_read(&chip_read("chip999", "finish", 1), &lookup));
+    // an empty lookup means nobody is known
+    let empty = ParticipantLookup::default();
+    assert!(!known.matches_read(&chip_read("chip100", "finish", 1), &empty));
+    assert!(unknown.matches_read(&chip_read("chip100", "finish", 1), &empty));
+}
+
+#[test]
+fn test_matches_sighting() {
+    let sighting = Sighting {
+        participant: participant("100", "5K"),
+        read: chip_read("chip100", "finish", 1),
+    };
+    let unknown_sighting = Sighting {
+        participant: placeholder("999"),
+        read: chip_read("chip999", "finish", 2),
+    };
+    assert!(SubscriptionFilter::default().matches_sighting(&sighting));
+    let filter = SubscriptionFilter { bibs: vec![String::from("100")], ..Default::default() };
+    assert!(filter.matches_sighting(&sighting));
+    assert!(!filter.matches_sighting(&unknown_sighting));
+    let filter = SubscriptionFilter { distances: vec![String::from("10K")], ..Default::default() };
+    assert!(!filter.matches_sighting(&sighting));
+    let filter = SubscriptionFilter { antennas: vec![2], ..Default::default() };
+    assert!(!filter.matches_sighting(&sighting));
+    assert!(filter.matches_sighting(&unknown_sighting));
+    let known = SubscriptionFilter { known: KnownFilter::Known, ..Default::default() };
+    let unknown = SubscriptionFilter { known: KnownFilter::Unknown, ..Default::default() };
+    assert!(known.matches_sighting(&sighting));
+    assert!(!known.matches_sighting(&unknown_sighting));
+    assert!(!unknown.matches_sighting(&sighting));
+    assert!(unknown.matches_sighting(&unknown_sighting));
+}
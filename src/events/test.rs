@@ -0,0 +1,143 @@
+use std::{io::{BufRead, BufReader, Read}, net::{TcpListener, TcpStream}, sync::mpsc::TryRecvError, time::Duration};
+
+use crate::{control::socket::{notifications::APINotification, ClientStream, CONNECTION_VERS, CONNECTION_VERS_MIN}, objects::{participant::Participant, read::{self, Read as ChipRead}, sighting::Sighting}};
+
+use super::{Event, EventBus, CLIENT_QUEUE_SIZE};
+
+// Returns the server side of the connection, which is what gets subscribed, and the client side.
+fn connection(listener: &TcpListener) -> (ClientStream, TcpStream) {
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    (ClientStream::new(server), client)
+}
+
+fn notification() -> Event {
+    Event::Notification {
+        notification: APINotification::ShuttingDown,
+        time: String::from("now"),
+    }
+}
+
+fn watched_bib() -> Event {
+    Event::WatchedBib {
+        sighting: Box::new(Sighting {
+            participant: Participant::new(0, String::from("100"), String::from("John"), String::from("Smith"), String::from("1/1/1990"), String::from("M"), String::from("30-39"), String::from("5K"), false),
+            read: ChipRead::new(0, String::from("chip100"), 100, 0, 100, 0, 1, String::from("finish"), String::from("-50"), read::READ_STATUS_USED, read::READ_UPLOADED_FALSE),
+        }),
+        note: String::from("watch"),
+        time: String::from("now"),
+    }
+}
+
+fn command(line: &str) -> String {
+    let value: serde_json::Value = serde_json::from_str(line).unwrap();
+    String::from(value["command"].as_str().unwrap())
+}
+
+#[test]
+fn test_listen_bounded() {
+    let bus = EventBus::default();
+    let receiver = bus.listen_bounded(2);
+    bus.publish(Event::Reads(Vec::new()));
+    bus.publish(Event::Reads(Vec::new()));
+    assert!(receiver.try_recv().is_ok());
+    assert!(receiver.try_recv().is_ok());
+    assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
+    // the third event overflows the queue and drops the listener
+    bus.publish(Event::Reads(Vec::new()));
+    bus.publish(Event::Reads(Vec::new()));
+    bus.publish(Event::Reads(Vec::new()));
+    assert!(receiver.try_recv().is_ok());
+    assert!(receiver.try_recv().is_ok());
+    assert!(matches!(receiver.try_recv(), Err(TryRecvError::Disconnected)));
+    assert!(bus.bounded_listeners.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_dropped_listeners_removed() {
+    let bus = EventBus::default();
+    let kept = bus.listen();
+    let dropped = bus.listen();
+    let dropped_bounded = bus.listen_bounded(CLIENT_QUEUE_SIZE);
+    assert_eq!(2, bus.listeners.lock().unwrap().len());
+    drop(dropped);
+    drop(dropped_bounded);
+    bus.publish(notification());
+    assert_eq!(1, bus.listeners.lock().unwrap().len());
+    assert!(bus.bounded_listeners.lock().unwrap().is_empty());
+    assert!(kept.try_recv().is_ok());
+}
+
+#[test]
+fn test_full_queue_disconnects_client() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let bus = EventBus::default();
+    let (slow, mut slow_client) = connection(&listener);
+    let (fast, _fast_client) = connection(&listener);
+    {
+        // writers can't take anything off their queues while they're waiting on keepalive
+        let mut keepalive = bus.keepalive.lock().unwrap();
+        bus.subscribe(0, &slow).unwrap();
+        for _ in 0..CLIENT_QUEUE_SIZE {
+            bus.publish(notification());
+        }
+        bus.subscribe(1, &fast).unwrap();
+        bus.publish(notification());
+        let subscribers: Vec<usize> = bus.subscribers.lock().unwrap().iter().map(|s| s.index).collect();
+        assert_eq!(vec![1], subscribers);
+        // stop the writers before letting them go so they don't write what's queued
+        *keepalive = false;
+    }
+    let mut buf = [0u8; 16];
+    assert_eq!(0, slow_client.read(&mut buf).unwrap());
+}
+
+#[test]
+fn test_min_version() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let bus = EventBus::default();
+    let (server, client) = connection(&listener);
+    bus.subscribe(0, &server).unwrap();
+    let mut lines = BufReader::new(client).lines();
+    // version 1 clients don't know about watched bibs
+    bus.publish(watched_bib());
+    bus.publish(notification());
+    assert_eq!("notification", command(&lines.next().unwrap().unwrap()));
+    bus.set_version(0, watched_bib().min_version() - 1);
+    bus.publish(watched_bib());
+    bus.publish(notification());
+    assert_eq!("notification", command(&lines.next().unwrap().unwrap()));
+    bus.set_version(0, watched_bib().min_version());
+    bus.publish(watched_bib());
+    bus.publish(notification());
+    assert_eq!("watched_bib_sighting", command(&lines.next().unwrap().unwrap()));
+    assert_eq!("notification", command(&lines.next().unwrap().unwrap()));
+    // unsubscribing goes back to the oldest version
+    bus.unsubscribe(0);
+    assert_eq!(CONNECTION_VERS_MIN, bus.version(0));
+    *bus.keepalive.lock().unwrap() = false;
+}
+
+#[test]
+fn test_publish_except() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let bus = EventBus::default();
+    let (asker, asker_client) = connection(&listener);
+    let (other, other_client) = connection(&listener);
+    bus.subscribe(0, &asker).unwrap();
+    bus.subscribe(1, &other).unwrap();
+    bus.set_version(1, CONNECTION_VERS);
+    let mut asker_lines = BufReader::new(asker_client).lines();
+    let mut other_lines = BufReader::new(other_client).lines();
+    // the client that made the change gets the response to its request instead
+    bus.publish_except(0, Event::ReaderList(Vec::new()));
+    bus.publish(notification());
+    assert_eq!("notification", command(&asker_lines.next().unwrap().unwrap()));
+    let line = other_lines.next().unwrap().unwrap();
+    assert_eq!("readers", command(&line));
+    let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(Some(true), value["event"].as_bool());
+    assert_eq!("notification", command(&other_lines.next().unwrap().unwrap()));
+    *bus.keepalive.lock().unwrap() = false;
+}
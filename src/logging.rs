@@ -0,0 +1,245 @@
+use std::{env, fs::{self, File, OpenOptions}, io::Write, path::{Path, PathBuf}, sync::{Mutex, OnceLock, RwLock}};
+
+use chrono::Local;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::reader::zebra::WRITEABLE_FILE_PATH;
+
+#[cfg(test)]
+pub mod test;
+
+pub const LOG_FILE_NAME: &str = "chronokeep-portal.log";
+// Size a log file can grow to before it's rotated.
+pub const MAX_LOG_FILE_SIZE: u64 = 5 * 1024 * 1024;
+// Rotated files kept around, the .1 file is the newest.
+pub const MAX_LOG_FILES: usize = 5;
+pub const DEFAULT_TAIL_LINES: usize = 200;
+pub const MAX_TAIL_LINES: usize = 5000;
+
+// Targets are module paths, but nobody wants to type the crate name for every one.
+const CRATE_PREFIX: &str = concat!(env!("CARGO_CRATE_NAME"), "::");
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+// Log levels by module, written like "info,reader=debug,control::socket=warn".
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filters {
+    default: LevelFilter,
+    // module and level, a module covers everything under it
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Filters {
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        let target = target.strip_prefix(CRATE_PREFIX).unwrap_or(target);
+        self.modules.iter()
+            .filter(|(module, _)| target == module || target.starts_with(&format!("{module}::")))
+            .max_by_key(|(module, _)| module.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    pub fn max_level(&self) -> LevelFilter {
+        self.modules.iter().map(|(_, level)| *level).fold(self.default, |a, b| a.max(b))
+    }
+}
+
+pub fn parse_spec(spec: &str) -> Result<Filters, String> {
+    let mut output = Filters {
+        default: LevelFilter::Info,
+        modules: Vec::new(),
+    };
+    for part in spec.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        match part.split_once('=') {
+            Some((module, level)) => {
+                let module = module.trim();
+                let module = module.strip_prefix(CRATE_PREFIX).unwrap_or(module);
+                if module.is_empty() {
+                    return Err(format!("missing module name in '{part}'"));
+                }
+                let level = level.trim().parse::<LevelFilter>().map_err(|_| format!("unknown log level in '{part}'"))?;
+                output.modules.push((String::from(module), level));
+            },
+            None => {
+                output.default = part.parse::<LevelFilter>().map_err(|_| format!("unknown log level '{part}'"))?;
+            }
+        }
+    }
+    Ok(output)
+}
+
+pub fn format_line(time: &str, level: Level, target: &str, message: &str) -> String {
+    let target = target.strip_prefix(CRATE_PREFIX).unwrap_or(target);
+    format!("{time} {level:<5} {target} - {message}")
+}
+
+// The last count lines of text, oldest first.
+pub fn last_lines(text: &str, count: usize) -> Vec<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    lines[lines.len().saturating_sub(count)..].iter().map(|l| String::from(*l)).collect()
+}
+
+// Logs go next to the other files we write, or the working directory if there isn't one.
+pub fn log_directory() -> PathBuf {
+    if let Ok(path) = env::var(WRITEABLE_FILE_PATH) {
+        let path = PathBuf::from(path);
+        if path.is_dir() {
+            return path;
+        }
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                return parent.to_path_buf();
+            }
+        }
+    }
+    PathBuf::from(".")
+}
+
+fn log_path(dir: &Path, number: usize) -> PathBuf {
+    if number == 0 {
+        dir.join(LOG_FILE_NAME)
+    } else {
+        dir.join(format!("{LOG_FILE_NAME}.{number}"))
+    }
+}
+
+struct LogFile {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(dir: &Path) -> std::io::Result<LogFile> {
+        let file = OpenOptions::new().append(true).create(true).open(log_path(dir, 0))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(LogFile {
+            dir: dir.to_path_buf(),
+            file,
+            size,
+        })
+    }
+
+    fn write(&mut self, line: &str) -> std::io::Result<()> {
+        if self.size + line.len() as u64 + 1 > MAX_LOG_FILE_SIZE && self.size > 0 {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{line}")?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        _ = fs::remove_file(log_path(&self.dir, MAX_LOG_FILES));
+        for number in (1..MAX_LOG_FILES).rev() {
+            let from = log_path(&self.dir, number);
+            if from.exists() {
+                fs::rename(from, log_path(&self.dir, number + 1))?;
+            }
+        }
+        fs::rename(log_path(&self.dir, 0), log_path(&self.dir, 1))?;
+        *self = LogFile::open(&self.dir)?;
+        Ok(())
+    }
+}
+
+struct Logger {
+    filters: RwLock<Filters>,
+    file: Mutex<Option<LogFile>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match self.filters.read() {
+            Ok(filters) => metadata.level() <= filters.level_for(metadata.target()),
+            Err(_) => true,
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let time = Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+        let line = format_line(&time, record.level(), record.target(), &record.args().to_string());
+        // still printed so it shows up in the journal
+        println!("{line}");
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(f) = &mut *file {
+                if let Err(e) = f.write(&line) {
+                    println!("Error writing to the log file. {e}");
+                }
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(f) = &mut *file {
+                _ = f.file.flush();
+            }
+        }
+    }
+}
+
+// Sets up logging to stdout and the log file.  Call it once, before anything logs.
+pub fn init(spec: &str) {
+    let filters = parse_spec(spec).unwrap_or_else(|e| {
+        println!("Invalid log level '{spec}', using info. {e}");
+        parse_spec("").unwrap()
+    });
+    let max_level = filters.max_level();
+    let dir = log_directory();
+    let file = match LogFile::open(&dir) {
+        Ok(f) => Some(f),
+        Err(e) => {
+            println!("Unable to open a log file in {}. {e}", dir.display());
+            None
+        }
+    };
+    let logger = LOGGER.get_or_init(|| Logger {
+        filters: RwLock::new(filters),
+        file: Mutex::new(file),
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+// Changes the log levels while running.
+pub fn set_level(spec: &str) -> Result<(), String> {
+    let filters = parse_spec(spec)?;
+    if let Some(logger) = LOGGER.get() {
+        log::set_max_level(filters.max_level());
+        if let Ok(mut current) = logger.filters.write() {
+            *current = filters;
+        }
+    }
+    Ok(())
+}
+
+// The newest lines from the log, reaching back into rotated files when needed.
+pub fn tail(count: usize) -> Vec<String> {
+    let count = count.min(MAX_TAIL_LINES);
+    let dir = match LOGGER.get().and_then(|l| l.file.lock().ok()) {
+        Some(file) => match &*file {
+            Some(f) => f.dir.clone(),
+            None => return Vec::new(),
+        },
+        None => return Vec::new(),
+    };
+    let mut output: Vec<String> = Vec::new();
+    for number in 0..=MAX_LOG_FILES {
+        if output.len() >= count {
+            break;
+        }
+        let text = match fs::read_to_string(log_path(&dir, number)) {
+            Ok(t) => t,
+            Err(_) => break,
+        };
+        let mut lines = last_lines(&text, count - output.len());
+        lines.append(&mut output);
+        output = lines;
+    }
+    output
+}
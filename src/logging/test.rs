@@ -0,0 +1,76 @@
+use std::fs;
+
+use log::{Level, LevelFilter};
+
+use super::{format_line, last_lines, log_path, parse_spec, LogFile, MAX_LOG_FILES};
+
+#[test]
+fn test_parse_spec() {
+    let filters = parse_spec("").unwrap();
+    assert_eq!(LevelFilter::Info, filters.level_for("chronokeep_portal::reader::zebra"));
+    let filters = parse_spec("warn").unwrap();
+    assert_eq!(LevelFilter::Warn, filters.level_for("chronokeep_portal::reader::zebra"));
+    assert_eq!(LevelFilter::Warn, filters.max_level());
+    let filters = parse_spec(" info, reader=debug ,control::socket=off, chronokeep_portal::notifier=TRACE").unwrap();
+    assert_eq!(LevelFilter::Info, filters.level_for("chronokeep_portal::control"));
+    assert_eq!(LevelFilter::Off, filters.level_for("chronokeep_portal::control::socket"));
+    assert_eq!(LevelFilter::Debug, filters.level_for("chronokeep_portal::reader::zebra"));
+    assert_eq!(LevelFilter::Trace, filters.level_for("chronokeep_portal::notifier::rules"));
+    assert_eq!(LevelFilter::Trace, filters.max_level());
+    assert!(parse_spec("loud").is_err());
+    assert!(parse_spec("info,reader=loud").is_err());
+    assert!(parse_spec("info,=debug").is_err());
+}
+
+#[test]
+fn test_level_for() {
+    let filters = parse_spec("error,reader=warn,reader::zebra=debug").unwrap();
+    // the most specific module wins
+    assert_eq!(LevelFilter::Debug, filters.level_for("chronokeep_portal::reader::zebra"));
+    assert_eq!(LevelFilter::Warn, filters.level_for("chronokeep_portal::reader::reconnector"));
+    assert_eq!(LevelFilter::Warn, filters.level_for("chronokeep_portal::reader"));
+    // only whole module names match
+    assert_eq!(LevelFilter::Error, filters.level_for("chronokeep_portal::readers"));
+    // other crates use the default
+    assert_eq!(LevelFilter::Error, filters.level_for("reqwest::connect"));
+}
+
+#[test]
+fn test_format_line() {
+    assert_eq!(
+        "2024-06-01 08:00:00.000 INFO  reader::zebra - Connected.",
+        format_line("2024-06-01 08:00:00.000", Level::Info, "chronokeep_portal::reader::zebra", "Connected.")
+    );
+    assert_eq!(
+        "2024-06-01 08:00:00.000 ERROR reqwest - Timed out.",
+        format_line("2024-06-01 08:00:00.000", Level::Error, "reqwest", "Timed out.")
+    );
+}
+
+#[test]
+fn test_last_lines() {
+    let text = "one\ntwo\nthree\n";
+    assert_eq!(vec!["two", "three"], last_lines(text, 2));
+    assert_eq!(vec!["one", "two", "three"], last_lines(text, 10));
+    assert!(last_lines(text, 0).is_empty());
+    assert!(last_lines("", 5).is_empty());
+}
+
+#[test]
+fn test_rotate() {
+    let dir = std::env::temp_dir().join("test_portal_log_rotate");
+    _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let mut file = LogFile::open(&dir).unwrap();
+    for ix in 0..MAX_LOG_FILES + 2 {
+        file.write(&format!("line {ix}")).unwrap();
+        file.rotate().unwrap();
+    }
+    file.write("current").unwrap();
+    assert_eq!("current\n", fs::read_to_string(log_path(&dir, 0)).unwrap());
+    assert_eq!(format!("line {}\n", MAX_LOG_FILES + 1), fs::read_to_string(log_path(&dir, 1)).unwrap());
+    assert_eq!("line 2\n", fs::read_to_string(log_path(&dir, MAX_LOG_FILES)).unwrap());
+    // older files are dropped
+    assert!(!log_path(&dir, MAX_LOG_FILES + 1).exists());
+    _ = fs::remove_dir_all(&dir);
+}
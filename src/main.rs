@@ -3,11 +3,13 @@ use std::env;
 use std::sync::Arc;
 use std::sync::Mutex;
 use dotenv::dotenv;
+use log::{error, info, warn};
 use crate::database::sqlite;
 use crate::database::Database;
 use crate::objects::backup;
 use crate::objects::backup::Backup;
 use crate::objects::setting;
+use crate::objects::notification_channel::NotificationChannel;
 
 pub mod control;
 pub mod defaults;
@@ -25,20 +27,28 @@ pub mod sound_board;
 pub mod screen;
 pub mod buttons;
 pub mod notifier;
+pub mod events;
+pub mod mqtt;
+pub mod output;
+pub mod logging;
+pub mod sms;
 #[cfg(target_os = "linux")]
 pub mod battery;
 
 fn main() {
-    println!("Chronokeep Portal starting up...");
-    if let Ok(_) = dotenv() {
-        println!(".env file loaded successfully.")
+    let env_loaded = dotenv().is_ok();
+    // the level from the settings is used once the database is open
+    logging::init(defaults::DEFAULT_LOG_LEVEL);
+    info!("Chronokeep Portal starting up...");
+    if env_loaded {
+        info!(".env file loaded successfully.")
     }
     let restore = sqlite::SQLite::already_exists() == false;
     let mut sqlite = sqlite::SQLite::new().unwrap();
     match sqlite.setup() {
-        Ok(_) => println!("Database successfully setup."),
+        Ok(_) => info!("Database successfully setup."),
         Err(e) => {
-            println!("Error setting up database: {e}");
+            error!("Error setting up database: {e}");
             panic!()
         }
     }
@@ -49,7 +59,7 @@ fn main() {
                     match sqlite.save_reader(&reader) {
                         Ok(_) => {},
                         Err(e) => {
-                            println!("error saving reader {e}");
+                            error!("error saving reader {e}");
                         }
                     }
                 }
@@ -57,7 +67,21 @@ fn main() {
                     match sqlite.save_api(&a) {
                         Ok(_) => {},
                         Err(e) => {
-                            println!("error saving api {e}");
+                            error!("error saving api {e}");
+                        }
+                    }
+                }
+                for c in val.notification_channels {
+                    // saved as new channels since the ids came from another database
+                    match sqlite.save_notification_channel(&NotificationChannel::new(
+                        0,
+                        String::from(c.name()),
+                        c.enabled(),
+                        c.config().clone()
+                    )) {
+                        Ok(_) => {},
+                        Err(e) => {
+                            error!("error saving notification channel {e}");
                         }
                     }
                 }
@@ -67,7 +91,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving portal name {e}");
+                        error!("error saving portal name {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -76,7 +100,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving sighting period {e}");
+                        error!("error saving sighting period {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -85,7 +109,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving read window {e}");
+                        error!("error saving read window {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -94,7 +118,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving chip type {e}");
+                        error!("error saving chip type {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -103,7 +127,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving chip type {e}");
+                        error!("error saving chip type {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -112,7 +136,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving chip type {e}");
+                        error!("error saving chip type {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -121,7 +145,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving chip type {e}");
+                        error!("error saving chip type {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -130,7 +154,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving chip type {e}");
+                        error!("error saving chip type {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -139,7 +163,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving chip type {e}");
+                        error!("error saving chip type {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -148,7 +172,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving ntfy url {e}");
+                        error!("error saving ntfy url {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -157,7 +181,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving ntfy user {e}");
+                        error!("error saving ntfy user {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -166,7 +190,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving ntfy pass {e}");
+                        error!("error saving ntfy pass {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -175,7 +199,7 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving ntfy topic {e}");
+                        error!("error saving ntfy topic {e}");
                     }
                 }
                 match sqlite.set_setting(&setting::Setting::new(
@@ -184,7 +208,51 @@ fn main() {
                 )) {
                     Ok(_) => {},
                     Err(e) => {
-                        println!("error saving enable ntfy {e}");
+                        error!("error saving enable ntfy {e}");
+                    }
+                }
+                for (name, value) in [
+                    (control::SETTING_MQTT_URL, val.mqtt_url),
+                    (control::SETTING_MQTT_USER, val.mqtt_user),
+                    (control::SETTING_MQTT_PASS, val.mqtt_pass),
+                    (control::SETTING_MQTT_TOPIC, val.mqtt_topic),
+                    (control::SETTING_MQTT_QOS, val.mqtt_qos.to_string()),
+                    (control::SETTING_ENABLE_MQTT, val.enable_mqtt.to_string()),
+                    (control::SETTING_ENABLE_OUTPUT, val.enable_output.to_string()),
+                    (control::SETTING_OUTPUT_PORT, val.output_port.to_string()),
+                    (control::SETTING_OUTPUT_FORMAT, val.output_format),
+                    (control::SETTING_ENABLE_RELAY, val.enable_relay.to_string()),
+                    (control::SETTING_RELAY_PORT, val.relay_port.to_string()),
+                    (control::SETTING_UPLOAD_BATCH_SIZE, val.upload_batch_size.to_string()),
+                    (control::SETTING_CONNECTIVITY_CHECK_URL, val.connectivity_check_url),
+                    (control::SETTING_ENABLE_PARTICIPANT_SYNC, val.enable_participant_sync.to_string()),
+                    (control::SETTING_PARTICIPANT_SYNC_API, val.participant_sync_api.to_string()),
+                    (control::SETTING_PARTICIPANT_SYNC_SLUG, val.participant_sync_slug),
+                    (control::SETTING_PARTICIPANT_SYNC_YEAR, val.participant_sync_year),
+                    (control::SETTING_PARTICIPANT_SYNC_INTERVAL, val.participant_sync_interval.to_string()),
+                    (control::SETTING_HTTP_PROXY_URL, val.http_proxy_url),
+                    (control::SETTING_HTTP_PROXY_USER, val.http_proxy_user),
+                    (control::SETTING_HTTP_PROXY_PASS, val.http_proxy_pass),
+                    (control::SETTING_HTTP_CA_BUNDLE, val.http_ca_bundle),
+                    (control::SETTING_HTTP_TIMEOUT, val.http_timeout.to_string()),
+                    (control::SETTING_HTTP_CONNECT_TIMEOUT, val.http_connect_timeout.to_string()),
+                    (control::SETTING_ENABLE_SIGHTING_UPLOAD, val.enable_sighting_upload.to_string()),
+                    (control::SETTING_SILENT_ANTENNA_SECONDS, val.silent_antenna_seconds.to_string()),
+                    (control::SETTING_LOG_LEVEL, val.log_level),
+                    (control::SETTING_ENABLE_SMS, val.enable_sms.to_string()),
+                    (control::SETTING_SMS_URL, val.sms_url),
+                    (control::SETTING_SMS_TOKEN, val.sms_token),
+                    (control::SETTING_SMS_FROM, val.sms_from),
+                    (control::SETTING_SMS_TEMPLATE, val.sms_template),
+                    (control::SETTING_SMS_INTERVAL, val.sms_interval.to_string()),
+                    (control::SETTING_SMS_HOURLY_LIMIT, val.sms_hourly_limit.to_string()),
+                    (control::SETTING_UNKNOWN_CHIP_POLICY, val.unknown_chip_policy),
+                ] {
+                    if let Err(e) = sqlite.set_setting(&setting::Setting::new(
+                        String::from(name),
+                        value
+                    )) {
+                        error!("error saving {name} {e}");
                     }
                 }
             },
@@ -193,15 +261,18 @@ fn main() {
     }
     let control = Arc::new(Mutex::new(control::Control::new(&sqlite).unwrap()));
     let sqlite = Arc::new(Mutex::new(sqlite));
-    println!("Control values retrieved from database.");
+    info!("Control values retrieved from database.");
     if let Ok(control) = control.lock() {
-        println!("Portal is named '{}'.", control.name);
-        println!("Portal version is '{}'", env!("CARGO_PKG_VERSION"));
-        println!("Sightings will be ignored if received within {}", util::pretty_time(&u64::from(control.sighting_period)));
-        println!("Play sound value set to {}.", control.play_sound);
+        if let Err(e) = logging::set_level(&control.log_level) {
+            warn!("Invalid log level '{}'. {e}", control.log_level);
+        }
+        info!("Portal is named '{}'.", control.name);
+        info!("Portal version is '{}'", env!("CARGO_PKG_VERSION"));
+        info!("Sightings will be ignored if received within {}", util::pretty_time(&u64::from(control.sighting_period)));
+        info!("Play sound value set to {}.", control.play_sound);
     }
     else {
-        println!("Unable to get control mutex for some reason.");
+        error!("Unable to get control mutex for some reason.");
     }
     let keepalive: Arc<Mutex<bool>> = Arc::new(Mutex::new(true));
     control::socket::control_loop(sqlite.clone(), &control, keepalive.clone());
@@ -209,6 +280,7 @@ fn main() {
         let control: control::Control = control::Control::new(&sq).unwrap();
         let readers = sq.get_readers().unwrap();
         let api = sq.get_apis().unwrap();
+        let notification_channels = sq.get_notification_channels().unwrap_or_default();
         let backup = Backup{
             name: control.name,
             sighting_period: control.sighting_period,
@@ -224,12 +296,48 @@ fn main() {
             ntfy_pass: control.ntfy_pass,
             ntfy_topic: control.ntfy_topic,
             enable_ntfy: control.enable_ntfy,
+            mqtt_url: control.mqtt_url,
+            mqtt_user: control.mqtt_user,
+            mqtt_pass: control.mqtt_pass,
+            mqtt_topic: control.mqtt_topic,
+            mqtt_qos: control.mqtt_qos,
+            enable_mqtt: control.enable_mqtt,
+            enable_output: control.enable_output,
+            output_port: control.output_port,
+            output_format: control.output_format,
+            enable_relay: control.enable_relay,
+            relay_port: control.relay_port,
+            upload_batch_size: control.upload_batch_size,
+            connectivity_check_url: control.connectivity_check_url,
+            enable_participant_sync: control.enable_participant_sync,
+            participant_sync_api: control.participant_sync_api,
+            participant_sync_slug: control.participant_sync_slug,
+            participant_sync_year: control.participant_sync_year,
+            participant_sync_interval: control.participant_sync_interval,
+            http_proxy_url: control.http_proxy_url,
+            http_proxy_user: control.http_proxy_user,
+            http_proxy_pass: control.http_proxy_pass,
+            http_ca_bundle: control.http_ca_bundle,
+            http_timeout: control.http_timeout,
+            http_connect_timeout: control.http_connect_timeout,
+            enable_sighting_upload: control.enable_sighting_upload,
+            silent_antenna_seconds: control.silent_antenna_seconds,
+            log_level: control.log_level,
+            enable_sms: control.enable_sms,
+            sms_url: control.sms_url,
+            sms_token: control.sms_token,
+            sms_from: control.sms_from,
+            sms_template: control.sms_template,
+            sms_interval: control.sms_interval,
+            sms_hourly_limit: control.sms_hourly_limit,
+            unknown_chip_policy: control.unknown_chip_policy,
             readers,
-            api
+            api,
+            notification_channels,
         };
         backup::save_backup(&backup, None);
     }
-    println!("Goodbye!");
+    info!("Goodbye!");
     if let Ok(control) = control.lock() {
         if control.play_sound {
             control.sound_board.play_shutdown(control.volume);
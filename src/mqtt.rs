@@ -0,0 +1,525 @@
+use std::{collections::VecDeque, fs::{self, File, OpenOptions}, io::{BufRead, BufReader, Write}, path::Path, sync::{mpsc::{Receiver, RecvTimeoutError}, Arc, Condvar, Mutex}, thread, time::{Duration, Instant}};
+
+use serde::{Deserialize, Serialize};
+use log::{error, info, warn};
+
+use crate::{control::Control, defaults, events::{Event, EventBus}, objects::sighting, reader::monitor};
+
+use self::client::Client;
+
+pub mod client;
+#[cfg(test)]
+pub mod test;
+
+pub const MQTT_BUFFER_FILE_PATH: &str = "./mqtt_buffer.jsonl";
+// Stop buffering once we've got this many messages waiting so we don't fill the disk.
+pub const MAX_BUFFERED_MESSAGES: usize = 1_000_000;
+// Messages kept in memory while waiting to be published, past this they go to the buffer file.
+pub const MAX_QUEUED_MESSAGES: usize = 10_000;
+pub const RECONNECT_SECONDS: u64 = 30;
+pub const WAKE_SECONDS: u64 = 1;
+
+pub const TOPIC_READS: &str = "reads";
+pub const TOPIC_SIGHTINGS: &str = "sightings";
+pub const TOPIC_READERS: &str = "readers";
+pub const TOPIC_NOTIFICATIONS: &str = "notifications";
+pub const TOPIC_ANTENNA_ALERTS: &str = "antenna_alerts";
+pub const TOPIC_WATCHED_BIBS: &str = "watched_bibs";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Message {
+    pub topic: String,
+    pub payload: String,
+    pub retain: bool,
+}
+
+#[derive(Serialize)]
+struct NotificationPayload<'a> {
+    kind: &'a crate::control::socket::notifications::APINotification,
+    time: &'a str,
+}
+
+#[derive(Serialize)]
+struct AntennaAlertPayload<'a> {
+    #[serde(flatten)]
+    alert: &'a monitor::AntennaAlert,
+    time: &'a str,
+}
+
+#[derive(Serialize)]
+struct WatchedBibPayload<'a> {
+    sighting: &'a sighting::Sighting,
+    note: &'a str,
+    time: &'a str,
+}
+
+#[derive(Clone, PartialEq)]
+struct Config {
+    url: String,
+    user: String,
+    pass: String,
+    client_id: String,
+}
+
+// Messages waiting to be published.  Up to limit messages are kept in memory, anything past
+// that goes to the buffer file.  Once something is on disk everything after it goes there as
+// well so messages always go out in the order they came in.
+pub struct Spool {
+    memory: VecDeque<Message>,
+    buffered: usize,
+    path: String,
+    limit: usize,
+}
+
+impl Spool {
+    pub fn new(path: &str, limit: usize) -> Self {
+        let mut output = Self {
+            memory: VecDeque::new(),
+            buffered: count_lines(path),
+            path: String::from(path),
+            limit,
+        };
+        // we stopped while sending the buffer, whatever is left of it goes first
+        let sending = read_lines(&output.sending_path());
+        if !sending.is_empty() {
+            output.restore(sending);
+        }
+        _ = fs::remove_file(output.sending_path());
+        output
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.memory.is_empty() && self.buffered == 0
+    }
+
+    pub fn queued(&self) -> usize {
+        self.memory.len()
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.buffered
+    }
+
+    pub fn push(&mut self, message: Message) {
+        if self.buffered == 0 && self.memory.len() < self.limit {
+            self.memory.push_back(message);
+            return
+        }
+        self.spill();
+        self.append(&[message]);
+    }
+
+    pub fn pop(&mut self) -> Option<Message> {
+        if self.buffered > 0 {
+            return None
+        }
+        self.memory.pop_front()
+    }
+
+    // Puts back a message we popped but couldn't send.
+    pub fn unpop(&mut self, message: Message) {
+        if self.buffered == 0 {
+            self.memory.push_front(message);
+            return
+        }
+        match serde_json::to_string(&message) {
+            Ok(line) => self.restore(vec![line]),
+            Err(e) => error!("Error serializing MQTT message. {e}"),
+        }
+    }
+
+    // Moves everything held in memory to the buffer file.
+    pub fn spill(&mut self) {
+        if self.memory.is_empty() {
+            return
+        }
+        let messages: Vec<Message> = self.memory.drain(..).collect();
+        self.append(&messages);
+    }
+
+    // Hands the buffer file over to be sent, returning the path to send it from.  Anything
+    // pushed while it's being sent starts a new buffer.
+    pub fn take_buffered(&mut self) -> Option<String> {
+        if self.buffered == 0 {
+            return None
+        }
+        let sending = self.sending_path();
+        if let Err(e) = fs::rename(&self.path, &sending) {
+            error!("Error moving MQTT buffer to send it. {e}");
+            return None
+        }
+        self.buffered = 0;
+        Some(sending)
+    }
+
+    // Puts lines from the buffer we were sending back in front of everything else waiting.
+    pub fn restore(&mut self, lines: Vec<String>) {
+        let mut output = lines;
+        output.extend(read_lines(&self.path));
+        for message in self.memory.drain(..) {
+            if let Ok(line) = serde_json::to_string(&message) {
+                output.push(line);
+            }
+        }
+        self.buffered = output.len();
+        output.push(String::new());
+        if let Err(e) = fs::write(&self.path, output.join("\n")) {
+            error!("Error rewriting MQTT buffer. {e}");
+        }
+    }
+
+    fn append(&mut self, messages: &[Message]) {
+        let mut file = match OpenOptions::new().append(true).create(true).open(&self.path) {
+            Ok(f) => f,
+            Err(e) => {
+                error!("Error opening MQTT buffer. {e}");
+                return
+            }
+        };
+        for message in messages {
+            if self.buffered >= MAX_BUFFERED_MESSAGES {
+                warn!("MQTT buffer is full, dropping message for {}.", message.topic);
+                continue;
+            }
+            let line = match serde_json::to_string(message) {
+                Ok(l) => l,
+                Err(e) => {
+                    error!("Error serializing MQTT message. {e}");
+                    continue;
+                }
+            };
+            if let Err(e) = writeln!(file, "{line}") {
+                error!("Error writing to MQTT buffer. {e}");
+                return
+            }
+            self.buffered += 1;
+        }
+    }
+
+    fn sending_path(&self) -> String {
+        format!("{}.sending", self.path)
+    }
+}
+
+// Publishes reads, sightings, reader status changes and notifications to an MQTT broker.
+// Events are queued as they're published and written to disk once the queue fills up, so
+// nothing is lost while the broker can't be reached.
+pub struct Publisher {
+    keepalive: Arc<Mutex<bool>>,
+    control: Arc<Mutex<Control>>,
+    receiver: Option<Receiver<Arc<Event>>>,
+    spool: Arc<(Mutex<Spool>, Condvar)>,
+    client: Option<Client>,
+    config: Option<Config>,
+    last_attempt: Option<Instant>,
+}
+
+impl Publisher {
+    pub fn new(
+        keepalive: Arc<Mutex<bool>>,
+        control: Arc<Mutex<Control>>,
+        event_bus: &EventBus,
+    ) -> Self {
+        Self {
+            keepalive,
+            control,
+            receiver: Some(event_bus.listen()),
+            spool: Arc::new((Mutex::new(Spool::new(MQTT_BUFFER_FILE_PATH, MAX_QUEUED_MESSAGES)), Condvar::new())),
+            client: None,
+            config: None,
+            last_attempt: None,
+        }
+    }
+
+    pub fn run(&mut self) {
+        info!("MQTT publisher thread started.");
+        let intake = match self.receiver.take() {
+            Some(receiver) => {
+                let keepalive = self.keepalive.clone();
+                let control = self.control.clone();
+                let spool = self.spool.clone();
+                Some(thread::spawn(move|| {
+                    take_events(keepalive, control, receiver, spool);
+                }))
+            },
+            None => None,
+        };
+        loop {
+            if let Ok(keepalive) = self.keepalive.lock() {
+                if !*keepalive {
+                    break;
+                }
+            }
+            let (enabled, qos, config) = match self.control.lock() {
+                Ok(control) => (
+                    control.enable_mqtt && !control.mqtt_url.is_empty(),
+                    control.mqtt_qos,
+                    Config {
+                        url: control.mqtt_url.clone(),
+                        user: control.mqtt_user.clone(),
+                        pass: control.mqtt_pass.clone(),
+                        client_id: client_id(&control.name),
+                    },
+                ),
+                Err(_) => break,
+            };
+            if !enabled {
+                if let Some(client) = self.client.take() {
+                    info!("MQTT disabled, disconnecting from broker.");
+                    client.disconnect();
+                }
+                self.config = None;
+            } else {
+                // settings changed so we need to reconnect with the new ones
+                if self.config.as_ref() != Some(&config) {
+                    if let Some(client) = self.client.take() {
+                        client.disconnect();
+                    }
+                    self.last_attempt = None;
+                    self.config = Some(config.clone());
+                }
+                if self.client.is_none() && self.last_attempt.is_none_or(|t| t.elapsed() >= Duration::from_secs(RECONNECT_SECONDS)) {
+                    self.connect(&config);
+                }
+                self.send_queued(qos);
+                if let Some(client) = &mut self.client {
+                    if let Err(e) = client.ping_if_idle() {
+                        warn!("Lost connection to MQTT broker. {e}");
+                        self.client = None;
+                    }
+                }
+            }
+            let (spool, cvar) = &*self.spool;
+            if let Ok(spool) = spool.lock() {
+                if self.client.is_none() || spool.is_empty() {
+                    _ = cvar.wait_timeout(spool, Duration::from_secs(WAKE_SECONDS));
+                }
+            }
+        }
+        if let Some(client) = self.client.take() {
+            client.disconnect();
+        }
+        if let Some(intake) = intake {
+            _ = intake.join();
+        }
+        // keep anything we didn't get to for next time
+        if let Ok(mut spool) = self.spool.0.lock() {
+            spool.spill();
+        }
+        info!("MQTT publisher thread stopping.");
+    }
+
+    fn connect(&mut self, config: &Config) {
+        self.last_attempt = Some(Instant::now());
+        let (host, port, tls) = match client::parse_url(&config.url) {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("Invalid MQTT url '{}'. {e}", config.url);
+                return
+            }
+        };
+        match Client::connect(&host, port, tls, &config.client_id, &config.user, &config.pass) {
+            Ok(client) => {
+                info!("Connected to MQTT broker at {host}:{port}.");
+                self.client = Some(client);
+            },
+            Err(e) => warn!("Unable to connect to MQTT broker at {host}:{port}. {e}"),
+        }
+    }
+
+    // Sends everything waiting, oldest first, until we run out or lose the connection.
+    fn send_queued(&mut self, qos: u8) {
+        while self.client.is_some() {
+            let (sending, message) = match self.spool.0.lock() {
+                Ok(mut spool) => match spool.take_buffered() {
+                    Some(path) => (Some(path), None),
+                    None => match spool.pop() {
+                        Some(m) => (None, Some(m)),
+                        None => return,
+                    },
+                },
+                Err(_) => return,
+            };
+            if let Some(path) = sending {
+                self.send_buffered(&path, qos);
+            }
+            if let Some(message) = message {
+                if !self.publish(&message, qos) {
+                    if let Ok(mut spool) = self.spool.0.lock() {
+                        spool.unpop(message);
+                    }
+                }
+            }
+        }
+    }
+
+    fn send_buffered(&mut self, path: &str, qos: u8) {
+        let file = match File::open(path) {
+            Ok(f) => f,
+            Err(e) => {
+                error!("Error opening MQTT buffer to send it. {e}");
+                return
+            }
+        };
+        info!("Sending buffered MQTT messages.");
+        let mut lines = BufReader::new(file).lines();
+        let mut remaining: Vec<String> = Vec::new();
+        for line in lines.by_ref() {
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => break,
+            };
+            let message: Message = match serde_json::from_str(&line) {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            if !self.publish(&message, qos) {
+                remaining.push(line);
+                break;
+            }
+        }
+        remaining.extend(lines.map_while(Result::ok));
+        if !remaining.is_empty() {
+            if let Ok(mut spool) = self.spool.0.lock() {
+                spool.restore(remaining);
+            }
+        }
+        if let Err(e) = fs::remove_file(path) {
+            error!("Error removing MQTT buffer. {e}");
+        }
+    }
+
+    fn publish(&mut self, message: &Message, qos: u8) -> bool {
+        let client = match &mut self.client {
+            Some(c) => c,
+            None => return false,
+        };
+        match client.publish(&message.topic, message.payload.as_bytes(), qos, message.retain) {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("Lost connection to MQTT broker. {e}");
+                self.client = None;
+                false
+            }
+        }
+    }
+}
+
+// Moves events off the bus and onto the spool so the bus never holds more than a moment's
+// worth of events, however long the broker is unreachable.
+fn take_events(
+    keepalive: Arc<Mutex<bool>>,
+    control: Arc<Mutex<Control>>,
+    receiver: Receiver<Arc<Event>>,
+    spool: Arc<(Mutex<Spool>, Condvar)>,
+) {
+    loop {
+        if let Ok(keepalive) = keepalive.lock() {
+            if !*keepalive {
+                break;
+            }
+        }
+        let event = match receiver.recv_timeout(Duration::from_secs(WAKE_SECONDS)) {
+            Ok(ev) => ev,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let (enabled, prefix) = match control.lock() {
+            Ok(control) => (control.enable_mqtt && !control.mqtt_url.is_empty(), control.mqtt_topic.clone()),
+            Err(_) => break,
+        };
+        if !enabled {
+            continue;
+        }
+        let (lock, cvar) = &*spool;
+        if let Ok(mut spool) = lock.lock() {
+            for message in messages(&event, &prefix) {
+                spool.push(message);
+            }
+        }
+        cvar.notify_one();
+    }
+}
+
+fn read_lines(path: &str) -> Vec<String> {
+    match File::open(path) {
+        Ok(file) => BufReader::new(file).lines().map_while(Result::ok).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn count_lines(path: &str) -> usize {
+    if !Path::new(path).exists() {
+        return 0
+    }
+    match File::open(path) {
+        Ok(file) => BufReader::new(file).lines().count(),
+        Err(_) => 0,
+    }
+}
+
+// Client ids are only guaranteed to be accepted if they're alphanumeric and 23 characters or less.
+pub fn client_id(name: &str) -> String {
+    let mut output = String::from("chronokeep");
+    output.extend(name.chars().filter(|c| c.is_ascii_alphanumeric()));
+    output.truncate(23);
+    output
+}
+
+pub fn topic(prefix: &str, kind: &str) -> String {
+    let prefix = prefix.trim().trim_end_matches('/');
+    if prefix.is_empty() {
+        return format!("{}/{kind}", defaults::DEFAULT_MQTT_TOPIC)
+    }
+    format!("{prefix}/{kind}")
+}
+
+// Turns an event into the messages we publish for it.  Reads and sightings are published
+// individually so dashboards don't need to unpack lists.
+pub fn messages(event: &Event, prefix: &str) -> Vec<Message> {
+    let mut output: Vec<Message> = Vec::new();
+    match event {
+        Event::Reads(reads) => {
+            for read in reads {
+                if let Ok(payload) = serde_json::to_string(read) {
+                    output.push(Message { topic: topic(prefix, TOPIC_READS), payload, retain: false });
+                }
+            }
+        },
+        Event::Sightings { sightings, .. } => {
+            for sighting in sightings {
+                if let Ok(payload) = serde_json::to_string(sighting) {
+                    output.push(Message { topic: topic(prefix, TOPIC_SIGHTINGS), payload, retain: false });
+                }
+            }
+        },
+        Event::ReaderList(readers) => {
+            // retained so anything subscribing later knows the current state of the readers
+            if let Ok(payload) = serde_json::to_string(readers) {
+                output.push(Message { topic: topic(prefix, TOPIC_READERS), payload, retain: true });
+            }
+        },
+        Event::Notification { notification, time } => {
+            if let Ok(payload) = serde_json::to_string(&NotificationPayload { kind: notification, time }) {
+                output.push(Message { topic: topic(prefix, TOPIC_NOTIFICATIONS), payload, retain: false });
+            }
+        },
+        Event::AntennaAlert { alert, time } => {
+            if let Ok(payload) = serde_json::to_string(&AntennaAlertPayload { alert, time }) {
+                output.push(Message { topic: topic(prefix, TOPIC_ANTENNA_ALERTS), payload, retain: false });
+            }
+        },
+        Event::WatchedBib { sighting, note, time } => {
+            if let Ok(payload) = serde_json::to_string(&WatchedBibPayload { sighting, note, time }) {
+                output.push(Message { topic: topic(prefix, TOPIC_WATCHED_BIBS), payload, retain: false });
+            }
+        },
+        Event::ReaderAntennas { .. } |
+        Event::Settings(_) |
+        Event::ApiList(_) |
+        Event::Participants(_) |
+        Event::UploaderStatus { .. } |
+        Event::Connectivity(_) => {},
+    }
+    output
+}
@@ -0,0 +1,330 @@
+use std::{io::{self, Read, Write}, net::{Shutdown, TcpStream, ToSocketAddrs}, sync::Arc, time::{Duration, Instant}};
+
+use rustls::{pki_types::ServerName, ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+// A minimal MQTT 3.1.1 client.  We only ever publish so there's no support for subscribing.
+pub const PROTOCOL_NAME: &str = "MQTT";
+pub const PROTOCOL_LEVEL: u8 = 4;
+pub const DEFAULT_PORT: u16 = 1883;
+pub const DEFAULT_TLS_PORT: u16 = 8883;
+pub const KEEPALIVE_SECONDS: u16 = 60;
+pub const TIMEOUT_SECONDS: u64 = 10;
+
+pub const PACKET_CONNECT: u8 = 0x10;
+pub const PACKET_CONNACK: u8 = 0x20;
+pub const PACKET_PUBLISH: u8 = 0x30;
+pub const PACKET_PUBACK: u8 = 0x40;
+pub const PACKET_PUBREC: u8 = 0x50;
+pub const PACKET_PUBREL: u8 = 0x62;
+pub const PACKET_PUBCOMP: u8 = 0x70;
+pub const PACKET_PINGREQ: u8 = 0xC0;
+pub const PACKET_PINGRESP: u8 = 0xD0;
+pub const PACKET_DISCONNECT: u8 = 0xE0;
+
+const CONNECT_FLAG_USERNAME: u8 = 0x80;
+const CONNECT_FLAG_PASSWORD: u8 = 0x40;
+const CONNECT_FLAG_CLEAN_SESSION: u8 = 0x02;
+
+const MAX_REMAINING_LENGTH: usize = 268_435_455;
+
+enum Stream {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+
+impl Stream {
+    fn shutdown(&mut self) {
+        match self {
+            Stream::Plain(stream) => _ = stream.shutdown(Shutdown::Both),
+            Stream::Tls(stream) => {
+                stream.conn.send_close_notify();
+                _ = stream.conn.complete_io(&mut stream.sock);
+                _ = stream.sock.shutdown(Shutdown::Both);
+            },
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Plain(stream) => stream.read(buf),
+            Stream::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Plain(stream) => stream.write(buf),
+            Stream::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Plain(stream) => stream.flush(),
+            Stream::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+pub struct Client {
+    stream: Stream,
+    next_id: u16,
+    last_sent: Instant,
+}
+
+// Accepts mqtt://host:port, tcp://host:port, host:port or just host.  mqtts:// and ssl://
+// connect using TLS.  Returns the host, the port, and whether to use TLS.
+pub fn parse_url(url: &str) -> Result<(String, u16, bool), &'static str> {
+    let url = url.trim();
+    let (url, tls) = match url.strip_prefix("mqtts://").or_else(|| url.strip_prefix("ssl://")) {
+        Some(u) => (u, true),
+        None => (url.strip_prefix("mqtt://").or_else(|| url.strip_prefix("tcp://")).unwrap_or(url), false),
+    };
+    let url = url.trim_end_matches('/');
+    if url.is_empty() {
+        return Err("no host specified")
+    }
+    match url.rsplit_once(':') {
+        Some((host, port)) => {
+            match port.parse::<u16>() {
+                Ok(port) if !host.is_empty() => Ok((String::from(host), port, tls)),
+                _ => Err("invalid port"),
+            }
+        },
+        None if tls => Ok((String::from(url), DEFAULT_TLS_PORT, tls)),
+        None => Ok((String::from(url), DEFAULT_PORT, tls)),
+    }
+}
+
+pub fn encode_remaining_length(mut length: usize, buf: &mut Vec<u8>) -> Result<(), &'static str> {
+    if length > MAX_REMAINING_LENGTH {
+        return Err("packet too large")
+    }
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        buf.push(byte);
+        if length == 0 {
+            return Ok(())
+        }
+    }
+}
+
+fn encode_string(value: &[u8], buf: &mut Vec<u8>) -> Result<(), &'static str> {
+    if value.len() > u16::MAX as usize {
+        return Err("string too long")
+    }
+    buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buf.extend_from_slice(value);
+    Ok(())
+}
+
+fn packet(header: u8, body: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut buf: Vec<u8> = Vec::with_capacity(body.len() + 5);
+    buf.push(header);
+    encode_remaining_length(body.len(), &mut buf)?;
+    buf.extend_from_slice(body);
+    Ok(buf)
+}
+
+pub fn connect_packet(client_id: &str, user: &str, pass: &str, keepalive: u16) -> Result<Vec<u8>, &'static str> {
+    let mut body: Vec<u8> = Vec::new();
+    encode_string(PROTOCOL_NAME.as_bytes(), &mut body)?;
+    body.push(PROTOCOL_LEVEL);
+    let mut flags = CONNECT_FLAG_CLEAN_SESSION;
+    if !user.is_empty() {
+        flags |= CONNECT_FLAG_USERNAME;
+        // a password without a username isn't allowed
+        if !pass.is_empty() {
+            flags |= CONNECT_FLAG_PASSWORD;
+        }
+    }
+    body.push(flags);
+    body.extend_from_slice(&keepalive.to_be_bytes());
+    encode_string(client_id.as_bytes(), &mut body)?;
+    if flags & CONNECT_FLAG_USERNAME != 0 {
+        encode_string(user.as_bytes(), &mut body)?;
+    }
+    if flags & CONNECT_FLAG_PASSWORD != 0 {
+        encode_string(pass.as_bytes(), &mut body)?;
+    }
+    packet(PACKET_CONNECT, &body)
+}
+
+pub fn publish_packet(topic: &str, payload: &[u8], qos: u8, retain: bool, id: u16) -> Result<Vec<u8>, &'static str> {
+    let mut body: Vec<u8> = Vec::with_capacity(topic.len() + payload.len() + 4);
+    encode_string(topic.as_bytes(), &mut body)?;
+    if qos > 0 {
+        body.extend_from_slice(&id.to_be_bytes());
+    }
+    body.extend_from_slice(payload);
+    let mut header = PACKET_PUBLISH | (qos.min(2) << 1);
+    if retain {
+        header |= 0x01;
+    }
+    packet(header, &body)
+}
+
+impl Client {
+    pub fn connect(host: &str, port: u16, tls: bool, client_id: &str, user: &str, pass: &str) -> Result<Client, &'static str> {
+        let addr = match (host, port).to_socket_addrs() {
+            Ok(mut addrs) => match addrs.next() {
+                Some(a) => a,
+                None => return Err("unable to resolve broker address"),
+            },
+            Err(_) => return Err("unable to resolve broker address"),
+        };
+        let stream = match TcpStream::connect_timeout(&addr, Duration::from_secs(TIMEOUT_SECONDS)) {
+            Ok(s) => s,
+            Err(_) => return Err("unable to connect to broker"),
+        };
+        if stream.set_read_timeout(Some(Duration::from_secs(TIMEOUT_SECONDS))).is_err()
+            || stream.set_write_timeout(Some(Duration::from_secs(TIMEOUT_SECONDS))).is_err() {
+            return Err("unable to set broker socket timeouts")
+        }
+        let stream = if tls {
+            Stream::Tls(Box::new(tls_stream(host, stream)?))
+        } else {
+            Stream::Plain(stream)
+        };
+        let mut output = Client {
+            stream,
+            next_id: 1,
+            last_sent: Instant::now(),
+        };
+        output.send(&connect_packet(client_id, user, pass, KEEPALIVE_SECONDS)?)?;
+        let (header, body) = output.read_packet()?;
+        if header & 0xF0 != PACKET_CONNACK || body.len() != 2 {
+            return Err("unexpected response to connect")
+        }
+        match body[1] {
+            0 => Ok(output),
+            1 => Err("broker refused connection: unacceptable protocol version"),
+            2 => Err("broker refused connection: identifier rejected"),
+            3 => Err("broker refused connection: server unavailable"),
+            4 => Err("broker refused connection: bad user name or password"),
+            5 => Err("broker refused connection: not authorized"),
+            _ => Err("broker refused connection"),
+        }
+    }
+
+    pub fn publish(&mut self, topic: &str, payload: &[u8], qos: u8, retain: bool) -> Result<(), &'static str> {
+        let id = self.next_id;
+        // packet identifiers must be non-zero
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        self.send(&publish_packet(topic, payload, qos, retain, id)?)?;
+        match qos {
+            0 => Ok(()),
+            1 => self.wait_for(PACKET_PUBACK, id),
+            _ => {
+                self.wait_for(PACKET_PUBREC, id)?;
+                let mut body: Vec<u8> = Vec::new();
+                body.extend_from_slice(&id.to_be_bytes());
+                self.send(&packet(PACKET_PUBREL, &body)?)?;
+                self.wait_for(PACKET_PUBCOMP, id)
+            },
+        }
+    }
+
+    // Sends a ping if we haven't sent anything in a while so the broker doesn't drop us.
+    pub fn ping_if_idle(&mut self) -> Result<(), &'static str> {
+        if self.last_sent.elapsed() < Duration::from_secs(u64::from(KEEPALIVE_SECONDS / 2)) {
+            return Ok(())
+        }
+        self.send(&[PACKET_PINGREQ, 0])?;
+        loop {
+            let (header, _) = self.read_packet()?;
+            if header & 0xF0 == PACKET_PINGRESP {
+                return Ok(())
+            }
+        }
+    }
+
+    pub fn disconnect(mut self) {
+        _ = self.send(&[PACKET_DISCONNECT, 0]);
+        self.stream.shutdown();
+    }
+
+    fn send(&mut self, buf: &[u8]) -> Result<(), &'static str> {
+        match self.stream.write_all(buf) {
+            Ok(_) => {
+                self.last_sent = Instant::now();
+                Ok(())
+            },
+            Err(_) => Err("unable to write to broker"),
+        }
+    }
+
+    fn wait_for(&mut self, kind: u8, id: u16) -> Result<(), &'static str> {
+        loop {
+            let (header, body) = self.read_packet()?;
+            // anything else (a late ping response for example) can be ignored
+            if header & 0xF0 == kind & 0xF0 && body.len() >= 2 && u16::from_be_bytes([body[0], body[1]]) == id {
+                return Ok(())
+            }
+        }
+    }
+
+    fn read_packet(&mut self) -> Result<(u8, Vec<u8>), &'static str> {
+        let mut byte = [0u8; 1];
+        if self.stream.read_exact(&mut byte).is_err() {
+            return Err("unable to read from broker")
+        }
+        let header = byte[0];
+        let mut length: usize = 0;
+        let mut multiplier: usize = 1;
+        loop {
+            if self.stream.read_exact(&mut byte).is_err() {
+                return Err("unable to read from broker")
+            }
+            length += (byte[0] & 0x7F) as usize * multiplier;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+            multiplier *= 128;
+            if multiplier > 128 * 128 * 128 {
+                return Err("malformed packet from broker")
+            }
+        }
+        let mut body = vec![0u8; length];
+        if self.stream.read_exact(&mut body).is_err() {
+            return Err("unable to read from broker")
+        }
+        Ok((header, body))
+    }
+}
+
+// Brokers are verified against the Mozilla root certificates, same as the remote APIs.
+fn tls_stream(host: &str, stream: TcpStream) -> Result<StreamOwned<ClientConnection, TcpStream>, &'static str> {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = match ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions() {
+        Ok(b) => b.with_root_certificates(roots).with_no_client_auth(),
+        Err(_) => return Err("unable to set up tls"),
+    };
+    let name = match ServerName::try_from(host.to_string()) {
+        Ok(n) => n,
+        Err(_) => return Err("invalid broker host name"),
+    };
+    let mut conn = match ClientConnection::new(Arc::new(config), name) {
+        Ok(c) => c,
+        Err(_) => return Err("unable to set up tls"),
+    };
+    let mut stream = stream;
+    // finish the handshake here so certificate problems show up as connection errors
+    while conn.is_handshaking() {
+        if conn.complete_io(&mut stream).is_err() {
+            return Err("tls handshake with broker failed")
+        }
+    }
+    Ok(StreamOwned::new(conn, stream))
+}
@@ -0,0 +1,175 @@
+use std::fs;
+
+use super::{client::{connect_packet, encode_remaining_length, parse_url, publish_packet, DEFAULT_PORT, DEFAULT_TLS_PORT}, client_id, topic, Message, Spool};
+
+#[test]
+fn test_parse_url() {
+    assert_eq!(Ok((String::from("localhost"), DEFAULT_PORT, false)), parse_url("localhost"));
+    assert_eq!(Ok((String::from("localhost"), 1884, false)), parse_url("mqtt://localhost:1884"));
+    assert_eq!(Ok((String::from("10.0.0.5"), 1883, false)), parse_url("tcp://10.0.0.5:1883/"));
+    assert_eq!(Ok((String::from("broker.local"), 9000, false)), parse_url("broker.local:9000"));
+    assert_eq!(Ok((String::from("broker.local"), DEFAULT_TLS_PORT, true)), parse_url("mqtts://broker.local"));
+    assert_eq!(Ok((String::from("localhost"), 8884, true)), parse_url("ssl://localhost:8884"));
+    assert!(parse_url("").is_err());
+    assert!(parse_url("mqtt://").is_err());
+    assert!(parse_url("localhost:port").is_err());
+    assert!(parse_url(":1883").is_err());
+    assert!(parse_url("mqtts://").is_err());
+}
+
+#[test]
+fn test_encode_remaining_length() {
+    let mut buf: Vec<u8> = Vec::new();
+    assert!(encode_remaining_length(0, &mut buf).is_ok());
+    assert_eq!(vec![0x00], buf);
+    buf.clear();
+    assert!(encode_remaining_length(127, &mut buf).is_ok());
+    assert_eq!(vec![0x7F], buf);
+    buf.clear();
+    assert!(encode_remaining_length(128, &mut buf).is_ok());
+    assert_eq!(vec![0x80, 0x01], buf);
+    buf.clear();
+    assert!(encode_remaining_length(16_383, &mut buf).is_ok());
+    assert_eq!(vec![0xFF, 0x7F], buf);
+    buf.clear();
+    assert!(encode_remaining_length(2_097_152, &mut buf).is_ok());
+    assert_eq!(vec![0x80, 0x80, 0x80, 0x01], buf);
+    buf.clear();
+    assert!(encode_remaining_length(268_435_456, &mut buf).is_err());
+}
+
+#[test]
+fn test_connect_packet() {
+    let packet = connect_packet("portal", "", "", 60).unwrap();
+    assert_eq!(vec![0x10, 18, 0, 4, b'M', b'Q', b'T', b'T', 4, 0x02, 0, 60, 0, 6, b'p', b'o', b'r', b't', b'a', b'l'], packet);
+    let packet = connect_packet("p", "u", "pw", 60).unwrap();
+    assert_eq!(vec![0x10, 20, 0, 4, b'M', b'Q', b'T', b'T', 4, 0xC2, 0, 60, 0, 1, b'p', 0, 1, b'u', 0, 2, b'p', b'w'], packet);
+    // password without a username isn't allowed by the spec
+    let packet = connect_packet("p", "", "pw", 60).unwrap();
+    assert_eq!(0x02, packet[9]);
+}
+
+#[test]
+fn test_publish_packet() {
+    let packet = publish_packet("a/b", b"hi", 0, false, 10).unwrap();
+    assert_eq!(vec![0x30, 7, 0, 3, b'a', b'/', b'b', b'h', b'i'], packet);
+    let packet = publish_packet("a/b", b"hi", 1, true, 10).unwrap();
+    assert_eq!(vec![0x33, 9, 0, 3, b'a', b'/', b'b', 0, 10, b'h', b'i'], packet);
+    let packet = publish_packet("a/b", b"hi", 2, false, 258).unwrap();
+    assert_eq!(vec![0x34, 9, 0, 3, b'a', b'/', b'b', 1, 2, b'h', b'i'], packet);
+}
+
+#[test]
+fn test_client_id() {
+    assert_eq!("chronokeepChronoPortal1", client_id("Chrono Portal 12"));
+    assert_eq!("chronokeep", client_id("!!"));
+}
+
+#[test]
+fn test_topic() {
+    assert_eq!("chronokeep/portal/reads", topic("", "reads"));
+    assert_eq!("race/finish/reads", topic("race/finish/", "reads"));
+    assert_eq!("race/sightings", topic(" race ", "sightings"));
+}
+
+fn message(n: usize) -> Message {
+    Message {
+        topic: String::from("test/reads"),
+        payload: format!("{n}"),
+        retain: false,
+    }
+}
+
+fn spool_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(name).to_string_lossy().to_string();
+    _ = fs::remove_file(&path);
+    _ = fs::remove_file(format!("{path}.sending"));
+    path
+}
+
+fn drain(spool: &mut Spool) -> Vec<String> {
+    let mut output: Vec<String> = Vec::new();
+    loop {
+        if let Some(path) = spool.take_buffered() {
+            for line in fs::read_to_string(&path).unwrap().lines() {
+                let m: Message = serde_json::from_str(line).unwrap();
+                output.push(m.payload);
+            }
+            fs::remove_file(&path).unwrap();
+        } else if let Some(m) = spool.pop() {
+            output.push(m.payload);
+        } else {
+            return output
+        }
+    }
+}
+
+#[test]
+fn test_spool_overflow() {
+    let path = spool_path("test_portal_mqtt_spool_overflow.jsonl");
+    let mut spool = Spool::new(&path, 3);
+    for n in 0..3 {
+        spool.push(message(n));
+    }
+    assert_eq!(3, spool.queued());
+    assert_eq!(0, spool.buffered());
+    // the queue is full so everything moves to disk
+    spool.push(message(3));
+    assert_eq!(0, spool.queued());
+    assert_eq!(4, spool.buffered());
+    // and stays there until the buffer has been sent
+    spool.push(message(4));
+    assert_eq!(0, spool.queued());
+    assert_eq!(5, spool.buffered());
+    assert!(spool.pop().is_none());
+    let expected: Vec<String> = (0..5).map(|n| n.to_string()).collect();
+    assert_eq!(expected, drain(&mut spool));
+    assert!(spool.is_empty());
+    spool.push(message(5));
+    assert_eq!(1, spool.queued());
+    assert_eq!(vec![String::from("5")], drain(&mut spool));
+}
+
+#[test]
+fn test_spool_restore() {
+    let path = spool_path("test_portal_mqtt_spool_restore.jsonl");
+    let mut spool = Spool::new(&path, 2);
+    for n in 0..4 {
+        spool.push(message(n));
+    }
+    let sending = spool.take_buffered().unwrap();
+    // new messages come in while the buffer is being sent
+    spool.push(message(4));
+    spool.push(message(5));
+    spool.push(message(6));
+    // only the first message got sent before the connection dropped
+    let remaining: Vec<String> = fs::read_to_string(&sending).unwrap().lines().skip(1).map(String::from).collect();
+    spool.restore(remaining);
+    fs::remove_file(&sending).unwrap();
+    let expected: Vec<String> = (1..7).map(|n| n.to_string()).collect();
+    assert_eq!(expected, drain(&mut spool));
+    // a message that failed to send goes back in front
+    spool.push(message(7));
+    spool.push(message(8));
+    let m = spool.pop().unwrap();
+    spool.unpop(m);
+    assert_eq!(vec![String::from("7"), String::from("8")], drain(&mut spool));
+}
+
+#[test]
+fn test_spool_recovers_sending() {
+    let path = spool_path("test_portal_mqtt_spool_recover.jsonl");
+    let mut spool = Spool::new(&path, 1);
+    for n in 0..3 {
+        spool.push(message(n));
+    }
+    spool.take_buffered().unwrap();
+    spool.push(message(3));
+    spool.push(message(4));
+    spool.spill();
+    // stopped partway through sending the buffer
+    let mut spool = Spool::new(&path, 1);
+    assert_eq!(5, spool.buffered());
+    let expected: Vec<String> = (0..5).map(|n| n.to_string()).collect();
+    assert_eq!(expected, drain(&mut spool));
+}
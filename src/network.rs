@@ -1 +1,3 @@
-pub mod api;
\ No newline at end of file
+pub mod api;
+pub mod connectivity;
+pub mod http;
\ No newline at end of file
@@ -4,6 +4,10 @@ pub const API_TYPE_CHRONOKEEP_RESULTS: &str = "CHRONOKEEP_RESULTS";
 pub const API_TYPE_CHRONOKEEP_RESULTS_SELF: &str = "CHRONOKEEP_RESULTS_SELF";
 pub const API_TYPE_CHRONOKEEP_REMOTE: &str = "CHRONOKEEP_REMOTE";
 pub const API_TYPE_CHRONOKEEP_REMOTE_SELF: &str = "CHRONOKEEP_REMOTE_SELF";
+// Webhooks use the token as the secret for signing what we send them.
+pub const API_TYPE_WEBHOOK: &str = "WEBHOOK";
+// Portals allowed to relay reads to us.  The nickname is used as the source of their reads.
+pub const API_TYPE_RELAY_SOURCE: &str = "RELAY_SOURCE";
 
 pub const API_URI_CHRONOKEEP_RESULTS: &str = "https://api.chronokeep.com/";
 pub const API_URI_CHRONOKEEP_REMOTE: &str = "https://remote.chronokeep.com/";
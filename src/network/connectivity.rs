@@ -0,0 +1,312 @@
+use std::{sync::{Arc, Condvar, Mutex}, time::{Duration, Instant}};
+
+use chrono::Utc;
+use serde::Serialize;
+use log::{info, warn};
+
+use crate::{control::Control, database::{sqlite, Database}, events::{Event, EventBus}, network::{api, http::ClientFactory}, screen::CharacterDisplay};
+
+#[cfg(test)]
+pub mod test;
+
+pub const PATH_HEALTH: &str = "health";
+// How long to wait between checks while everything is reachable, and while it isn't.
+pub const CHECK_SECONDS: u64 = 30;
+pub const RETRY_SECONDS: u64 = 10;
+pub const TIMEOUT_SECONDS: u64 = 5;
+// Responses slower than this mean the connection is degraded.
+pub const SLOW_MILLISECONDS: u128 = 2000;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Debug)]
+pub enum State {
+    Unknown,
+    Online,
+    Degraded,
+    Offline,
+}
+
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct Status {
+    pub state: State,
+    pub last_success: Option<i64>,
+    pub last_check: Option<i64>,
+    pub targets: Vec<String>,
+}
+
+// Result of checking a single target.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Check {
+    Ok,
+    Slow,
+    Failed,
+}
+
+// The last result of checking each target.  Each destination is judged by the checks of its own
+// host so one server being down doesn't hold up everything else, unless the user gave us their
+// own url to check, which failing means we're offline altogether.
+#[derive(Clone, Default, Debug)]
+pub struct Reachability {
+    pub overridden: bool,
+    pub results: Vec<(String, Check)>,
+}
+
+impl Reachability {
+    pub fn is_online(&self) -> bool {
+        !self.overridden || !self.results.iter().all(|(_, c)| *c == Check::Failed)
+    }
+
+    // Anything we haven't checked, or that isn't a url, is worth trying.
+    pub fn is_reachable(&self, uri: &str) -> bool {
+        if !self.is_online() {
+            return false
+        }
+        let dest = match host(uri) {
+            Some(h) => h,
+            None => return true,
+        };
+        !self.results.iter().any(|(target, check)| *check == Check::Failed && host(target).as_ref() == Some(&dest))
+    }
+}
+
+// Keeps track of whether we can reach the places we send things to.  The uploaders, notifier and
+// webhooks check with it before doing any work so they pause while their destination is down
+// instead of piling up errors, and they can ask for a check right away when a request fails.
+pub struct Connectivity {
+    keepalive: Arc<Mutex<bool>>,
+    sqlite: Arc<Mutex<sqlite::SQLite>>,
+    control: Arc<Mutex<Control>>,
+    event_bus: EventBus,
+    screen: Arc<Mutex<Option<CharacterDisplay>>>,
+    http: Arc<ClientFactory>,
+    status: Mutex<Status>,
+    reachability: Mutex<Reachability>,
+    waiter: (Mutex<bool>, Condvar),
+}
+
+impl Connectivity {
+    pub fn new(
+        keepalive: Arc<Mutex<bool>>,
+        sqlite: Arc<Mutex<sqlite::SQLite>>,
+        control: Arc<Mutex<Control>>,
+        event_bus: EventBus,
+        screen: Arc<Mutex<Option<CharacterDisplay>>>,
+        http: Arc<ClientFactory>,
+    ) -> Self {
+        Self {
+            keepalive,
+            sqlite,
+            control,
+            event_bus,
+            screen,
+            http,
+            status: Mutex::new(Status {
+                state: State::Unknown,
+                last_success: None,
+                last_check: None,
+                targets: Vec::new(),
+            }),
+            reachability: Mutex::new(Reachability::default()),
+            waiter: (Mutex::new(false), Condvar::new()),
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        match self.status.lock() {
+            Ok(s) => s.clone(),
+            Err(_) => Status {
+                state: State::Unknown,
+                last_success: None,
+                last_check: None,
+                targets: Vec::new(),
+            },
+        }
+    }
+
+    // Whether we can get to the internet at all, for things sent somewhere we don't check.
+    pub fn is_online(&self) -> bool {
+        match self.reachability.lock() {
+            Ok(r) => r.is_online(),
+            Err(_) => true,
+        }
+    }
+
+    // Whether anything sent to uri should be tried, including before the first check.
+    pub fn is_reachable(&self, uri: &str) -> bool {
+        match self.reachability.lock() {
+            Ok(r) => r.is_reachable(uri),
+            Err(_) => true,
+        }
+    }
+
+    // Whether the api with the given id can be reached, ones we can't find are left to whoever
+    // is sending to report.
+    pub fn is_api_reachable(&self, api_id: i64) -> bool {
+        let uri = match self.sqlite.lock() {
+            Ok(sq) => match sq.get_apis() {
+                Ok(apis) => apis.into_iter().find(|a| a.id() == api_id).map(|a| String::from(a.uri())),
+                Err(_) => None,
+            },
+            Err(_) => None,
+        };
+        uri.is_none_or(|u| self.is_reachable(&u))
+    }
+
+    // Asks for a check as soon as possible, used when a request fails.
+    pub fn recheck(&self) {
+        let (lock, cvar) = &self.waiter;
+        if let Ok(mut requested) = lock.lock() {
+            *requested = true;
+            cvar.notify_one();
+        }
+    }
+
+    pub fn run(&self) {
+        loop {
+            match self.keepalive.lock() {
+                Ok(ka) if *ka => {},
+                _ => break,
+            }
+            let state = match self.http.client() {
+                Ok(http_client) => self.check(&http_client),
+                Err(e) => {
+                    warn!("Unable to get http client to check connectivity. {e}");
+                    State::Unknown
+                }
+            };
+            let wait = match state {
+                State::Online | State::Unknown => CHECK_SECONDS,
+                State::Degraded | State::Offline => RETRY_SECONDS,
+            };
+            self.wait(Duration::from_secs(wait));
+        }
+        info!("Connectivity thread stopping.");
+    }
+
+    fn wait(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        let (lock, cvar) = &self.waiter;
+        let mut requested = match lock.lock() {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        // wake up every second to see if we should be shutting down
+        while !*requested && Instant::now() < until {
+            match self.keepalive.lock() {
+                Ok(ka) if *ka => {},
+                _ => return,
+            }
+            let timeout = (until - Instant::now()).min(Duration::from_secs(1));
+            requested = match cvar.wait_timeout(requested, timeout) {
+                Ok((r, _)) => r,
+                Err(_) => return,
+            };
+        }
+        *requested = false;
+    }
+
+    fn check(&self, http_client: &reqwest::blocking::Client) -> State {
+        let override_url = match self.control.lock() {
+            Ok(c) => c.connectivity_check_url.clone(),
+            Err(_) => return State::Unknown,
+        };
+        let apis = match self.sqlite.lock() {
+            Ok(sq) => match sq.get_apis() {
+                Ok(apis) => apis,
+                Err(e) => {
+                    warn!("Unable to get apis to check connectivity. {e}");
+                    Vec::new()
+                }
+            },
+            Err(_) => return State::Unknown,
+        };
+        let targets = check_targets(&override_url, &apis);
+        let checks: Vec<Check> = targets.iter().map(|t| check_target(http_client, t)).collect();
+        let state = summarize(&checks);
+        if let Ok(mut reachability) = self.reachability.lock() {
+            *reachability = Reachability {
+                overridden: !override_url.trim().is_empty(),
+                results: targets.iter().cloned().zip(checks.iter().copied()).collect(),
+            };
+        }
+        let now = Utc::now().timestamp();
+        let (changed, updated) = match self.status.lock() {
+            Ok(mut status) => {
+                let changed = status.state != state || status.targets != targets;
+                status.state = state;
+                status.targets = targets;
+                status.last_check = Some(now);
+                if state == State::Online || state == State::Degraded {
+                    status.last_success = Some(now);
+                }
+                (changed, status.clone())
+            },
+            Err(_) => return state,
+        };
+        if changed {
+            info!("Connectivity is now {:?}.", updated.state);
+            if let Ok(mut screen_opt) = self.screen.lock() {
+                if let Some(screen) = &mut *screen_opt {
+                    screen.update_connectivity(updated.state);
+                }
+            }
+            self.event_bus.publish(Event::Connectivity(updated));
+        }
+        state
+    }
+}
+
+fn check_target(http_client: &reqwest::blocking::Client, target: &str) -> Check {
+    let start = Instant::now();
+    // checks are kept short no matter how long other requests are allowed to take
+    match http_client.get(target).timeout(Duration::from_secs(TIMEOUT_SECONDS)).send() {
+        // any answer from the server means we can reach it
+        Ok(resp) if !resp.status().is_server_error() => {
+            if start.elapsed().as_millis() > SLOW_MILLISECONDS {
+                Check::Slow
+            } else {
+                Check::Ok
+            }
+        },
+        _ => Check::Failed,
+    }
+}
+
+// The override is used on its own when set, otherwise the health endpoint of every remote api.
+pub fn check_targets(override_url: &str, apis: &[api::Api]) -> Vec<String> {
+    let override_url = override_url.trim();
+    if !override_url.is_empty() {
+        return vec![String::from(override_url)]
+    }
+    let mut output: Vec<String> = Vec::new();
+    for api in apis.iter().filter(|a| a.kind() == api::API_TYPE_CHRONOKEEP_REMOTE || a.kind() == api::API_TYPE_CHRONOKEEP_REMOTE_SELF) {
+        let url = health_url(api.uri());
+        if !output.contains(&url) {
+            output.push(url);
+        }
+    }
+    output
+}
+
+// The host and port of a url, which is what's checked and what's sent to share.
+pub fn host(uri: &str) -> Option<(String, u16)> {
+    let url = reqwest::Url::parse(uri.trim()).ok()?;
+    Some((url.host_str()?.to_lowercase(), url.port_or_known_default()?))
+}
+
+pub fn health_url(uri: &str) -> String {
+    format!("{}/{PATH_HEALTH}", uri.trim().trim_end_matches('/'))
+}
+
+// Online when every target is fine, offline when none can be reached and degraded otherwise.
+// With nothing to check we don't know either way.
+pub fn summarize(checks: &[Check]) -> State {
+    if checks.is_empty() {
+        State::Unknown
+    } else if checks.iter().all(|c| *c == Check::Ok) {
+        State::Online
+    } else if checks.iter().all(|c| *c == Check::Failed) {
+        State::Offline
+    } else {
+        State::Degraded
+    }
+}
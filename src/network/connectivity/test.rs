@@ -0,0 +1,89 @@
+use crate::network::api;
+
+use super::{check_targets, health_url, host, summarize, Check, Reachability, State};
+
+fn make_api(kind: &str, uri: &str) -> api::Api {
+    api::Api::new(
+        0,
+        String::from(kind),
+        String::from(kind),
+        String::from("token"),
+        String::from(uri),
+    )
+}
+
+#[test]
+fn test_health_url() {
+    assert_eq!("https://remote.chronokeep.com/health", health_url(api::API_URI_CHRONOKEEP_REMOTE));
+    assert_eq!("http://10.0.0.5:4480/health", health_url("http://10.0.0.5:4480"));
+    assert_eq!("http://10.0.0.5:4480/remote/health", health_url(" http://10.0.0.5:4480/remote/ "));
+}
+
+#[test]
+fn test_check_targets() {
+    let apis = vec![
+        make_api(api::API_TYPE_CHRONOKEEP_REMOTE, api::API_URI_CHRONOKEEP_REMOTE),
+        make_api(api::API_TYPE_CHRONOKEEP_REMOTE_SELF, "http://10.0.0.5:4480/"),
+        make_api(api::API_TYPE_CHRONOKEEP_REMOTE_SELF, "http://10.0.0.5:4480"),
+        make_api(api::API_TYPE_CHRONOKEEP_RESULTS, api::API_URI_CHRONOKEEP_RESULTS),
+    ];
+    assert_eq!(
+        vec!["https://remote.chronokeep.com/health", "http://10.0.0.5:4480/health"],
+        check_targets("", &apis)
+    );
+    assert_eq!(vec!["http://example.com/ping"], check_targets(" http://example.com/ping ", &apis));
+    assert!(check_targets("", &[]).is_empty());
+}
+
+#[test]
+fn test_summarize() {
+    assert_eq!(State::Unknown, summarize(&[]));
+    assert_eq!(State::Online, summarize(&[Check::Ok, Check::Ok]));
+    assert_eq!(State::Degraded, summarize(&[Check::Ok, Check::Slow]));
+    assert_eq!(State::Degraded, summarize(&[Check::Ok, Check::Failed]));
+    assert_eq!(State::Degraded, summarize(&[Check::Slow]));
+    assert_eq!(State::Offline, summarize(&[Check::Failed, Check::Failed]));
+}
+
+#[test]
+fn test_host() {
+    assert_eq!(Some((String::from("remote.chronokeep.com"), 443)), host(api::API_URI_CHRONOKEEP_REMOTE));
+    assert_eq!(Some((String::from("10.0.0.5"), 4480)), host(" http://10.0.0.5:4480/remote/health"));
+    assert_eq!(Some((String::from("example.com"), 80)), host("http://EXAMPLE.com/hook"));
+    assert_eq!(None, host("not a url"));
+}
+
+#[test]
+fn test_reachability() {
+    // nothing checked yet
+    let reachability = Reachability::default();
+    assert!(reachability.is_online());
+    assert!(reachability.is_reachable(api::API_URI_CHRONOKEEP_REMOTE));
+    // one remote down only holds up things sent to it
+    let reachability = Reachability {
+        overridden: false,
+        results: vec![
+            (health_url(api::API_URI_CHRONOKEEP_REMOTE), Check::Failed),
+            (health_url("http://10.0.0.5:4480"), Check::Slow),
+        ],
+    };
+    assert!(reachability.is_online());
+    assert!(!reachability.is_reachable(api::API_URI_CHRONOKEEP_REMOTE));
+    assert!(reachability.is_reachable("http://10.0.0.5:4480/"));
+    assert!(reachability.is_reachable(api::API_URI_CHRONOKEEP_RESULTS));
+    assert!(reachability.is_reachable("https://hooks.example.com/portal"));
+    assert!(reachability.is_reachable(""));
+    // the connectivity check url failing means nothing can be reached
+    let reachability = Reachability {
+        overridden: true,
+        results: vec![(String::from("http://example.com/ping"), Check::Failed)],
+    };
+    assert!(!reachability.is_online());
+    assert!(!reachability.is_reachable("https://hooks.example.com/portal"));
+    let reachability = Reachability {
+        overridden: true,
+        results: vec![(String::from("http://example.com/ping"), Check::Ok)],
+    };
+    assert!(reachability.is_online());
+    assert!(reachability.is_reachable(api::API_URI_CHRONOKEEP_REMOTE));
+}
@@ -0,0 +1,113 @@
+use std::{fs, sync::{Arc, Mutex}, time::Duration};
+
+use reqwest::{blocking::Client, Certificate, Proxy};
+
+use crate::control::Control;
+
+#[cfg(test)]
+pub mod test;
+
+// Everything from the settings that goes into building a client.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ClientSettings {
+    pub name: String,
+    pub proxy_url: String,
+    pub proxy_user: String,
+    pub proxy_pass: String,
+    pub ca_bundle: String,
+    pub timeout: u64,
+    pub connect_timeout: u64,
+}
+
+impl ClientSettings {
+    pub fn from_control(control: &Control) -> ClientSettings {
+        ClientSettings {
+            name: control.name.clone(),
+            proxy_url: control.http_proxy_url.clone(),
+            proxy_user: control.http_proxy_user.clone(),
+            proxy_pass: control.http_proxy_pass.clone(),
+            ca_bundle: control.http_ca_bundle.clone(),
+            timeout: control.http_timeout,
+            connect_timeout: control.http_connect_timeout,
+        }
+    }
+}
+
+// Builds the http client every network module uses so proxies, extra certificate authorities
+// and timeouts only have to be set up once.  The client is cached and rebuilt when the settings
+// it was built from change, so callers should ask for it each time they need it.
+pub struct ClientFactory {
+    control: Arc<Mutex<Control>>,
+    current: Mutex<Option<(ClientSettings, Client)>>,
+}
+
+impl ClientFactory {
+    pub fn new(control: Arc<Mutex<Control>>) -> Self {
+        Self {
+            control,
+            current: Mutex::new(None),
+        }
+    }
+
+    pub fn client(&self) -> Result<Client, String> {
+        let settings = match self.control.lock() {
+            Ok(c) => ClientSettings::from_control(&c),
+            Err(_) => return Err(String::from("unable to get control mutex")),
+        };
+        let mut current = match self.current.lock() {
+            Ok(c) => c,
+            Err(_) => return Err(String::from("unable to get http client mutex")),
+        };
+        if let Some((built_with, client)) = &*current {
+            if *built_with == settings {
+                return Ok(client.clone())
+            }
+        }
+        let client = build_client(&settings)?;
+        *current = Some((settings, client.clone()));
+        Ok(client)
+    }
+}
+
+pub fn user_agent(name: &str) -> String {
+    let name: String = name.chars().filter(|c| c.is_ascii_graphic() || *c == ' ').collect();
+    format!("{}/{} ({})", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), name.trim())
+}
+
+pub fn build_client(settings: &ClientSettings) -> Result<Client, String> {
+    let mut builder = reqwest::blocking::ClientBuilder::new()
+        .timeout(Duration::from_secs(settings.timeout))
+        .connect_timeout(Duration::from_secs(settings.connect_timeout))
+        .user_agent(user_agent(&settings.name));
+    let proxy_url = settings.proxy_url.trim();
+    if !proxy_url.is_empty() {
+        let mut proxy = match Proxy::all(proxy_url) {
+            Ok(p) => p,
+            Err(e) => return Err(format!("invalid proxy url: {e}")),
+        };
+        if !settings.proxy_user.is_empty() {
+            proxy = proxy.basic_auth(&settings.proxy_user, &settings.proxy_pass);
+        }
+        builder = builder.proxy(proxy);
+    }
+    let ca_bundle = settings.ca_bundle.trim();
+    if !ca_bundle.is_empty() {
+        let pem = match fs::read(ca_bundle) {
+            Ok(p) => p,
+            Err(e) => return Err(format!("unable to read ca bundle {ca_bundle}: {e}")),
+        };
+        // added alongside the default roots so public apis keep working
+        match Certificate::from_pem_bundle(&pem) {
+            Ok(certs) => {
+                for cert in certs {
+                    builder = builder.add_root_certificate(cert);
+                }
+            },
+            Err(e) => return Err(format!("invalid ca bundle {ca_bundle}: {e}")),
+        }
+    }
+    match builder.build() {
+        Ok(client) => Ok(client),
+        Err(e) => Err(format!("unable to build http client: {e}")),
+    }
+}
@@ -0,0 +1,59 @@
+use super::{build_client, user_agent, ClientSettings};
+
+fn make_settings() -> ClientSettings {
+    ClientSettings {
+        name: String::from("Portal 1"),
+        proxy_url: String::from(""),
+        proxy_user: String::from(""),
+        proxy_pass: String::from(""),
+        ca_bundle: String::from(""),
+        timeout: 30,
+        connect_timeout: 10,
+    }
+}
+
+#[test]
+fn test_user_agent() {
+    let expected = format!("{}/{} (Portal 1)", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    assert_eq!(expected, user_agent("Portal 1"));
+    assert_eq!(expected, user_agent(" Portal\n 1\t"));
+}
+
+#[test]
+fn test_build_client() {
+    let mut settings = make_settings();
+    assert!(build_client(&settings).is_ok());
+    settings.proxy_url = String::from("http://10.0.0.1:3128");
+    assert!(build_client(&settings).is_ok());
+    settings.proxy_user = String::from("portal");
+    settings.proxy_pass = String::from("secret");
+    assert!(build_client(&settings).is_ok());
+    settings.proxy_url = String::from("not a url");
+    assert!(build_client(&settings).is_err());
+}
+
+#[test]
+fn test_build_client_ca_bundle() {
+    let mut settings = make_settings();
+    settings.ca_bundle = String::from("./does_not_exist.pem");
+    let err = build_client(&settings).err().unwrap();
+    assert!(err.contains("unable to read ca bundle"));
+    let path = std::env::temp_dir().join("chronokeep_portal_invalid_bundle.pem");
+    std::fs::write(&path, "-----BEGIN CERTIFICATE-----\nnot a certificate\n-----END CERTIFICATE-----\n").unwrap();
+    settings.ca_bundle = String::from(path.to_str().unwrap());
+    let result = build_client(&settings);
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_settings_changed() {
+    let settings = make_settings();
+    assert_eq!(settings, settings.clone());
+    let mut other = settings.clone();
+    other.timeout = 60;
+    assert_ne!(settings, other);
+    let mut other = settings.clone();
+    other.proxy_pass = String::from("new");
+    assert_ne!(settings, other);
+}
@@ -1,9 +1,17 @@
-use std::{sync::{Arc, Condvar, Mutex, WaitTimeoutResult}, time::Duration};
+use std::{sync::{Arc, Condvar, Mutex, WaitTimeoutResult}, time::{Duration, SystemTime, UNIX_EPOCH}};
 
-use chrono::Utc;
+use chrono::{Local, Timelike, Utc};
 use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE};
+use log::{error, info, warn};
 
-use crate::{control::{socket::notifications::APINotification, Control}, network::api::Api, objects::notification::RemoteNotification, remote};
+use crate::{control::{socket::notifications::APINotification, Control}, database::{self, sqlite, Database}, network::{api::Api, connectivity::Connectivity, http::ClientFactory}, objects::{notification::RemoteNotification, notification_channel::{ChannelConfig, NotificationChannel, NtfyConfig}, notification_rule::NotificationRule, system_event}, remote};
+
+pub mod channels;
+pub mod rules;
+
+// Id used for the ntfy channel set up through the settings instead of saved as a channel.
+pub const SETTINGS_CHANNEL_ID: i64 = 0;
+pub const MAX_ATTEMPTS: u32 = 5;
 
 #[derive(Clone, Debug)]
 pub enum Notification {
@@ -17,32 +25,134 @@ pub enum Notification {
     UnableToStartReading,
     Location,
     Shutdown,
+    AntennaSilent {
+        reader: String,
+        antenna: u32,
+    },
+    AntennaDisconnected {
+        reader: String,
+        antenna: u32,
+    },
+    AntennaRecovered {
+        reader: String,
+        antenna: u32,
+    },
+    WatchedBib {
+        bib: String,
+        // left empty for anonymous participants
+        participant: String,
+        note: String,
+        reader: String,
+    },
+}
+
+pub const ANTENNA_SILENT: &str = "antenna_silent";
+pub const ANTENNA_DISCONNECTED: &str = "antenna_disconnected";
+pub const ANTENNA_RECOVERED: &str = "antenna_recovered";
+pub const WATCHED_BIB: &str = "watched_bib";
+
+pub const NOTIFICATION_KINDS: [&str; 14] = [
+    "start",
+    "stop",
+    "battery_low",
+    "battery_critical",
+    "battery_unknown",
+    "start_reading",
+    "stop_reading",
+    "unable_to_start_reading",
+    "location",
+    "shutdown",
+    ANTENNA_SILENT,
+    ANTENNA_DISCONNECTED,
+    ANTENNA_RECOVERED,
+    WATCHED_BIB,
+];
+
+impl Notification {
+    // Name used for the notification's rule.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Notification::Start => NOTIFICATION_KINDS[0],
+            Notification::Stop => NOTIFICATION_KINDS[1],
+            Notification::BatteryLow => NOTIFICATION_KINDS[2],
+            Notification::BatteryCritical => NOTIFICATION_KINDS[3],
+            Notification::BatteryUnknown => NOTIFICATION_KINDS[4],
+            Notification::StartReading => NOTIFICATION_KINDS[5],
+            Notification::StopReading => NOTIFICATION_KINDS[6],
+            Notification::UnableToStartReading => NOTIFICATION_KINDS[7],
+            Notification::Location => NOTIFICATION_KINDS[8],
+            Notification::Shutdown => NOTIFICATION_KINDS[9],
+            Notification::AntennaSilent { .. } => ANTENNA_SILENT,
+            Notification::AntennaDisconnected { .. } => ANTENNA_DISCONNECTED,
+            Notification::AntennaRecovered { .. } => ANTENNA_RECOVERED,
+            Notification::WatchedBib { .. } => WATCHED_BIB,
+        }
+    }
+
+    // What the notification is about, so a repeat for one antenna or bib doesn't hold back another.
+    pub fn subject(&self) -> String {
+        match self {
+            Notification::AntennaSilent { reader, antenna } |
+            Notification::AntennaDisconnected { reader, antenna } |
+            Notification::AntennaRecovered { reader, antenna } => format!("{reader}:{antenna}"),
+            Notification::WatchedBib { bib, .. } => bib.clone(),
+            _ => String::new(),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct Notifier {
     keepalive: Arc<Mutex<bool>>,
     control: Arc<Mutex<Control>>,
+    sqlite: Arc<Mutex<sqlite::SQLite>>,
     notifications: Arc<Mutex<Vec<(Notification, String)>>>,
     api_notifications: Arc<Mutex<Vec<(Api, APINotification)>>>,
+    retries: Arc<Mutex<Vec<(channels::Message, i64, u32)>>>,
+    rules: Arc<Mutex<Vec<NotificationRule>>>,
+    limiter: Arc<Mutex<rules::Limiter>>,
     waiter: Arc<(Mutex<bool>, Condvar)>,
+    connectivity: Arc<Connectivity>,
+    http: Arc<ClientFactory>,
 }
 
 impl Notifier {
     pub fn new(
         keepalive: Arc<Mutex<bool>>,
         control: Arc<Mutex<Control>>,
+        sqlite: Arc<Mutex<sqlite::SQLite>>,
+        connectivity: Arc<Connectivity>,
+        http: Arc<ClientFactory>,
     ) -> Self {
         Self {
             keepalive,
             control,
+            sqlite,
             notifications: Arc::new(Mutex::new(vec!())),
             api_notifications: Arc::new(Mutex::new(vec!())),
+            retries: Arc::new(Mutex::new(vec!())),
+            rules: Arc::new(Mutex::new(rules::effective_rules(&[]))),
+            limiter: Arc::new(Mutex::new(rules::Limiter::default())),
             waiter: Arc::new((Mutex::new(true), Condvar::new())),
+            connectivity,
+            http,
         }
     }
 
-    pub fn send_notification(&self, note: Notification, time: String) {
+    // Returns false when the notification was held back as a repeat so producers can hold back
+    // anything else they'd send along with it.
+    pub fn send_notification(&self, note: Notification, time: String) -> bool {
+        let rule = self.rule(note.kind());
+        if !rule.enabled() {
+            return true
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        if let Ok(mut limiter) = self.limiter.lock() {
+            if !limiter.allow(&rule, &note.subject(), now) {
+                info!("Not sending {} notification, one was sent too recently.", rule.kind());
+                return false
+            }
+        }
         if let Ok(mut notifications) = self.notifications.lock() {
             notifications.push((note, time));
         }
@@ -50,6 +160,40 @@ impl Notifier {
         let mut waiting = lock.lock().unwrap();
         *waiting = false;
         cvar.notify_one();
+        true
+    }
+
+    fn rule(&self, kind: &str) -> NotificationRule {
+        if let Ok(rules) = self.rules.lock() {
+            if let Some(rule) = rules.iter().find(|r| r.kind() == kind) {
+                return rule.clone()
+            }
+        }
+        rules::default_rule(kind)
+    }
+
+    pub fn rules(&self) -> Vec<NotificationRule> {
+        match self.rules.lock() {
+            Ok(rules) => rules.clone(),
+            Err(_) => rules::effective_rules(&[]),
+        }
+    }
+
+    // Picks up changes to the saved rules, don't call it while holding the database lock.
+    pub fn reload_rules(&self) {
+        let saved = match self.sqlite.lock() {
+            Ok(sq) => match sq.get_notification_rules() {
+                Ok(saved) => saved,
+                Err(e) => {
+                    error!("Error getting notification rules. {e}");
+                    return
+                }
+            },
+            Err(_) => return,
+        };
+        if let Ok(mut rules) = self.rules.lock() {
+            *rules = rules::effective_rules(&saved);
+        }
     }
 
     pub fn send_api_notification(&self, api: &Api, note: APINotification) {
@@ -63,21 +207,11 @@ impl Notifier {
     }
 
     pub fn run(&mut self) {
-        let http_client: reqwest::blocking::Client;
-        match reqwest::blocking::ClientBuilder::new().timeout(Duration::from_secs(5))
-                                    .connect_timeout(Duration::from_secs(5)).build() {
-            Ok(client) => {
-                http_client = client;
-            },
-            Err(_) => {
-                println!("Unable to get our http client. Cannot start notifier thread.");
-                return;
-            },
-        }
+        self.reload_rules();
         loop {
             if let Ok(keepalive) = self.keepalive.try_lock() {
                 if *keepalive == false {
-                    println!("Notifier thread stopping.");
+                    info!("Notifier thread stopping.");
                     break;
                 }
             }
@@ -92,100 +226,59 @@ impl Notifier {
             }
             *waiting = true;
             drop(waiting);
+            // leave everything queued until we're back online, the channels go all over so only
+            // the connectivity check url tells us if they can't be reached
+            if !self.connectivity.is_online() {
+                continue;
+            }
+            let http_client = match self.http.client() {
+                Ok(client) => client,
+                Err(e) => {
+                    warn!("Unable to get http client for notifications. {e}");
+                    continue;
+                }
+            };
             let mut work_list: Vec<(Notification, String)> = vec!();
             if let Ok(mut notifications) = self.notifications.lock() {
                 work_list.append(&mut *notifications);
             }
-            for (note, time) in work_list.iter() {
-                let mut name = String::from("Chronokeep Portal");
-                let mut url = String::from("");
-                let mut topic = String::from("");
-                let mut user = String::from("");
-                let mut pass = String::from("");
-                let mut enabled: bool = false;
-                if let Ok(control) = self.control.lock() {
-                    name = control.name.clone();
-                    url = control.ntfy_url.clone();
-                    topic = control.ntfy_topic.clone();
-                    user = control.ntfy_user.clone();
-                    pass = control.ntfy_pass.clone();
-                    enabled = control.enable_ntfy;
-                }
-                let mut priority: u8 = 3;
-                let tag: String;
-                let message = match note {
-                    Notification::Start => {
-                        tag = String::from("green_circle");
-                        format!("{time} - {name} has started.")
-                    },
-                    Notification::Stop => {
-                        tag = String::from("red_square");
-                        format!("{time} - {name} is shutting down.")
-                    },
-                    Notification::BatteryLow => {
-                        tag = String::from("battery");
-                        priority = 4;
-                        format!("{time} - Battery is low on {name}.")
-                    },
-                    Notification::BatteryCritical => {
-                        tag = String::from("battery");
-                        priority = 5;
-                        format!("{time} - Warning! Battery critical on {name}.")
-                    },
-                    Notification::BatteryUnknown => {
-                        tag = String::from("battery");
-                        format!("{time} - {name} is unable to detect the battery level.")
-                    },
-                    Notification::Location => {
-                        tag = String::from("world_map");
-                        format!("{time} - Location for {name} is...")
-                    },
-                    Notification::StartReading => { // used when Auto Start is set
-                        tag = String::from("medal_sports");
-                        format!("{time} - {name} has successfully connected to the reader.")
-                    },
-                    Notification::StopReading => {
-                        tag = String::from("warning");
-                        priority = 5;
-                        format!("{time} - A reader on {name} has unexpectedly disconnected.")
-                    },
-                    Notification::UnableToStartReading => {
-                        tag = String::from("warning");
-                        priority = 5;
-                        format!("{time} - Unable to connect to a reader on {name}.")
-                    },
-                    Notification::Shutdown => {
-                        tag = String::from("stop_sign");
-                        format!("{time} - {name} is shutting down.")
+            let mut retries: Vec<(channels::Message, i64, u32)> = vec!();
+            if let Ok(mut r) = self.retries.lock() {
+                retries.append(&mut *r);
+            }
+            if !work_list.is_empty() || !retries.is_empty() {
+                let targets = self.channels();
+                // anything that failed last time is only tried again on the channel it failed on
+                for (message, id, attempts) in retries {
+                    if let Some(channel) = targets.iter().find(|c| c.id() == id) {
+                        self.deliver(&http_client, channel, message, attempts + 1);
                     }
+                }
+                let name = match self.control.lock() {
+                    Ok(control) => control.name.clone(),
+                    Err(_) => String::from("Chronokeep Portal"),
                 };
-                if enabled && !url.is_empty() && !topic.is_empty() && !user.is_empty() && !pass.is_empty() {
-                    println!("Sending notification...");
-                    match http_client.post(format!("{}{}", url, topic))
-                        .headers(construct_headers(priority, tag))
-                        .basic_auth(user, Some(pass))
-                        .body(message)
-                        .send() {
-                            Ok(resp) => {
-                                match resp.status() {
-                                    reqwest::StatusCode::OK | reqwest::StatusCode::NO_CONTENT => {}, // success
-                                    other => {
-                                        println!("Unknown status code trying to send notification: {other}");
-                                    }
-                                }
-                            },
-                            Err(e) => {
-                                println!("Error sending notification: {e}");
-                                if let Ok(mut notifications) = self.notifications.lock() {
-                                    notifications.push((note.clone(), time.clone()));
-                                }
-                            }
-                        };
+                let now = Local::now();
+                let minute_of_day = now.hour() * 60 + now.minute();
+                for (note, time) in work_list.iter() {
+                    let rule = self.rule(note.kind());
+                    if rules::in_quiet_hours(&rule, minute_of_day) {
+                        info!("Not sending {} notification during quiet hours.", rule.kind());
+                        continue;
+                    }
+                    let mut message = message(note, time, &name);
+                    message.priority = rule.priority();
+                    for channel in targets.iter().filter(|c| rules::allows_channel(&rule, c.id())) {
+                        self.deliver(&http_client, channel, message.clone(), 1);
+                    }
                 }
-            };
+            }
             let mut api_list: Vec<(Api, APINotification)> = vec!();
             if let Ok(mut notifications) = self.api_notifications.lock() {
-                api_list.append(&mut notifications);
+                // anything for an api that's down waits for it to come back
+                let (reachable, waiting) = notifications.drain(..).partition(|(api, _)| self.connectivity.is_reachable(api.uri()));
+                api_list = reachable;
+                *notifications = waiting;
             }
             for (api, note) in api_list.iter() {
                 let url = api.uri();
@@ -202,24 +295,148 @@ impl Notifier {
                             match response.status() {
                                 reqwest::StatusCode::OK | reqwest::StatusCode::NO_CONTENT => {},
                                 default => {
-                                    println!("invalid status code returned: {default}")
+                                    warn!("invalid status code returned: {default}")
                                 },
                             }
                         },
                         Err(e) => {
-                            println!("error trying to talk to api: {e}")
+                            error!("error trying to talk to api: {e}")
                         }
                     };
             }
         } // end loop
     }
+
+    // Every enabled channel, plus ntfy if it's set up the old way in the settings.
+    fn channels(&self) -> Vec<NotificationChannel> {
+        let mut output: Vec<NotificationChannel> = Vec::new();
+        if let Ok(control) = self.control.lock() {
+            if control.enable_ntfy && !control.ntfy_url.is_empty() && !control.ntfy_topic.is_empty() && !control.ntfy_user.is_empty() && !control.ntfy_pass.is_empty() {
+                output.push(NotificationChannel::new(
+                    SETTINGS_CHANNEL_ID,
+                    String::from("ntfy"),
+                    true,
+                    ChannelConfig::Ntfy(NtfyConfig {
+                        url: control.ntfy_url.clone(),
+                        topic: control.ntfy_topic.clone(),
+                        user: control.ntfy_user.clone(),
+                        pass: control.ntfy_pass.clone(),
+                    }),
+                ));
+            }
+        }
+        if let Ok(sq) = self.sqlite.lock() {
+            match sq.get_notification_channels() {
+                Ok(channels) => output.extend(channels.into_iter().filter(|c| c.enabled())),
+                Err(e) => error!("Error getting notification channels. {e}"),
+            }
+        }
+        output
+    }
+
+    fn deliver(&self, http_client: &reqwest::blocking::Client, channel: &NotificationChannel, message: channels::Message, attempts: u32) {
+        info!("Sending notification to {}...", channel.name());
+        if let Err(e) = channel.config().channel().send(http_client, &message) {
+            error!("Error sending notification to {}: {e}", channel.name());
+            if attempts < MAX_ATTEMPTS {
+                if let Ok(mut retries) = self.retries.lock() {
+                    retries.push((message, channel.id(), attempts));
+                }
+            } else {
+                database::log_event(
+                    &self.sqlite,
+                    system_event::SEVERITY_ERROR,
+                    system_event::SOURCE_NOTIFIER,
+                    system_event::SYSTEM_EVENT_NOTIFICATION_FAILED,
+                    serde_json::json!({ "channel": channel.name(), "title": message.title, "error": e.to_string(), "attempts": attempts }),
+                );
+            }
+        }
+    }
 }
 
-fn construct_headers(priority: u8, tag: String) -> HeaderMap {
-    let mut headers = HeaderMap::new();
-    headers.insert("X-Priority", format!("{}", priority).parse().unwrap());
-    headers.insert("X-Tags", tag.parse().unwrap());
-    headers
+pub fn message(note: &Notification, time: &str, name: &str) -> channels::Message {
+    let mut priority: u8 = 3;
+    let tag: String;
+    let message = match note {
+        Notification::Start => {
+            tag = String::from("green_circle");
+            format!("{time} - {name} has started.")
+        },
+        Notification::Stop => {
+            tag = String::from("red_square");
+            format!("{time} - {name} is shutting down.")
+        },
+        Notification::BatteryLow => {
+            tag = String::from("battery");
+            priority = 4;
+            format!("{time} - Battery is low on {name}.")
+        },
+        Notification::BatteryCritical => {
+            tag = String::from("battery");
+            priority = 5;
+            format!("{time} - Warning! Battery critical on {name}.")
+        },
+        Notification::BatteryUnknown => {
+            tag = String::from("battery");
+            format!("{time} - {name} is unable to detect the battery level.")
+        },
+        Notification::Location => {
+            tag = String::from("world_map");
+            format!("{time} - Location for {name} is...")
+        },
+        Notification::StartReading => { // used when Auto Start is set
+            tag = String::from("medal_sports");
+            format!("{time} - {name} has successfully connected to the reader.")
+        },
+        Notification::StopReading => {
+            tag = String::from("warning");
+            priority = 5;
+            format!("{time} - A reader on {name} has unexpectedly disconnected.")
+        },
+        Notification::UnableToStartReading => {
+            tag = String::from("warning");
+            priority = 5;
+            format!("{time} - Unable to connect to a reader on {name}.")
+        },
+        Notification::Shutdown => {
+            tag = String::from("stop_sign");
+            format!("{time} - {name} is shutting down.")
+        },
+        Notification::AntennaSilent { reader, antenna } => {
+            tag = String::from("warning");
+            priority = 5;
+            format!("{time} - Antenna {antenna} on {reader} at {name} has stopped reading tags.")
+        },
+        Notification::AntennaDisconnected { reader, antenna } => {
+            tag = String::from("warning");
+            priority = 5;
+            format!("{time} - Antenna {antenna} on {reader} at {name} has disconnected.")
+        },
+        Notification::AntennaRecovered { reader, antenna } => {
+            tag = String::from("white_check_mark");
+            format!("{time} - Antenna {antenna} on {reader} at {name} is reading tags again.")
+        },
+        Notification::WatchedBib { bib, participant, note, reader } => {
+            tag = String::from("rotating_light");
+            priority = 5;
+            let mut message = match participant.is_empty() {
+                true => format!("{time} - Watched bib {bib} was seen by {reader} at {name}."),
+                false => format!("{time} - Watched bib {bib} ({participant}) was seen by {reader} at {name}."),
+            };
+            if !note.is_empty() {
+                message.push_str(&format!(" {note}"));
+            }
+            message
+        },
+    };
+    channels::Message {
+        portal: String::from(name),
+        title: String::from(name),
+        message,
+        priority,
+        tag,
+    }
 }
 
 fn construct_api_headers(key: &str) -> HeaderMap {
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use lettre::{message::{header::ContentType, Mailbox}, transport::smtp::{authentication::Credentials, client::{Tls, TlsParameters}}, SmtpTransport, Transport};
+use reqwest::{blocking::Client, header::{HeaderMap, HeaderValue, CONTENT_TYPE}};
+use serde::Serialize;
+
+use crate::objects::notification_channel::{ChannelConfig, EmailConfig, GotifyConfig, NtfyConfig, PushoverConfig, WebhookConfig, EMAIL_SECURITY_NONE, EMAIL_SECURITY_STARTTLS};
+
+#[cfg(test)]
+pub mod test;
+
+pub const PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+pub const GOTIFY_KEY_HEADER: &str = "X-Gotify-Key";
+pub const SMTP_TIMEOUT_SECONDS: u64 = 30;
+
+// A notification ready to be sent anywhere.  Priority goes from 1 (lowest) to 5 (urgent) like
+// ntfy's, other channels map it onto their own scale.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Message {
+    pub portal: String,
+    pub title: String,
+    pub message: String,
+    pub priority: u8,
+    pub tag: String,
+}
+
+pub trait Channel {
+    fn send(&self, http_client: &Client, message: &Message) -> Result<(), String>;
+}
+
+impl ChannelConfig {
+    pub fn channel(&self) -> &dyn Channel {
+        match self {
+            ChannelConfig::Ntfy(c) => c,
+            ChannelConfig::Webhook(c) => c,
+            ChannelConfig::Gotify(c) => c,
+            ChannelConfig::Pushover(c) => c,
+            ChannelConfig::Email(c) => c,
+        }
+    }
+}
+
+fn check_response(result: reqwest::Result<reqwest::blocking::Response>) -> Result<(), String> {
+    match result {
+        Ok(resp) if resp.status().is_success() => Ok(()),
+        Ok(resp) => Err(format!("unexpected status code {}", resp.status())),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn ntfy_url(url: &str, topic: &str) -> String {
+    format!("{}/{}", url.trim().trim_end_matches('/'), topic.trim().trim_start_matches('/'))
+}
+
+impl Channel for NtfyConfig {
+    fn send(&self, http_client: &Client, message: &Message) -> Result<(), String> {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Priority", HeaderValue::from(message.priority as u16));
+        if let Ok(tag) = message.tag.parse() {
+            headers.insert("X-Tags", tag);
+        }
+        let mut request = http_client.post(ntfy_url(&self.url, &self.topic))
+            .headers(headers)
+            .body(message.message.clone());
+        if !self.user.is_empty() {
+            request = request.basic_auth(&self.user, Some(&self.pass));
+        }
+        check_response(request.send())
+    }
+}
+
+impl Channel for WebhookConfig {
+    fn send(&self, http_client: &Client, message: &Message) -> Result<(), String> {
+        let mut request = http_client.post(self.url.trim()).json(message);
+        if !self.token.is_empty() {
+            request = request.bearer_auth(&self.token);
+        }
+        check_response(request.send())
+    }
+}
+
+// Gotify priorities run from 0 to 10.
+pub fn gotify_priority(priority: u8) -> u8 {
+    priority.clamp(1, 5) * 2
+}
+
+#[derive(Serialize)]
+struct GotifyMessage<'a> {
+    title: &'a str,
+    message: &'a str,
+    priority: u8,
+}
+
+impl Channel for GotifyConfig {
+    fn send(&self, http_client: &Client, message: &Message) -> Result<(), String> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+        match self.token.parse() {
+            Ok(token) => { headers.insert(GOTIFY_KEY_HEADER, token); },
+            Err(_) => return Err(String::from("invalid token")),
+        }
+        check_response(http_client.post(format!("{}/message", self.url.trim().trim_end_matches('/')))
+            .headers(headers)
+            .json(&GotifyMessage {
+                title: &message.title,
+                message: &message.message,
+                priority: gotify_priority(message.priority),
+            })
+            .send())
+    }
+}
+
+// Pushover priorities run from -2 to 2, we never use 2 since it needs extra parameters.
+pub fn pushover_priority(priority: u8) -> i8 {
+    (priority.clamp(1, 5) as i8 - 3).min(1)
+}
+
+impl Channel for PushoverConfig {
+    fn send(&self, http_client: &Client, message: &Message) -> Result<(), String> {
+        let url = if self.url.trim().is_empty() { PUSHOVER_URL } else { self.url.trim() };
+        let priority = pushover_priority(message.priority).to_string();
+        check_response(http_client.post(url)
+            .form(&[
+                ("token", self.token.as_str()),
+                ("user", self.user.as_str()),
+                ("title", message.title.as_str()),
+                ("message", message.message.as_str()),
+                ("priority", priority.as_str()),
+            ])
+            .send())
+    }
+}
+
+pub fn email(config: &EmailConfig, message: &Message) -> Result<lettre::Message, String> {
+    let from: Mailbox = config.from.trim().parse().map_err(|e| format!("invalid from address: {e}"))?;
+    // priority 4 and 5 are the ones people need to see right away
+    let subject = if message.priority >= 4 {
+        format!("[!] {}", message.title)
+    } else {
+        message.title.clone()
+    };
+    let mut builder = lettre::Message::builder()
+        .from(from)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for to in config.to.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        let to: Mailbox = to.parse().map_err(|e| format!("invalid to address: {e}"))?;
+        builder = builder.to(to);
+    }
+    builder.body(message.message.clone()).map_err(|e| e.to_string())
+}
+
+impl Channel for EmailConfig {
+    fn send(&self, _http_client: &Client, message: &Message) -> Result<(), String> {
+        let email = email(self, message)?;
+        let host = self.host.trim();
+        let tls = match self.security.as_str() {
+            EMAIL_SECURITY_NONE => Tls::None,
+            security => {
+                let params = TlsParameters::new(String::from(host)).map_err(|e| e.to_string())?;
+                if security == EMAIL_SECURITY_STARTTLS {
+                    Tls::Required(params)
+                } else {
+                    Tls::Wrapper(params)
+                }
+            },
+        };
+        let mut builder = SmtpTransport::builder_dangerous(host)
+            .port(self.port)
+            .tls(tls)
+            .timeout(Some(Duration::from_secs(SMTP_TIMEOUT_SECONDS)));
+        if !self.user.is_empty() {
+            builder = builder.credentials(Credentials::new(self.user.clone(), self.pass.clone()));
+        }
+        match builder.build().send(&email) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
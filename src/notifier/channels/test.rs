@@ -0,0 +1,108 @@
+use crate::{notifier::{self, Notification}, objects::notification_channel::{ChannelConfig, EmailConfig, GotifyConfig, NtfyConfig, PushoverConfig, WebhookConfig, EMAIL_SECURITY_STARTTLS}};
+
+use super::{email, gotify_priority, ntfy_url, pushover_priority, Message};
+
+fn make_message(priority: u8) -> Message {
+    Message {
+        portal: String::from("Portal 1"),
+        title: String::from("Portal 1"),
+        message: String::from("10:00 - Battery is low on Portal 1."),
+        priority,
+        tag: String::from("battery"),
+    }
+}
+
+fn make_email() -> EmailConfig {
+    EmailConfig {
+        host: String::from("smtp.example.com"),
+        port: 587,
+        security: String::from(EMAIL_SECURITY_STARTTLS),
+        user: String::from("portal"),
+        pass: String::from("secret"),
+        from: String::from("Portal <portal@example.com>"),
+        to: vec![String::from("director@example.com"), String::from(" crew@example.com ")],
+    }
+}
+
+#[test]
+fn test_ntfy_url() {
+    assert_eq!("https://ntfy.sh/alerts", ntfy_url("https://ntfy.sh/", "alerts"));
+    assert_eq!("https://ntfy.sh/alerts", ntfy_url(" https://ntfy.sh", "/alerts "));
+}
+
+#[test]
+fn test_priorities() {
+    assert_eq!(2, gotify_priority(1));
+    assert_eq!(6, gotify_priority(3));
+    assert_eq!(10, gotify_priority(5));
+    assert_eq!(10, gotify_priority(9));
+    assert_eq!(-2, pushover_priority(1));
+    assert_eq!(0, pushover_priority(3));
+    assert_eq!(1, pushover_priority(4));
+    assert_eq!(1, pushover_priority(5));
+}
+
+#[test]
+fn test_email() {
+    let result = email(&make_email(), &make_message(3));
+    assert!(result.is_ok());
+    let formatted = String::from_utf8(result.unwrap().formatted()).unwrap();
+    assert!(formatted.contains("Subject: Portal 1\r\n"));
+    assert!(formatted.contains("director@example.com"));
+    assert!(formatted.contains("crew@example.com"));
+    assert!(formatted.contains("Battery is low on Portal 1."));
+    let formatted = String::from_utf8(email(&make_email(), &make_message(5)).unwrap().formatted()).unwrap();
+    assert!(formatted.contains("Subject: [!] Portal 1\r\n"));
+    let mut config = make_email();
+    config.from = String::from("not an address");
+    assert!(email(&config, &make_message(3)).is_err());
+}
+
+#[test]
+fn test_validate() {
+    let ntfy = NtfyConfig {
+        url: String::from("https://ntfy.sh/"),
+        topic: String::from("alerts"),
+        user: String::new(),
+        pass: String::new(),
+    };
+    assert!(ChannelConfig::Ntfy(ntfy.clone()).validate().is_ok());
+    let mut bad = ntfy.clone();
+    bad.user = String::from("portal");
+    assert!(ChannelConfig::Ntfy(bad).validate().is_err());
+    let mut bad = ntfy;
+    bad.topic = String::from(" ");
+    assert_eq!(Err(String::from("topic is required")), ChannelConfig::Ntfy(bad).validate());
+    assert!(ChannelConfig::Webhook(WebhookConfig { url: String::from("https://example.com/hook"), token: String::new() }).validate().is_ok());
+    assert!(ChannelConfig::Gotify(GotifyConfig { url: String::from("https://gotify.example.com"), token: String::new() }).validate().is_err());
+    assert!(ChannelConfig::Pushover(PushoverConfig { url: String::new(), token: String::from("app"), user: String::from("user") }).validate().is_ok());
+    assert!(ChannelConfig::Email(make_email()).validate().is_ok());
+    let mut bad = make_email();
+    bad.to = vec![String::new()];
+    assert!(ChannelConfig::Email(bad).validate().is_err());
+    let mut bad = make_email();
+    bad.security = String::from("ssl");
+    assert!(ChannelConfig::Email(bad).validate().is_err());
+}
+
+#[test]
+fn test_config_serialization() {
+    let config: ChannelConfig = serde_json::from_str(r#"{"kind":"gotify","url":"https://gotify.example.com","token":"abc"}"#).unwrap();
+    assert_eq!(ChannelConfig::Gotify(GotifyConfig { url: String::from("https://gotify.example.com"), token: String::from("abc") }), config);
+    assert_eq!("gotify", config.kind());
+    let value = serde_json::to_value(ChannelConfig::Email(make_email())).unwrap();
+    assert_eq!("email", value["kind"]);
+    assert_eq!(587, value["port"]);
+}
+
+#[test]
+fn test_message() {
+    let message = notifier::message(&Notification::BatteryCritical, "10:00", "Portal 1");
+    assert_eq!("Portal 1", message.title);
+    assert_eq!("10:00 - Warning! Battery critical on Portal 1.", message.message);
+    assert_eq!(5, message.priority);
+    assert_eq!("battery", message.tag);
+    let message = notifier::message(&Notification::Start, "10:00", "Portal 1");
+    assert_eq!(3, message.priority);
+    assert_eq!("green_circle", message.tag);
+}
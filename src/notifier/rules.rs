@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use crate::objects::notification_rule::NotificationRule;
+
+use super::{Notification, ANTENNA_DISCONNECTED, ANTENNA_SILENT, NOTIFICATION_KINDS, WATCHED_BIB};
+
+#[cfg(test)]
+pub mod test;
+
+pub const DEFAULT_PRIORITY: u8 = 3;
+// Battery readings bounce around the thresholds so those alerts wait a bit before repeating.
+pub const DEFAULT_BATTERY_REPEAT_INTERVAL: u64 = 60;
+
+pub fn default_rule(kind: &str) -> NotificationRule {
+    let (priority, repeat_interval) = match kind {
+        k if k == Notification::BatteryLow.kind() => (4, DEFAULT_BATTERY_REPEAT_INTERVAL),
+        k if k == Notification::BatteryCritical.kind() => (5, DEFAULT_BATTERY_REPEAT_INTERVAL),
+        k if k == Notification::StopReading.kind() || k == Notification::UnableToStartReading.kind() => (5, 0),
+        // a dead mat needs fixing right away
+        k if k == ANTENNA_SILENT || k == ANTENNA_DISCONNECTED => (5, 0),
+        // somebody is waiting on that runner
+        k if k == WATCHED_BIB => (5, 0),
+        _ => (DEFAULT_PRIORITY, 0),
+    };
+    NotificationRule::new(
+        String::from(kind),
+        true,
+        priority,
+        Vec::new(),
+        repeat_interval,
+        String::new(),
+        String::new(),
+    )
+}
+
+// A rule for every kind, using the saved one where there is one.
+pub fn effective_rules(saved: &[NotificationRule]) -> Vec<NotificationRule> {
+    NOTIFICATION_KINDS.iter().map(|kind| {
+        match saved.iter().find(|r| r.kind() == *kind) {
+            Some(rule) => rule.clone(),
+            None => default_rule(kind),
+        }
+    }).collect()
+}
+
+// Minutes since midnight for a HH:MM time.
+pub fn parse_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    if hours > 23 || minutes > 59 {
+        return None
+    }
+    Some(hours * 60 + minutes)
+}
+
+// Quiet hours that end earlier in the day than they start run past midnight.
+pub fn in_quiet_hours(rule: &NotificationRule, minute_of_day: u32) -> bool {
+    let (start, end) = match (parse_time(rule.quiet_start()), parse_time(rule.quiet_end())) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return false,
+    };
+    if start <= end {
+        minute_of_day >= start && minute_of_day < end
+    } else {
+        minute_of_day >= start || minute_of_day < end
+    }
+}
+
+pub fn allows_channel(rule: &NotificationRule, channel_id: i64) -> bool {
+    rule.channels().is_empty() || rule.channels().contains(&channel_id)
+}
+
+pub fn validate(rule: &NotificationRule) -> Result<(), String> {
+    if !NOTIFICATION_KINDS.contains(&rule.kind()) {
+        return Err(format!("unknown notification kind {}", rule.kind()))
+    }
+    if !(1..=5).contains(&rule.priority()) {
+        return Err(String::from("priority must be between 1 and 5"))
+    }
+    if rule.quiet_start().is_empty() && rule.quiet_end().is_empty() {
+        return Ok(())
+    }
+    match (parse_time(rule.quiet_start()), parse_time(rule.quiet_end())) {
+        (Some(_), Some(_)) => Ok(()),
+        _ => Err(String::from("quiet hours must both be set as HH:MM")),
+    }
+}
+
+// Keeps track of when each kind was last let through for each subject so repeats can be held back.
+#[derive(Default)]
+pub struct Limiter {
+    last_sent: HashMap<(String, String), u64>,
+}
+
+impl Limiter {
+    pub fn allow(&mut self, rule: &NotificationRule, subject: &str, now: u64) -> bool {
+        let key = (String::from(rule.kind()), String::from(subject));
+        if let Some(last) = self.last_sent.get(&key) {
+            if now < last + rule.repeat_interval() {
+                return false
+            }
+        }
+        self.last_sent.insert(key, now);
+        true
+    }
+}
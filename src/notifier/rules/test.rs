@@ -0,0 +1,139 @@
+use crate::{notifier::{Notification, NOTIFICATION_KINDS, WATCHED_BIB}, objects::notification_rule::NotificationRule};
+
+use super::{allows_channel, default_rule, effective_rules, in_quiet_hours, parse_time, validate, Limiter, DEFAULT_BATTERY_REPEAT_INTERVAL, DEFAULT_PRIORITY};
+
+fn make_rule(kind: &str, priority: u8, channels: Vec<i64>, repeat_interval: u64, quiet_start: &str, quiet_end: &str) -> NotificationRule {
+    NotificationRule::new(
+        String::from(kind),
+        true,
+        priority,
+        channels,
+        repeat_interval,
+        String::from(quiet_start),
+        String::from(quiet_end),
+    )
+}
+
+#[test]
+fn test_parse_time() {
+    assert_eq!(Some(0), parse_time("00:00"));
+    assert_eq!(Some(22 * 60 + 5), parse_time("22:05"));
+    assert_eq!(Some(6 * 60), parse_time(" 6:00 "));
+    assert_eq!(Some(23 * 60 + 59), parse_time("23:59"));
+    assert_eq!(None, parse_time("24:00"));
+    assert_eq!(None, parse_time("12:60"));
+    assert_eq!(None, parse_time("1200"));
+    assert_eq!(None, parse_time(""));
+    assert_eq!(None, parse_time("ab:cd"));
+}
+
+#[test]
+fn test_in_quiet_hours() {
+    let rule = make_rule("start", 3, Vec::new(), 0, "", "");
+    assert!(!in_quiet_hours(&rule, 0));
+    assert!(!in_quiet_hours(&rule, 12 * 60));
+    let rule = make_rule("start", 3, Vec::new(), 0, "12:00", "13:00");
+    assert!(!in_quiet_hours(&rule, 11 * 60 + 59));
+    assert!(in_quiet_hours(&rule, 12 * 60));
+    assert!(in_quiet_hours(&rule, 12 * 60 + 59));
+    assert!(!in_quiet_hours(&rule, 13 * 60));
+    // overnight quiet hours
+    let rule = make_rule("start", 3, Vec::new(), 0, "22:00", "06:00");
+    assert!(!in_quiet_hours(&rule, 21 * 60 + 59));
+    assert!(in_quiet_hours(&rule, 22 * 60));
+    assert!(in_quiet_hours(&rule, 23 * 60 + 59));
+    assert!(in_quiet_hours(&rule, 0));
+    assert!(in_quiet_hours(&rule, 5 * 60 + 59));
+    assert!(!in_quiet_hours(&rule, 6 * 60));
+    assert!(!in_quiet_hours(&rule, 12 * 60));
+    // one bad time means no quiet hours
+    let rule = make_rule("start", 3, Vec::new(), 0, "22:00", "6am");
+    assert!(!in_quiet_hours(&rule, 23 * 60));
+}
+
+#[test]
+fn test_allows_channel() {
+    let rule = make_rule("start", 3, Vec::new(), 0, "", "");
+    assert!(allows_channel(&rule, 0));
+    assert!(allows_channel(&rule, 15));
+    let rule = make_rule("start", 3, vec![0, 2], 0, "", "");
+    assert!(allows_channel(&rule, 0));
+    assert!(allows_channel(&rule, 2));
+    assert!(!allows_channel(&rule, 1));
+}
+
+#[test]
+fn test_default_rule() {
+    let rule = default_rule(Notification::BatteryLow.kind());
+    assert!(rule.enabled());
+    assert_eq!(4, rule.priority());
+    assert_eq!(DEFAULT_BATTERY_REPEAT_INTERVAL, rule.repeat_interval());
+    assert!(rule.channels().is_empty());
+    let rule = default_rule(Notification::BatteryCritical.kind());
+    assert_eq!(5, rule.priority());
+    assert_eq!(DEFAULT_BATTERY_REPEAT_INTERVAL, rule.repeat_interval());
+    assert_eq!(5, default_rule(Notification::StopReading.kind()).priority());
+    assert_eq!(5, default_rule(Notification::UnableToStartReading.kind()).priority());
+    assert_eq!(5, default_rule(WATCHED_BIB).priority());
+    let rule = default_rule(Notification::Start.kind());
+    assert_eq!(DEFAULT_PRIORITY, rule.priority());
+    assert_eq!(0, rule.repeat_interval());
+    assert!(rule.quiet_start().is_empty());
+    assert!(rule.quiet_end().is_empty());
+}
+
+#[test]
+fn test_effective_rules() {
+    let rules = effective_rules(&[]);
+    assert_eq!(NOTIFICATION_KINDS.len(), rules.len());
+    for (kind, rule) in NOTIFICATION_KINDS.iter().zip(rules.iter()) {
+        assert_eq!(default_rule(kind), *rule);
+    }
+    let saved = make_rule("location", 1, vec![3], 120, "22:00", "06:00");
+    let rules = effective_rules(std::slice::from_ref(&saved));
+    assert_eq!(NOTIFICATION_KINDS.len(), rules.len());
+    assert_eq!(Some(&saved), rules.iter().find(|r| r.kind() == "location"));
+    assert_eq!(Some(&default_rule("start")), rules.iter().find(|r| r.kind() == "start"));
+    // rules for kinds we don't know about are ignored
+    let rules = effective_rules(&[make_rule("unknown", 1, Vec::new(), 0, "", "")]);
+    assert_eq!(NOTIFICATION_KINDS.len(), rules.len());
+    assert!(rules.iter().all(|r| r.kind() != "unknown"));
+}
+
+#[test]
+fn test_validate() {
+    assert!(validate(&make_rule("start", 3, Vec::new(), 0, "", "")).is_ok());
+    assert!(validate(&make_rule("battery_low", 1, vec![1], 60, "22:00", "06:00")).is_ok());
+    assert!(validate(&make_rule("unknown", 3, Vec::new(), 0, "", "")).is_err());
+    assert!(validate(&make_rule("start", 0, Vec::new(), 0, "", "")).is_err());
+    assert!(validate(&make_rule("start", 6, Vec::new(), 0, "", "")).is_err());
+    assert!(validate(&make_rule("start", 3, Vec::new(), 0, "22:00", "")).is_err());
+    assert!(validate(&make_rule("start", 3, Vec::new(), 0, "", "06:00")).is_err());
+    assert!(validate(&make_rule("start", 3, Vec::new(), 0, "22:00", "25:00")).is_err());
+}
+
+#[test]
+fn test_limiter() {
+    let mut limiter = Limiter::default();
+    let battery = make_rule("battery_low", 4, Vec::new(), 60, "", "");
+    let start = make_rule("start", 3, Vec::new(), 0, "", "");
+    assert!(limiter.allow(&battery, "", 1000));
+    assert!(!limiter.allow(&battery, "", 1000));
+    assert!(!limiter.allow(&battery, "", 1059));
+    // other kinds aren't held back by it
+    assert!(limiter.allow(&start, "", 1059));
+    assert!(limiter.allow(&start, "", 1059));
+    assert!(limiter.allow(&battery, "", 1060));
+    assert!(!limiter.allow(&battery, "", 1100));
+    assert!(limiter.allow(&battery, "", 1120));
+    // each antenna or bib is limited on its own
+    let silent = make_rule("antenna_silent", 5, Vec::new(), 300, "", "");
+    let first = Notification::AntennaSilent { reader: String::from("reader1"), antenna: 1 };
+    let second = Notification::AntennaSilent { reader: String::from("reader1"), antenna: 2 };
+    let other = Notification::AntennaSilent { reader: String::from("reader2"), antenna: 1 };
+    assert!(limiter.allow(&silent, &first.subject(), 2000));
+    assert!(!limiter.allow(&silent, &first.subject(), 2100));
+    assert!(limiter.allow(&silent, &second.subject(), 2100));
+    assert!(limiter.allow(&silent, &other.subject(), 2100));
+    assert!(limiter.allow(&silent, &first.subject(), 2300));
+}
@@ -6,4 +6,12 @@ pub mod read;
 pub mod event;
 pub mod event_year;
 pub mod backup;
-pub mod notification;
\ No newline at end of file
+pub mod notification;
+pub mod system_event;
+pub mod upload_batch;
+pub mod notification_channel;
+pub mod notification_rule;
+pub mod watched_bib;
+pub mod sms_supporter;
+pub mod sms_delivery;
+pub mod unknown_chip;
@@ -1,8 +1,9 @@
 use std::{path::Path, fs::File, io::Read};
 
 use serde::{Serialize, Deserialize};
+use log::{error, info};
 
-use crate::{network::api, reader, sound_board::Voice};
+use crate::{defaults, network::api, objects::notification_channel, reader, sound_board::Voice};
 
 pub const BACKUP_FILE_PATH: &str = "./portal_backup.json";
 
@@ -23,9 +24,142 @@ pub struct Backup {
     pub ntfy_pass: String,
     pub ntfy_topic: String,
     pub enable_ntfy: bool,
+    // Backups made before MQTT support won't have these.
+    #[serde(default)]
+    pub mqtt_url: String,
+    #[serde(default)]
+    pub mqtt_user: String,
+    #[serde(default)]
+    pub mqtt_pass: String,
+    #[serde(default="default_mqtt_topic")]
+    pub mqtt_topic: String,
+    #[serde(default="default_mqtt_qos")]
+    pub mqtt_qos: u8,
+    #[serde(default)]
+    pub enable_mqtt: bool,
+    #[serde(default)]
+    pub enable_output: bool,
+    #[serde(default="default_output_port")]
+    pub output_port: u16,
+    #[serde(default="default_output_format")]
+    pub output_format: String,
+    #[serde(default)]
+    pub enable_relay: bool,
+    #[serde(default="default_relay_port")]
+    pub relay_port: u16,
+    #[serde(default="default_upload_batch_size")]
+    pub upload_batch_size: usize,
+    #[serde(default)]
+    pub connectivity_check_url: String,
+    #[serde(default)]
+    pub enable_participant_sync: bool,
+    #[serde(default)]
+    pub participant_sync_api: i64,
+    #[serde(default)]
+    pub participant_sync_slug: String,
+    #[serde(default)]
+    pub participant_sync_year: String,
+    #[serde(default="default_participant_sync_interval")]
+    pub participant_sync_interval: u64,
+    #[serde(default)]
+    pub http_proxy_url: String,
+    #[serde(default)]
+    pub http_proxy_user: String,
+    #[serde(default)]
+    pub http_proxy_pass: String,
+    #[serde(default)]
+    pub http_ca_bundle: String,
+    #[serde(default="default_http_timeout")]
+    pub http_timeout: u64,
+    #[serde(default="default_http_connect_timeout")]
+    pub http_connect_timeout: u64,
+    #[serde(default)]
+    pub enable_sighting_upload: bool,
+    #[serde(default="default_silent_antenna_seconds")]
+    pub silent_antenna_seconds: u64,
+    #[serde(default="default_log_level")]
+    pub log_level: String,
+    #[serde(default)]
+    pub enable_sms: bool,
+    #[serde(default)]
+    pub sms_url: String,
+    #[serde(default)]
+    pub sms_token: String,
+    #[serde(default)]
+    pub sms_from: String,
+    #[serde(default="default_sms_template")]
+    pub sms_template: String,
+    #[serde(default="default_sms_interval")]
+    pub sms_interval: u64,
+    #[serde(default="default_sms_hourly_limit")]
+    pub sms_hourly_limit: u32,
+    #[serde(default="default_unknown_chip_policy")]
+    pub unknown_chip_policy: String,
 
     pub readers: Vec<reader::Reader>,
     pub api: Vec<api::Api>,
+    #[serde(default)]
+    pub notification_channels: Vec<notification_channel::NotificationChannel>,
+}
+
+fn default_mqtt_topic() -> String {
+    String::from(defaults::DEFAULT_MQTT_TOPIC)
+}
+
+fn default_mqtt_qos() -> u8 {
+    defaults::DEFAULT_MQTT_QOS
+}
+
+fn default_output_port() -> u16 {
+    defaults::DEFAULT_OUTPUT_PORT
+}
+
+fn default_output_format() -> String {
+    String::from(defaults::DEFAULT_OUTPUT_FORMAT)
+}
+
+fn default_relay_port() -> u16 {
+    defaults::DEFAULT_RELAY_PORT
+}
+
+fn default_upload_batch_size() -> usize {
+    defaults::DEFAULT_UPLOAD_BATCH_SIZE
+}
+
+fn default_participant_sync_interval() -> u64 {
+    defaults::DEFAULT_PARTICIPANT_SYNC_INTERVAL
+}
+
+fn default_http_timeout() -> u64 {
+    defaults::DEFAULT_HTTP_TIMEOUT
+}
+
+fn default_http_connect_timeout() -> u64 {
+    defaults::DEFAULT_HTTP_CONNECT_TIMEOUT
+}
+
+fn default_silent_antenna_seconds() -> u64 {
+    defaults::DEFAULT_SILENT_ANTENNA_SECONDS
+}
+
+fn default_log_level() -> String {
+    String::from(defaults::DEFAULT_LOG_LEVEL)
+}
+
+fn default_sms_template() -> String {
+    String::from(defaults::DEFAULT_SMS_TEMPLATE)
+}
+
+fn default_sms_interval() -> u64 {
+    defaults::DEFAULT_SMS_INTERVAL
+}
+
+fn default_sms_hourly_limit() -> u32 {
+    defaults::DEFAULT_SMS_HOURLY_LIMIT
+}
+
+fn default_unknown_chip_policy() -> String {
+    String::from(defaults::DEFAULT_UNKNOWN_CHIP_POLICY)
 }
 
 pub fn restore_backup() -> Result<Backup, &'static str> {
@@ -33,7 +167,7 @@ pub fn restore_backup() -> Result<Backup, &'static str> {
     let mut file = match File::open(&path) {
         Ok(file) => file,
         Err(e) => {
-            println!("Nothing to restore. {e}");
+            info!("Nothing to restore. {e}");
             return Err("nothing to restore")
         }
     };
@@ -44,14 +178,14 @@ pub fn restore_backup() -> Result<Backup, &'static str> {
             let output: Backup = match serde_json::from_str(s.as_str()) {
                 Ok(it) => it,
                 Err(e) => {
-                    println!("Error deserializing backup. {e}");
+                    error!("Error deserializing backup. {e}");
                     return Err("unable to deserialize backed up settings")
                 }
             };
             Ok(output)
         }
         Err(e) => {
-            println!("Error reading file. {e}");
+            error!("Error reading file. {e}");
             Err("error reading the file")
         }
     }
@@ -65,14 +199,14 @@ pub fn save_backup(backup: &Backup, back_path: Option<&str>) {
     let file = match File::create(&path) {
         Ok(file) => file,
         Err(e) => {
-            println!("error creating new file {e}");
+            error!("error creating new file {e}");
             return
         }
     };
     match serde_json::to_writer_pretty(&file, backup) {
         Ok(_) => (),
         Err(e) => {
-            println!("error writing backup {e}")
+            error!("error writing backup {e}")
         }
     }
 }
\ No newline at end of file
@@ -4,6 +4,12 @@ use serde::{Deserialize, Serialize};
 pub struct BibChip {
     bib: String,
     chip: String,
+    // Seconds since the unix epoch the chip belongs to the bib from and until, 0 leaves that
+    // end open.  A chip swapped to someone else keeps its old entry so earlier reads stay put.
+    #[serde(default)]
+    effective_from: i64,
+    #[serde(default)]
+    effective_to: i64,
 }
 
 impl BibChip {
@@ -14,9 +20,17 @@ impl BibChip {
         BibChip {
             bib,
             chip,
+            effective_from: 0,
+            effective_to: 0,
         }
     }
 
+    pub fn with_effective(mut self, effective_from: i64, effective_to: i64) -> BibChip {
+        self.effective_from = effective_from;
+        self.effective_to = effective_to;
+        self
+    }
+
     pub fn bib(&self) -> &str {
         &self.bib
     }
@@ -25,8 +39,28 @@ impl BibChip {
         &self.chip
     }
 
+    pub fn effective_from(&self) -> i64 {
+        self.effective_from
+    }
+
+    pub fn effective_to(&self) -> i64 {
+        self.effective_to
+    }
+
+    // Whether the chip still belongs to the bib, or will once its time comes.
+    pub fn is_current(&self) -> bool {
+        self.effective_to == 0
+    }
+
+    pub fn covers(&self, seconds: i64) -> bool {
+        (self.effective_from == 0 || self.effective_from <= seconds)
+        && (self.effective_to == 0 || seconds < self.effective_to)
+    }
+
     pub fn equals(&self, other: &BibChip) -> bool {
         return self.bib == other.bib
-            && self.chip == other.chip;
+            && self.chip == other.chip
+            && self.effective_from == other.effective_from
+            && self.effective_to == other.effective_to;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,153 @@
+use serde::{Serialize, Deserialize};
+
+pub const CHANNEL_KIND_NTFY: &str = "ntfy";
+pub const CHANNEL_KIND_WEBHOOK: &str = "webhook";
+pub const CHANNEL_KIND_GOTIFY: &str = "gotify";
+pub const CHANNEL_KIND_PUSHOVER: &str = "pushover";
+pub const CHANNEL_KIND_EMAIL: &str = "email";
+
+pub const EMAIL_SECURITY_NONE: &str = "none";
+pub const EMAIL_SECURITY_STARTTLS: &str = "starttls";
+pub const EMAIL_SECURITY_TLS: &str = "tls";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NtfyConfig {
+    pub url: String,
+    pub topic: String,
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub pass: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GotifyConfig {
+    pub url: String,
+    pub token: String,
+}
+
+// Anything that takes the pushover message api, the url can be left empty for pushover itself.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PushoverConfig {
+    #[serde(default)]
+    pub url: String,
+    pub token: String,
+    pub user: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EmailConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: String,
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub pass: String,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag="kind", rename_all="snake_case")]
+pub enum ChannelConfig {
+    Ntfy(NtfyConfig),
+    Webhook(WebhookConfig),
+    Gotify(GotifyConfig),
+    Pushover(PushoverConfig),
+    Email(EmailConfig),
+}
+
+impl ChannelConfig {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ChannelConfig::Ntfy(_) => CHANNEL_KIND_NTFY,
+            ChannelConfig::Webhook(_) => CHANNEL_KIND_WEBHOOK,
+            ChannelConfig::Gotify(_) => CHANNEL_KIND_GOTIFY,
+            ChannelConfig::Pushover(_) => CHANNEL_KIND_PUSHOVER,
+            ChannelConfig::Email(_) => CHANNEL_KIND_EMAIL,
+        }
+    }
+
+    // Checks everything a channel needs to be able to send is there.
+    pub fn validate(&self) -> Result<(), String> {
+        let missing = match self {
+            ChannelConfig::Ntfy(c) => {
+                if c.user.is_empty() != c.pass.is_empty() {
+                    return Err(String::from("user and pass must be set together"))
+                }
+                [("url", &c.url), ("topic", &c.topic)].into_iter().find(|(_, v)| v.trim().is_empty())
+            },
+            ChannelConfig::Webhook(c) => [("url", &c.url)].into_iter().find(|(_, v)| v.trim().is_empty()),
+            ChannelConfig::Gotify(c) => [("url", &c.url), ("token", &c.token)].into_iter().find(|(_, v)| v.trim().is_empty()),
+            ChannelConfig::Pushover(c) => [("token", &c.token), ("user", &c.user)].into_iter().find(|(_, v)| v.trim().is_empty()),
+            ChannelConfig::Email(c) => {
+                if c.port == 0 {
+                    return Err(String::from("port is required"))
+                }
+                if c.to.iter().all(|t| t.trim().is_empty()) {
+                    return Err(String::from("to is required"))
+                }
+                match c.security.as_str() {
+                    EMAIL_SECURITY_NONE | EMAIL_SECURITY_STARTTLS | EMAIL_SECURITY_TLS => {},
+                    other => return Err(format!("unknown security {other}")),
+                }
+                [("host", &c.host), ("from", &c.from)].into_iter().find(|(_, v)| v.trim().is_empty())
+            },
+        };
+        match missing {
+            Some((name, _)) => Err(format!("{name} is required")),
+            None => Ok(()),
+        }
+    }
+}
+
+// Somewhere notifications get sent.  Any number of channels can be set up, each with its own
+// credentials, and every enabled one gets every notification.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all="snake_case")]
+pub struct NotificationChannel {
+    id: i64,
+    name: String,
+    enabled: bool,
+    config: ChannelConfig,
+}
+
+impl NotificationChannel {
+    pub fn new(
+        id: i64,
+        name: String,
+        enabled: bool,
+        config: ChannelConfig,
+    ) -> NotificationChannel {
+        NotificationChannel {
+            id,
+            name,
+            enabled,
+            config,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn config(&self) -> &ChannelConfig {
+        &self.config
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Serialize, Deserialize};
+
+// How one kind of notification is handled.  Kinds without a saved rule use the defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all="snake_case")]
+pub struct NotificationRule {
+    kind: String,
+    enabled: bool,
+    // 1 (lowest) to 5 (urgent).
+    priority: u8,
+    // Ids of the channels it goes to, every channel when empty.
+    #[serde(default)]
+    channels: Vec<i64>,
+    // Seconds before the same kind of notification can be sent again.
+    #[serde(default)]
+    repeat_interval: u64,
+    // Local times as HH:MM, nothing is sent between them.  Empty for no quiet hours.
+    #[serde(default)]
+    quiet_start: String,
+    #[serde(default)]
+    quiet_end: String,
+}
+
+impl NotificationRule {
+    pub fn new(
+        kind: String,
+        enabled: bool,
+        priority: u8,
+        channels: Vec<i64>,
+        repeat_interval: u64,
+        quiet_start: String,
+        quiet_end: String,
+    ) -> NotificationRule {
+        NotificationRule {
+            kind,
+            enabled,
+            priority,
+            channels,
+            repeat_interval,
+            quiet_start,
+            quiet_end,
+        }
+    }
+
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    pub fn channels(&self) -> &[i64] {
+        &self.channels
+    }
+
+    pub fn repeat_interval(&self) -> u64 {
+        self.repeat_interval
+    }
+
+    pub fn quiet_start(&self) -> &str {
+        &self.quiet_start
+    }
+
+    pub fn quiet_end(&self) -> &str {
+        &self.quiet_end
+    }
+}
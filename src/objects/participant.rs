@@ -12,6 +12,16 @@ pub struct Participant {
     age_group: String,
     distance: String,
     anonymous: bool,
+    // Whether they've agreed to get texts at the mobile number.
+    #[serde(default)]
+    sms_enabled: bool,
+    #[serde(default)]
+    mobile: String,
+    // The participant's id on the results site we synced them from.
+    #[serde(default)]
+    external_id: String,
+    #[serde(default)]
+    apparel: String,
 }
 
 impl Participant {
@@ -36,9 +46,25 @@ impl Participant {
             age_group,
             distance,
             anonymous,
+            sms_enabled: false,
+            mobile: String::new(),
+            external_id: String::new(),
+            apparel: String::new(),
         }
     }
 
+    pub fn with_sms(mut self, sms_enabled: bool, mobile: String) -> Participant {
+        self.sms_enabled = sms_enabled;
+        self.mobile = mobile;
+        self
+    }
+
+    pub fn with_registration(mut self, external_id: String, apparel: String) -> Participant {
+        self.external_id = external_id;
+        self.apparel = apparel;
+        self
+    }
+
     pub fn equals(&self, other: &Participant) -> bool {
         self.bib == other.bib &&
         self.first == other.first &&
@@ -47,7 +73,11 @@ impl Participant {
         self.gender == other.gender &&
         self.age_group == other.age_group &&
         self.distance == other.distance &&
-        self.anonymous == other.anonymous
+        self.anonymous == other.anonymous &&
+        self.sms_enabled == other.sms_enabled &&
+        self.mobile == other.mobile &&
+        self.external_id == other.external_id &&
+        self.apparel == other.apparel
     }
 
     pub fn id(&self) -> u64 {
@@ -85,4 +115,20 @@ impl Participant {
     pub fn anonymous(&self) -> bool {
         self.anonymous
     }
+
+    pub fn sms_enabled(&self) -> bool {
+        self.sms_enabled
+    }
+
+    pub fn mobile(&self) -> &str {
+        &self.mobile
+    }
+
+    pub fn external_id(&self) -> &str {
+        &self.external_id
+    }
+
+    pub fn apparel(&self) -> &str {
+        &self.apparel
+    }
 }
\ No newline at end of file
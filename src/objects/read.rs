@@ -3,6 +3,9 @@ use serde::{Serialize, Deserialize};
 pub const READ_STATUS_UNUSED: u8 = 0;
 pub const READ_STATUS_USED: u8 = 1;
 pub const READ_STATUS_TOO_SOON: u8 = 2;
+// Reads for chips nobody has, held until the chip is given to someone or ignored for good.
+pub const READ_STATUS_HELD: u8 = 3;
+pub const READ_STATUS_IGNORED: u8 = 4;
 
 pub const READ_UPLOADED_FALSE: u8 = 0;
 pub const READ_UPLOADED_TRUE: u8 = 1;
@@ -38,6 +41,9 @@ pub struct Read {
     status: u8,
     #[serde(skip)]
     uploaded: u8,
+    // Name of the portal a relayed read came from, empty for our own reads.
+    #[serde(default, skip_serializing_if="String::is_empty")]
+    source: String,
 }
 
 impl Read {
@@ -67,10 +73,16 @@ impl Read {
                 status,
                 uploaded,
                 ident_type: String::from(READ_IDENT_TYPE_CHIP),
-                kind: String::from(READ_KIND_CHIP)
+                kind: String::from(READ_KIND_CHIP),
+                source: String::new(),
             }
     }
 
+    pub fn with_source(mut self, source: String) -> Read {
+        self.source = source;
+        self
+    }
+
     pub fn equals(&self, other: &Read) -> bool {
         self.identifier == other.identifier &&
         self.seconds == other.seconds &&
@@ -119,6 +131,10 @@ impl Read {
         &self.rssi
     }
 
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
     pub fn status(&self) -> u8 {
         self.status
     }
@@ -0,0 +1,90 @@
+use serde::{Serialize, Deserialize};
+
+pub const SMS_STATUS_SENT: &str = "sent";
+pub const SMS_STATUS_FAILED: &str = "failed";
+
+// A text we tried to send about a participant.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all="snake_case")]
+pub struct SmsDelivery {
+    id: i64,
+    bib: String,
+    mobile: String,
+    message: String,
+    // Seconds since the unix epoch.
+    time: i64,
+    status: String,
+    #[serde(default)]
+    error: String,
+    // When the read the text was about happened, so we never text about the same read twice.
+    #[serde(default)]
+    read_seconds: i64,
+    #[serde(default)]
+    read_milliseconds: u32,
+}
+
+impl SmsDelivery {
+    pub fn new(
+        id: i64,
+        bib: String,
+        mobile: String,
+        message: String,
+        time: i64,
+        status: String,
+        error: String,
+    ) -> SmsDelivery {
+        SmsDelivery {
+            id,
+            bib,
+            mobile,
+            message,
+            time,
+            status,
+            error,
+            read_seconds: 0,
+            read_milliseconds: 0,
+        }
+    }
+
+    pub fn with_read(mut self, read_seconds: i64, read_milliseconds: u32) -> SmsDelivery {
+        self.read_seconds = read_seconds;
+        self.read_milliseconds = read_milliseconds;
+        self
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn bib(&self) -> &str {
+        &self.bib
+    }
+
+    pub fn mobile(&self) -> &str {
+        &self.mobile
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn time(&self) -> i64 {
+        self.time
+    }
+
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+
+    pub fn read_seconds(&self) -> i64 {
+        self.read_seconds
+    }
+
+    pub fn read_milliseconds(&self) -> u32 {
+        self.read_milliseconds
+    }
+}
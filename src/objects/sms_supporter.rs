@@ -0,0 +1,26 @@
+use serde::{Serialize, Deserialize};
+
+// Someone other than the participant who wants a text when the participant is seen.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all="snake_case")]
+pub struct SmsSupporter {
+    bib: String,
+    mobile: String,
+}
+
+impl SmsSupporter {
+    pub fn new(bib: String, mobile: String) -> SmsSupporter {
+        SmsSupporter {
+            bib,
+            mobile,
+        }
+    }
+
+    pub fn bib(&self) -> &str {
+        &self.bib
+    }
+
+    pub fn mobile(&self) -> &str {
+        &self.mobile
+    }
+}
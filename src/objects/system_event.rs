@@ -0,0 +1,136 @@
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+
+pub const SYSTEM_EVENT_NOTIFICATION: &str = "notification";
+pub const SYSTEM_EVENT_READER_STATUS: &str = "reader_status";
+pub const SYSTEM_EVENT_PARTICIPANT_SYNC: &str = "participant_sync";
+pub const SYSTEM_EVENT_READER_CONNECTED: &str = "reader_connected";
+pub const SYSTEM_EVENT_READER_DISCONNECTED: &str = "reader_disconnected";
+pub const SYSTEM_EVENT_RECONNECT_ATTEMPT: &str = "reconnect_attempt";
+pub const SYSTEM_EVENT_UPLOAD_FAILED: &str = "upload_failed";
+pub const SYSTEM_EVENT_BATTERY: &str = "battery";
+pub const SYSTEM_EVENT_TIME_CHANGED: &str = "time_changed";
+pub const SYSTEM_EVENT_SHUTDOWN: &str = "shutdown";
+pub const SYSTEM_EVENT_NOTIFICATION_FAILED: &str = "notification_failed";
+pub const SYSTEM_EVENT_WATCHED_BIB: &str = "watched_bib";
+
+pub const SEVERITY_DEBUG: &str = "debug";
+pub const SEVERITY_INFO: &str = "info";
+pub const SEVERITY_WARNING: &str = "warning";
+pub const SEVERITY_ERROR: &str = "error";
+// Lowest to highest, used to ask for everything at or above a severity.
+pub const SEVERITIES: [&str; 4] = [SEVERITY_DEBUG, SEVERITY_INFO, SEVERITY_WARNING, SEVERITY_ERROR];
+
+pub const SOURCE_READER: &str = "reader";
+pub const SOURCE_RECONNECTOR: &str = "reconnector";
+pub const SOURCE_UPLOADER: &str = "uploader";
+pub const SOURCE_BATTERY: &str = "battery";
+pub const SOURCE_NOTIFIER: &str = "notifier";
+pub const SOURCE_CONTROL: &str = "control";
+pub const SOURCE_SCREEN: &str = "screen";
+pub const SOURCE_PARTICIPANT_SYNC: &str = "participant_sync";
+pub const SOURCE_PROCESSOR: &str = "processor";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all="snake_case")]
+pub struct SystemEvent {
+    id: i64,
+    kind: String,
+    // Seconds since the unix epoch.
+    time: i64,
+    #[serde(default="default_severity")]
+    severity: String,
+    // Module that wrote it.
+    #[serde(default)]
+    source: String,
+    detail: serde_json::Value,
+}
+
+fn default_severity() -> String {
+    String::from(SEVERITY_INFO)
+}
+
+// Position in SEVERITIES, anything we don't know is treated as info.
+pub fn severity_level(severity: &str) -> usize {
+    SEVERITIES.iter().position(|s| *s == severity).unwrap_or(1)
+}
+
+impl SystemEvent {
+    pub fn new(
+        id: i64,
+        kind: String,
+        time: i64,
+        detail: serde_json::Value,
+    ) -> SystemEvent {
+        SystemEvent {
+            id,
+            kind,
+            time,
+            severity: default_severity(),
+            source: String::new(),
+            detail,
+        }
+    }
+
+    // An event for the log, timestamped now.
+    pub fn log(severity: &str, source: &str, kind: &str, detail: serde_json::Value) -> SystemEvent {
+        SystemEvent::new(0, String::from(kind), Utc::now().timestamp(), detail)
+            .with_severity(severity)
+            .with_source(source)
+    }
+
+    pub fn with_severity(mut self, severity: &str) -> SystemEvent {
+        self.severity = String::from(severity);
+        self
+    }
+
+    pub fn with_source(mut self, source: &str) -> SystemEvent {
+        self.source = String::from(source);
+        self
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn time(&self) -> i64 {
+        self.time
+    }
+
+    pub fn severity(&self) -> &str {
+        &self.severity
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn detail(&self) -> &serde_json::Value {
+        &self.detail
+    }
+}
+
+// What to get from the event log, newest first.  Anything left out isn't filtered on.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all="snake_case")]
+pub struct EventLogQuery {
+    // Lowest severity to include.
+    #[serde(default)]
+    pub severity: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub kind: Option<String>,
+    // Seconds since the unix epoch.
+    #[serde(default)]
+    pub since: Option<i64>,
+    // Only events with an id lower than this, for paging back through the log.
+    #[serde(default)]
+    pub before: Option<i64>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
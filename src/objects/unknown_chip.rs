@@ -0,0 +1,51 @@
+use serde::{Serialize, Deserialize};
+
+// A chip we've seen that doesn't belong to anyone, summarized from the reads being held or
+// ignored for it so a timer can work out who it should go to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all="snake_case")]
+pub struct UnknownChip {
+    chip: String,
+    first_seen: u64,
+    last_seen: u64,
+    count: u64,
+    antennas: Vec<u32>,
+}
+
+impl UnknownChip {
+    pub fn new(
+        chip: String,
+        first_seen: u64,
+        last_seen: u64,
+        count: u64,
+        antennas: Vec<u32>,
+    ) -> UnknownChip {
+        UnknownChip {
+            chip,
+            first_seen,
+            last_seen,
+            count,
+            antennas,
+        }
+    }
+
+    pub fn chip(&self) -> &str {
+        &self.chip
+    }
+
+    pub fn first_seen(&self) -> u64 {
+        self.first_seen
+    }
+
+    pub fn last_seen(&self) -> u64 {
+        self.last_seen
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn antennas(&self) -> &[u32] {
+        &self.antennas
+    }
+}
@@ -0,0 +1,47 @@
+use crate::objects::read;
+
+// A group of reads waiting to be uploaded to a remote api.  Batches are saved before they're
+// sent and keep the same id until the server accepts them.
+#[derive(Debug, Clone)]
+pub struct UploadBatch {
+    id: String,
+    api_id: i64,
+    attempts: u32,
+    reads: Vec<read::Read>,
+}
+
+impl UploadBatch {
+    pub fn new(
+        id: String,
+        api_id: i64,
+        attempts: u32,
+        reads: Vec<read::Read>,
+    ) -> UploadBatch {
+        UploadBatch {
+            id,
+            api_id,
+            attempts,
+            reads,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn api_id(&self) -> i64 {
+        self.api_id
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn reads(&self) -> &[read::Read] {
+        &self.reads
+    }
+
+    pub fn read_ids(&self) -> Vec<u64> {
+        self.reads.iter().map(|r| r.id()).collect()
+    }
+}
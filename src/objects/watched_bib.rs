@@ -0,0 +1,29 @@
+use serde::{Serialize, Deserialize};
+
+// A bib someone wants to hear about the moment it crosses a mat, like a runner with a medical
+// condition or one of the leaders.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all="snake_case")]
+pub struct WatchedBib {
+    bib: String,
+    // Why they're being watched, it's included in the alert.
+    #[serde(default)]
+    note: String,
+}
+
+impl WatchedBib {
+    pub fn new(bib: String, note: String) -> WatchedBib {
+        WatchedBib {
+            bib,
+            note,
+        }
+    }
+
+    pub fn bib(&self) -> &str {
+        &self.bib
+    }
+
+    pub fn note(&self) -> &str {
+        &self.note
+    }
+}
@@ -0,0 +1,320 @@
+use std::{io::{ErrorKind, Read, Write}, net::{Shutdown, TcpListener, TcpStream}, sync::{mpsc::{Receiver, TryRecvError}, Arc, Mutex}, thread, time::Duration};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use log::{error, info, warn};
+
+use crate::{control::Control, database::{sqlite, Database}, events::{Event, EventBus, CLIENT_QUEUE_SIZE}, objects::read};
+
+#[cfg(test)]
+pub mod test;
+
+pub const OUTPUT_FORMAT_ULTRA: &str = "ultra";
+pub const OUTPUT_FORMAT_CSV: &str = "csv";
+
+// Ultra readers count seconds from 1980-01-01 instead of the unix epoch.
+pub const ULTRA_EPOCH_OFFSET: i64 = 315_532_800;
+
+pub const COMMAND_REWIND: &str = "REWIND";
+pub const COMMAND_SINCE: &str = "SINCE";
+pub const COMMAND_FORMAT: &str = "FORMAT";
+
+pub const WAKE_MILLISECONDS: u64 = 250;
+// A client that won't take what we write for this long is dropped.
+pub const WRITE_TIMEOUT_SECONDS: u64 = 10;
+pub const MAX_COMMAND_LENGTH: usize = 256;
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Rewind { start: i64, end: i64 },
+    Format(String),
+}
+
+// Re-emits saved reads over plain TCP in line formats third party timing software already
+// understands so it can connect to the portal as if it were the reader.
+pub struct OutputServer {
+    keepalive: Arc<Mutex<bool>>,
+    sqlite: Arc<Mutex<sqlite::SQLite>>,
+    control: Arc<Mutex<Control>>,
+    event_bus: EventBus,
+}
+
+impl OutputServer {
+    pub fn new(
+        keepalive: Arc<Mutex<bool>>,
+        sqlite: Arc<Mutex<sqlite::SQLite>>,
+        control: Arc<Mutex<Control>>,
+        event_bus: EventBus,
+    ) -> Self {
+        Self {
+            keepalive,
+            sqlite,
+            control,
+            event_bus,
+        }
+    }
+
+    pub fn run(&self) {
+        let mut listener: Option<(TcpListener, u16)> = None;
+        loop {
+            if !self.running() {
+                break;
+            }
+            let (enabled, port) = match self.control.lock() {
+                Ok(c) => (c.enable_output, c.output_port),
+                Err(_) => break,
+            };
+            if !enabled {
+                if listener.take().is_some() {
+                    info!("Output server stopped.");
+                }
+                thread::sleep(Duration::from_secs(1));
+                continue;
+            }
+            // bind again if we haven't yet or the port has changed
+            if listener.as_ref().is_none_or(|(_, p)| *p != port) {
+                listener = None;
+                match TcpListener::bind(("0.0.0.0", port)) {
+                    Ok(l) => {
+                        if let Err(e) = l.set_nonblocking(true) {
+                            warn!("Unable to set output server to nonblocking. {e}");
+                            thread::sleep(Duration::from_secs(1));
+                            continue;
+                        }
+                        info!("Output server listening on port {port}.");
+                        listener = Some((l, port));
+                    },
+                    Err(e) => {
+                        warn!("Unable to start output server on port {port}. {e}");
+                        thread::sleep(Duration::from_secs(5));
+                        continue;
+                    }
+                }
+            }
+            if let Some((l, _)) = &listener {
+                match l.accept() {
+                    Ok((stream, addr)) => {
+                        info!("New output connection: {addr}");
+                        let client = OutputClient {
+                            keepalive: self.keepalive.clone(),
+                            sqlite: self.sqlite.clone(),
+                            control: self.control.clone(),
+                            receiver: self.event_bus.listen_bounded(CLIENT_QUEUE_SIZE),
+                            port,
+                        };
+                        thread::spawn(move|| {
+                            client.run(stream);
+                        });
+                    },
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(WAKE_MILLISECONDS));
+                    },
+                    Err(e) => {
+                        error!("Error accepting output connection. {e}");
+                        thread::sleep(Duration::from_millis(WAKE_MILLISECONDS));
+                    }
+                }
+            }
+        }
+        info!("Output server thread stopping.");
+    }
+
+    fn running(&self) -> bool {
+        match self.keepalive.lock() {
+            Ok(ka) => *ka,
+            Err(_) => false,
+        }
+    }
+}
+
+struct OutputClient {
+    keepalive: Arc<Mutex<bool>>,
+    sqlite: Arc<Mutex<sqlite::SQLite>>,
+    control: Arc<Mutex<Control>>,
+    receiver: Receiver<Arc<Event>>,
+    port: u16,
+}
+
+impl OutputClient {
+    fn run(&self, mut stream: TcpStream) {
+        if let Err(e) = stream.set_nonblocking(false)
+            .and_then(|_| stream.set_read_timeout(Some(Duration::from_millis(WAKE_MILLISECONDS))))
+            .and_then(|_| stream.set_write_timeout(Some(Duration::from_secs(WRITE_TIMEOUT_SECONDS)))) {
+            error!("Error setting output connection timeouts. {e}");
+            return
+        }
+        let mut format = match self.control.lock() {
+            Ok(c) => c.output_format.clone(),
+            Err(_) => return,
+        };
+        let mut pending: Vec<u8> = Vec::new();
+        let mut buf = [0u8; 256];
+        'client: loop {
+            // drop the connection when the server is turned off or moved to another port
+            match (self.keepalive.lock(), self.control.lock()) {
+                (Ok(ka), Ok(c)) if *ka && c.enable_output && c.output_port == self.port => {},
+                _ => break,
+            }
+            match stream.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    pending.extend_from_slice(&buf[..n]);
+                    while let Some(ix) = pending.iter().position(|b| *b == b'\n' || *b == b'\r') {
+                        let line: Vec<u8> = pending.drain(..=ix).collect();
+                        let line = String::from_utf8_lossy(&line);
+                        let line = line.trim();
+                        if line.is_empty() {
+                            continue;
+                        }
+                        let ok = match parse_command(line) {
+                            Some(Command::Rewind { start, end }) => self.rewind(&mut stream, &format, start, end),
+                            Some(Command::Format(f)) => {
+                                format = f;
+                                true
+                            },
+                            None => stream.write_all(format!("ERROR unknown command {line}\r\n").as_bytes()).is_ok(),
+                        };
+                        if !ok {
+                            break 'client;
+                        }
+                    }
+                    if pending.len() > MAX_COMMAND_LENGTH {
+                        pending.clear();
+                    }
+                },
+                Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {},
+                Err(_) => break,
+            }
+            loop {
+                match self.receiver.try_recv() {
+                    Ok(event) => {
+                        if let Event::Reads(reads) = &*event {
+                            if !write_reads(&mut stream, reads, &format, false) {
+                                break 'client;
+                            }
+                        }
+                    },
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        warn!("Output connection is not keeping up with reads, disconnecting.");
+                        break 'client;
+                    },
+                }
+            }
+        }
+        _ = stream.shutdown(Shutdown::Both);
+        info!("Output connection closed.");
+    }
+
+    fn rewind(&self, stream: &mut TcpStream, format: &str, start: i64, end: i64) -> bool {
+        let mut reads = match self.sqlite.lock() {
+            Ok(sq) => match sq.get_reads(start, end) {
+                Ok(r) => r,
+                Err(e) => {
+                    error!("Error getting reads to rewind. {e}");
+                    return stream.write_all(b"ERROR unable to get reads\r\n").is_ok()
+                }
+            },
+            Err(_) => return false,
+        };
+        reads.sort_by_key(|r| (r.seconds(), r.milliseconds()));
+        write_reads(stream, &reads, format, true)
+    }
+}
+
+fn write_reads(stream: &mut TcpStream, reads: &[read::Read], format: &str, rewind: bool) -> bool {
+    let offset = Local::now().offset().local_minus_utc() as i64;
+    let mut output = String::new();
+    for read in reads.iter().filter(|r| r.ident_type() == read::READ_IDENT_TYPE_CHIP) {
+        output.push_str(&format_read(read, format, rewind, offset));
+        output.push_str("\r\n");
+    }
+    output.is_empty() || stream.write_all(output.as_bytes()).is_ok()
+}
+
+// Formats a read as a single line without the line ending.  Times are sent in local time
+// since that's what the software expects from a reader.
+pub fn format_read(read: &read::Read, format: &str, rewind: bool, utc_offset: i64) -> String {
+    let local_seconds = read.seconds() as i64 + utc_offset;
+    match format {
+        OUTPUT_FORMAT_CSV => {
+            let time = match NaiveDateTime::from_timestamp_opt(local_seconds, read.milliseconds() * 1_000_000) {
+                Some(t) => t.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+                None => String::from("0000-00-00 00:00:00.000"),
+            };
+            format!("{},{},{}", read.chip(), time, read.antenna())
+        },
+        _ => format!(
+            "0,{},{},{},{},{},{},1,{}",
+            read.chip(),
+            local_seconds - ULTRA_EPOCH_OFFSET,
+            read.milliseconds(),
+            read.antenna(),
+            rssi(read.rssi()),
+            if rewind { 1 } else { 0 },
+            read.id(),
+        ),
+    }
+}
+
+// Readers report rssi with units attached, only the number is sent.
+fn rssi(value: &str) -> i64 {
+    let value = value.trim();
+    let end = value.char_indices()
+        .find(|(ix, c)| !(c.is_ascii_digit() || (*ix == 0 && (*c == '-' || *c == '+'))))
+        .map_or(value.len(), |(ix, _)| ix);
+    value[..end].parse().unwrap_or(0)
+}
+
+// Commands are case insensitive:
+//   REWIND                 - every saved read
+//   REWIND <from> <to>     - reads between the two times
+//   SINCE <time>           - reads from the time on
+//   FORMAT <ultra|csv>     - changes the format for this connection
+pub fn parse_command(line: &str) -> Option<Command> {
+    let mut parts = line.split_whitespace();
+    let command = parts.next()?.to_uppercase();
+    let args: Vec<&str> = parts.collect();
+    match command.as_str() {
+        COMMAND_REWIND => {
+            if args.is_empty() {
+                return Some(Command::Rewind { start: 0, end: i64::MAX })
+            }
+            let (start, end) = split_times(&args)?;
+            Some(Command::Rewind { start: parse_time(&start)?, end: parse_time(&end)? })
+        },
+        COMMAND_SINCE => Some(Command::Rewind { start: parse_time(&args.join(" "))?, end: i64::MAX }),
+        COMMAND_FORMAT => match args.first()?.to_lowercase().as_str() {
+            OUTPUT_FORMAT_ULTRA => Some(Command::Format(String::from(OUTPUT_FORMAT_ULTRA))),
+            OUTPUT_FORMAT_CSV => Some(Command::Format(String::from(OUTPUT_FORMAT_CSV))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Times may be a single value or a date and time separated by a space, so there
+// are either two or four arguments.
+fn split_times(args: &[&str]) -> Option<(String, String)> {
+    match args.len() {
+        2 => Some((String::from(args[0]), String::from(args[1]))),
+        4 => Some((args[..2].join(" "), args[2..].join(" "))),
+        _ => None,
+    }
+}
+
+// Accepts unix seconds, a local "YYYY-MM-DD HH:MM:SS" or a local "HH:MM:SS" for today.
+pub fn parse_time(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<i64>() {
+        return Some(seconds)
+    }
+    let naive = match NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        Ok(dt) => dt,
+        Err(_) => {
+            let time = NaiveTime::parse_from_str(value, "%H:%M:%S").ok()?;
+            let today: NaiveDate = Local::now().date_naive();
+            today.and_time(time)
+        }
+    };
+    Local.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp())
+}
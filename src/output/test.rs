@@ -0,0 +1,56 @@
+use crate::objects::read::{self, Read};
+
+use super::{format_read, parse_command, parse_time, Command, OUTPUT_FORMAT_CSV, OUTPUT_FORMAT_ULTRA, ULTRA_EPOCH_OFFSET};
+
+fn make_read(rssi: &str) -> Read {
+    Read::new(
+        12,
+        String::from("1001"),
+        1_700_000_000,
+        250,
+        1_700_000_000,
+        250,
+        2,
+        String::from("reader"),
+        String::from(rssi),
+        read::READ_STATUS_UNUSED,
+        read::READ_UPLOADED_FALSE,
+    )
+}
+
+#[test]
+fn test_format_read_ultra() {
+    let seconds = 1_700_000_000 - ULTRA_EPOCH_OFFSET;
+    assert_eq!(format!("0,1001,{seconds},250,2,-45,0,1,12"), format_read(&make_read("-45dBm"), OUTPUT_FORMAT_ULTRA, false, 0));
+    assert_eq!(format!("0,1001,{},250,2,0,1,1,12", seconds + 3600), format_read(&make_read(""), OUTPUT_FORMAT_ULTRA, true, 3600));
+    // anything we don't know about gets the ultra format
+    assert_eq!(format!("0,1001,{seconds},250,2,30,0,1,12"), format_read(&make_read("30"), "unknown", false, 0));
+}
+
+#[test]
+fn test_format_read_csv() {
+    assert_eq!("1001,2023-11-14 22:13:20.250,2", format_read(&make_read("-45"), OUTPUT_FORMAT_CSV, false, 0));
+    assert_eq!("1001,2023-11-14 15:13:20.250,2", format_read(&make_read("-45"), OUTPUT_FORMAT_CSV, true, -7 * 3600));
+}
+
+#[test]
+fn test_parse_command() {
+    assert_eq!(Some(Command::Rewind { start: 0, end: i64::MAX }), parse_command("rewind"));
+    assert_eq!(Some(Command::Rewind { start: 100, end: 200 }), parse_command("REWIND 100 200"));
+    assert_eq!(Some(Command::Rewind { start: 100, end: i64::MAX }), parse_command("since 100"));
+    assert_eq!(Some(Command::Format(String::from(OUTPUT_FORMAT_CSV))), parse_command("FORMAT CSV"));
+    assert_eq!(None, parse_command("FORMAT xml"));
+    assert_eq!(None, parse_command("REWIND 100"));
+    assert_eq!(None, parse_command("SINCE later"));
+    assert_eq!(None, parse_command("START"));
+    assert!(matches!(parse_command("REWIND 2024-05-01 08:00:00 2024-05-01 09:00:00"), Some(Command::Rewind { start, end }) if end - start == 3600));
+}
+
+#[test]
+fn test_parse_time() {
+    assert_eq!(Some(1_700_000_000), parse_time("1700000000"));
+    assert!(parse_time("2024-05-01 08:00:00").is_some());
+    assert!(parse_time("08:00:00").is_some());
+    assert_eq!(None, parse_time("2024-05-01"));
+    assert_eq!(None, parse_time(""));
+}
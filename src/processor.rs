@@ -1,10 +1,103 @@
-use std::{sync::{Arc, Mutex, Condvar}, net::TcpStream, collections::HashMap, str::FromStr};
+use std::{sync::{Arc, Mutex, Condvar}, collections::HashMap, str::FromStr};
+use log::{error, info, warn};
 
-use crate::{control::{socket::{self, MAX_CONNECTED}, SETTING_SIGHTING_PERIOD}, database::{sqlite, Database}, defaults::DEFAULT_SIGHTING_PERIOD, objects::{bibchip, participant, read, sighting}};
+use crate::{control::{SETTING_SIGHTING_PERIOD, SETTING_UNKNOWN_CHIP_POLICY}, database::{sqlite, Database, DBError}, defaults::{DEFAULT_SIGHTING_PERIOD, DEFAULT_UNKNOWN_CHIP_POLICY}, events::{self, filter::ParticipantLookup, EventBus}, objects::{bibchip, participant, read, sighting}};
+
+use self::chips::ChipMap;
+
+pub mod chips;
+pub mod watch;
+
+// Values used for the participant we create when we see a chip that isn't assigned to anyone.
+pub const PLACEHOLDER_FIRST: &str = "J";
+pub const PLACEHOLDER_LAST: &str = "Doe";
+pub const PLACEHOLDER_BIRTHDATE: &str = "1/1/1900";
+pub const PLACEHOLDER_GENDER: &str = "U";
+pub const PLACEHOLDER_AGE_GROUP: &str = "0-110";
+pub const PLACEHOLDER_DISTANCE: &str = "Unknown";
+
+// What we do with reads from a chip nobody has been given.
+pub const UNKNOWN_CHIP_PLACEHOLDER: &str = "placeholder";
+pub const UNKNOWN_CHIP_IGNORE: &str = "ignore";
+pub const UNKNOWN_CHIP_HOLD: &str = "hold";
+
+pub fn is_unknown_chip_policy(policy: &str) -> bool {
+    matches!(policy, UNKNOWN_CHIP_PLACEHOLDER | UNKNOWN_CHIP_IGNORE | UNKNOWN_CHIP_HOLD)
+}
+
+// The status reads from unknown chips are left with, or None if we make a placeholder for them.
+fn unknown_chip_status(policy: &str) -> Option<u8> {
+    match policy {
+        UNKNOWN_CHIP_IGNORE => Some(read::READ_STATUS_IGNORED),
+        UNKNOWN_CHIP_HOLD => Some(read::READ_STATUS_HELD),
+        _ => None,
+    }
+}
+
+pub fn is_placeholder(part: &participant::Participant) -> bool {
+    part.first() == PLACEHOLDER_FIRST
+    && part.last() == PLACEHOLDER_LAST
+    && part.birthdate() == PLACEHOLDER_BIRTHDATE
+    && part.distance() == PLACEHOLDER_DISTANCE
+}
+
+// What the sightings processor keeps between runs so it only has to go back to the database for
+// new reads, or when someone changes the participants, bibchips or reads out from under it.
+#[derive(Default)]
+struct Indexes {
+    participants_generation: Option<u64>,
+    reads_generation: Option<u64>,
+    chips: ChipMap,
+    bibchips: Vec<bibchip::BibChip>,
+    participants: HashMap<String, participant::Participant>,
+    // The last read we've used for each person.
+    used: HashMap<String, read::Read>,
+    // Every unused read at or before this id has been processed.
+    watermark: u64,
+}
+
+impl Indexes {
+    // Reloads anything that's changed since we last looked, returns whether the participants did.
+    fn refresh(&mut self, sq: &sqlite::SQLite) -> Result<bool, DBError> {
+        let participants_changed = self.participants_generation != Some(sq.participants_generation());
+        if participants_changed {
+            self.bibchips = sq.get_bibchips()?;
+            self.chips = ChipMap::new(&self.bibchips);
+            self.participants.clear();
+            for part in sq.get_participants()? {
+                self.participants.insert(String::from(part.bib()), part);
+            }
+            self.participants_generation = Some(sq.participants_generation());
+        }
+        // who a used read belongs to depends on the chips so those changing means starting over too
+        if participants_changed || self.reads_generation != Some(sq.reads_generation()) {
+            self.used.clear();
+            for read in sq.get_useful_reads()? {
+                if read.status() != read::READ_STATUS_USED {
+                    continue;
+                }
+                let bib = match self.chips.person(&read) {
+                    Some(bib) => bib,
+                    None => continue,
+                };
+                let newer = match self.used.get(&bib) {
+                    Some(last) => last.seconds() < read.seconds() ||
+                        (last.seconds() == read.seconds() && last.milliseconds() < read.milliseconds()),
+                    None => true,
+                };
+                if newer {
+                    self.used.insert(bib, read);
+                }
+            }
+            self.watermark = 0;
+            self.reads_generation = Some(sq.reads_generation());
+        }
+        Ok(participants_changed)
+    }
+}
 
 pub struct SightingsProcessor {
-    control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
-    sighting_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+    event_bus: EventBus,
     sqlite: Arc<Mutex<sqlite::SQLite>>,
     keepalive: Arc<Mutex<bool>>,
     running: Arc<Mutex<bool>>,
@@ -13,14 +106,12 @@ pub struct SightingsProcessor {
 
 impl SightingsProcessor {
     pub fn new(
-        control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED+1]>>,
-        sighting_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+        event_bus: EventBus,
         sqlite: Arc<Mutex<sqlite::SQLite>>,
         keepalive: Arc<Mutex<bool>>
     ) -> SightingsProcessor {
         return SightingsProcessor {
-            control_sockets,
-            sighting_repeaters,
+            event_bus,
             sqlite,
             keepalive,
             running: Arc::new(Mutex::new(false)),
@@ -36,7 +127,7 @@ impl SightingsProcessor {
     }
 
     pub fn stop(&self) {
-        println!("Sending shutdown command to sightings processor.");
+        info!("Sending shutdown command to sightings processor.");
         if let Ok(mut run) = self.running.lock() {
             *run = false;
         }
@@ -55,20 +146,21 @@ impl SightingsProcessor {
         } else {
             return
         }
-        println!("Starting sightings processor.");
+        info!("Starting sightings processor.");
+        let mut indexes = Indexes::default();
         'main: loop {
             if let Ok(ka) = self.keepalive.lock() {
                 if *ka == false {
-                    println!("Sightings processor told to quit. /1/");
+                    info!("Sightings processor told to quit. /1/");
                     break;
                 }
             } else {
-                println!("Error getting keep alive mutex. Exiting.");
+                error!("Error getting keep alive mutex. Exiting.");
                 break;
             }
             if let Ok(run) = self.running.lock() {
                 if *run == false {
-                    println!("Sightings processor told to quit. /2/");
+                    info!("Sightings processor told to quit. /2/");
                     break;
                 }
             }
@@ -80,87 +172,56 @@ impl SightingsProcessor {
                 Ok(_) => {
                     // once we've been notified, keep processing reads until there's nothing left to do
                     loop {
-                        let reads: Vec<read::Read>;
-                        let parts: Vec<participant::Participant>;
-                        let bibchips: Vec<bibchip::BibChip>;
+                        let mut unused: Vec<read::Read>;
+                        let mut period = DEFAULT_SIGHTING_PERIOD as u64;
+                        let mut policy = String::from(DEFAULT_UNKNOWN_CHIP_POLICY);
                         if let Ok(sq) = self.sqlite.lock() {
-                            reads = match sq.get_useful_reads() {
-                                Ok(r) => r,
+                            match indexes.refresh(&sq) {
+                                Ok(true) => {
+                                    // let the event bus know who is who so it can filter reads for subscribers
+                                    self.event_bus.set_participant_lookup(ParticipantLookup::new(&indexes.bibchips, &indexes.participants));
+                                },
+                                Ok(false) => {},
                                 Err(e) => {
-                                    println!("error getting useful reads: {e}");
+                                    error!("error refreshing sightings indexes: {e}");
                                     break 'main;
                                 }
-                            };
-                            parts = match sq.get_participants() {
-                                Ok(p) => p,
+                            }
+                            unused = match sq.get_unused_reads_after(indexes.watermark) {
+                                Ok(r) => r,
                                 Err(e) => {
-                                    println!("error getting participants: {e}");
+                                    error!("error getting unused reads: {e}");
                                     break 'main;
                                 }
                             };
-                            bibchips = match sq.get_bibchips() {
-                                Ok(b) => b,
+                            match sq.get_setting(SETTING_SIGHTING_PERIOD) {
+                                Ok(setting) => {
+                                    period = u64::from_str(setting.value()).unwrap();
+                                }
                                 Err(e) => {
-                                    println!("error getting bibchips: {e}");
-                                    break 'main;
+                                    error!("error getting sighting period: {e}");
                                 }
-                            };
-                        } else {
-                            println!("error getting sqlite database lock");
-                            break 'main;
-                        }
-                        // sort values into unused reads and the last read we've seen from a person
-                        let mut unused: Vec<read::Read> = Vec::new();
-                        let mut used: HashMap<String, read::Read> = HashMap::new();
-                        // create a hashmap for changing bibs to chips
-                        let mut bib_chip_map: HashMap<String, String> = HashMap::new();
-                        // make a map of participants based upon their chip
-                        let mut part_map: HashMap<String, participant::Participant> = HashMap::new();
-                        // update the bibchip map separately from the participants map
-                        for bc in bibchips {
-                            bib_chip_map.insert(String::from(bc.bib()), String::from(bc.chip()));
-                        }
-                        for part in parts {
-                            // verify we actually have a bib -> chip association
-                            if bib_chip_map.contains_key(part.bib()) {
-                                part_map.insert(bib_chip_map[part.bib()].clone(), part);
                             }
-                        }
-                        for read in reads {
-                            if read.status() == read::READ_STATUS_UNUSED {
-                                unused.push(read);
-                            } else if read.status() == read::READ_STATUS_USED {
-                                // if the identifier type is a bib we need to get the chip from our bibChipMap by
-                                // the bib stored in the chip() value of read
-                                let mut chip = String::from(read.chip());
-                                match read.ident_type() {
-                                    read::READ_IDENT_TYPE_BIB => {
-                                        let bib = String::from(read.chip());
-                                        if bib_chip_map.contains_key(&bib) {
-                                            chip = bib_chip_map[&bib].clone();
-                                        }
-                                    }
-                                    read::READ_IDENT_TYPE_CHIP => {}
-                                    e => {
-                                        println!("Error occurred during sightings processing. Unknown read identifier type. {e}");
-                                    }
+                            match sq.get_setting(SETTING_UNKNOWN_CHIP_POLICY) {
+                                Ok(setting) => {
+                                    policy = String::from(setting.value());
                                 }
-                                if used.contains_key(&chip) {
-                                    let last = &used[&chip];
-                                    if last.seconds() < read.seconds() ||
-                                        (last.seconds() == read.seconds() && last.milliseconds() < read.milliseconds())
-                                    {
-                                        used.insert(chip, read);
-                                    }
-                                } else {
-                                    used.insert(chip, read);
+                                Err(e) => {
+                                    error!("error getting unknown chip policy: {e}");
                                 }
                             }
+                        } else {
+                            error!("error getting sqlite database lock");
+                            break 'main;
                         }
                         // if nothing left to process, we can exit
                         if unused.len() == 0 {
                             break;
                         }
+                        // reads come back in the order they were saved so the last one is the newest
+                        if let Some(last) = unused.last() {
+                            indexes.watermark = last.id();
+                        }
                         // sort all the unused reads by second
                         unused.sort_by(|a, b|
                             if a.seconds() == b.seconds() {
@@ -169,57 +230,48 @@ impl SightingsProcessor {
                                 a.seconds().cmp(&b.seconds())
                             }
                         );
-                        let mut period = DEFAULT_SIGHTING_PERIOD as u64;
-                        if let Ok(sq) = self.sqlite.lock() {
-                            match sq.get_setting(SETTING_SIGHTING_PERIOD) {
-                                Ok(setting) => {
-                                    period = u64::from_str(setting.value()).unwrap();
-                                }
-                                Err(e) => {
-                                    println!("error getting sighting period: {e}");
-                                }
-                            }
-                        }
                         // these vecs need to be added to the database
                         let mut upd_reads: Vec<read::Read> = Vec::new();
                         let mut upd_parts: Vec<participant::Participant> = Vec::new();
                         let mut upd_bibchips: Vec<bibchip::BibChip> = Vec::new();
                         let mut sightings: Vec<sighting::Sighting> = Vec::new();
                         for mut read in unused {
-                            // if the identifier type is a bib we need to get the chip from our bibChipMap by
-                            // the bib stored in the chip() value of read
-                            let mut chip = String::from(read.chip());
-                            match read.ident_type() {
-                                read::READ_IDENT_TYPE_BIB => {
-                                    let bib = String::from(read.chip());
-                                    if bib_chip_map.contains_key(&bib) {
-                                        chip = bib_chip_map[&bib].clone();
+                            // a chip is only unknown if nobody has been given it, a bib we just don't
+                            // have the participant for yet still gets a placeholder
+                            let bib = match indexes.chips.person(&read) {
+                                Some(bib) => bib,
+                                None => {
+                                    // leave it for the unknown chip report instead of making someone up
+                                    if let Some(status) = unknown_chip_status(&policy) {
+                                        read.set_status(status);
+                                        upd_reads.push(read);
+                                        continue;
                                     }
+                                    // the placeholder for a chip nobody has is named after the chip
+                                    let bc = bibchip::BibChip::new(String::from(read.chip()), String::from(read.chip()));
+                                    indexes.chips.add(bc.clone());
+                                    upd_bibchips.push(bc);
+                                    String::from(read.chip())
                                 }
-                                read::READ_IDENT_TYPE_CHIP => {}
-                                e => {
-                                    println!("Error occurred during sightings processing. Unknown read identifier type. {e}");
-                                }
-                            }
-                            if part_map.contains_key(&chip) == false {
+                            };
+                            if indexes.participants.contains_key(&bib) == false {
                                 let new_part = participant::Participant::new(
                                     0,
-                                    chip.clone(),
-                                    String::from("J"),
-                                    String::from("Doe"),
-                                    String::from("1/1/1900"),
-                                    String::from("U"),
-                                    String::from("0-110"),
-                                    String::from("Unknown"),
+                                    bib.clone(),
+                                    String::from(PLACEHOLDER_FIRST),
+                                    String::from(PLACEHOLDER_LAST),
+                                    String::from(PLACEHOLDER_BIRTHDATE),
+                                    String::from(PLACEHOLDER_GENDER),
+                                    String::from(PLACEHOLDER_AGE_GROUP),
+                                    String::from(PLACEHOLDER_DISTANCE),
                                     false
                                 );
-                                upd_bibchips.push(bibchip::BibChip::new(String::from(&chip), String::from(&chip)));
                                 upd_parts.push(new_part.clone());
-                                part_map.insert(chip.clone(), new_part);
+                                indexes.participants.insert(bib.clone(), new_part);
                             }
                             // check if we're within the period where we should ignore the read
-                            if used.contains_key(&chip) {
-                                let tmp = &used[&chip];
+                            if indexes.used.contains_key(&bib) {
+                                let tmp = &indexes.used[&bib];
                                 // not out of ignore period
                                 if tmp.seconds() + period > read.seconds() {
                                     read.set_status(read::READ_STATUS_TOO_SOON);
@@ -234,9 +286,9 @@ impl SightingsProcessor {
                                     read.set_status(read::READ_STATUS_USED);
                                     upd_reads.push(read.clone());
                                     // update the map
-                                    used.insert(chip.clone(), read.clone());
+                                    indexes.used.insert(bib.clone(), read.clone());
                                     // get the participant
-                                    let part = &part_map[&chip];
+                                    let part = &indexes.participants[&bib];
                                     sightings.push(sighting::Sighting {
                                         participant: part.clone(),
                                         read
@@ -248,9 +300,9 @@ impl SightingsProcessor {
                                 read.set_status(read::READ_STATUS_USED);
                                 upd_reads.push(read.clone());
                                 // update the map
-                                used.insert(chip.clone(), read.clone());
+                                indexes.used.insert(bib.clone(), read.clone());
                                 // get the participant
-                                let part = &part_map[&chip];
+                                let part = &indexes.participants[&bib];
                                 sightings.push(sighting::Sighting {
                                     participant: part.clone(),
                                     read
@@ -258,56 +310,48 @@ impl SightingsProcessor {
                             }
                         }
                         if let Ok(mut sq) = self.sqlite.lock() {
-                            if upd_parts.len() > 0 {
+                            if upd_parts.len() > 0 || upd_bibchips.len() > 0 {
+                                // only our own changes can be folded in, anyone else's means a reload
+                                let up_to_date = indexes.participants_generation == Some(sq.participants_generation());
                                 match sq.add_participants(&upd_parts) {
                                     Ok(_) => (),
                                     Err(e) => {
-                                        println!("error adding participants: {e}");
+                                        error!("error adding participants: {e}");
                                         break 'main;
                                     }
                                 }
                                 match sq.add_bibchips(&upd_bibchips) {
                                     Ok(_) => (),
                                     Err(e) => {
-                                        println!("error adding bibchips: {e}");
+                                        error!("error adding bibchips: {e}");
                                         break 'main;
                                     }
                                 }
                                 let participants = match sq.get_participants() {
                                     Ok(p) => p,
                                     Err(e) => {
-                                        println!("error getting participants: {e}");
-                                        break 'main;
-                                    }
-                                };
-                                let bibchips = match sq.get_bibchips() {
-                                    Ok(b) => b,
-                                    Err(e) => {
-                                        println!("error getting bibchips: {e}");
+                                        error!("error getting participants: {e}");
                                         break 'main;
                                     }
                                 };
-                                // update the bib_chip_map
-                                for bc in bibchips {
-                                    bib_chip_map.insert(String::from(bc.bib()), String::from(bc.chip()));
-                                }
                                 // update part map so we have id's for any participants we added
                                 for part in participants {
-                                    // verify we actually have a bib -> chip association
-                                    if bib_chip_map.contains_key(part.bib()) {
-                                        part_map.insert(bib_chip_map[part.bib()].clone(), part);
-                                    }
+                                    indexes.participants.insert(String::from(part.bib()), part);
+                                }
+                                indexes.bibchips.extend(upd_bibchips);
+                                if up_to_date {
+                                    indexes.participants_generation = Some(sq.participants_generation());
                                 }
                                 // update all the sightings
                                 let tmp_sightings = Vec::from(sightings);
                                 sightings = Vec::new();
                                 for mut sight in tmp_sightings {
-                                    let chip = String::from(sight.read.chip());
-                                    if part_map.contains_key(&chip) {
-                                        sight.participant = part_map[&chip].clone();
+                                    let bib = String::from(sight.participant.bib());
+                                    if indexes.participants.contains_key(&bib) {
+                                        sight.participant = indexes.participants[&bib].clone();
                                         sightings.push(sight);
                                     } else {
-                                        println!("participant not found somehow...");
+                                        warn!("participant not found somehow...");
                                         break 'main;
                                     }
                                 }
@@ -315,52 +359,30 @@ impl SightingsProcessor {
                             match sq.update_reads_status(&upd_reads) {
                                 Ok(_) => (),
                                 Err(e) => {
-                                    println!("error updating read statuses: {e}");
+                                    error!("error updating read statuses: {e}");
                                     break 'main;
                                 }
                             }
                             match sq.save_sightings(&sightings) {
                                 Ok(_) => (),
                                 Err(e) => {
-                                    println!("error saving sightings: {e}");
+                                    error!("error saving sightings: {e}");
                                     break 'main;
                                 }
                             }
-                            let bibchips = match sq.get_bibchips() {
-                                Ok(b) => b,
-                                Err(e) => {
-                                    println!("error getting bibchips: {e}");
-                                    break 'main;
-                                }
-                            };
                             // send sightings
-                            if let Ok(sockets) = self.control_sockets.lock() {
-                                if let Ok(repeaters) = self.sighting_repeaters.lock() {
-                                    for ix in 0..MAX_CONNECTED {
-                                        match &sockets[ix] {
-                                            Some(sock) => {
-                                                if repeaters[ix] == true {
-                                                    //println!("Sending sightings to subscribed socket {ix}.");
-                                                    socket::write_sightings(&sock, &sightings, &bibchips);
-                                                }
-                                            },
-                                            None => ()
-                                        }
-                                    }
-                                } else {
-                                    println!("error getting repeaters mutex");
-                                }
-                            } else {
-                                println!("error getting control sockets mutex");
-                            }
+                            self.event_bus.publish(events::Event::Sightings {
+                                sightings,
+                                bibchips: indexes.bibchips.clone(),
+                            });
                         } else {
-                            println!("error getting database to update sightings");
+                            error!("error getting database to update sightings");
                             break 'main;
                         }
                     }
                 },
                 Err(e) => {
-                    println!("unable to aquire semaphore: {e}");
+                    warn!("unable to aquire semaphore: {e}");
                     break;
                 }
             }
@@ -413,7 +435,7 @@ impl ReadSaver {
 
 
     pub fn stop(&self) {
-        println!("Sending shutdown command to read saver.");
+        info!("Sending shutdown command to read saver.");
         if let Ok(mut run) = self.running.lock() {
             *run = false;
         }
@@ -432,20 +454,20 @@ impl ReadSaver {
         } else {
             return
         }
-        println!("Starting read saver.");
+        info!("Starting read saver.");
         loop {
             if let Ok(ka) = self.keepalive.lock() {
                 if *ka == false {
-                    println!("Sightings processor told to quit. /1/");
+                    info!("Sightings processor told to quit. /1/");
                     break;
                 }
             } else {
-                println!("Error getting keep alive mutex. Exiting.");
+                error!("Error getting keep alive mutex. Exiting.");
                 break;
             }
             if let Ok(run) = self.running.lock() {
                 if *run == false {
-                    println!("Sightings processor told to quit. /2/");
+                    info!("Sightings processor told to quit. /2/");
                     break;
                 }
             }
@@ -467,7 +489,7 @@ impl ReadSaver {
                             match db.save_reads(&tmp_reads) {
                                 Ok(_num) => { },
                                 Err(e) => {
-                                    println!("Error saving reads. {e}");
+                                    error!("Error saving reads. {e}");
                                     if let Ok(mut reads) = self.reads.lock() {
                                         reads.append(&mut tmp_reads);
                                     }
@@ -477,7 +499,7 @@ impl ReadSaver {
                     }
                 },
                 Err(e) => {
-                    println!("unable to aquire semaphore: {e}");
+                    warn!("unable to aquire semaphore: {e}");
                     break;
                 }
             }
@@ -494,10 +516,10 @@ impl ReadSaver {
                             //println!("Saved {num} reads.");
                             reads.clear();
                         },
-                        Err(e) => println!("Error saving reads. {e}"),
+                        Err(e) => error!("Error saving reads. {e}"),
                     }
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
\ No newline at end of file
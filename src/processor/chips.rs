@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use log::error;
+
+use crate::objects::{bibchip, read};
+
+#[cfg(test)]
+pub mod test;
+
+// Who each chip belonged to and when, so a read goes to whoever was wearing the chip at the
+// time it was read.  A bib can have as many chips as it likes.
+#[derive(Debug, Default)]
+pub struct ChipMap {
+    chips: HashMap<String, Vec<bibchip::BibChip>>,
+}
+
+impl ChipMap {
+    pub fn new(bibchips: &[bibchip::BibChip]) -> ChipMap {
+        let mut output = ChipMap::default();
+        for bc in bibchips {
+            output.chips.entry(String::from(bc.chip())).or_default().push(bc.clone());
+        }
+        output
+    }
+
+    pub fn add(&mut self, bibchip: bibchip::BibChip) {
+        self.chips.entry(String::from(bibchip.chip())).or_default().push(bibchip);
+    }
+
+    // When entries overlap the one that started last wins.
+    pub fn bib(&self, chip: &str, seconds: i64) -> Option<&str> {
+        self.chips.get(chip)?
+            .iter()
+            .filter(|bc| bc.covers(seconds))
+            .max_by_key(|bc| bc.effective_from())
+            .map(|bc| bc.bib())
+    }
+
+    // The bib a read belongs to.  Reads saved with a bib already have it, a chip nobody has
+    // doesn't belong to anyone.
+    pub fn person(&self, read: &read::Read) -> Option<String> {
+        match read.ident_type() {
+            read::READ_IDENT_TYPE_BIB => Some(String::from(read.chip())),
+            read::READ_IDENT_TYPE_CHIP => self.bib(read.chip(), read.seconds() as i64).map(String::from),
+            e => {
+                error!("Error occurred during sightings processing. Unknown read identifier type. {e}");
+                None
+            }
+        }
+    }
+}
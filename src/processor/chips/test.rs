@@ -0,0 +1,57 @@
+use crate::objects::{bibchip::BibChip, read::Read};
+
+use super::ChipMap;
+
+fn make_read(chip: &str, seconds: u64) -> Read {
+    Read::new(
+        0,
+        String::from(chip),
+        seconds,
+        0,
+        seconds,
+        0,
+        1,
+        String::from("Finish"),
+        String::from("-50"),
+        0,
+        0,
+    )
+}
+
+#[test]
+fn test_multiple_chips() {
+    let chips = ChipMap::new(&[
+        BibChip::new(String::from("104"), String::from("1000")),
+        BibChip::new(String::from("104"), String::from("1001")),
+        BibChip::new(String::from("105"), String::from("1002")),
+    ]);
+    assert_eq!(Some("104"), chips.bib("1000", 100));
+    assert_eq!(Some("104"), chips.bib("1001", 100));
+    assert_eq!(Some("105"), chips.bib("1002", 100));
+    assert_eq!(None, chips.bib("1003", 100));
+    assert_eq!(Some(String::from("104")), chips.person(&make_read("1001", 100)));
+    // unknown chips don't belong to anyone
+    assert_eq!(None, chips.person(&make_read("1003", 100)));
+}
+
+#[test]
+fn test_chip_swap() {
+    let chips = ChipMap::new(&[
+        BibChip::new(String::from("104"), String::from("1000")).with_effective(0, 5000),
+        BibChip::new(String::from("105"), String::from("1000")).with_effective(5000, 0),
+        BibChip::new(String::from("106"), String::from("1001")).with_effective(2000, 3000),
+    ]);
+    assert_eq!(Some("104"), chips.bib("1000", 4999));
+    assert_eq!(Some("105"), chips.bib("1000", 5000));
+    assert_eq!(Some(String::from("105")), chips.person(&make_read("1000", 9000)));
+    assert_eq!(None, chips.bib("1001", 1999));
+    assert_eq!(Some("106"), chips.bib("1001", 2000));
+    assert_eq!(None, chips.bib("1001", 3000));
+    // overlapping entries go to the newer one
+    let chips = ChipMap::new(&[
+        BibChip::new(String::from("104"), String::from("1000")),
+        BibChip::new(String::from("105"), String::from("1000")).with_effective(5000, 0),
+    ]);
+    assert_eq!(Some("104"), chips.bib("1000", 4000));
+    assert_eq!(Some("105"), chips.bib("1000", 6000));
+}
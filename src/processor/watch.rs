@@ -0,0 +1,149 @@
+use std::{sync::{mpsc::{Receiver, RecvTimeoutError}, Arc, Mutex}, time::{Duration, SystemTime}};
+
+use chrono::{DateTime, Local, Utc};
+use log::{error, info};
+
+use crate::{control::sound::{SoundNotifier, SoundType}, database::{self, sqlite, Database}, events::{Event, EventBus}, notifier::{self, Notifier}, objects::{sighting, system_event, watched_bib::WatchedBib}};
+
+#[cfg(test)]
+pub mod test;
+
+pub const WAKE_SECONDS: u64 = 1;
+
+// Sightings for bibs on the watch list, along with the note they were saved with.
+pub fn watched_sightings(sightings: &[sighting::Sighting], watched: &[WatchedBib]) -> Vec<(sighting::Sighting, String)> {
+    sightings.iter().filter_map(|s| {
+        watched.iter()
+            .find(|w| w.bib() == s.participant.bib().trim())
+            .map(|w| (s.clone(), String::from(w.note())))
+    }).collect()
+}
+
+// The name we're allowed to put in an alert, anonymous participants are only a bib.
+pub fn participant_name(sighting: &sighting::Sighting) -> String {
+    if sighting.participant.anonymous() {
+        return String::new()
+    }
+    format!("{} {}", sighting.participant.first(), sighting.participant.last()).trim().to_string()
+}
+
+// Watches sightings for bibs someone asked to hear about, like a runner with a medical condition
+// or the race leaders, and lets everyone know through the sockets, the notifier and a sound.
+pub struct BibWatcher {
+    keepalive: Arc<Mutex<bool>>,
+    sqlite: Arc<Mutex<sqlite::SQLite>>,
+    receiver: Receiver<Arc<Event>>,
+    event_bus: EventBus,
+    notifier: Notifier,
+    sound: Arc<SoundNotifier>,
+}
+
+impl BibWatcher {
+    pub fn new(
+        keepalive: Arc<Mutex<bool>>,
+        sqlite: Arc<Mutex<sqlite::SQLite>>,
+        event_bus: EventBus,
+        notifier: Notifier,
+        sound: Arc<SoundNotifier>,
+    ) -> Self {
+        Self {
+            keepalive,
+            sqlite,
+            receiver: event_bus.listen(),
+            event_bus,
+            notifier,
+            sound,
+        }
+    }
+
+    pub fn run(&self) {
+        loop {
+            match self.keepalive.lock() {
+                Ok(ka) if *ka => {},
+                _ => break,
+            }
+            let mut sightings: Vec<sighting::Sighting> = Vec::new();
+            match self.receiver.recv_timeout(Duration::from_secs(WAKE_SECONDS)) {
+                Ok(event) => add_sightings(&mut sightings, &event),
+                Err(RecvTimeoutError::Timeout) => {},
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            while let Ok(event) = self.receiver.try_recv() {
+                add_sightings(&mut sightings, &event);
+            }
+            if sightings.is_empty() {
+                continue;
+            }
+            let watched = match self.sqlite.lock() {
+                Ok(sq) => match sq.get_watched_bibs() {
+                    Ok(w) => w,
+                    Err(e) => {
+                        error!("Error getting watched bibs. {e}");
+                        continue;
+                    }
+                },
+                Err(_) => break,
+            };
+            let found = watched_sightings(&sightings, &watched);
+            if found.is_empty() {
+                continue;
+            }
+            let mut alerted = false;
+            for (sighting, note) in found.iter() {
+                // sightings are worked out again on boot and when participants change, only
+                // alert the first time we see a bib on a read
+                let new = match self.sqlite.lock() {
+                    Ok(sq) => sq.save_watched_bib_alert(sighting.participant.bib(), sighting.read.id()),
+                    Err(_) => break,
+                };
+                match new {
+                    Ok(true) => {
+                        self.alert(sighting, note);
+                        alerted = true;
+                    },
+                    Ok(false) => {},
+                    Err(e) => error!("Error saving watched bib alert. {e}"),
+                }
+            }
+            if alerted {
+                self.sound.notify_custom(SoundType::WatchedBib);
+            }
+        }
+        info!("Bib watcher thread stopping.");
+    }
+
+    fn alert(&self, sighting: &sighting::Sighting, note: &str) {
+        let bib = String::from(sighting.participant.bib());
+        let reader = String::from(sighting.read.reader());
+        info!("Watched bib {bib} seen by {reader}.");
+        database::log_event(
+            &self.sqlite,
+            system_event::SEVERITY_WARNING,
+            system_event::SOURCE_PROCESSOR,
+            system_event::SYSTEM_EVENT_WATCHED_BIB,
+            serde_json::json!({
+                "bib": bib,
+                "reader": reader,
+                "note": note,
+            }),
+        );
+        let date_time: DateTime<Local> = SystemTime::now().into();
+        self.notifier.send_notification(notifier::Notification::WatchedBib {
+            bib,
+            participant: participant_name(sighting),
+            note: String::from(note),
+            reader,
+        }, format!("{}", date_time.format("%Y/%m/%d %T")));
+        self.event_bus.publish(Event::WatchedBib {
+            sighting: Box::new(sighting.clone()),
+            note: String::from(note),
+            time: Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string(),
+        });
+    }
+}
+
+fn add_sightings(sightings: &mut Vec<sighting::Sighting>, event: &Event) {
+    if let Event::Sightings { sightings: new, .. } = event {
+        sightings.extend(new.iter().cloned());
+    }
+}
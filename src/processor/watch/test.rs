@@ -0,0 +1,57 @@
+use crate::objects::{participant::Participant, read::Read, sighting::Sighting, watched_bib::WatchedBib};
+
+use super::{participant_name, watched_sightings};
+
+fn make_sighting(bib: &str, anonymous: bool) -> Sighting {
+    Sighting {
+        participant: Participant::new(
+            0,
+            String::from(bib),
+            String::from("John"),
+            String::from("Smith"),
+            String::from("1/1/1990"),
+            String::from("M"),
+            String::from("30-39"),
+            String::from("Marathon"),
+            anonymous,
+        ),
+        read: Read::new(
+            0,
+            String::from("1000"),
+            100,
+            0,
+            100,
+            0,
+            1,
+            String::from("Finish"),
+            String::from("-50"),
+            0,
+            0,
+        ),
+    }
+}
+
+#[test]
+fn test_watched_sightings() {
+    let sightings = vec![
+        make_sighting("1", false),
+        make_sighting("104", false),
+        make_sighting("2", false),
+    ];
+    let watched = vec![
+        WatchedBib::new(String::from("104"), String::from("diabetic")),
+        WatchedBib::new(String::from("3"), String::new()),
+    ];
+    let found = watched_sightings(&sightings, &watched);
+    assert_eq!(1, found.len());
+    assert_eq!("104", found[0].0.participant.bib());
+    assert_eq!("diabetic", found[0].1);
+    assert!(watched_sightings(&sightings, &[]).is_empty());
+    assert!(watched_sightings(&[], &watched).is_empty());
+}
+
+#[test]
+fn test_participant_name() {
+    assert_eq!("John Smith", participant_name(&make_sighting("104", false)));
+    assert_eq!("", participant_name(&make_sighting("104", true)));
+}
@@ -3,12 +3,13 @@ use std::{io::Write, net::TcpStream, sync::{self, Arc, Mutex}, thread::JoinHandl
 use reconnector::Reconnector;
 use serde::{Deserialize, Serialize};
 
-use crate::{control::{self, socket::MAX_CONNECTED, sound::SoundNotifier}, database::{sqlite, DBError}, notifier, processor, screen::CharacterDisplay};
+use crate::{control::{self, sound::SoundNotifier}, database::{sqlite, DBError}, events::EventBus, notifier, processor, screen::CharacterDisplay};
 
 pub mod zebra;
 pub mod auto_connect;
 pub mod reconnector;
 pub mod helpers;
+pub mod monitor;
 
 pub const READER_KIND_ZEBRA: &str = "ZEBRA";
 pub const READER_KIND_RFID: &str = "RFID";
@@ -71,9 +72,7 @@ pub struct Reader {
     pub status_retries: Arc<sync::Mutex<u16>>,
     
     #[serde(skip)]
-    control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
-    #[serde(skip)]
-    read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+    event_bus: EventBus,
     #[serde(skip)]
     sight_processor: Option<Arc<processor::SightingsProcessor>>,
     #[serde(skip)]
@@ -103,8 +102,7 @@ impl Reader {
             status: Arc::new(Mutex::new(ReaderStatus::Disconnected)),
             status_retries: Arc::new(Mutex::new(0)),
             auto_connect,
-            control_sockets: Arc::new(Mutex::new(Default::default())),
-            read_repeaters: Arc::new(Mutex::new(Default::default())),
+            event_bus: EventBus::default(),
             sight_processor: None,
             antennas: Arc::new(Mutex::new([0;MAX_ANTENNAS])),
             screen: Arc::new(Mutex::new(None)),
@@ -137,8 +135,7 @@ impl Reader {
         ip_address: String,
         port: u16,
         auto_connect: u8,
-        control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
-        read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+        event_bus: EventBus,
         sight_processor: Arc<processor::SightingsProcessor>,
         screen: Arc<Mutex<Option<CharacterDisplay>>>,
         readers: Arc<Mutex<Vec<Reader>>>,
@@ -157,8 +154,7 @@ impl Reader {
                     status: Arc::new(sync::Mutex::new(ReaderStatus::Disconnected)),
                     status_retries: Arc::new(Mutex::new(0)),
                     auto_connect,
-                    control_sockets,
-                    read_repeaters,
+                    event_bus,
                     sight_processor: Some(sight_processor),
                     antennas: Arc::new(Mutex::new([0;MAX_ANTENNAS])),
                     screen,
@@ -219,12 +215,8 @@ impl Reader {
         self.auto_connect
     }
 
-    pub fn set_control_sockets(&mut self, c_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>) {
-        self.control_sockets = c_sockets
-    }
-
-    pub fn set_read_repeaters(&mut self, r_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>) {
-        self.read_repeaters = r_repeaters
+    pub fn set_event_bus(&mut self, bus: EventBus) {
+        self.event_bus = bus
     }
 
     pub fn set_sight_processor(&mut self, s_processor: Arc<processor::SightingsProcessor>) {
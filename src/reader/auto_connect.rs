@@ -1,9 +1,10 @@
-use std::{net::TcpStream, sync::{Arc, Mutex}, thread::{self, JoinHandle}, time::{Duration, SystemTime}};
+use std::{sync::{Arc, Mutex}, thread::{self, JoinHandle}, time::{Duration, SystemTime}};
 
 use chrono::{DateTime, Local};
 use serde::{Serialize, Deserialize};
+use log::{error, info};
 
-use crate::{control::{self, socket::MAX_CONNECTED, sound::{SoundNotifier, SoundType}}, database::sqlite, notifier, processor, reader::{reconnector::Reconnector, AUTO_CONNECT_TRUE}};
+use crate::{control::{self, sound::{SoundNotifier, SoundType}}, database::sqlite, events::EventBus, notifier, processor, reader::{reconnector::Reconnector, AUTO_CONNECT_TRUE}};
 
 pub const START_UP_WAITING_PERIOD_SECONDS: u64 = 60;
 
@@ -19,8 +20,7 @@ pub struct AutoConnector {
     state: Arc<Mutex<State>>,
     readers: Arc<Mutex<Vec<super::Reader>>>,
     joiners: Arc<Mutex<Vec<JoinHandle<()>>>>,
-    control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
-    read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+    event_bus: EventBus,
     sight_processor: Arc<processor::SightingsProcessor>,
     control: Arc<Mutex<control::Control>>,
     sqlite: Arc<Mutex<sqlite::SQLite>>,
@@ -34,8 +34,7 @@ impl AutoConnector {
         state: Arc<Mutex<State>>,
         readers: Arc<Mutex<Vec<super::Reader>>>,
         joiners: Arc<Mutex<Vec<JoinHandle<()>>>>,
-        control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
-        read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+        event_bus: EventBus,
         sight_processor: Arc<processor::SightingsProcessor>,
         control: Arc<Mutex<control::Control>>,
         sqlite: Arc<Mutex<sqlite::SQLite>>,
@@ -47,8 +46,7 @@ impl AutoConnector {
             state,
             readers,
             joiners,
-            control_sockets,
-            read_repeaters,
+            event_bus,
             sight_processor,
             control,
             sqlite,
@@ -70,32 +68,30 @@ impl AutoConnector {
         if let Ok(mut state) = self.state.lock() {
             *state = State::Waiting;
         } else {
-            println!("Error getting state mutex during reader auto start sequence.");
+            error!("Error getting state mutex during reader auto start sequence.");
             return
         }
-        println!("Auto connect is pausing for {START_UP_WAITING_PERIOD_SECONDS} seconds before trying to connect to readers.");
+        info!("Auto connect is pausing for {START_UP_WAITING_PERIOD_SECONDS} seconds before trying to connect to readers.");
         thread::sleep(Duration::from_secs(START_UP_WAITING_PERIOD_SECONDS));
         if let Ok(mut state) = self.state.lock() {
             *state = State::Running;
         } else {
-            println!("Error getting state mutex during reader auto start sequence.");
+            error!("Error getting state mutex during reader auto start sequence.");
             return
         }
-        println!("Auto connect is done waiting. Connecting now.");
+        info!("Auto connect is done waiting. Connecting now.");
         let mut unable_to_connect = false;
         if let Ok(mut readers) = self.readers.lock() {
             for reader in readers.iter_mut() {
                 if reader.auto_connect() == AUTO_CONNECT_TRUE {
-                    println!("Connecting to reader {}.", reader.nickname());
-                    reader.set_control_sockets(self.control_sockets.clone());
+                    info!("Connecting to reader {}.", reader.nickname());
+                    reader.set_event_bus(self.event_bus.clone());
                     reader.set_readers(self.readers.clone());
-                    reader.set_read_repeaters(self.read_repeaters.clone());
                     reader.set_sight_processor(self.sight_processor.clone());
                     let reconnector = Reconnector::new(
                         self.readers.clone(),
                         self.joiners.clone(),
-                        self.control_sockets.clone(),
-                        self.read_repeaters.clone(),
+                        self.event_bus.clone(),
                         self.sight_processor.clone(),
                         self.control.clone(),
                         self.sqlite.clone(),
@@ -119,14 +115,14 @@ impl AutoConnector {
                             }
                         }
                         Err(e) => {
-                            println!("Error connecting to reader: {e}");
+                            error!("Error connecting to reader: {e}");
                             unable_to_connect = true;
                         }
                     }
                 }
             }
         }
-        println!("All done connecting to readers.");
+        info!("All done connecting to readers.");
         if unable_to_connect {
             let date_time: DateTime<Local> = SystemTime::now().into();
             self.notifier.send_notification(notifier::Notification::UnableToStartReading, format!("{}", date_time.format("%Y/%m/%d %T")));
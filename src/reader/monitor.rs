@@ -0,0 +1,214 @@
+use std::{collections::{HashMap, HashSet}, sync::{mpsc::{Receiver, RecvTimeoutError}, Arc, Mutex}, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use log::info;
+
+use crate::{control::{sound::{SoundNotifier, SoundType}, Control}, events::{Event, EventBus}, notifier::{self, Notifier}, objects::read, screen::CharacterDisplay};
+
+use super::{ANTENNA_STATUS_CONNECTED, ANTENNA_STATUS_DISCONNECTED, MAX_ANTENNAS};
+
+#[cfg(test)]
+pub mod test;
+
+pub const WAKE_SECONDS: u64 = 1;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all="snake_case")]
+pub enum AlertKind {
+    Silent,
+    Disconnected,
+    Recovered,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AntennaAlert {
+    pub reader: String,
+    pub antenna: u32,
+    pub kind: AlertKind,
+}
+
+impl AntennaAlert {
+    fn new(reader: &str, antenna: u32, kind: AlertKind) -> AntennaAlert {
+        AntennaAlert {
+            reader: String::from(reader),
+            antenna,
+            kind,
+        }
+    }
+}
+
+// Works out which antennas need an alert from the reads and antenna statuses we've seen.  An
+// antenna stays alerted until it sees a tag again.
+#[derive(Default)]
+pub struct Tracker {
+    // last time each antenna saw a tag, by reader then antenna
+    last_read: HashMap<String, HashMap<u32, u64>>,
+    statuses: HashMap<String, [u8; MAX_ANTENNAS]>,
+    alerted: HashSet<(String, u32)>,
+}
+
+impl Tracker {
+    pub fn reads(&mut self, reads: &[read::Read], now: u64, period: u64) -> Vec<AntennaAlert> {
+        let mut output: Vec<AntennaAlert> = Vec::new();
+        // relayed reads come from antennas on another portal
+        for r in reads.iter().filter(|r| r.source().is_empty()) {
+            let antennas = self.last_read.entry(String::from(r.reader())).or_default();
+            // when the whole reader has been quiet start everything over, otherwise the first tag
+            // after a break would make every other antenna look dead
+            if antennas.values().all(|last| last + period <= now) {
+                for last in antennas.values_mut() {
+                    *last = now;
+                }
+            }
+            antennas.insert(r.antenna(), now);
+            if self.alerted.remove(&(String::from(r.reader()), r.antenna())) {
+                output.push(AntennaAlert::new(r.reader(), r.antenna(), AlertKind::Recovered));
+            }
+        }
+        output
+    }
+
+    pub fn antennas(&mut self, reader: &str, antennas: &[u8; MAX_ANTENNAS]) -> Vec<AntennaAlert> {
+        let mut output: Vec<AntennaAlert> = Vec::new();
+        if let Some(previous) = self.statuses.insert(String::from(reader), *antennas) {
+            for (ix, (before, after)) in previous.iter().zip(antennas.iter()).enumerate() {
+                let antenna = ix as u32 + 1;
+                if *before == ANTENNA_STATUS_CONNECTED
+                    && *after == ANTENNA_STATUS_DISCONNECTED
+                    && self.alerted.insert((String::from(reader), antenna)) {
+                    output.push(AntennaAlert::new(reader, antenna, AlertKind::Disconnected));
+                }
+            }
+        }
+        output
+    }
+
+    // A period of 0 turns off looking for silent antennas.
+    pub fn check(&mut self, now: u64, period: u64) -> Vec<AntennaAlert> {
+        let mut output: Vec<AntennaAlert> = Vec::new();
+        if period == 0 {
+            return output
+        }
+        for (reader, antennas) in self.last_read.iter() {
+            // it's only a dead mat if the others are still seeing tags
+            if !antennas.values().any(|last| last + period > now) {
+                continue;
+            }
+            for (antenna, last) in antennas.iter() {
+                if last + period <= now && self.alerted.insert((reader.clone(), *antenna)) {
+                    output.push(AntennaAlert::new(reader, *antenna, AlertKind::Silent));
+                }
+            }
+        }
+        output.sort_by(|a, b| (&a.reader, a.antenna).cmp(&(&b.reader, b.antenna)));
+        output
+    }
+
+    pub fn alerted(&self) -> Vec<(String, u32)> {
+        let mut output: Vec<(String, u32)> = self.alerted.iter().cloned().collect();
+        output.sort();
+        output
+    }
+}
+
+// Watches for mats that stop reading during a race, like when a cable gets kicked, and lets
+// everyone know through the sockets, the notifier, a sound and the screen.
+pub struct AntennaMonitor {
+    keepalive: Arc<Mutex<bool>>,
+    control: Arc<Mutex<Control>>,
+    receiver: Receiver<Arc<Event>>,
+    event_bus: EventBus,
+    notifier: Notifier,
+    sound: Arc<SoundNotifier>,
+    screen: Arc<Mutex<Option<CharacterDisplay>>>,
+}
+
+impl AntennaMonitor {
+    pub fn new(
+        keepalive: Arc<Mutex<bool>>,
+        control: Arc<Mutex<Control>>,
+        event_bus: EventBus,
+        notifier: Notifier,
+        sound: Arc<SoundNotifier>,
+        screen: Arc<Mutex<Option<CharacterDisplay>>>,
+    ) -> Self {
+        Self {
+            keepalive,
+            control,
+            receiver: event_bus.listen(),
+            event_bus,
+            notifier,
+            sound,
+            screen,
+        }
+    }
+
+    pub fn run(&self) {
+        let mut tracker = Tracker::default();
+        loop {
+            match self.keepalive.lock() {
+                Ok(ka) if *ka => {},
+                _ => break,
+            }
+            let period = match self.control.lock() {
+                Ok(c) => c.silent_antenna_seconds,
+                Err(_) => break,
+            };
+            let mut alerts: Vec<AntennaAlert> = Vec::new();
+            match self.receiver.recv_timeout(Duration::from_secs(WAKE_SECONDS)) {
+                Ok(event) => alerts.append(&mut handle_event(&mut tracker, &event, now(), period)),
+                Err(RecvTimeoutError::Timeout) => {},
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            while let Ok(event) = self.receiver.try_recv() {
+                alerts.append(&mut handle_event(&mut tracker, &event, now(), period));
+            }
+            alerts.append(&mut tracker.check(now(), period));
+            if alerts.is_empty() {
+                continue;
+            }
+            for alert in alerts.iter() {
+                self.alert(alert);
+            }
+            if alerts.iter().any(|a| a.kind != AlertKind::Recovered) {
+                self.sound.notify_custom(SoundType::Alarm);
+            }
+            if let Ok(mut screen_opt) = self.screen.lock() {
+                if let Some(screen) = &mut *screen_opt {
+                    screen.update_antenna_alerts(tracker.alerted());
+                }
+            }
+        }
+        info!("Antenna monitor thread stopping.");
+    }
+
+    fn alert(&self, alert: &AntennaAlert) {
+        info!("Antenna {} on {} is {:?}.", alert.antenna, alert.reader, alert.kind);
+        let reader = alert.reader.clone();
+        let antenna = alert.antenna;
+        let note = match alert.kind {
+            AlertKind::Silent => notifier::Notification::AntennaSilent { reader, antenna },
+            AlertKind::Disconnected => notifier::Notification::AntennaDisconnected { reader, antenna },
+            AlertKind::Recovered => notifier::Notification::AntennaRecovered { reader, antenna },
+        };
+        let date_time: DateTime<Local> = SystemTime::now().into();
+        self.notifier.send_notification(note, format!("{}", date_time.format("%Y/%m/%d %T")));
+        self.event_bus.publish(Event::AntennaAlert {
+            alert: alert.clone(),
+            time: Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string(),
+        });
+    }
+}
+
+fn handle_event(tracker: &mut Tracker, event: &Event, now: u64, period: u64) -> Vec<AntennaAlert> {
+    match event {
+        Event::Reads(reads) => tracker.reads(reads, now, period),
+        Event::ReaderAntennas { reader_name, antennas } => tracker.antennas(reader_name, antennas),
+        _ => Vec::new(),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,103 @@
+use crate::{objects::read::Read, reader::{ANTENNA_STATUS_CONNECTED, ANTENNA_STATUS_DISCONNECTED, ANTENNA_STATUS_NONE, MAX_ANTENNAS}};
+
+use super::{AlertKind, AntennaAlert, Tracker};
+
+fn make_read(reader: &str, antenna: u32) -> Read {
+    Read::new(
+        0,
+        String::from("1000"),
+        100,
+        0,
+        100,
+        0,
+        antenna,
+        String::from(reader),
+        String::from("-50"),
+        0,
+        0,
+    )
+}
+
+fn alert(reader: &str, antenna: u32, kind: AlertKind) -> AntennaAlert {
+    AntennaAlert {
+        reader: String::from(reader),
+        antenna,
+        kind,
+    }
+}
+
+#[test]
+fn test_silent_antenna() {
+    let mut tracker = Tracker::default();
+    assert!(tracker.reads(&[make_read("Finish", 1), make_read("Finish", 2)], 1000, 60).is_empty());
+    assert!(tracker.check(1030, 60).is_empty());
+    // antenna 2 keeps reading while antenna 1 goes quiet
+    assert!(tracker.reads(&[make_read("Finish", 2)], 1050, 60).is_empty());
+    assert!(tracker.check(1059, 60).is_empty());
+    assert_eq!(vec![alert("Finish", 1, AlertKind::Silent)], tracker.check(1060, 60));
+    // only alerted once
+    assert!(tracker.check(1070, 60).is_empty());
+    assert_eq!(vec![(String::from("Finish"), 1)], tracker.alerted());
+    assert_eq!(vec![alert("Finish", 1, AlertKind::Recovered)], tracker.reads(&[make_read("Finish", 1)], 1080, 60));
+    assert!(tracker.alerted().is_empty());
+}
+
+#[test]
+fn test_quiet_reader() {
+    let mut tracker = Tracker::default();
+    assert!(tracker.reads(&[make_read("Finish", 1), make_read("Finish", 2)], 1000, 60).is_empty());
+    // nobody crossing the mats isn't a problem
+    assert!(tracker.check(1100, 60).is_empty());
+    assert!(tracker.check(5000, 60).is_empty());
+    // the first tag after a break doesn't make the other antenna look dead
+    assert!(tracker.reads(&[make_read("Finish", 1)], 5000, 60).is_empty());
+    assert!(tracker.check(5030, 60).is_empty());
+    assert!(tracker.reads(&[make_read("Finish", 1)], 5050, 60).is_empty());
+    assert_eq!(vec![alert("Finish", 2, AlertKind::Silent)], tracker.check(5060, 60));
+}
+
+#[test]
+fn test_other_readers() {
+    let mut tracker = Tracker::default();
+    assert!(tracker.reads(&[make_read("Start", 1), make_read("Finish", 1), make_read("Finish", 2)], 1000, 60).is_empty());
+    // a reader that stopped seeing tags isn't dead because another reader is still busy
+    assert!(tracker.reads(&[make_read("Finish", 1), make_read("Finish", 2)], 1050, 60).is_empty());
+    assert!(tracker.check(1080, 60).is_empty());
+    // relayed reads come from somewhere else
+    let relayed = make_read("Start", 2).with_source(String::from("Portal 2"));
+    assert!(tracker.reads(&[make_read("Start", 1), relayed], 1100, 60).is_empty());
+    assert!(tracker.check(1100, 60).is_empty());
+}
+
+#[test]
+fn test_silent_disabled() {
+    let mut tracker = Tracker::default();
+    assert!(tracker.reads(&[make_read("Finish", 1), make_read("Finish", 2)], 1000, 0).is_empty());
+    assert!(tracker.reads(&[make_read("Finish", 2)], 1100, 0).is_empty());
+    assert!(tracker.check(1100, 0).is_empty());
+}
+
+#[test]
+fn test_disconnected_antenna() {
+    let mut tracker = Tracker::default();
+    let mut antennas = [ANTENNA_STATUS_NONE; MAX_ANTENNAS];
+    antennas[0] = ANTENNA_STATUS_CONNECTED;
+    antennas[1] = ANTENNA_STATUS_DISCONNECTED;
+    // nothing to compare the first statuses to
+    assert!(tracker.antennas("Finish", &antennas).is_empty());
+    antennas[0] = ANTENNA_STATUS_DISCONNECTED;
+    assert_eq!(vec![alert("Finish", 1, AlertKind::Disconnected)], tracker.antennas("Finish", &antennas));
+    assert!(tracker.antennas("Finish", &antennas).is_empty());
+    // the other reader's antennas are separate
+    assert!(tracker.antennas("Start", &antennas).is_empty());
+    // recovered once it reads again, not when it reconnects
+    antennas[0] = ANTENNA_STATUS_CONNECTED;
+    assert!(tracker.antennas("Finish", &antennas).is_empty());
+    assert_eq!(vec![(String::from("Finish"), 1)], tracker.alerted());
+    assert_eq!(vec![alert("Finish", 1, AlertKind::Recovered)], tracker.reads(&[make_read("Finish", 1)], 1000, 60));
+    // a disconnected antenna isn't also called silent
+    antennas[0] = ANTENNA_STATUS_DISCONNECTED;
+    assert_eq!(1, tracker.antennas("Finish", &antennas).len());
+    assert!(tracker.reads(&[make_read("Finish", 2)], 1060, 60).is_empty());
+    assert!(tracker.check(1060, 60).is_empty());
+}
@@ -1,6 +1,7 @@
-use std::{net::TcpStream, sync::{Arc, Mutex}, thread::{self, JoinHandle}, time::Duration};
+use std::{sync::{Arc, Mutex}, thread::{self, JoinHandle}, time::Duration};
+use log::{debug, error, info};
 
-use crate::{control::{self, socket::{self, MAX_CONNECTED}, sound::SoundNotifier}, database::sqlite, notifier, processor::{self}};
+use crate::{control::{self, socket, sound::SoundNotifier}, database::{self, sqlite}, events::{self, EventBus}, notifier, objects::system_event, processor::{self}};
 
 // Total potential time before reconnect attempts are stopped
 // is WAITING_PERIOD_SECONDS * RECONNECT_ATTEMPTS
@@ -11,8 +12,7 @@ pub const RECONNECT_ATTEMPTS: i32 = 30;
 pub struct Reconnector {
     readers: Arc<Mutex<Vec<super::Reader>>>,
     joiners: Arc<Mutex<Vec<JoinHandle<()>>>>,
-    control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
-    read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+    event_bus: EventBus,
     sight_processor: Arc<processor::SightingsProcessor>,
     control: Arc<Mutex<control::Control>>,
     sqlite: Arc<Mutex<sqlite::SQLite>>,
@@ -28,8 +28,7 @@ impl Reconnector {
     pub fn new(
         readers: Arc<Mutex<Vec<super::Reader>>>,
         joiners: Arc<Mutex<Vec<JoinHandle<()>>>>,
-        control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
-        read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+        event_bus: EventBus,
         sight_processor: Arc<processor::SightingsProcessor>,
         control: Arc<Mutex<control::Control>>,
         sqlite: Arc<Mutex<sqlite::SQLite>>,
@@ -42,8 +41,7 @@ impl Reconnector {
         Reconnector {
             readers,
             joiners,
-            control_sockets,
-            read_repeaters,
+            event_bus,
             sight_processor,
             control,
             sqlite,
@@ -58,23 +56,35 @@ impl Reconnector {
     pub fn run(self) {
         // Try to connect at most 5 times.
         if self.count > RECONNECT_ATTEMPTS {
+            database::log_event(
+                &self.sqlite,
+                system_event::SEVERITY_ERROR,
+                system_event::SOURCE_RECONNECTOR,
+                system_event::SYSTEM_EVENT_RECONNECT_ATTEMPT,
+                serde_json::json!({ "reader_id": self.id, "attempt": self.count, "gave_up": true }),
+            );
             return;
         }
-        println!("Attempting to reconnect to reader. Attempt {0}.", self.count);
+        info!("Attempting to reconnect to reader. Attempt {0}.", self.count);
         if let Ok(mut readers) = self.readers.lock() {
             match readers.iter().position(|x| x.id() == self.id) {
                 Some(ix) => {
                     let mut old_reader = readers.remove(ix);
-                    println!("Reconnecting to reader {}.", old_reader.nickname());
-                    old_reader.set_control_sockets(self.control_sockets.clone());
+                    info!("Reconnecting to reader {}.", old_reader.nickname());
+                    database::log_event(
+                        &self.sqlite,
+                        system_event::SEVERITY_WARNING,
+                        system_event::SOURCE_RECONNECTOR,
+                        system_event::SYSTEM_EVENT_RECONNECT_ATTEMPT,
+                        serde_json::json!({ "reader_id": self.id, "reader": old_reader.nickname(), "attempt": self.count, "gave_up": false }),
+                    );
+                    old_reader.set_event_bus(self.event_bus.clone());
                     old_reader.set_readers(self.readers.clone());
-                    old_reader.set_read_repeaters(self.read_repeaters.clone());
                     old_reader.set_sight_processor(self.sight_processor.clone());
                     let reconnector = Reconnector::new(
                         self.readers.clone(),
                         self.joiners.clone(),
-                        self.control_sockets.clone(),
-                        self.read_repeaters.clone(),
+                        self.event_bus.clone(),
                         self.sight_processor.clone(),
                         self.control.clone(),
                         self.sqlite.clone(),
@@ -84,7 +94,7 @@ impl Reconnector {
                         1,
                         self.notifier.clone(),
                     );
-                    println!("Initializing reader.");
+                    info!("Initializing reader.");
                     match old_reader.connect(
                             &self.sqlite.clone(),
                             &self.control.clone(),
@@ -99,15 +109,14 @@ impl Reconnector {
                             }
                         },
                         Err(e) => {
-                            println!("Error connecting to reader: {e}");
+                            error!("Error connecting to reader: {e}");
                             // wait for a few seconds before retrying
                             readers.push(old_reader);
                             thread::sleep(Duration::from_secs(WAITING_PERIOD_SECONDS));
                             let new_reconnector = Reconnector::new(
                                 self.readers.clone(),
                                 self.joiners.clone(),
-                                self.control_sockets.clone(),
-                                self.read_repeaters.clone(),
+                                self.event_bus.clone(),
                                 self.sight_processor.clone(),
                                 self.control.clone(),
                                 self.sqlite.clone(),
@@ -129,7 +138,7 @@ impl Reconnector {
                         match old_reader.disconnect() {
                             Ok(_) => {},
                             Err(_) => {
-                                eprintln!("error attempting to disconnect from reader before reconnect attempt")
+                                error!("error attempting to disconnect from reader before reconnect attempt")
                             },
                         }
                         readers.push(old_reader);
@@ -137,8 +146,7 @@ impl Reconnector {
                         let new_reconnector = Reconnector::new(
                             self.readers.clone(),
                             self.joiners.clone(),
-                            self.control_sockets.clone(),
-                            self.read_repeaters.clone(),
+                            self.event_bus.clone(),
                             self.sight_processor.clone(),
                             self.control.clone(),
                             self.sqlite.clone(),
@@ -157,14 +165,8 @@ impl Reconnector {
                 },
                 None => {  },
             }
-            println!("Sending reader updates to connected sockets.");
-            if let Ok(c_socks) = self.control_sockets.lock() {
-                for sock in c_socks.iter() {
-                    if let Some(sock) = sock {
-                        _ = socket::write_reader_list(&sock, &readers);
-                    }
-                }
-            }
+            debug!("Sending reader updates to connected sockets.");
+            self.event_bus.publish(events::Event::ReaderList(socket::get_reader_list(&readers)));
         }
     }
 }
\ No newline at end of file
@@ -3,8 +3,9 @@ use std::{collections::HashMap, env, fs::{File, OpenOptions}, io::{ErrorKind, Re
 use std::time::Duration;
 
 use chrono::{DateTime, Local};
+use log::{debug, error, info, warn};
 
-use crate::{control::{self, socket::{self, MAX_CONNECTED}, sound::SoundNotifier}, database::{sqlite, Database}, defaults, llrp::{self, bit_masks::ParamTypeInfo, message_types::{self, get_message_name}, parameter_types::{self, get_llrp_custom_message_name}}, notifier, objects::read, processor, reader::ANTENNA_STATUS_NONE, types};
+use crate::{control::{self, socket, sound::SoundNotifier}, database::{self, sqlite, Database}, defaults, events::{self, EventBus}, llrp::{self, bit_masks::ParamTypeInfo, message_types::{self, get_message_name}, parameter_types::{self, get_llrp_custom_message_name}}, notifier, objects::{read, system_event}, processor, reader::ANTENNA_STATUS_NONE, types};
 
 use super::{reconnector::Reconnector, ReaderStatus, ANTENNA_STATUS_CONNECTED, ANTENNA_STATUS_DISCONNECTED, MAX_ANTENNAS};
 
@@ -38,7 +39,7 @@ pub fn connect(
     let ip_addr = match IpAddr::from_str(&reader.ip_address) {
         Ok(addr) => addr,
         Err(e) => {
-            println!("Error parsing ip address. {e}");
+            error!("Error parsing ip address. {e}");
             return Err("error parsing reader ip address")
         }
     };
@@ -48,11 +49,11 @@ pub fn connect(
         Ok(mut tcp_stream) => {
             match tcp_stream.set_read_timeout(Some(Duration::from_secs(1))) {
                 Ok(_) => {},
-                Err(e) => println!("unexpected error setting read timeout on tcp stream: {e}")
+                Err(e) => error!("unexpected error setting read timeout on tcp stream: {e}")
             }
             match tcp_stream.set_write_timeout(Some(Duration::from_secs(1))) {
                 Ok(_) => {},
-                Err(e) => println!("unexpected error setting write timeout on tcp stream: {e}")
+                Err(e) => error!("unexpected error setting write timeout on tcp stream: {e}")
             }
             // Set reader status to Initial connection state.
             if let Ok(mut con) = reader.status.lock() {
@@ -60,7 +61,7 @@ pub fn connect(
             }
             // try to send connection messages
             match send_set_keepalive(&mut tcp_stream, &reader.msg_id) {
-                Ok(_) => println!("Connection process started on reader {}.", reader.nickname()),
+                Ok(_) => info!("Connection process started on reader {}.", reader.nickname()),
                 Err(e) => return Err(e),
             };
             // copy tcp stream into the mutex
@@ -82,9 +83,8 @@ pub fn connect(
             let t_read_saver = read_saver.clone();
             let t_reader_status = reader.status.clone();
             let t_reader_status_retries = reader.status_retries.clone();
-            let t_control_sockets = reader.control_sockets.clone();
+            let t_event_bus = reader.event_bus.clone();
             let t_readers = reader.readers.clone();
-            let t_read_repeaters = reader.read_repeaters.clone();
             let mut t_sight_processor = reader.sight_processor.clone();
             let t_reconnector = reconnector.clone();
 
@@ -95,7 +95,7 @@ pub fn connect(
                 match t_stream.set_read_timeout(Some(Duration::from_secs(1))) {
                     Ok(_) => (),
                     Err(e) => {
-                        println!("Error setting read timeout. {e}")
+                        error!("Error setting read timeout. {e}")
                     }
                 }
                 let mut read_map: HashMap<u128, (u128, TagData)> = HashMap::new();
@@ -143,11 +143,11 @@ pub fn connect(
                                                             *stat = ReaderStatus::ConnectingPurgeTags;
                                                             match send_purge_tags(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Purge Tags request on connection sent.")
+                                                                    debug!("-- Purge Tags request on connection sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending purge tags message: {e}")
+                                                                    error!("error sending purge tags message: {e}")
                                                                 },
                                                             }
                                                         },
@@ -155,11 +155,11 @@ pub fn connect(
                                                             *stat = ReaderStatus::ConnectingSetReaderConfig;
                                                             match send_set_reader_config(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Set Reader Config request on connection sent.")
+                                                                    debug!("-- Set Reader Config request on connection sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending set reader config message: {e}")
+                                                                    error!("error sending set reader config message: {e}")
                                                                 },
                                                             }
                                                         },
@@ -168,35 +168,35 @@ pub fn connect(
                                                             // ENABLE_EVENTS_AND_REPORTS and GET_READER_CONFIG fail to report success from the reader
                                                             match send_enable_events_and_reports(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Send Enable Events and Reports request on connection sent.")
+                                                                    debug!("-- Send Enable Events and Reports request on connection sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending enable events and reports message: {e}")
+                                                                    error!("error sending enable events and reports message: {e}")
                                                                 },
                                                             }
                                                             match send_get_reader_config(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Get Reader Config request on connection sent.")
+                                                                    debug!("-- Get Reader Config request on connection sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending get reader config message: {e}")
+                                                                    error!("error sending get reader config message: {e}")
                                                                 },
                                                             }
                                                             match send_delete_access_spec(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Delete Access Spec request on connection sent.")
+                                                                    debug!("-- Delete Access Spec request on connection sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending delete access spec message: {e}")
+                                                                    error!("error sending delete access spec message: {e}")
                                                                 },
                                                             }
                                                         },
                                                         _ => {
                                                             *stat = ReaderStatus::Disconnected;
-                                                            println!("unknown reader status while processing SET_READER_CONFIG_RESPONSE")
+                                                            warn!("unknown reader status while processing SET_READER_CONFIG_RESPONSE")
                                                         },
                                                     }
                                                 } else {
@@ -207,39 +207,39 @@ pub fn connect(
                                                             ReaderStatus::ConnectingKeepalive => {
                                                                 match send_set_keepalive(&mut t_stream, &msg_id) {
                                                                     Ok(_) => {
-                                                                        println!("-- Set Keepalive request on connection sent.")
+                                                                        debug!("-- Set Keepalive request on connection sent.")
                                                                     },
                                                                     Err(e) => {
                                                                         *stat = ReaderStatus::Disconnected;
-                                                                        eprintln!("error sending set keepalive message: {e}")
+                                                                        error!("error sending set keepalive message: {e}")
                                                                     },
                                                                 }
                                                             },
                                                             ReaderStatus::ConnectingSetNoFilter => {
                                                                 match send_set_no_filter(&mut t_stream, &msg_id) {
                                                                     Ok(_) => {
-                                                                        println!("-- Set No Filter request on connection sent.")
+                                                                        debug!("-- Set No Filter request on connection sent.")
                                                                     },
                                                                     Err(e) => {
                                                                         *stat = ReaderStatus::Disconnected;
-                                                                        eprintln!("error sending set no filter message: {e}")
+                                                                        error!("error sending set no filter message: {e}")
                                                                     },
                                                                 }
                                                             },
                                                             ReaderStatus::ConnectingSetReaderConfig => {
                                                                 match send_set_reader_config(&mut t_stream, &msg_id) {
                                                                     Ok(_) => {
-                                                                        println!("-- Set Reader Config request on connection sent.")
+                                                                        debug!("-- Set Reader Config request on connection sent.")
                                                                     },
                                                                     Err(e) => {
                                                                         *stat = ReaderStatus::Disconnected;
-                                                                        eprintln!("error sending set reader config message: {e}")
+                                                                        error!("error sending set reader config message: {e}")
                                                                     },
                                                                 }
                                                             },
                                                             _ => {
                                                                 *stat = ReaderStatus::Disconnected;
-                                                                println!("unknown reader status while processing SET_READER_CONFIG_RESPONSE")
+                                                                warn!("unknown reader status while processing SET_READER_CONFIG_RESPONSE")
                                                             },
                                                         }
                                                     }
@@ -258,20 +258,20 @@ pub fn connect(
                                                             *stat = ReaderStatus::ConnectingSetNoFilter;
                                                             match send_set_no_filter(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Set No Filter request on connection sent.")
+                                                                    debug!("-- Set No Filter request on connection sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending set no filter message: {e}")
+                                                                    error!("error sending set no filter message: {e}")
                                                                 },
                                                             }
                                                         },
                                                         ReaderStatus::Connected => {
-                                                            println!("Successfully purged tags while connected.");
+                                                            info!("Successfully purged tags while connected.");
                                                         },
                                                         _ => {
                                                             *stat = ReaderStatus::Disconnected;
-                                                            println!("unknown reader status while processing CUSTOM_MESSAGE")
+                                                            warn!("unknown reader status while processing CUSTOM_MESSAGE")
                                                         }
                                                     }
                                                 } else {
@@ -282,20 +282,20 @@ pub fn connect(
                                                             ReaderStatus::ConnectingPurgeTags => {
                                                                 match send_purge_tags(&mut t_stream, &msg_id) {
                                                                     Ok(_) => {
-                                                                        println!("-- Purge Tags request on connection sent.")
+                                                                        debug!("-- Purge Tags request on connection sent.")
                                                                     },
                                                                     Err(e) => {
                                                                         *stat = ReaderStatus::Disconnected;
-                                                                        eprintln!("error sending purge tags message: {e}")
+                                                                        error!("error sending purge tags message: {e}")
                                                                     },
                                                                 }
                                                             },
                                                             ReaderStatus::Connected => {
-                                                                println!("Successfully purged tags while connected.");
+                                                                info!("Successfully purged tags while connected.");
                                                             },
                                                             _ => {
                                                                 *stat = ReaderStatus::Disconnected;
-                                                                println!("unknown reader status while processing CUSTOM_MESSAGE")
+                                                                warn!("unknown reader status while processing CUSTOM_MESSAGE")
                                                             }
                                                         }
                                                     }
@@ -314,17 +314,17 @@ pub fn connect(
                                                             *stat = ReaderStatus::ConnectingDeleteRospec;
                                                             match send_delete_rospec(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Delete Rospec request on connection sent.")
+                                                                    debug!("-- Delete Rospec request on connection sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending delete rospec message: {e}")
+                                                                    error!("error sending delete rospec message: {e}")
                                                                 },
                                                             }
                                                         }
                                                         _ => {
                                                             *stat = ReaderStatus::Disconnected;
-                                                            println!("unknown reader status while processing DELETE_ACCESS_SPEC_RESPONSE")
+                                                            warn!("unknown reader status while processing DELETE_ACCESS_SPEC_RESPONSE")
                                                         }
                                                     }
                                                 } else {
@@ -335,17 +335,17 @@ pub fn connect(
                                                             ReaderStatus::ConnectingDeleteRospec => {
                                                                 match send_delete_access_spec(&mut t_stream, &msg_id) {
                                                                     Ok(_) => {
-                                                                        println!("-- Delete Access Spec request on connection sent.")
+                                                                        debug!("-- Delete Access Spec request on connection sent.")
                                                                     },
                                                                     Err(e) => {
                                                                         *stat = ReaderStatus::Disconnected;
-                                                                        eprintln!("error sending delete access spec message: {e}")
+                                                                        error!("error sending delete access spec message: {e}")
                                                                     },
                                                                 }
                                                             }
                                                             _ => {
                                                                 *stat = ReaderStatus::Disconnected;
-                                                                println!("unknown reader status while processing DELETE_ACCESS_SPEC_RESPONSE")
+                                                                warn!("unknown reader status while processing DELETE_ACCESS_SPEC_RESPONSE")
                                                             }
                                                         }
                                                     }
@@ -364,17 +364,17 @@ pub fn connect(
                                                             *stat = ReaderStatus::StoppingDeleteRospec;
                                                             match send_delete_rospec(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Delete Rospec request on disconnect sent.")
+                                                                    debug!("-- Delete Rospec request on disconnect sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending delete rospec message: {e}")
+                                                                    error!("error sending delete rospec message: {e}")
                                                                 },
                                                             }
                                                         }
                                                         _ => {
                                                             *stat = ReaderStatus::Disconnected;
-                                                            println!("unknown reader status while processing DISABLE_ROSPEC_RESPONSE")
+                                                            warn!("unknown reader status while processing DISABLE_ROSPEC_RESPONSE")
                                                         }
                                                     }
                                                 } else {
@@ -386,17 +386,17 @@ pub fn connect(
                                                                 *stat = ReaderStatus::StoppingDeleteRospec;
                                                                 match send_delete_rospec(&mut t_stream, &msg_id) {
                                                                     Ok(_) => {
-                                                                        println!("-- Delete Rospec request on disconnect sent.")
+                                                                        debug!("-- Delete Rospec request on disconnect sent.")
                                                                     },
                                                                     Err(e) => {
                                                                         *stat = ReaderStatus::Disconnected;
-                                                                        eprintln!("error sending delete rospec message: {e}")
+                                                                        error!("error sending delete rospec message: {e}")
                                                                     },
                                                                 }
                                                             }
                                                             _ => {
                                                                 *stat = ReaderStatus::Disconnected;
-                                                                println!("unknown reader status while processing DISABLE_ROSPEC_RESPONSE")
+                                                                warn!("unknown reader status while processing DISABLE_ROSPEC_RESPONSE")
                                                             }
                                                         }
                                                     }
@@ -415,21 +415,21 @@ pub fn connect(
                                                             *stat = ReaderStatus::ConnectingAddRospec;
                                                             match send_add_rospec(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Add Rospec request on connection sent.")
+                                                                    debug!("-- Add Rospec request on connection sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending add rospec message: {e}")
+                                                                    error!("error sending add rospec message: {e}")
                                                                 },
                                                             }
                                                         },
                                                         ReaderStatus::StoppingDeleteRospec => {
                                                             *stat = ReaderStatus::Disconnected;
-                                                            println!("-- Reader successfully disconnected.")
+                                                            info!("-- Reader successfully disconnected.")
                                                         },
                                                         _ => {
                                                             *stat = ReaderStatus::Disconnected;
-                                                            println!("unknown reader status while processing DELETE_ROSPEC_RESPONSE")
+                                                            warn!("unknown reader status while processing DELETE_ROSPEC_RESPONSE")
                                                         }
                                                     }
                                                 } else {
@@ -440,21 +440,21 @@ pub fn connect(
                                                             ReaderStatus::ConnectingAddRospec => {
                                                                 match send_delete_rospec(&mut t_stream, &msg_id) {
                                                                     Ok(_) => {
-                                                                        println!("-- Delete Rospec request on connection sent.")
+                                                                        debug!("-- Delete Rospec request on connection sent.")
                                                                     },
                                                                     Err(e) => {
                                                                         *stat = ReaderStatus::Disconnected;
-                                                                        eprintln!("error sending delete rospec message: {e}")
+                                                                        error!("error sending delete rospec message: {e}")
                                                                     },
                                                                 }
                                                             },
                                                             ReaderStatus::StoppingDeleteRospec => {
                                                                 *stat = ReaderStatus::Disconnected;
-                                                                println!("-- Reader successfully disconnected.")
+                                                                info!("-- Reader successfully disconnected.")
                                                             },
                                                             _ => {
                                                                 *stat = ReaderStatus::Disconnected;
-                                                                println!("unknown reader status while processing DELETE_ROSPEC_RESPONSE")
+                                                                warn!("unknown reader status while processing DELETE_ROSPEC_RESPONSE")
                                                             }
                                                         }
                                                     }
@@ -473,17 +473,17 @@ pub fn connect(
                                                             *stat = ReaderStatus::ConnectingEnableRospec;
                                                             match send_enable_rospec(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Enable Rospec request on connection sent.")
+                                                                    debug!("-- Enable Rospec request on connection sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending enable rospec message: {e}")
+                                                                    error!("error sending enable rospec message: {e}")
                                                                 },
                                                             }
                                                         }
                                                         _ => {
                                                             *stat = ReaderStatus::Disconnected;
-                                                            println!("unknown reader status while processing ADD_ROSPEC_RESPONSE")
+                                                            warn!("unknown reader status while processing ADD_ROSPEC_RESPONSE")
                                                         }
                                                     }
                                                 } else {
@@ -494,17 +494,17 @@ pub fn connect(
                                                             ReaderStatus::ConnectingAddRospec => {
                                                                 match send_add_rospec(&mut t_stream, &msg_id) {
                                                                     Ok(_) => {
-                                                                        println!("-- Add Rospec request on connection sent.")
+                                                                        debug!("-- Add Rospec request on connection sent.")
                                                                     },
                                                                     Err(e) => {
                                                                         *stat = ReaderStatus::Disconnected;
-                                                                        eprintln!("error sending add rospec message: {e}")
+                                                                        error!("error sending add rospec message: {e}")
                                                                     },
                                                                 }
                                                             },
                                                             _ => {
                                                                 *stat = ReaderStatus::Disconnected;
-                                                                println!("unknown reader status while processing ADD_ROSPEC_RESPONSE")
+                                                                warn!("unknown reader status while processing ADD_ROSPEC_RESPONSE")
                                                             }
                                                         }
                                                     }
@@ -523,17 +523,17 @@ pub fn connect(
                                                             *stat = ReaderStatus::ConnectingStartRospec;
                                                             match send_start_rospec(&mut t_stream, &msg_id) {
                                                                 Ok(_) => {
-                                                                    println!("-- Start Rospec request on connection sent.")
+                                                                    debug!("-- Start Rospec request on connection sent.")
                                                                 },
                                                                 Err(e) => {
                                                                     *stat = ReaderStatus::Disconnected;
-                                                                    eprintln!("error sending start rospec message: {e}")
+                                                                    error!("error sending start rospec message: {e}")
                                                                 },
                                                             }
                                                         }
                                                         _ => {
                                                             *stat = ReaderStatus::Disconnected;
-                                                            println!("unknown reader status while processing ENABLE_ROSPEC_RESPONSE")
+                                                            warn!("unknown reader status while processing ENABLE_ROSPEC_RESPONSE")
                                                         }
                                                     }
                                                 } else {
@@ -544,17 +544,17 @@ pub fn connect(
                                                             ReaderStatus::ConnectingEnableRospec => {
                                                                 match send_enable_rospec(&mut t_stream, &msg_id) {
                                                                     Ok(_) => {
-                                                                        println!("-- Enable Rospec request on connection sent.")
+                                                                        debug!("-- Enable Rospec request on connection sent.")
                                                                     },
                                                                     Err(e) => {
                                                                         *stat = ReaderStatus::Disconnected;
-                                                                        eprintln!("error sending enable rospec message: {e}")
+                                                                        error!("error sending enable rospec message: {e}")
                                                                     },
                                                                 }
                                                             },
                                                             _ => {
                                                                 *stat = ReaderStatus::Disconnected;
-                                                                println!("unknown reader status while processing ENABLE_ROSPEC_RESPONSE")
+                                                                warn!("unknown reader status while processing ENABLE_ROSPEC_RESPONSE")
                                                             }
                                                         }
                                                     }
@@ -571,12 +571,12 @@ pub fn connect(
                                                     match *stat {
                                                         ReaderStatus::ConnectingStartRospec => {
                                                             *stat = ReaderStatus::Connected;
-                                                            println!("-- Reader status set to connected.")
+                                                            debug!("-- Reader status set to connected.")
                                                             // inform control sockets of reader connection change?
                                                         }
                                                         _ => {
                                                             *stat = ReaderStatus::Disconnected;
-                                                            println!("unknown reader status while processing START_ROSPEC_RESPONSE")
+                                                            warn!("unknown reader status while processing START_ROSPEC_RESPONSE")
                                                         }
                                                     }
                                                 } else {
@@ -587,17 +587,17 @@ pub fn connect(
                                                             ReaderStatus::ConnectingStartRospec => {
                                                                 match send_start_rospec(&mut t_stream, &msg_id) {
                                                                     Ok(_) => {
-                                                                        println!("-- Start Rospec request on connection sent.")
+                                                                        debug!("-- Start Rospec request on connection sent.")
                                                                     },
                                                                     Err(e) => {
                                                                         *stat = ReaderStatus::Disconnected;
-                                                                        eprintln!("error sending start rospec message: {e}")
+                                                                        error!("error sending start rospec message: {e}")
                                                                     },
                                                                 }
                                                             },
                                                             _ => {
                                                                 *stat = ReaderStatus::Disconnected;
-                                                                println!("unknown reader status while processing START_ROSPEC_RESPONSE")
+                                                                warn!("unknown reader status while processing START_ROSPEC_RESPONSE")
                                                             }
                                                         }
                                                     }
@@ -605,7 +605,7 @@ pub fn connect(
                                             }
                                         },
                                         _ => {
-                                            println!("Error, unknown status message processed.");
+                                            error!("Error, unknown status message processed.");
                                         }
                                     }
                                 }
@@ -618,19 +618,14 @@ pub fn connect(
                                 match process_tags(&mut read_map, &mut tags, &mut unsaved_reads, &t_control, &t_read_saver, t_reader_name.as_str()) {
                                     Ok(new_reads) => {
                                         if new_reads.len() > 0 {
-                                            match send_new(new_reads, &t_control_sockets, &t_read_repeaters) {
-                                                Ok(_) => {},
-                                                Err(e) => {
-                                                    println!("error sending new reads to repeaters: {e}")
-                                                }
-                                            }
+                                            send_new(new_reads, &t_event_bus);
                                             if let Some(processor) = t_sight_processor {
                                                 processor.notify();
                                                 t_sight_processor = Some(processor);
                                             }
                                         }
                                     },
-                                    Err(e) => println!("Error processing tags. {e}"),
+                                    Err(e) => error!("Error processing tags. {e}"),
                                 };
                             }
                             // if antenna data exists then we can update the readers antennas
@@ -664,10 +659,10 @@ pub fn connect(
                                 }
                                 // send out notification that we updated the readers
                                 if updated {
-                                    match send_antennas(t_reader_name.as_str(), &t_antennas, &t_control_sockets) {
+                                    match send_antennas(t_reader_name.as_str(), &t_antennas, &t_event_bus) {
                                         Ok(_) => {},
                                         Err(e) => {
-                                            println!("error sending antennas to control sockets: {e}")
+                                            error!("error sending antennas to control sockets: {e}")
                                         }
                                     }
                                 }
@@ -677,7 +672,7 @@ pub fn connect(
                             }
                             let right_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
                             if right_now - 5 > last_ka_received_at {
-                                println!("no keep alive message received in the last 5 seconds");
+                                warn!("no keep alive message received in the last 5 seconds");
                                 if let Ok(stat) = t_reader_status.lock() {
                                     if *stat != ReaderStatus::Disconnected && *stat != ReaderStatus::StoppingDeleteRospec && *stat != ReaderStatus::StoppingDisableRospec {
                                         reconnect = true;
@@ -690,7 +685,7 @@ pub fn connect(
                             *leftover_num = 0;
                             match e.kind() {
                                 ErrorKind::ConnectionAborted | ErrorKind::ConnectionReset => {
-                                    println!("connection aborted/reset");
+                                    info!("connection aborted/reset");
                                     reconnect = true;
                                     let date_time: DateTime<Local> = SystemTime::now().into();
                                     notifier.send_notification(notifier::Notification::StopReading, format!("{}", date_time.format("%Y/%m/%d %T")));
@@ -701,42 +696,37 @@ pub fn connect(
                                     match process_tags(&mut read_map, &mut Vec::new(), &mut unsaved_reads, &t_control, &t_read_saver, t_reader_name.as_str()) {
                                         Ok(new_reads) => {
                                             if new_reads.len() > 0 {
-                                                match send_new(new_reads, &t_control_sockets, &t_read_repeaters) {
-                                                    Ok(_) => {},
-                                                    Err(e) => {
-                                                        println!("error sending new reads to repeaters: {e}")
-                                                    }
-                                                }
+                                                send_new(new_reads, &t_event_bus);
                                                 if let Some(processor) = t_sight_processor {
                                                     processor.notify();
                                                     t_sight_processor = Some(processor);
                                                 }
                                             }
                                         },
-                                        Err(e) => println!("Error processing tags. {e}"),
+                                        Err(e) => error!("Error processing tags. {e}"),
                                     }
                                     let right_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
                                     if right_now - 5 > last_ka_received_at {
-                                        println!("no keep alive message received in the last 5 seconds");
+                                        warn!("no keep alive message received in the last 5 seconds");
                                         reconnect = true;
                                         let date_time: DateTime<Local> = SystemTime::now().into();
                                         notifier.send_notification(notifier::Notification::StopReading, format!("{}", date_time.format("%Y/%m/%d %T")));
                                         break;
                                     }
                                 },
-                                _ => println!("Error reading from reader. {e}"),
+                                _ => error!("Error reading from reader. {e}"),
                             }
                         }
                     }
                     if count > TAG_LIMIT {
                         purge_count += 1;
-                        println!("Purging tags. This is purge number {purge_count}.");
+                        info!("Purging tags. This is purge number {purge_count}.");
                         match send_purge_tags(&mut t_stream, &msg_id) {
                             Ok(_) => {
                                 count = 0;
                             },
                             Err(e) => {
-                                println!("Error sending purge tag message. {e}");
+                                error!("Error sending purge tag message. {e}");
                             }
                         }
                     }
@@ -756,15 +746,16 @@ pub fn connect(
                         }
                     }
                     if send_reader_list {
+                        database::log_event(
+                            &t_sqlite,
+                            system_event::SEVERITY_INFO,
+                            system_event::SOURCE_READER,
+                            system_event::SYSTEM_EVENT_READER_CONNECTED,
+                            serde_json::json!({ "reader": t_reader_name }),
+                        );
                         if let Ok(u_readers) = t_readers.lock() {
-                            if let Ok(c_socks) = t_control_sockets.lock() {
-                                for sock in c_socks.iter() {
-                                    if let Some(sock) = sock {
-                                        println!("Sending reader list!");
-                                        _ = socket::write_reader_list(&sock, &*u_readers);
-                                    }
-                                }
-                            }
+                            debug!("Sending reader list!");
+                            t_event_bus.publish(events::Event::ReaderList(socket::get_reader_list(&*u_readers)));
                         }
                     }
                     /*
@@ -777,21 +768,32 @@ pub fn connect(
                 if let Ok(mut db) = t_sqlite.lock() {
                     match db.save_reads(&unsaved_reads) {
                         Ok(_num) => { },
-                        Err(e) => println!("Error saving reads. {e}"),
+                        Err(e) => error!("Error saving reads. {e}"),
                     }
                 }
                 if let Err(e) = t_stream.shutdown(Shutdown::Both) {
-                    println!("Error shutting down socket. {e}");
+                    error!("Error shutting down socket. {e}");
                 }
                 if let Ok(mut con) = t_reader_status.lock() {
                     *con = ReaderStatus::Disconnected;
                 }
+                // we only reconnect when we lost the reader, not when we were told to stop
+                database::log_event(
+                    &t_sqlite,
+                    if reconnect { system_event::SEVERITY_ERROR } else { system_event::SEVERITY_INFO },
+                    system_event::SOURCE_READER,
+                    system_event::SYSTEM_EVENT_READER_DISCONNECTED,
+                    serde_json::json!({ "reader": t_reader_name, "unexpected": reconnect }),
+                );
+                if let Ok(u_readers) = t_readers.lock() {
+                    t_event_bus.publish(events::Event::ReaderList(socket::get_reader_list(&u_readers)));
+                }
                 if reconnect == true {
                     if let Some(rec) = t_reconnector {
                         rec.run();
                     }
                 }
-                println!("Thread reading from this reader has now closed.");
+                info!("Thread reading from this reader has now closed.");
             });
             Ok(output)
         },
@@ -902,7 +904,7 @@ pub fn stop_reader(reader: &mut super::Reader) -> Result<(), &'static str> {
                 };
                 match stop_reading(&mut w_stream, msg_id) {
                     Ok(_) => {
-                        println!("No longer reading from reader {}", reader.nickname());
+                        info!("No longer reading from reader {}", reader.nickname());
                     }
                     Err(e) => return Err(e),
                 }
@@ -934,7 +936,7 @@ fn stop(
         msg_id = *id+1;
     }
     match stop_reading(socket, msg_id) {
-        Ok(_) => println!("No longer reading from reader {}", nickname),
+        Ok(_) => info!("No longer reading from reader {}", nickname),
         Err(_) => (),
     }
 }
@@ -973,11 +975,11 @@ fn save_reads(
                     Ok(_num) => {
                         //println!("Saved {_num} reads.")
                     },
-                    Err(e) => println!("Error saving reads. {e}"),
+                    Err(e) => error!("Error saving reads. {e}"),
                 }
             },
             Err(e) => {
-                println!("Error saving reads on thread close. {e}");
+                error!("Error saving reads on thread close. {e}");
             }
         }
     }
@@ -986,68 +988,24 @@ fn save_reads(
 fn send_antennas(
     reader_name: &str,
     antennas: &Arc<Mutex<[u8;MAX_ANTENNAS]>>,
-    control_sockets: &Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED+1]>>
+    event_bus: &EventBus,
 ) -> Result<(), &'static str> {
-    let mut no_error = true;
-    if let Ok(sockets) = control_sockets.lock() {
-        if let Ok(ant) = antennas.lock() {
-            for ix in 0..MAX_CONNECTED {
-                match &sockets[ix] {
-                    Some(sock) => {
-                        no_error = no_error && socket::write_reader_antennas(sock, reader_name.to_string(), &*ant)
-                    },
-                    None => {}
-                }
-            }
-        } else {
-            return Err("error getting antennas mutex")
-        }
+    if let Ok(ant) = antennas.lock() {
+        event_bus.publish(events::Event::ReaderAntennas {
+            reader_name: reader_name.to_string(),
+            antennas: *ant,
+        });
     } else {
-        return Err("error getting sockets mutex")
-    }
-    if no_error == false {
-        return Err("error occurred writing to one or more sockets")
+        return Err("error getting antennas mutex")
     }
     Ok(())
 }
 
 fn send_new(
     reads: Vec<read::Read>,
-    control_sockets: &Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED+1]>>,
-    read_repeaters: &Arc<Mutex<[bool;MAX_CONNECTED]>>,
-) -> Result<(), &'static str> {
-    let mut no_error = true;
-    if let Ok(sockets) = control_sockets.lock() {
-        if let Ok(mut repeaters) = read_repeaters.lock() {
-            for ix in 0..MAX_CONNECTED {
-                match &sockets[ix] {
-                    Some(sock) => {
-                        if repeaters[ix] == true {
-                            //println!("Sending reads to subscribed socket {ix}.");
-                            // If write_reads returned false it wasn't able to write the reads due to connection being broken.
-                            let loc_err = socket::write_reads(&sock, &reads);
-                            if !loc_err {
-                                repeaters[ix] = false;
-                                if let Err(e) = sock.shutdown(std::net::Shutdown::Both) {
-                                    println!("Error shutting down closed socket. {e}");
-                                }
-                            }
-                            no_error = no_error && loc_err;
-                        }
-                    },
-                    None => {}
-                }
-            }
-        } else {
-            return Err("error getting repeaters mutex")
-        }
-    } else {
-        return Err("error getting sockets mutex")
-    }
-    if no_error == false {
-        return Err("error occurred writing to one or more sockets")
-    }
-    Ok(())
+    event_bus: &EventBus,
+) {
+    event_bus.publish(events::Event::Reads(reads));
 }
 
 fn process_tags(
@@ -1166,7 +1124,7 @@ fn process_tags(
         unsaved_reads.append(cloned_reads);
         // upload reads to database
         if let Err(_) = read_saver.save_reads(unsaved_reads) {
-            println!("something went wrong saving reads");
+            error!("something went wrong saving reads");
         } else { // was able to add reads to save queue
             unsaved_reads.clear();
         }
@@ -1199,7 +1157,7 @@ fn finalize(
         if ReaderStatus::Disconnected != *r {
             match stop_reading(t_stream, fin_id) {
                 Ok(_) => (),
-                Err(e) => println!("Error trying to stop reading. {e}"),
+                Err(e) => error!("Error trying to stop reading. {e}"),
             };
             fin_id = fin_id + 2;
         }
@@ -1215,14 +1173,14 @@ fn finalize(
                 Err(e) => {
                     match e.kind() {
                         ErrorKind::ConnectionAborted | ErrorKind::ConnectionReset | ErrorKind::TimedOut | ErrorKind::WouldBlock => (),
-                        _ => println!("Error reading from reader. {e}"),
+                        _ => error!("Error reading from reader. {e}"),
                     }
                 }
             }
         },
         Err(e) => {
             if e.kind() != ErrorKind::BrokenPipe {
-                println!("Error closing connection. {e}")
+                error!("Error closing connection. {e}")
             }
         },
     }
@@ -1381,7 +1339,7 @@ fn read(
                                     Ok(_) => (),
                                     Err(e) => {
                                         if e.kind() != ErrorKind::BrokenPipe {
-                                            eprintln!("Error responding to keepalive. {e}")
+                                            error!("Error responding to keepalive. {e}")
                                         }
                                     },
                                 }
@@ -1438,7 +1396,7 @@ fn read(
                                 output.status_messages.push((leftover_type.kind, success));
                                 if let Some(ref mut file) = file {
                                     if let Err(e) = writeln!(file, "{} - {response_message}", message_types::get_message_name(leftover_type.kind).unwrap()) {
-                                        eprintln!("Couldn't write to file: {}", e);
+                                        error!("Couldn't write to file: {}", e);
                                     }
                                 }
                             },
@@ -1469,7 +1427,7 @@ fn read(
                                 output.status_messages.push((leftover_type.kind, success));
                                 if let Some(ref mut file) = file {
                                     if let Err(e) = writeln!(file, "{message_name} - {response_message}") {
-                                        eprintln!("Couldn't write to file: {}", e);
+                                        error!("Couldn't write to file: {}", e);
                                     }
                                 }
                             },
@@ -1477,7 +1435,7 @@ fn read(
                                 //println!("Message Type Found! V: {} - {:?}", leftover_type.version, get_message_name(found_type));
                                 if let Some(ref mut file) = file {
                                     if let Err(e) = writeln!(file, "Message Type Found! V: {} - {:?}", leftover_type.version, get_message_name(found_type)) {
-                                        eprintln!("Couldn't write to file: {}", e);
+                                        error!("Couldn't write to file: {}", e);
                                     }
                                 }
                             },
@@ -1511,7 +1469,7 @@ fn read(
                                     Ok(_) => (),
                                     Err(e) => {
                                         if e.kind() != ErrorKind::BrokenPipe {
-                                            eprintln!("Error responding to keepalive. {e}")
+                                            error!("Error responding to keepalive. {e}")
                                         }
                                     },
                                 }
@@ -1567,7 +1525,7 @@ fn read(
                                 output.status_messages.push((info.kind, success));
                                 if let Some(ref mut file) = file {
                                     if let Err(e) = writeln!(file, "{} - {response_message}", message_types::get_message_name(info.kind).unwrap()) {
-                                        eprintln!("Couldn't write to file: {}", e);
+                                        error!("Couldn't write to file: {}", e);
                                     }
                                 }
                             },
@@ -1598,7 +1556,7 @@ fn read(
                                 output.status_messages.push((info.kind, success));
                                 if let Some(ref mut file) = file {
                                     if let Err(e) = writeln!(file, "{message_name} - {response_message}") {
-                                        eprintln!("Couldn't write to file: {}", e);
+                                        error!("Couldn't write to file: {}", e);
                                     }
                                 }
                             },
@@ -1606,7 +1564,7 @@ fn read(
                                 //println!("Message Type Found! V: {} - {:?}", info.version, get_message_name(found_type));
                                 if let Some(ref mut file) = file {
                                     if let Err(e) = writeln!(file, "Unknown message Type Found! V: {} - {:?}", info.version, get_message_name(found_type)) {
-                                        eprintln!("Couldn't write to file: {}", e);
+                                        error!("Couldn't write to file: {}", e);
                                     }
                                 }
                             },
@@ -1729,7 +1687,7 @@ fn process_llrp_status_parameter(buf: &[u8;BUFFER_SIZE], start_ix: usize, max_ix
         Err(_) => return Err("unable to get parameter info"),
     };
     if parameter_types::LLRP_STATUS != param_info.kind {
-        println!("invalid llrp status parameter parsed: {}", param_info.kind);
+        warn!("invalid llrp status parameter parsed: {}", param_info.kind);
         return Err("invalid llrp status parameter")
     }
     let mut param_ix = start_ix + 4;
@@ -1802,7 +1760,7 @@ fn process_reader_config(buf: &[u8;BUFFER_SIZE], start_ix: usize, max_ix: &usize
             parameter_types::LLRP_STATUS => { },
             parameter_types::IDENTIFICATION => { },
             other => {
-                println!("unknown parameter type found: {:?}", other);
+                warn!("unknown parameter type found: {:?}", other);
             }
         }
         param_ix += param_info.length as usize;
@@ -1912,7 +1870,7 @@ fn _process_parameters(buf: &[u8;BUFFER_SIZE], start_ix: usize, num: &usize) {
         let param_info = match llrp::bit_masks::get_param_type(&bits) {
             Ok(info) => info,
             Err(e) => {
-                println!("Unable to process parameters. {e}");
+                warn!("Unable to process parameters. {e}");
                 return
             }
         };
@@ -1922,7 +1880,7 @@ fn _process_parameters(buf: &[u8;BUFFER_SIZE], start_ix: usize, num: &usize) {
         match param_info.kind {
             parameter_types::RO_SPEC => {
                 if start + 10 > *num {
-                    println!("Out of bounds.");
+                    warn!("Out of bounds.");
                     return
                 }
                 // ID is an unsigned integer. 0 is invalid
@@ -1935,11 +1893,11 @@ fn _process_parameters(buf: &[u8;BUFFER_SIZE], start_ix: usize, num: &usize) {
                 // 0 = disabled, 1 = inactive, 2 = active
                 let current_state: u8 = buf[start+9];
                 // 10 is a ROBoundarySpec parameter followed by 1-n SpecParameters followed by 0-1 ROReportSpec parameters
-                println!("RO_SPEC Parameter -- id {} - priority {} - current state {}", rospec_id, priority, current_state);
+                debug!("RO_SPEC Parameter -- id {} - priority {} - current state {}", rospec_id, priority, current_state);
             },
             parameter_types::LLRP_STATUS => {
                 if start + 8 > *num {
-                    println!("Out of bounds.");
+                    warn!("Out of bounds.");
                     return
                 }
                 // Status code          - integer
@@ -1951,11 +1909,11 @@ fn _process_parameters(buf: &[u8;BUFFER_SIZE], start_ix: usize, num: &usize) {
                 let err_des: &str = match str::from_utf8(&buf[start+8..param_ix]) {
                     Ok(v) => v,
                     Err(e) => {
-                        println!("Error converting error description. {e}");
+                        error!("Error converting error description. {e}");
                         return
                     }
                 };
-                println!("LLRP_STATUS parameter - Code {} - Descr {}", status_code, err_des);
+                debug!("LLRP_STATUS parameter - Code {} - Descr {}", status_code, err_des);
                 // check if more available to read
                 let end: usize = param_info.length as usize + start;
                 if end < *num {
@@ -1964,7 +1922,7 @@ fn _process_parameters(buf: &[u8;BUFFER_SIZE], start_ix: usize, num: &usize) {
             },
             parameter_types::ACCESS_SPEC => {
                 if start + 24 > *num {
-                    println!("Out of bounds.");
+                    warn!("Out of bounds.");
                     return
                 }
                 let spec_id: u32 = ((buf[start+4] as u32) << 24) +
@@ -1987,7 +1945,7 @@ fn _process_parameters(buf: &[u8;BUFFER_SIZE], start_ix: usize, num: &usize) {
                                         ((buf[start+21] as u32) << 16) +
                                         ((buf[start+22] as u32) << 8) +
                                         (buf[start+23] as u32);
-                println!("ACCESS_SPEC parameter. Spec {}, Ant {}, Prot {}, Act {}, ROSpec {}, ASSTrigger {}, AccessCommand {}",
+                debug!("ACCESS_SPEC parameter. Spec {}, Ant {}, Prot {}, Act {}, ROSpec {}, ASSTrigger {}, AccessCommand {}",
                         spec_id,
                         antenna_id,
                         protocol_id,
@@ -2010,7 +1968,7 @@ fn _process_parameters(buf: &[u8;BUFFER_SIZE], start_ix: usize, num: &usize) {
                 // ReportBufferLevelWarningEvent Parameter ?
             }
             _ => {
-                println!("Parameter found -- {:?} -- TV? {}", parameter_types::get_parameter_name(param_info.kind), param_info.tv);
+                debug!("Parameter found -- {:?} -- TV? {}", parameter_types::get_parameter_name(param_info.kind), param_info.tv);
             }
         }
         start = start + param_info.length as usize;
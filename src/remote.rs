@@ -1,4 +1,6 @@
 pub mod requests;
 pub mod responses;
 pub mod uploader;
-pub mod remote_util;
\ No newline at end of file
+pub mod remote_util;
+pub mod webhook;
+pub mod relay;
\ No newline at end of file
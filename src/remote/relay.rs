@@ -0,0 +1,274 @@
+use std::{collections::HashMap, io::{ErrorKind, Read, Write}, net::{Shutdown, TcpListener, TcpStream}, sync::{Arc, Mutex}, thread, time::Duration};
+use log::{error, info, warn};
+
+use crate::{control::Control, database::{sqlite, Database}, events::{Event, EventBus}, network::api, objects::read, processor::SightingsProcessor};
+
+use super::{requests::UploadReadsRequest, responses::UploadReadsResponse};
+
+#[cfg(test)]
+pub mod test;
+
+pub const PATH_READS_ADD: &str = "/reads/add";
+pub const PATH_HEALTH: &str = "/health";
+pub const WAKE_MILLISECONDS: u64 = 250;
+pub const TIMEOUT_SECONDS: u64 = 10;
+pub const MAX_HEADER_LENGTH: usize = 16 * 1024;
+// Uploads are sent 50 reads at a time so this is far more than we should ever see.
+pub const MAX_BODY_LENGTH: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+// Accepts reads uploaded by other portals the same way they'd upload them to the remote api so
+// one portal can show everything happening on the course.  Each portal relaying to us needs a
+// RELAY_SOURCE api with a matching token, and its nickname is saved as the source of the reads.
+pub struct Relay {
+    keepalive: Arc<Mutex<bool>>,
+    sqlite: Arc<Mutex<sqlite::SQLite>>,
+    control: Arc<Mutex<Control>>,
+    event_bus: EventBus,
+    sight_processor: Arc<SightingsProcessor>,
+}
+
+impl Relay {
+    pub fn new(
+        keepalive: Arc<Mutex<bool>>,
+        sqlite: Arc<Mutex<sqlite::SQLite>>,
+        control: Arc<Mutex<Control>>,
+        event_bus: EventBus,
+        sight_processor: Arc<SightingsProcessor>,
+    ) -> Self {
+        Self {
+            keepalive,
+            sqlite,
+            control,
+            event_bus,
+            sight_processor,
+        }
+    }
+
+    pub fn run(self) {
+        let relay = Arc::new(self);
+        let mut listener: Option<(TcpListener, u16)> = None;
+        loop {
+            match relay.keepalive.lock() {
+                Ok(ka) if *ka => {},
+                _ => break,
+            }
+            let (enabled, port) = match relay.control.lock() {
+                Ok(c) => (c.enable_relay, c.relay_port),
+                Err(_) => break,
+            };
+            if !enabled {
+                if listener.take().is_some() {
+                    info!("Relay receiver stopped.");
+                }
+                thread::sleep(Duration::from_secs(1));
+                continue;
+            }
+            // bind again if we haven't yet or the port has changed
+            if listener.as_ref().is_none_or(|(_, p)| *p != port) {
+                listener = None;
+                match TcpListener::bind(("0.0.0.0", port)) {
+                    Ok(l) => {
+                        if let Err(e) = l.set_nonblocking(true) {
+                            warn!("Unable to set relay receiver to nonblocking. {e}");
+                            thread::sleep(Duration::from_secs(1));
+                            continue;
+                        }
+                        info!("Relay receiver listening on port {port}.");
+                        listener = Some((l, port));
+                    },
+                    Err(e) => {
+                        warn!("Unable to start relay receiver on port {port}. {e}");
+                        thread::sleep(Duration::from_secs(5));
+                        continue;
+                    }
+                }
+            }
+            if let Some((l, _)) = &listener {
+                match l.accept() {
+                    Ok((stream, _)) => {
+                        let t_relay = relay.clone();
+                        thread::spawn(move|| {
+                            t_relay.handle(stream);
+                        });
+                    },
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(WAKE_MILLISECONDS));
+                    },
+                    Err(e) => {
+                        error!("Error accepting relay connection. {e}");
+                        thread::sleep(Duration::from_millis(WAKE_MILLISECONDS));
+                    }
+                }
+            }
+        }
+        info!("Relay receiver thread stopping.");
+    }
+
+    fn handle(&self, mut stream: TcpStream) {
+        if stream.set_nonblocking(false).is_err()
+            || stream.set_read_timeout(Some(Duration::from_secs(TIMEOUT_SECONDS))).is_err()
+            || stream.set_write_timeout(Some(Duration::from_secs(TIMEOUT_SECONDS))).is_err() {
+            return
+        }
+        let (status, body) = match read_request(&mut stream) {
+            Ok(request) => self.respond(&request),
+            Err(e) => (400, error_body(e)),
+        };
+        _ = stream.write_all(&response(status, &body));
+        _ = stream.shutdown(Shutdown::Both);
+    }
+
+    fn respond(&self, request: &HttpRequest) -> (u16, String) {
+        // lets portals uploading to us check if we're reachable
+        if request.path.trim_end_matches('/') == PATH_HEALTH && request.method == "GET" {
+            return (200, serde_json::json!({ "status": "ok" }).to_string())
+        }
+        if request.path.trim_end_matches('/') != PATH_READS_ADD {
+            return (404, error_body("not found"))
+        }
+        if request.method != "POST" {
+            return (405, error_body("method not allowed"))
+        }
+        let source = match request.headers.get("authorization").and_then(|v| v.strip_prefix("Bearer ")) {
+            Some(token) => match self.source(token.trim()) {
+                Some(s) => s,
+                None => return (401, error_body("unauthorized")),
+            },
+            None => return (401, error_body("unauthorized")),
+        };
+        let upload: UploadReadsRequest = match serde_json::from_slice(&request.body) {
+            Ok(u) => u,
+            Err(_) => return (400, error_body("invalid request body")),
+        };
+        // The sender only marks reads as uploaded when the count matches what it sent, so the
+        // count covers anything we skip as well or it would keep sending them.
+        let count = upload.reads.len();
+        let accepted = if upload.ids.is_empty() { None } else { Some(upload.ids) };
+        let reads: Vec<read::Read> = upload.reads.into_iter()
+            .filter(|r| r.is_valid())
+            .map(|r| r.with_source(source.clone()))
+            .collect();
+        if reads.len() < count {
+            warn!("Ignoring {} invalid reads relayed from {source}.", count - reads.len());
+        }
+        if !reads.is_empty() {
+            match self.sqlite.lock() {
+                Ok(mut sq) => {
+                    if let Err(e) = sq.save_reads(&reads) {
+                        error!("Error saving reads relayed from {source}. {e}");
+                        return (500, error_body("unable to save reads"))
+                    }
+                },
+                Err(_) => return (500, error_body("unable to save reads")),
+            }
+            self.event_bus.publish(Event::Reads(reads));
+            self.sight_processor.notify();
+        }
+        match serde_json::to_string(&UploadReadsResponse { count, accepted }) {
+            Ok(body) => (200, body),
+            Err(_) => (500, error_body("unable to serialize response")),
+        }
+    }
+
+    // Finds the name of the portal the token belongs to.
+    fn source(&self, token: &str) -> Option<String> {
+        if token.is_empty() {
+            return None
+        }
+        let apis = match self.sqlite.lock() {
+            Ok(sq) => sq.get_apis().ok()?,
+            Err(_) => return None,
+        };
+        apis.into_iter()
+            .find(|a| a.kind() == api::API_TYPE_RELAY_SOURCE && a.token() == token)
+            .map(|a| String::from(a.nickname()))
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<HttpRequest, &'static str> {
+    let mut buf: Vec<u8> = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(request) = parse_request(&buf)? {
+            return Ok(request)
+        }
+        match stream.read(&mut chunk) {
+            Ok(0) => return Err("connection closed before request was complete"),
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            Err(_) => return Err("unable to read request"),
+        }
+    }
+}
+
+// Returns the request once we've got all of it, or None if we need to read more.
+pub fn parse_request(buf: &[u8]) -> Result<Option<HttpRequest>, &'static str> {
+    let header_end = match buf.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(ix) => ix,
+        None if buf.len() > MAX_HEADER_LENGTH => return Err("headers too large"),
+        None => return Ok(None),
+    };
+    let head = match std::str::from_utf8(&buf[..header_end]) {
+        Ok(h) => h,
+        Err(_) => return Err("invalid headers"),
+    };
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or("").split_whitespace();
+    let (method, path) = match (request_line.next(), request_line.next()) {
+        (Some(m), Some(p)) => (m.to_uppercase(), String::from(p)),
+        _ => return Err("invalid request line"),
+    };
+    let mut headers: HashMap<String, String> = HashMap::new();
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), String::from(value.trim()));
+        }
+    }
+    if headers.get("transfer-encoding").is_some_and(|v| v.eq_ignore_ascii_case("chunked")) {
+        return Err("chunked requests are not supported")
+    }
+    let length = match headers.get("content-length") {
+        Some(l) => match l.parse::<usize>() {
+            Ok(l) if l <= MAX_BODY_LENGTH => l,
+            Ok(_) => return Err("request body too large"),
+            Err(_) => return Err("invalid content length"),
+        },
+        None => 0,
+    };
+    let body_start = header_end + 4;
+    if buf.len() < body_start + length {
+        return Ok(None)
+    }
+    Ok(Some(HttpRequest {
+        method,
+        path,
+        headers,
+        body: buf[body_start..body_start + length].to_vec(),
+    }))
+}
+
+pub fn response(status: u16, body: &str) -> Vec<u8> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    ).into_bytes()
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "message": message }).to_string()
+}
@@ -0,0 +1,35 @@
+use super::{parse_request, response, MAX_HEADER_LENGTH};
+
+#[test]
+fn test_parse_request() {
+    let raw = b"POST /reads/add HTTP/1.1\r\nHost: portal\r\nAuthorization: Bearer abc\r\nContent-Type: application/json\r\nContent-Length: 11\r\n\r\n{\"reads\":[]}";
+    // not everything has arrived yet
+    assert_eq!(Ok(None), parse_request(&raw[..30]));
+    assert_eq!(Ok(None), parse_request(&raw[..raw.len() - 2]));
+    let request = parse_request(raw).unwrap().unwrap();
+    assert_eq!("POST", request.method);
+    assert_eq!("/reads/add", request.path);
+    assert_eq!(Some(&String::from("Bearer abc")), request.headers.get("authorization"));
+    assert_eq!(b"{\"reads\":[]", request.body.as_slice());
+    let request = parse_request(b"get / HTTP/1.1\r\n\r\n").unwrap().unwrap();
+    assert_eq!("GET", request.method);
+    assert!(request.body.is_empty());
+}
+
+#[test]
+fn test_parse_request_errors() {
+    assert!(parse_request(b"POST\r\n\r\n").is_err());
+    assert!(parse_request(b"POST / HTTP/1.1\r\nContent-Length: abc\r\n\r\n").is_err());
+    assert!(parse_request(b"POST / HTTP/1.1\r\nContent-Length: 999999999\r\n\r\n").is_err());
+    assert!(parse_request(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n").is_err());
+    assert!(parse_request(&vec![b'a'; MAX_HEADER_LENGTH + 1]).is_err());
+}
+
+#[test]
+fn test_response() {
+    assert_eq!(
+        b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 11\r\nConnection: close\r\n\r\n{\"count\":5}".to_vec(),
+        response(200, "{\"count\":5}")
+    );
+    assert!(response(401, "").starts_with(b"HTTP/1.1 401 Unauthorized\r\n"));
+}
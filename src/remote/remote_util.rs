@@ -1,122 +1,77 @@
-use crate::{control::socket, network::api, objects::read};
+use std::time::Duration;
+
+use rand::Rng;
+use log::error;
+
+use crate::{control::socket, network::api, objects::{read, upload_batch::UploadBatch}, remote::responses::UploadReadsResponse};
+
+#[cfg(test)]
+pub mod test;
+
+pub const HEADER_IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+pub const BASE_BACKOFF_SECONDS: u64 = 5;
+pub const MAX_BACKOFF_SECONDS: u64 = 10 * 60;
 
 pub fn upload_all_reads(
     http_client: &reqwest::blocking::Client,
     api: &api::Api,
-    reads: Vec<read::Read>
+    reads: Vec<read::Read>,
+    batch_size: usize,
 ) -> (Vec<read::Read>, usize)
 {
     let mut modified_reads: Vec<read::Read> = Vec::new();
     let mut err_count: usize = 0;
-    // only upload in chunks of 50
-    if reads.len() > 50 {
-        //println!("Attempting to upload {} reads.", reads.len());
-        // get the total number of full 50 count loops to do
-        let num_loops = reads.len() / 50;
-        let mut loop_counter = 0;
-        // counter starts at 0, num_loops is at minimum 1
-        // after the first loop counter is 1 and should exit if only 50 items
-        while loop_counter < num_loops {
-            let start_ix = loop_counter * 50;
-            let slice = &reads[start_ix..start_ix+50];
-            match socket::upload_reads(http_client, api, &slice) {
-                Ok(count) => {
-                    // if we uploaded the correct
-                    if count == 50 {
-                        for read in slice {
-                            modified_reads.push(read::Read::new(
-                                read.id(),
-                                String::from(read.chip()),
-                                read.seconds(),
-                                read.milliseconds(),
-                                read.reader_seconds(),
-                                read.reader_milliseconds(),
-                                read.antenna(),
-                                String::from(read.reader()),
-                                String::from(read.rssi()),
-                                read.status(),
-                                read::READ_UPLOADED_TRUE
-                            ))
-                        }
-                    } else {
-                        println!("Error uploading reads. Count doesn't match. {} uploaded, expected {}", count, 50);
-                        err_count += 1;
-                    }
-                },
-                Err(e) => {
-                    println!("Error uploading reads: {:?}", e);
-                    err_count += 1;
-                }
-            }
-            loop_counter = loop_counter + 1;
-        }
-        let start_ix = loop_counter * 50;
-        let slice = &reads[start_ix..reads.len()];
-        match socket::upload_reads(http_client, api, &slice) {
-            Ok(count) => {
-                // Need to calculate the count... for 75 items (0-74)
-                // only 1 loop, start_ix should be (1 * 50)
-                // 75 - 50 = 25
-                let amt = reads.len() - start_ix;
-                // check for correct amout
-                if count == amt {
+    // only upload in chunks of batch_size
+    for slice in reads.chunks(batch_size.max(1)) {
+        match socket::upload_reads(http_client, api, slice, None) {
+            Ok(resp) => {
+                // if we uploaded the correct amount
+                if resp.count == slice.len() {
                     for read in slice {
-                        modified_reads.push(read::Read::new(
-                            read.id(),
-                            String::from(read.chip()),
-                            read.seconds(),
-                            read.milliseconds(),
-                            read.reader_seconds(),
-                            read.reader_milliseconds(),
-                            read.antenna(),
-                            String::from(read.reader()),
-                            String::from(read.rssi()),
-                            read.status(),
-                            read::READ_UPLOADED_TRUE
-                        ));
+                        let mut read = read.clone();
+                        read.set_uploaded(read::READ_UPLOADED_TRUE);
+                        modified_reads.push(read);
                     }
                 } else {
-                    println!("Error uploading reads. Count doesn't match. {} uploaded, expected {}", count, amt);
+                    error!("Error uploading reads. Count doesn't match. {} uploaded, expected {}", resp.count, slice.len());
                     err_count += 1;
                 }
             },
             Err(e) => {
-                println!("Error uploading reads: {:?}", e);
-                err_count += 1;
-            }
-        }
-    } else if reads.len() > 0 {
-        //println!("Attempting to upload {} reads.", reads.len());
-        match socket::upload_reads(http_client, api, &reads) {
-            Ok(count) => {
-                // if we uploaded the correct
-                if count == reads.len() {
-                    let mut modified_reads: Vec<read::Read> = Vec::new();
-                    for read in reads {
-                        modified_reads.push(read::Read::new(
-                            read.id(),
-                            String::from(read.chip()),
-                            read.seconds(),
-                            read.milliseconds(),
-                            read.reader_seconds(),
-                            read.reader_milliseconds(),
-                            read.antenna(),
-                            String::from(read.reader()),
-                            String::from(read.rssi()),
-                            read.status(),
-                            read::READ_UPLOADED_TRUE
-                        ));
-                    }
-                } else {
-                    println!("Error uploading reads. Count doesn't match. {} uploaded, expected {}", count, reads.len());
-                    err_count += 1;
-                }
-            },
-            Err(e) => {
-                println!("Error uploading reads: {:?}", e);
+                error!("Error uploading reads: {:?}", e);
                 err_count += 1;
             }
         }
     }
-    return (modified_reads, err_count);
-}
\ No newline at end of file
+    (modified_reads, err_count)
+}
+
+// Exponential backoff with jitter so a group of portals that lost their connection at the
+// same time don't all retry at once.  Half the delay is fixed and the jitter, between 0 and 1,
+// decides how much of the other half we wait.
+pub fn backoff(failures: u32, jitter: f64) -> Duration {
+    let exp = failures.saturating_sub(1).min(16);
+    let max = (BASE_BACKOFF_SECONDS << exp).min(MAX_BACKOFF_SECONDS) as f64;
+    Duration::from_secs_f64(max / 2.0 + max / 2.0 * jitter.clamp(0.0, 1.0))
+}
+
+pub fn backoff_with_jitter(failures: u32) -> Duration {
+    backoff(failures, rand::thread_rng().gen::<f64>())
+}
+
+// Batch ids are sent with each upload so the server can ignore a batch it has already
+// received if we didn't get the response.
+pub fn new_batch_id() -> String {
+    let bytes: [u8; 16] = rand::thread_rng().gen();
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+// The reads in a batch the server says it took.  Servers that don't tell us which only
+// took all of them if the count says so.
+pub fn accepted_reads(batch: &UploadBatch, response: &UploadReadsResponse) -> Vec<u64> {
+    match &response.accepted {
+        Some(ids) => batch.read_ids().into_iter().filter(|id| ids.contains(id)).collect(),
+        None if response.count == batch.reads().len() => batch.read_ids(),
+        None => Vec::new(),
+    }
+}
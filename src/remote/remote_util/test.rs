@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use crate::{objects::{read::{self, Read}, upload_batch::UploadBatch}, remote::responses::UploadReadsResponse};
+
+use super::{accepted_reads, backoff, new_batch_id, MAX_BACKOFF_SECONDS};
+
+fn make_reads(count: u64) -> Vec<Read> {
+    (1..=count).map(|id| Read::new(
+        id,
+        format!("{id}"),
+        1_700_000_000,
+        0,
+        1_700_000_000,
+        0,
+        1,
+        String::from("reader"),
+        String::from("-50"),
+        read::READ_STATUS_UNUSED,
+        read::READ_UPLOADED_FALSE,
+    )).collect()
+}
+
+#[test]
+fn test_backoff() {
+    assert_eq!(Duration::from_secs_f64(2.5), backoff(1, 0.0));
+    assert_eq!(Duration::from_secs(5), backoff(1, 1.0));
+    assert_eq!(Duration::from_secs(5), backoff(2, 0.0));
+    assert_eq!(Duration::from_secs(15), backoff(3, 0.5));
+    assert_eq!(Duration::from_secs(MAX_BACKOFF_SECONDS), backoff(20, 1.0));
+    assert_eq!(Duration::from_secs(MAX_BACKOFF_SECONDS / 2), backoff(u32::MAX, -1.0));
+}
+
+#[test]
+fn test_new_batch_id() {
+    let id = new_batch_id();
+    assert_eq!(32, id.len());
+    assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_ne!(id, new_batch_id());
+}
+
+#[test]
+fn test_accepted_reads() {
+    let batch = UploadBatch::new(String::from("abc"), 4, 3, make_reads(5));
+    let response = |count: usize, accepted: Option<Vec<u64>>| UploadReadsResponse { count, accepted };
+    assert_eq!(vec![1, 2, 3, 4, 5], accepted_reads(&batch, &response(5, Some(vec![1, 2, 3, 4, 5]))));
+    assert_eq!(vec![2, 4], accepted_reads(&batch, &response(2, Some(vec![4, 2]))));
+    // ids that weren't in the batch don't count
+    assert_eq!(vec![3], accepted_reads(&batch, &response(2, Some(vec![3, 9]))));
+    assert!(accepted_reads(&batch, &response(0, Some(Vec::new()))).is_empty());
+    // without ids only a full count tells us anything
+    assert_eq!(vec![1, 2, 3, 4, 5], accepted_reads(&batch, &response(5, None)));
+    assert!(accepted_reads(&batch, &response(3, None)).is_empty());
+}
@@ -1,10 +1,13 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::objects::{notification::RemoteNotification, read};
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UploadReadsRequest {
-    pub reads: Vec<read::Read>
+    pub reads: Vec<read::Read>,
+    // Our ids for the reads, in the same order, so the server can tell us which ones it took.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ids: Vec<u64>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -2,5 +2,8 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct UploadReadsResponse {
-    pub(crate) count: usize
+    pub(crate) count: usize,
+    // The ids of the reads the server took, servers that don't send them only tell us how many.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) accepted: Option<Vec<u64>>,
 }
\ No newline at end of file
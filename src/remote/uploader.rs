@@ -1,9 +1,9 @@
-use std::{net::TcpStream, sync::{Arc, Mutex}, thread, time::Duration};
+use std::{collections::HashMap, sync::{Arc, Mutex}, thread, time::{Duration, Instant}};
 
-use reqwest::StatusCode;
 use serde::Serialize;
+use log::{error, info, warn};
 
-use crate::{control::{socket::{write_uploader_status, MAX_CONNECTED}, Control}, database::{sqlite, Database}, defaults, network::api, objects::read, screen::CharacterDisplay};
+use crate::{control::{socket, Control}, database::{self, sqlite, Database}, defaults, events::{self, EventBus}, network::{api, connectivity::Connectivity, http::ClientFactory}, objects::{system_event, upload_batch::UploadBatch}, screen::CharacterDisplay};
 use crate::remote::remote_util;
 
 #[derive(Clone, PartialEq, Serialize, Debug)]
@@ -14,32 +14,54 @@ pub enum Status {
     Unknown
 }
 
+// How often we check for remote apis being added or removed.
+pub const WAKE_SECONDS: u64 = 5;
+
+// Upload progress for a single remote api.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct Destination {
+    pub api_id: i64,
+    pub nickname: String,
+    pub pending: usize,
+    pub uploaded: usize,
+    pub rejected: usize,
+    pub errors: usize,
+}
+
 pub struct Uploader {
     server_keepalive: Arc<Mutex<bool>>,
     local_keepalive: Arc<Mutex<bool>>,
     sqlite: Arc<Mutex<sqlite::SQLite>>,
     status: Arc<Mutex<Status>>,
-    control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
+    event_bus: EventBus,
     control: Arc<Mutex<Control>>,
     screen: Arc<Mutex<Option<CharacterDisplay>>>,
+    destinations: Arc<Mutex<Vec<Destination>>>,
+    connectivity: Arc<Connectivity>,
+    http: Arc<ClientFactory>,
 }
 
 impl Uploader {
     pub fn new(
         keepalive: Arc<Mutex<bool>>,
         sqlite: Arc<Mutex<sqlite::SQLite>>,
-        control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
+        event_bus: EventBus,
         control: Arc<Mutex<Control>>,
         screen: Arc<Mutex<Option<CharacterDisplay>>>,
+        connectivity: Arc<Connectivity>,
+        http: Arc<ClientFactory>,
     ) -> Uploader {
         Uploader {
             server_keepalive: keepalive,
             local_keepalive: Arc::new(Mutex::new(false)),
             sqlite,
             status: Arc::new(Mutex::new(Status::Stopped)),
-            control_sockets,
+            event_bus,
             control,
             screen,
+            destinations: Arc::new(Mutex::new(Vec::new())),
+            connectivity,
+            http,
         }
     }
 
@@ -51,6 +73,13 @@ impl Uploader {
         output
     }
 
+    pub fn destinations(&self) -> Vec<Destination> {
+        match self.destinations.lock() {
+            Ok(dests) => dests.clone(),
+            Err(_) => Vec::new(),
+        }
+    }
+
     pub fn running(&self) -> bool {
         let mut output = false;
         if let Ok(r) = self.status.lock() {
@@ -67,11 +96,10 @@ impl Uploader {
             *r = Status::Stopping
         }
         // let everyone know we're stopping
-        self.update_control_socks(0);
+        self.update_control_socks();
     }
 
     pub fn run(&self) {
-        let mut err_count: usize = 0;
         // check if we're already running, exit if so, otherwise set to running
         if let Ok(mut r) = self.status.lock() {
             if *r == Status::Running {
@@ -80,155 +108,270 @@ impl Uploader {
             *r = Status::Running;
         }
         // let everyone know we're running
-        self.update_control_socks(err_count);
+        self.update_control_socks();
         // set local keepalive to true to keep running until told to stop
         if let Ok(mut ka) = self.local_keepalive.lock() {
             *ka = true;
         }
-        let http_client: reqwest::blocking::Client;
-        match reqwest::blocking::ClientBuilder::new().timeout(Duration::from_secs(30))
-                                    .connect_timeout(Duration::from_secs(30)).build() {
-            Ok(client) => {
-                http_client = client;
-            },
-            Err(_) => {
-                if let Ok(mut r) = self.status.lock() {
-                    *r = Status::Stopped;
-                    // let everyone know we're stopped
-                    self.update_control_socks(0);
-                }
-                println!("Unable to get our http client. Auto upload terminating.");
-                return;
-            },
-        }
-        // work loop
-        loop {
-            // exit our loop and terminate if local keep alive is done
-            if let Ok(ka) = self.local_keepalive.lock() {
-                if *ka == false {
+        // each remote api gets its own worker so a slow or unreachable one doesn't hold up the rest
+        thread::scope(|scope| {
+            let mut workers: HashMap<i64, Arc<Mutex<bool>>> = HashMap::new();
+            while self.keep_running() {
+                let apis: Vec<api::Api> = match self.sqlite.lock() {
+                    Ok(sq) => match sq.get_apis() {
+                        Ok(apis) => apis.into_iter()
+                            .filter(|a| a.kind() == api::API_TYPE_CHRONOKEEP_REMOTE || a.kind() == api::API_TYPE_CHRONOKEEP_REMOTE_SELF)
+                            .collect(),
+                        Err(e) => {
+                            warn!("Unable to get apis: {e}");
+                            thread::sleep(Duration::from_secs(WAKE_SECONDS));
+                            continue;
+                        }
+                    },
+                    Err(_) => break,
+                };
+                if apis.is_empty() {
+                    info!("No remote API set up.");
                     break;
                 }
-            } else {
-                println!("Unable to grab local keep alive mutex. Exiting.");
-                break;
+                // stop the workers for anything that has been removed
+                workers.retain(|id, active| {
+                    if apis.iter().any(|a| a.id() == *id) {
+                        return true
+                    }
+                    if let Ok(mut a) = active.lock() {
+                        *a = false;
+                    }
+                    false
+                });
+                if let Ok(mut dests) = self.destinations.lock() {
+                    dests.retain(|d| apis.iter().any(|a| a.id() == d.api_id));
+                }
+                for api in apis {
+                    if workers.contains_key(&api.id()) {
+                        continue;
+                    }
+                    let active = Arc::new(Mutex::new(true));
+                    workers.insert(api.id(), active.clone());
+                    scope.spawn(move|| {
+                        self.upload_to(api, active);
+                    });
+                }
+                thread::sleep(Duration::from_secs(WAKE_SECONDS));
             }
-            // exit our loop and terminate if server is shutting down
-            if let Ok(ka) = self.server_keepalive.lock() {
-                if *ka == false {
-                    break;
+            for active in workers.values() {
+                if let Ok(mut a) = active.lock() {
+                    *a = false;
                 }
-            } else {
-                println!("Unable to grab server keep alive mutex. Exiting.");
+            }
+        });
+        if let Ok(mut r) = self.status.lock() {
+            *r = Status::Stopped;
+        }
+        if let Ok(mut dests) = self.destinations.lock() {
+            dests.clear();
+        }
+        // let everyone know we're stopped
+        self.update_control_socks();
+        info!("Auto upload of reads finished.")
+    }
+
+    fn keep_running(&self) -> bool {
+        // exit our loop and terminate if local keep alive is done
+        match self.local_keepalive.lock() {
+            Ok(ka) if *ka => {},
+            Ok(_) => return false,
+            Err(_) => {
+                warn!("Unable to grab local keep alive mutex. Exiting.");
+                return false
+            }
+        }
+        // exit our loop and terminate if server is shutting down
+        match self.server_keepalive.lock() {
+            Ok(ka) => *ka,
+            Err(_) => {
+                warn!("Unable to grab server keep alive mutex. Exiting.");
+                false
+            }
+        }
+    }
+
+    fn upload_to(&self, api: api::Api, active: Arc<Mutex<bool>>) {
+        let mut failures: u32 = 0;
+        info!("Starting uploads to {}.", api.nickname());
+        self.update_destination(&api, failures as usize);
+        loop {
+            if !self.keep_running() {
                 break;
             }
-            let mut to_upload: Vec<read::Read> = Vec::new();
-            let mut upload_api: Option<api::Api> = None;
-            // get our reads and then upload them
-            // check error count
-            // if there are 2 or more errors then don't try to grab the database mutex, instead check net first
-            // if the chronokeep results api health endpoint isn't reachable then the rest of the internet
-            // is probably not reachable
-            // one error could just be random, two means it's happened multiple times in a row
-            // and a check to the health endpoint is a quick way to save some work
-            let mut attempt_upload = true;
-            if err_count > 1 {
-                attempt_upload = false;
-                match http_client.get("https://api.chronokeep.com/health").send() {
-                    Ok(resp) => {
-                        match resp.status() {
-                            StatusCode::OK | StatusCode::NO_CONTENT => {
-                                attempt_upload = true;
-                            }
-                            _ => {
-                                err_count += 1;
-                            }
-                        }
+            match active.lock() {
+                Ok(a) if *a => {},
+                _ => break,
+            }
+            // wait for the connection to come back instead of counting errors
+            if !self.connectivity.is_reachable(api.uri()) {
+                self.sleep_while_active(&active, Duration::from_secs(WAKE_SECONDS));
+                continue;
+            }
+            let (batch_size, upload_interval) = match self.control.lock() {
+                Ok(c) => (c.upload_batch_size, c.upload_interval),
+                Err(_) => (defaults::DEFAULT_UPLOAD_BATCH_SIZE, defaults::DEFAULT_UPLOAD_INTERVAL),
+            };
+            let pause = match self.next_batch(&api, batch_size) {
+                // the client is asked for each batch so settings changes are picked up
+                Ok(Some(batch)) => match self.http.client().and_then(|c| self.send_batch(&c, &api, batch)) {
+                    Ok(()) => {
+                        failures = 0;
+                        // keep going while there's a backlog
+                        Duration::ZERO
                     },
-                    Err(_) => {
-                        err_count += 1;
+                    Err(e) => {
+                        failures += 1;
+                        self.connectivity.recheck();
+                        let delay = remote_util::backoff_with_jitter(failures);
+                        error!("Error uploading reads to {}: {e}. Retrying in {} seconds.", api.nickname(), delay.as_secs());
+                        database::log_event(
+                            &self.sqlite,
+                            system_event::SEVERITY_WARNING,
+                            system_event::SOURCE_UPLOADER,
+                            system_event::SYSTEM_EVENT_UPLOAD_FAILED,
+                            serde_json::json!({ "api": api.nickname(), "error": e.to_string(), "failures": failures }),
+                        );
+                        delay
                     }
+                },
+                Ok(None) => Duration::from_secs(upload_interval),
+                Err(e) => {
+                    error!("Error getting reads to upload: {e}");
+                    Duration::from_secs(upload_interval)
                 }
-            }
-            if attempt_upload {
-                if let Ok(sq) = self.sqlite.lock() {
-                    match sq.get_apis() {
-                        Ok(apis) => {
-                            let mut found = false;
-                            for api in apis {
-                                if api.kind() == api::API_TYPE_CHRONOKEEP_REMOTE || api.kind() == api::API_TYPE_CHRONOKEEP_REMOTE_SELF {
-                                    found = true;
-                                    upload_api = Some(api.clone());
-                                    match sq.get_not_uploaded_reads() {
-                                        Ok(mut reads) => {
-                                            to_upload.append(&mut reads);
-                                        },
-                                        Err(e) => {
-                                            println!("Error getting reads to upload: {e}");
-                                        }
-                                    }
-                                    // should only be one REMOTE or REMOTE_SELF type of API in the database
-                                    // so we can break
-                                    break;
-                                }
-                            }
-                            if found == false {
-                                println!("No remote API set up.");
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            println!("Unable to get apis: {e}");
-                        }
-                    }
+            };
+            self.update_destination(&api, failures as usize);
+            self.sleep_while_active(&active, pause);
+        } // end auto upload loop
+        info!("Stopped uploads to {}.", api.nickname());
+    }
+
+    // Returns the oldest batch we haven't finished sending, or saves a new one from
+    // the reads that haven't been uploaded yet.
+    fn next_batch(&self, api: &api::Api, batch_size: usize) -> Result<Option<UploadBatch>, String> {
+        let mut sq = match self.sqlite.lock() {
+            Ok(sq) => sq,
+            Err(_) => return Err(String::from("unable to get database mutex")),
+        };
+        match sq.get_upload_batches(api.id()) {
+            Ok(batches) => {
+                if let Some(batch) = batches.into_iter().next() {
+                    return Ok(Some(batch))
                 }
-            }
-            // upload any reads we found in the database if we found our remote API
-            if let Some(api) = upload_api {
-                if to_upload.len() > 0 {
-                    let (modified_reads, e_count) = remote_util::upload_all_reads(&http_client, &api, to_upload);
-                    if let Ok(mut sq) = self.sqlite.lock() {
-                        match sq.update_reads_status(&modified_reads) {
-                            Ok(_) => {},
-                            Err(e) => {
-                                println!("Error updating uploaded reads: {e}");
-                            }
-                        }
-                    }
-                    if e_count > 0 {
-                        err_count += e_count;
-                        self.update_control_socks(err_count);
-                    } else if err_count != 0 {
-                        err_count = 0;
-                        self.update_control_socks(err_count);
-                    }
+            },
+            Err(e) => return Err(e.to_string()),
+        }
+        let reads = match sq.get_reads_to_upload(api.id(), batch_size.max(1) as u32) {
+            Ok(reads) => reads,
+            Err(e) => return Err(e.to_string()),
+        };
+        if reads.is_empty() {
+            return Ok(None)
+        }
+        let batch = UploadBatch::new(remote_util::new_batch_id(), api.id(), 0, reads);
+        match sq.save_upload_batch(&batch) {
+            Ok(_) => Ok(Some(batch)),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    // Sends a batch.  If the server only takes some of the reads the ones it took are recorded
+    // and the rest are sent again under the same id, anything it won't take at all is set aside
+    // instead of blocking everything after it.
+    fn send_batch(&self, http_client: &reqwest::blocking::Client, api: &api::Api, batch: UploadBatch) -> Result<(), String> {
+        let result = socket::upload_reads(http_client, api, batch.reads(), Some(batch.id()));
+        let mut sq = match self.sqlite.lock() {
+            Ok(sq) => sq,
+            Err(_) => return Err(String::from("unable to get database mutex")),
+        };
+        let saved = match result.map(|resp| (resp.count, remote_util::accepted_reads(&batch, &resp))) {
+            Ok((_, accepted)) if accepted.len() == batch.reads().len() => sq.complete_upload_batch(&batch),
+            Ok((count, accepted)) if !accepted.is_empty() => {
+                info!("Server accepted {count} of {} reads in batch {}, retrying the rest.", batch.reads().len(), batch.id());
+                sq.accept_upload_batch_reads(&batch, &accepted)
+            },
+            Ok((count, _)) => {
+                warn!("Server didn't take any of reads {:?} in batch {} that we can tell, it counted {count}. Setting them aside.", batch.read_ids(), batch.id());
+                sq.reject_upload_batch(batch.id())
+            },
+            Err(e) => {
+                if let Err(e) = sq.set_upload_batch_attempts(batch.id(), batch.attempts() + 1) {
+                    error!("Error saving upload attempts: {e}");
                 }
+                return Err(format!("{e:?}"))
             }
+        };
+        match saved {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
 
-            let mut upload_pause: u64 = defaults::DEFAULT_UPLOAD_INTERVAL;
-            if let Ok(control) = self.control.lock() {
-                upload_pause = control.upload_interval;
+    fn sleep_while_active(&self, active: &Arc<Mutex<bool>>, duration: Duration) {
+        let until = Instant::now() + duration;
+        while Instant::now() < until && self.keep_running() {
+            match active.lock() {
+                Ok(a) if *a => {},
+                _ => return,
             }
-            // sleep for AUTO_UPLOAD_PAUSE seconds
-            thread::sleep(Duration::from_secs(upload_pause));
-        } // end auto upload loop
-        if let Ok(mut r) = self.status.lock() {
-            *r = Status::Stopped;
+            thread::sleep((until - Instant::now()).min(Duration::from_secs(1)));
         }
-        // let everyone know we're stopped
-        self.update_control_socks(0);
-        println!("Auto upload of reads finished.")
     }
 
-    fn update_control_socks(&self, err_count: usize) {
-        // let all the control sockets know of our status
-        let stat = self.status();
-        if let Ok(c_socks) = self.control_sockets.lock() {
-            for sock in c_socks.iter() {
-                if let Some(sock) = sock {
-                    _ = write_uploader_status(&sock, stat.clone());
+    // Updates the counts for the destination, letting everyone know if anything changed.
+    fn update_destination(&self, api: &api::Api, errors: usize) {
+        let (pending, uploaded, rejected) = match self.sqlite.lock() {
+            Ok(sq) => match sq.get_upload_counts(api.id()) {
+                Ok(counts) => counts,
+                Err(e) => {
+                    error!("Error getting upload counts: {e}");
+                    return
                 }
-            }
+            },
+            Err(_) => return,
+        };
+        let updated = Destination {
+            api_id: api.id(),
+            nickname: String::from(api.nickname()),
+            pending,
+            uploaded,
+            rejected,
+            errors,
+        };
+        let changed = match self.destinations.lock() {
+            Ok(mut dests) => match dests.iter_mut().find(|d| d.api_id == api.id()) {
+                Some(d) if *d == updated => false,
+                Some(d) => {
+                    *d = updated;
+                    true
+                },
+                None => {
+                    dests.push(updated);
+                    true
+                }
+            },
+            Err(_) => false,
+        };
+        if changed {
+            self.update_control_socks();
         }
+    }
+
+    fn update_control_socks(&self) {
+        // let all the control sockets know of our status
+        let stat = self.status();
+        let destinations = self.destinations();
+        let err_count = destinations.iter().map(|d| d.errors).sum();
+        self.event_bus.publish(events::Event::UploaderStatus {
+            status: stat.clone(),
+            destinations,
+        });
         if let Ok(mut screen_opt) = self.screen.lock() {
             if let Some(screen) = &mut *screen_opt {
                 screen.update_upload_status(stat, err_count);
@@ -0,0 +1,293 @@
+use std::{collections::HashMap, sync::{mpsc::{Receiver, RecvTimeoutError}, Arc, Mutex}, time::{Duration, Instant}};
+
+use chrono::Utc;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use ring::hmac;
+use serde::Serialize;
+use log::{error, info, warn};
+
+use crate::{control::Control, database::{sqlite, Database, DBError}, events::{Event, EventBus}, network::{api, connectivity::Connectivity, http::ClientFactory}, objects::{sighting, system_event::{self, SystemEvent}}, remote::remote_util};
+
+#[cfg(test)]
+pub mod test;
+
+pub const STREAM_READS: &str = "reads";
+pub const STREAM_SIGHTINGS: &str = "sightings";
+pub const STREAM_EVENTS: &str = "events";
+pub const STREAMS: [&str; 3] = [STREAM_READS, STREAM_SIGHTINGS, STREAM_EVENTS];
+
+pub const HEADER_SIGNATURE: &str = "X-Chronokeep-Signature";
+pub const HEADER_STREAM: &str = "X-Chronokeep-Stream";
+pub const HEADER_DELIVERY: &str = "X-Chronokeep-Delivery";
+
+pub const BATCH_SIZE: u32 = 100;
+// Limits how long one target can hold things up when it has a big backlog.
+pub const MAX_BATCHES_PER_PASS: usize = 10;
+pub const WAKE_SECONDS: u64 = 1;
+
+#[derive(Serialize)]
+struct Batch<'a, T: Serialize> {
+    portal: &'a str,
+    stream: &'a str,
+    delivery_id: &'a str,
+    items: &'a [T],
+}
+
+// Sightings go out as they are, the id is only used for the cursor.  A sighting keeps its id
+// when it's worked out again so re-processing reads doesn't send it twice.
+#[derive(Serialize)]
+struct SightingItem<'a> {
+    #[serde(skip)]
+    id: i64,
+    #[serde(flatten)]
+    sighting: &'a sighting::Sighting,
+}
+
+struct Backoff {
+    failures: u32,
+    next_attempt: Instant,
+}
+
+// Delivers reads, sightings and system events to every WEBHOOK api in batches.  Each target
+// has its own cursor per stream saved in the database so nothing is missed or re-sent across
+// restarts, and a target that's failing is backed off without holding up the others.
+pub struct Webhooks {
+    keepalive: Arc<Mutex<bool>>,
+    sqlite: Arc<Mutex<sqlite::SQLite>>,
+    control: Arc<Mutex<Control>>,
+    receiver: Receiver<Arc<Event>>,
+    backoff: HashMap<i64, Backoff>,
+    connectivity: Arc<Connectivity>,
+    http: Arc<ClientFactory>,
+}
+
+impl Webhooks {
+    pub fn new(
+        keepalive: Arc<Mutex<bool>>,
+        sqlite: Arc<Mutex<sqlite::SQLite>>,
+        control: Arc<Mutex<Control>>,
+        event_bus: &EventBus,
+        connectivity: Arc<Connectivity>,
+        http: Arc<ClientFactory>,
+    ) -> Self {
+        Self {
+            keepalive,
+            sqlite,
+            control,
+            receiver: event_bus.listen(),
+            backoff: HashMap::new(),
+            connectivity,
+            http,
+        }
+    }
+
+    pub fn run(&mut self) {
+        loop {
+            if let Ok(keepalive) = self.keepalive.lock() {
+                if !*keepalive {
+                    break;
+                }
+            }
+            match self.receiver.recv_timeout(Duration::from_secs(WAKE_SECONDS)) {
+                Ok(event) => self.record(&event),
+                Err(RecvTimeoutError::Timeout) => {},
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            while let Ok(event) = self.receiver.try_recv() {
+                self.record(&event);
+            }
+            match self.http.client() {
+                Ok(http_client) => self.deliver(&http_client),
+                Err(e) => warn!("Unable to get http client for webhooks. {e}"),
+            }
+        }
+        info!("Webhook thread stopping.");
+    }
+
+    // Saves the events that aren't already stored elsewhere so they can be delivered.
+    fn record(&self, event: &Event) {
+        let (kind, source, detail) = match event {
+            Event::Notification { notification, time } => (
+                system_event::SYSTEM_EVENT_NOTIFICATION,
+                system_event::SOURCE_NOTIFIER,
+                serde_json::json!({ "kind": notification, "time": time }),
+            ),
+            Event::ReaderList(readers) => (
+                system_event::SYSTEM_EVENT_READER_STATUS,
+                system_event::SOURCE_READER,
+                serde_json::json!({ "readers": readers }),
+            ),
+            _ => return,
+        };
+        if let Ok(sq) = self.sqlite.lock() {
+            if let Err(e) = sq.save_system_event(&SystemEvent::new(
+                0,
+                String::from(kind),
+                Utc::now().timestamp(),
+                detail,
+            ).with_source(source)) {
+                error!("Error saving system event. {e}");
+            }
+        }
+    }
+
+    fn deliver(&mut self, http_client: &reqwest::blocking::Client) {
+        let targets: Vec<api::Api> = match self.sqlite.lock() {
+            Ok(sq) => match sq.get_apis() {
+                Ok(apis) => apis.into_iter().filter(|a| a.kind() == api::API_TYPE_WEBHOOK).collect(),
+                Err(e) => {
+                    error!("Error getting webhook targets. {e}");
+                    return
+                }
+            },
+            Err(_) => return,
+        };
+        // forget about anything that has been removed
+        self.backoff.retain(|id, _| targets.iter().any(|t| t.id() == *id));
+        if targets.is_empty() {
+            return
+        }
+        let portal = match self.control.lock() {
+            Ok(c) => c.name.clone(),
+            Err(_) => return,
+        };
+        for target in targets.iter() {
+            // everything stays saved until we can reach them again
+            if !self.connectivity.is_reachable(target.uri()) {
+                continue;
+            }
+            if let Some(backoff) = self.backoff.get(&target.id()) {
+                if Instant::now() < backoff.next_attempt {
+                    continue;
+                }
+            }
+            let mut failed = false;
+            'streams: for stream in STREAMS {
+                for _ in 0..MAX_BATCHES_PER_PASS {
+                    match self.deliver_batch(http_client, target, stream, &portal) {
+                        Ok(true) => {},
+                        Ok(false) => break,
+                        Err(e) => {
+                            error!("Error delivering {stream} to webhook {}. {e}", target.nickname());
+                            failed = true;
+                            break 'streams;
+                        }
+                    }
+                }
+            }
+            if failed {
+                let failures = self.backoff.get(&target.id()).map_or(0, |b| b.failures) + 1;
+                let delay = remote_util::backoff_with_jitter(failures);
+                warn!("Retrying webhook {} in {} seconds.", target.nickname(), delay.as_secs());
+                self.backoff.insert(target.id(), Backoff {
+                    failures,
+                    next_attempt: Instant::now() + delay,
+                });
+            } else {
+                self.backoff.remove(&target.id());
+            }
+        }
+    }
+
+    // Sends the next batch for the stream.  Returns true if something was sent.
+    fn deliver_batch(
+        &self,
+        http_client: &reqwest::blocking::Client,
+        target: &api::Api,
+        stream: &str,
+        portal: &str,
+    ) -> Result<bool, String> {
+        let (body, first, last) = {
+            let sq = match self.sqlite.lock() {
+                Ok(sq) => sq,
+                Err(_) => return Err(String::from("unable to get database mutex")),
+            };
+            let cursor = match sq.get_webhook_cursor(target.id(), stream) {
+                Ok(c) => c,
+                Err(DBError::NotFound) => 0,
+                Err(e) => return Err(e.to_string()),
+            };
+            let result = match stream {
+                STREAM_READS => sq.get_reads_after(cursor, BATCH_SIZE)
+                    .map(|items| serialize_batch(portal, stream, target.id(), &items, |r| r.id() as i64)),
+                STREAM_SIGHTINGS => sq.get_sightings_after(cursor, BATCH_SIZE)
+                    .map(|items| {
+                        let items: Vec<SightingItem> = items.iter().map(|(id, sighting)| SightingItem { id: *id, sighting }).collect();
+                        serialize_batch(portal, stream, target.id(), &items, |s| s.id)
+                    }),
+                _ => sq.get_system_events_after(cursor, BATCH_SIZE)
+                    .map(|items| serialize_batch(portal, stream, target.id(), &items, |e| e.id())),
+            };
+            match result {
+                Ok(Ok(Some(batch))) => batch,
+                Ok(Ok(None)) => return Ok(false),
+                Ok(Err(e)) => return Err(e),
+                Err(e) => return Err(e.to_string()),
+            }
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Ok(val) = HeaderValue::from_str(stream) {
+            headers.insert(HEADER_STREAM, val);
+        }
+        if let Ok(val) = HeaderValue::from_str(&delivery_id(target.id(), stream, first, last)) {
+            headers.insert(HEADER_DELIVERY, val);
+        }
+        if !target.token().is_empty() {
+            if let Ok(val) = HeaderValue::from_str(&format!("sha256={}", sign(target.token(), body.as_bytes()))) {
+                headers.insert(HEADER_SIGNATURE, val);
+            }
+        }
+        match http_client.post(target.uri()).headers(headers).body(body).send() {
+            Ok(resp) => {
+                if !resp.status().is_success() {
+                    return Err(format!("unexpected status code {}", resp.status()))
+                }
+            },
+            Err(e) => return Err(e.to_string()),
+        }
+        match self.sqlite.lock() {
+            Ok(sq) => {
+                if let Err(e) = sq.set_webhook_cursor(target.id(), stream, last) {
+                    error!("Error saving webhook cursor. {e}");
+                }
+            },
+            Err(_) => return Err(String::from("unable to get database mutex")),
+        }
+        Ok(true)
+    }
+}
+
+// Identifies a batch so receivers can ignore one they've already seen if we have to retry.
+pub fn delivery_id(api_id: i64, stream: &str, first: i64, last: i64) -> String {
+    format!("{api_id}-{stream}-{first}-{last}")
+}
+
+// Returns the body to send along with the first and last id in it, or None if there's nothing to send.
+fn serialize_batch<T: Serialize>(
+    portal: &str,
+    stream: &str,
+    api_id: i64,
+    items: &[T],
+    id: impl Fn(&T) -> i64,
+) -> Result<Option<(String, i64, i64)>, String> {
+    let (first, last) = match (items.first(), items.last()) {
+        (Some(f), Some(l)) => (id(f), id(l)),
+        _ => return Ok(None),
+    };
+    match serde_json::to_string(&Batch {
+        portal,
+        stream,
+        delivery_id: &delivery_id(api_id, stream, first, last),
+        items,
+    }) {
+        Ok(body) => Ok(Some((body, first, last))),
+        Err(e) => Err(format!("unable to serialize batch: {e}")),
+    }
+}
+
+// Hex encoded HMAC-SHA256 of the body using the target's token as the key.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::sign(&key, body).as_ref().iter().map(|b| format!("{b:02x}")).collect()
+}
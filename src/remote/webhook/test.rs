@@ -0,0 +1,15 @@
+use super::{delivery_id, sign};
+
+#[test]
+fn test_sign() {
+    // RFC 4231 test case 2
+    assert_eq!(
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        sign("Jefe", b"what do ya want for nothing?")
+    );
+}
+
+#[test]
+fn test_delivery_id() {
+    assert_eq!("3-reads-10-109", delivery_id(3, "reads", 10, 109));
+}
@@ -1,2 +1,4 @@
 pub mod requests;
-pub mod responses;
\ No newline at end of file
+pub mod responses;
+pub mod sync;
+pub mod upload;
\ No newline at end of file
@@ -15,4 +15,30 @@ pub struct GetBibChipsRequest {
 #[derive(Serialize, Debug, Clone)]
 pub struct GetEventRequest {
     pub(crate) slug: String
+}
+
+// A sighting as the results api wants it, names are left out for anonymous participants.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct UploadSighting {
+    // The results site's own id for the participant, empty for anyone added on the portal.
+    #[serde(skip_serializing_if="String::is_empty")]
+    pub(crate) participant_id: String,
+    pub(crate) bib: String,
+    pub(crate) first: String,
+    pub(crate) last: String,
+    pub(crate) gender: String,
+    pub(crate) age_group: String,
+    pub(crate) distance: String,
+    pub(crate) anonymous: bool,
+    pub(crate) chip: String,
+    pub(crate) location: String,
+    pub(crate) seconds: u64,
+    pub(crate) milliseconds: u32
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AddSightingsRequest {
+    pub(crate) slug: String,
+    pub(crate) year: String,
+    pub(crate) sightings: Vec<UploadSighting>
 }
\ No newline at end of file
@@ -21,4 +21,9 @@ pub struct GetParticipantsResponse {
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GetBibChipsResponse {
     pub bib_chips: Vec<bibchip::BibChip>
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AddSightingsResponse {
+    pub count: usize
 }
\ No newline at end of file
@@ -0,0 +1,298 @@
+use std::{collections::{HashMap, HashSet}, sync::{Arc, Mutex}, thread, time::{Duration, Instant}};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use log::{error, info, warn};
+
+use crate::{control::{socket, Control}, database::{sqlite, Database}, network::{api, connectivity::Connectivity, http::ClientFactory}, objects::{bibchip, participant, system_event::{self, SystemEvent}}, processor::{self, SightingsProcessor}};
+
+#[cfg(test)]
+pub mod test;
+
+pub const WAKE_SECONDS: u64 = 1;
+pub const HISTORY_LIMIT: u32 = 50;
+
+// What a single sync did, saved as a system event so the history survives restarts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SyncResult {
+    pub time: i64,
+    pub api_id: i64,
+    pub slug: String,
+    pub year: String,
+    pub participants_added: usize,
+    pub participants_changed: usize,
+    pub participants_removed: usize,
+    pub bibchips_added: usize,
+    pub bibchips_removed: usize,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Changes {
+    pub add_participants: Vec<participant::Participant>,
+    pub change_participants: Vec<participant::Participant>,
+    pub remove_participants: Vec<String>,
+    pub add_bibchips: Vec<bibchip::BibChip>,
+    pub remove_bibchips: Vec<bibchip::BibChip>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.add_participants.is_empty()
+            && self.change_participants.is_empty()
+            && self.remove_participants.is_empty()
+            && self.add_bibchips.is_empty()
+            && self.remove_bibchips.is_empty()
+    }
+
+    // Every chip and bib a read could have been saved with whose sightings may now
+    // belong to someone else.
+    pub fn affected(&self, local_bibchips: &[bibchip::BibChip]) -> Vec<String> {
+        let mut bibs: HashSet<&str> = HashSet::new();
+        for part in self.add_participants.iter().chain(self.change_participants.iter()) {
+            bibs.insert(part.bib());
+        }
+        for bib in self.remove_participants.iter() {
+            bibs.insert(bib);
+        }
+        let mut output: HashSet<String> = HashSet::new();
+        for bc in self.add_bibchips.iter().chain(self.remove_bibchips.iter()) {
+            output.insert(String::from(bc.chip()));
+            output.insert(String::from(bc.bib()));
+        }
+        for bc in local_bibchips.iter().filter(|bc| bibs.contains(bc.bib())) {
+            output.insert(String::from(bc.chip()));
+        }
+        for bib in bibs {
+            output.insert(String::from(bib));
+        }
+        let mut output: Vec<String> = output.into_iter().collect();
+        output.sort();
+        output
+    }
+}
+
+// Works out what has to change locally to match the results api.  Participants are matched on
+// bib and bibchips on chip.  Placeholders we made for chips nobody was assigned to are only
+// removed once the chip has been given to someone.
+pub fn diff(
+    local_parts: &[participant::Participant],
+    local_bibchips: &[bibchip::BibChip],
+    remote_parts: &[participant::Participant],
+    remote_bibchips: &[bibchip::BibChip],
+) -> Changes {
+    let mut output = Changes::default();
+    let local_map: HashMap<&str, &participant::Participant> = local_parts.iter().map(|p| (p.bib(), p)).collect();
+    let remote_bibs: HashSet<&str> = remote_parts.iter().map(|p| p.bib()).collect();
+    let remote_chips: HashSet<&str> = remote_bibchips.iter().map(|bc| bc.chip()).collect();
+    for part in remote_parts {
+        match local_map.get(part.bib()) {
+            Some(local) if local.equals(part) => {},
+            Some(_) => output.change_participants.push(part.clone()),
+            None => output.add_participants.push(part.clone()),
+        }
+    }
+    let mut placeholders: HashSet<&str> = HashSet::new();
+    for part in local_parts {
+        if remote_bibs.contains(part.bib()) {
+            continue;
+        }
+        if processor::is_placeholder(part) {
+            if remote_chips.contains(part.bib()) {
+                output.remove_participants.push(String::from(part.bib()));
+            } else {
+                placeholders.insert(part.bib());
+            }
+        } else {
+            output.remove_participants.push(String::from(part.bib()));
+        }
+    }
+    // only compare against who holds each chip now, closed out assignments are history
+    let current: Vec<&bibchip::BibChip> = local_bibchips.iter().filter(|bc| bc.is_current()).collect();
+    let local_chips: HashMap<&str, &str> = current.iter().map(|bc| (bc.chip(), bc.bib())).collect();
+    for bc in remote_bibchips {
+        if local_chips.get(bc.chip()) != Some(&bc.bib()) {
+            output.add_bibchips.push(bc.clone());
+        }
+    }
+    for bc in current {
+        if !remote_chips.contains(bc.chip()) && !placeholders.contains(bc.bib()) {
+            output.remove_bibchips.push(bc.clone());
+        }
+    }
+    output
+}
+
+// Keeps participants and bibchips up to date with an event on the results api so late
+// registrations and chip swaps show up without anyone having to ask for them.
+pub struct ParticipantSync {
+    keepalive: Arc<Mutex<bool>>,
+    sqlite: Arc<Mutex<sqlite::SQLite>>,
+    control: Arc<Mutex<Control>>,
+    sight_processor: Arc<SightingsProcessor>,
+    connectivity: Arc<Connectivity>,
+    http: Arc<ClientFactory>,
+}
+
+impl ParticipantSync {
+    pub fn new(
+        keepalive: Arc<Mutex<bool>>,
+        sqlite: Arc<Mutex<sqlite::SQLite>>,
+        control: Arc<Mutex<Control>>,
+        sight_processor: Arc<SightingsProcessor>,
+        connectivity: Arc<Connectivity>,
+        http: Arc<ClientFactory>,
+    ) -> Self {
+        Self {
+            keepalive,
+            sqlite,
+            control,
+            sight_processor,
+            connectivity,
+            http,
+        }
+    }
+
+    pub fn run(&self) {
+        let mut last_sync: Option<(Instant, (i64, String, String))> = None;
+        loop {
+            match self.keepalive.lock() {
+                Ok(ka) if *ka => {},
+                _ => break,
+            }
+            let (enabled, api_id, slug, year, interval) = match self.control.lock() {
+                Ok(c) => (
+                    c.enable_participant_sync,
+                    c.participant_sync_api,
+                    c.participant_sync_slug.clone(),
+                    c.participant_sync_year.clone(),
+                    c.participant_sync_interval,
+                ),
+                Err(_) => break,
+            };
+            let target = (api_id, slug, year);
+            // sync right away when turned on or pointed at another event
+            let due = match &last_sync {
+                Some((at, t)) => *t != target || at.elapsed() >= Duration::from_secs(interval),
+                None => true,
+            };
+            if !enabled || target.1.is_empty() || target.2.is_empty() {
+                last_sync = None;
+            } else if due && self.connectivity.is_api_reachable(target.0) {
+                match self.http.client() {
+                    Ok(http_client) => self.sync(&http_client, target.0, &target.1, &target.2),
+                    Err(e) => warn!("Unable to get http client for participant sync. {e}"),
+                }
+                last_sync = Some((Instant::now(), target));
+            }
+            thread::sleep(Duration::from_secs(WAKE_SECONDS));
+        }
+        info!("Participant sync thread stopping.");
+    }
+
+    fn sync(&self, http_client: &reqwest::blocking::Client, api_id: i64, slug: &str, year: &str) {
+        let mut result = SyncResult {
+            time: Utc::now().timestamp(),
+            api_id,
+            slug: String::from(slug),
+            year: String::from(year),
+            participants_added: 0,
+            participants_changed: 0,
+            participants_removed: 0,
+            bibchips_added: 0,
+            bibchips_removed: 0,
+            error: None,
+        };
+        match self.apply(http_client, api_id, slug, year) {
+            Ok(Some(changes)) => {
+                result.participants_added = changes.add_participants.len();
+                result.participants_changed = changes.change_participants.len();
+                result.participants_removed = changes.remove_participants.len();
+                result.bibchips_added = changes.add_bibchips.len();
+                result.bibchips_removed = changes.remove_bibchips.len();
+                info!(
+                    "Participant sync for {slug} {year}: {} added, {} changed, {} removed, {} chips assigned, {} chips removed.",
+                    result.participants_added,
+                    result.participants_changed,
+                    result.participants_removed,
+                    result.bibchips_added,
+                    result.bibchips_removed,
+                );
+            },
+            // don't fill the history with syncs that didn't do anything
+            Ok(None) => return,
+            Err(e) => {
+                error!("Error syncing participants for {slug} {year}: {e}");
+                result.error = Some(e);
+            }
+        }
+        let detail = match serde_json::to_value(&result) {
+            Ok(d) => d,
+            Err(_) => return,
+        };
+        if let Ok(sq) = self.sqlite.lock() {
+            if let Err(e) = sq.save_system_event(&SystemEvent::new(
+                0,
+                String::from(system_event::SYSTEM_EVENT_PARTICIPANT_SYNC),
+                result.time,
+                detail,
+            ).with_source(system_event::SOURCE_PARTICIPANT_SYNC)) {
+                error!("Error saving participant sync history. {e}");
+            }
+        }
+    }
+
+    // Returns the changes made, or None if nothing was different.
+    fn apply(&self, http_client: &reqwest::blocking::Client, api_id: i64, slug: &str, year: &str) -> Result<Option<Changes>, String> {
+        let api = match self.sqlite.lock() {
+            Ok(sq) => match sq.get_apis() {
+                Ok(apis) => apis.into_iter().find(|a| a.id() == api_id),
+                Err(e) => return Err(e.to_string()),
+            },
+            Err(_) => return Err(String::from("unable to get database mutex")),
+        };
+        let api = match api {
+            Some(a) if a.kind() == api::API_TYPE_CHRONOKEEP_RESULTS || a.kind() == api::API_TYPE_CHRONOKEEP_RESULTS_SELF => a,
+            Some(_) => return Err(String::from("api is not a results api")),
+            None => return Err(String::from("api not found")),
+        };
+        let remote_parts: Vec<participant::Participant> = match socket::get_participants(http_client, &api, slug, year) {
+            Ok(parts) => parts.iter().map(|p| p.get_participant()).collect(),
+            Err(e) => return Err(format!("error getting participants: {e:?}")),
+        };
+        let remote_bibchips = match socket::get_bibchips(http_client, &api, slug, year) {
+            Ok(bcs) => bcs,
+            Err(e) => return Err(format!("error getting bibchips: {e:?}")),
+        };
+        let mut sq = match self.sqlite.lock() {
+            Ok(sq) => sq,
+            Err(_) => return Err(String::from("unable to get database mutex")),
+        };
+        let local_parts = sq.get_participants().map_err(|e| e.to_string())?;
+        let local_bibchips = sq.get_bibchips().map_err(|e| e.to_string())?;
+        let changes = diff(&local_parts, &local_bibchips, &remote_parts, &remote_bibchips);
+        if changes.is_empty() {
+            return Ok(None)
+        }
+        for bib in changes.remove_participants.iter() {
+            sq.delete_participant(bib).map_err(|e| e.to_string())?;
+        }
+        for bc in changes.remove_bibchips.iter() {
+            sq.delete_bibchip(bc.chip()).map_err(|e| e.to_string())?;
+        }
+        let mut parts = changes.add_participants.clone();
+        parts.extend(changes.change_participants.iter().cloned());
+        if !parts.is_empty() {
+            sq.add_participants(&parts).map_err(|e| e.to_string())?;
+        }
+        if !changes.add_bibchips.is_empty() {
+            sq.add_bibchips(&changes.add_bibchips).map_err(|e| e.to_string())?;
+        }
+        // sightings for anyone whose chips or details changed have to be worked out again
+        sq.reset_reads_status_for(&changes.affected(&local_bibchips)).map_err(|e| e.to_string())?;
+        drop(sq);
+        self.sight_processor.notify();
+        Ok(Some(changes))
+    }
+}
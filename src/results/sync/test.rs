@@ -0,0 +1,83 @@
+use crate::{objects::{bibchip::BibChip, participant::Participant}, processor};
+
+use super::diff;
+
+fn make_participant(bib: &str, first: &str) -> Participant {
+    Participant::new(
+        0,
+        String::from(bib),
+        String::from(first),
+        String::from("Smith"),
+        String::from("1/1/1990"),
+        String::from("F"),
+        String::from("30-39"),
+        String::from("10K"),
+        false,
+    )
+}
+
+fn make_placeholder(chip: &str) -> Participant {
+    Participant::new(
+        0,
+        String::from(chip),
+        String::from(processor::PLACEHOLDER_FIRST),
+        String::from(processor::PLACEHOLDER_LAST),
+        String::from(processor::PLACEHOLDER_BIRTHDATE),
+        String::from(processor::PLACEHOLDER_GENDER),
+        String::from(processor::PLACEHOLDER_AGE_GROUP),
+        String::from(processor::PLACEHOLDER_DISTANCE),
+        false,
+    )
+}
+
+fn make_bibchip(bib: &str, chip: &str) -> BibChip {
+    BibChip::new(String::from(bib), String::from(chip))
+}
+
+#[test]
+fn test_diff_unchanged() {
+    let parts = vec![make_participant("1", "Jane"), make_participant("2", "Ann")];
+    let bibchips = vec![make_bibchip("1", "A1"), make_bibchip("2", "A2")];
+    let changes = diff(&parts, &bibchips, &parts, &bibchips);
+    assert!(changes.is_empty());
+    assert!(changes.affected(&bibchips).is_empty());
+}
+
+#[test]
+fn test_diff_participants() {
+    let local = vec![make_participant("1", "Jane"), make_participant("2", "Ann"), make_participant("3", "Sue")];
+    let remote = vec![make_participant("1", "Jane"), make_participant("2", "Anne"), make_participant("4", "Kim")];
+    let bibchips = vec![make_bibchip("1", "A1"), make_bibchip("2", "A2"), make_bibchip("3", "A3")];
+    let remote_bibchips = vec![make_bibchip("1", "A1"), make_bibchip("2", "A2"), make_bibchip("4", "A4")];
+    let changes = diff(&local, &bibchips, &remote, &remote_bibchips);
+    assert_eq!(vec!["4"], changes.add_participants.iter().map(|p| p.bib()).collect::<Vec<&str>>());
+    assert_eq!(vec!["2"], changes.change_participants.iter().map(|p| p.bib()).collect::<Vec<&str>>());
+    assert_eq!(vec![String::from("3")], changes.remove_participants);
+    assert_eq!(vec!["A4"], changes.add_bibchips.iter().map(|b| b.chip()).collect::<Vec<&str>>());
+    assert_eq!(vec!["A3"], changes.remove_bibchips.iter().map(|b| b.chip()).collect::<Vec<&str>>());
+    assert_eq!(vec!["2", "3", "4", "A2", "A3", "A4"], changes.affected(&bibchips));
+}
+
+#[test]
+fn test_diff_chip_swap() {
+    let parts = vec![make_participant("1", "Jane")];
+    let changes = diff(&parts, &[make_bibchip("1", "A1")], &parts, &[make_bibchip("1", "B1")]);
+    assert!(changes.add_participants.is_empty() && changes.change_participants.is_empty() && changes.remove_participants.is_empty());
+    assert_eq!(vec!["B1"], changes.add_bibchips.iter().map(|b| b.chip()).collect::<Vec<&str>>());
+    assert_eq!(vec!["A1"], changes.remove_bibchips.iter().map(|b| b.chip()).collect::<Vec<&str>>());
+    assert_eq!(vec!["1", "A1", "B1"], changes.affected(&[make_bibchip("1", "A1")]));
+}
+
+#[test]
+fn test_diff_placeholders() {
+    // placeholders stay until their chip is given to someone
+    let local = vec![make_placeholder("C1"), make_placeholder("C2")];
+    let bibchips = vec![make_bibchip("C1", "C1"), make_bibchip("C2", "C2")];
+    let remote = vec![make_participant("5", "Lee")];
+    let remote_bibchips = vec![make_bibchip("5", "C1")];
+    let changes = diff(&local, &bibchips, &remote, &remote_bibchips);
+    assert_eq!(vec![String::from("C1")], changes.remove_participants);
+    assert_eq!(vec!["C1"], changes.add_bibchips.iter().map(|b| b.chip()).collect::<Vec<&str>>());
+    assert!(changes.remove_bibchips.is_empty());
+    assert_eq!(vec!["5", "C1"], changes.affected(&bibchips));
+}
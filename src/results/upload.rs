@@ -0,0 +1,174 @@
+use std::{sync::{mpsc::{Receiver, RecvTimeoutError}, Arc, Mutex}, time::{Duration, Instant}};
+
+use serde::Serialize;
+use log::{error, info};
+
+use crate::{control::{socket, Control}, database::{sqlite, Database}, events::{Event, EventBus}, network::{api, connectivity::Connectivity, http::ClientFactory}, objects::sighting, remote::remote_util, results::requests::UploadSighting};
+
+#[cfg(test)]
+pub mod test;
+
+pub const WAKE_SECONDS: u64 = 1;
+pub const BATCH_SIZE: u32 = 100;
+// Limits how long we go without checking for shutdown when there's a big backlog.
+pub const MAX_BATCHES_PER_PASS: usize = 10;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Status {
+    pub enabled: bool,
+    pub api_id: i64,
+    pub slug: String,
+    pub year: String,
+    pub pending: usize,
+    pub uploaded: usize,
+}
+
+pub fn to_upload(sightings: &[sighting::Sighting]) -> Vec<UploadSighting> {
+    sightings.iter().map(|s| {
+        let anonymous = s.participant.anonymous();
+        UploadSighting {
+            participant_id: String::from(s.participant.external_id()),
+            bib: String::from(s.participant.bib()),
+            first: if anonymous { String::new() } else { String::from(s.participant.first()) },
+            last: if anonymous { String::new() } else { String::from(s.participant.last()) },
+            gender: String::from(s.participant.gender()),
+            age_group: String::from(s.participant.age_group()),
+            distance: String::from(s.participant.distance()),
+            anonymous,
+            chip: String::from(s.read.chip()),
+            location: String::from(s.read.reader()),
+            seconds: s.read.seconds(),
+            milliseconds: s.read.milliseconds(),
+        }
+    }).collect()
+}
+
+// Sends the sightings we've processed to the event participants are synced from so the results
+// site shows splits as they happen, even with no timing laptop online.  Each sighting is
+// recorded once the results api accepts it so nothing is sent twice or missed across restarts.
+pub struct SightingUploader {
+    keepalive: Arc<Mutex<bool>>,
+    sqlite: Arc<Mutex<sqlite::SQLite>>,
+    control: Arc<Mutex<Control>>,
+    receiver: Receiver<Arc<Event>>,
+    connectivity: Arc<Connectivity>,
+    http: Arc<ClientFactory>,
+}
+
+impl SightingUploader {
+    pub fn new(
+        keepalive: Arc<Mutex<bool>>,
+        sqlite: Arc<Mutex<sqlite::SQLite>>,
+        control: Arc<Mutex<Control>>,
+        event_bus: &EventBus,
+        connectivity: Arc<Connectivity>,
+        http: Arc<ClientFactory>,
+    ) -> Self {
+        Self {
+            keepalive,
+            sqlite,
+            control,
+            receiver: event_bus.listen(),
+            connectivity,
+            http,
+        }
+    }
+
+    pub fn run(&self) {
+        let mut last_target: Option<(i64, String, String)> = None;
+        // start off checking for anything left over from before we were stopped
+        let mut pending = true;
+        let mut failures: u32 = 0;
+        let mut next_attempt = Instant::now();
+        loop {
+            match self.keepalive.lock() {
+                Ok(ka) if *ka => {},
+                _ => break,
+            }
+            match self.receiver.recv_timeout(Duration::from_secs(WAKE_SECONDS)) {
+                Ok(event) => pending |= matches!(*event, Event::Sightings { .. }),
+                Err(RecvTimeoutError::Timeout) => {},
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            while let Ok(event) = self.receiver.try_recv() {
+                pending |= matches!(*event, Event::Sightings { .. });
+            }
+            let (enabled, target) = match self.control.lock() {
+                Ok(c) => (
+                    c.enable_sighting_upload,
+                    (c.participant_sync_api, c.participant_sync_slug.clone(), c.participant_sync_year.clone()),
+                ),
+                Err(_) => break,
+            };
+            if !enabled || target.1.is_empty() || target.2.is_empty() {
+                last_target = None;
+                continue;
+            }
+            // a new event has its own set of sightings to send
+            if last_target.as_ref() != Some(&target) {
+                pending = true;
+                failures = 0;
+                next_attempt = Instant::now();
+                last_target = Some(target.clone());
+            }
+            if !pending || Instant::now() < next_attempt || !self.connectivity.is_api_reachable(target.0) {
+                continue;
+            }
+            match self.upload(target.0, &target.1, &target.2) {
+                Ok(more) => {
+                    pending = more;
+                    failures = 0;
+                },
+                Err(e) => {
+                    failures += 1;
+                    self.connectivity.recheck();
+                    let delay = remote_util::backoff_with_jitter(failures);
+                    error!("Error uploading sightings for {} {}: {e}. Retrying in {} seconds.", target.1, target.2, delay.as_secs());
+                    next_attempt = Instant::now() + delay;
+                }
+            }
+        }
+        info!("Sighting upload thread stopping.");
+    }
+
+    // Returns whether there are still sightings waiting to be sent.
+    fn upload(&self, api_id: i64, slug: &str, year: &str) -> Result<bool, String> {
+        let api = match self.sqlite.lock() {
+            Ok(sq) => match sq.get_apis() {
+                Ok(apis) => apis.into_iter().find(|a| a.id() == api_id),
+                Err(e) => return Err(e.to_string()),
+            },
+            Err(_) => return Err(String::from("unable to get database mutex")),
+        };
+        let api = match api {
+            Some(a) if a.kind() == api::API_TYPE_CHRONOKEEP_RESULTS || a.kind() == api::API_TYPE_CHRONOKEEP_RESULTS_SELF => a,
+            Some(_) => return Err(String::from("api is not a results api")),
+            None => return Err(String::from("api not found")),
+        };
+        let http_client = self.http.client()?;
+        for _ in 0..MAX_BATCHES_PER_PASS {
+            let sightings = match self.sqlite.lock() {
+                Ok(sq) => sq.get_sightings_to_upload(api_id, slug, year, BATCH_SIZE).map_err(|e| e.to_string())?,
+                Err(_) => return Err(String::from("unable to get database mutex")),
+            };
+            if sightings.is_empty() {
+                return Ok(false)
+            }
+            let count = match socket::upload_sightings(&http_client, &api, slug, year, to_upload(&sightings)) {
+                Ok(count) => count,
+                Err(e) => return Err(format!("{e:?}")),
+            };
+            if count != sightings.len() {
+                return Err(format!("count doesn't match, {count} uploaded, expected {}", sightings.len()))
+            }
+            match self.sqlite.lock() {
+                Ok(mut sq) => sq.save_uploaded_sightings(api_id, slug, year, &sightings).map_err(|e| e.to_string())?,
+                Err(_) => return Err(String::from("unable to get database mutex")),
+            };
+            if sightings.len() < BATCH_SIZE as usize {
+                return Ok(false)
+            }
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,55 @@
+use crate::objects::{participant::Participant, read::{self, Read}, sighting::Sighting};
+
+use super::to_upload;
+
+fn make_sighting(bib: &str, anonymous: bool) -> Sighting {
+    Sighting {
+        participant: Participant::new(
+            1,
+            String::from(bib),
+            String::from("Jane"),
+            String::from("Smith"),
+            String::from("1/1/1990"),
+            String::from("F"),
+            String::from("30-39"),
+            String::from("10K"),
+            anonymous,
+        ).with_registration(format!("p{bib}"), String::from("M")),
+        read: Read::new(
+            7,
+            String::from("A1"),
+            1700000000,
+            250,
+            1700000000,
+            250,
+            1,
+            String::from("Finish"),
+            String::from("-50"),
+            read::READ_STATUS_USED,
+            read::READ_UPLOADED_FALSE,
+        ),
+    }
+}
+
+#[test]
+fn test_to_upload() {
+    let output = to_upload(&[make_sighting("10", false), make_sighting("11", true)]);
+    assert_eq!(2, output.len());
+    assert_eq!("p10", output[0].participant_id);
+    assert_eq!("10", output[0].bib);
+    assert_eq!("Jane", output[0].first);
+    assert_eq!("Smith", output[0].last);
+    assert_eq!("F", output[0].gender);
+    assert_eq!("30-39", output[0].age_group);
+    assert_eq!("10K", output[0].distance);
+    assert!(!output[0].anonymous);
+    assert_eq!("A1", output[0].chip);
+    assert_eq!("Finish", output[0].location);
+    assert_eq!(1700000000, output[0].seconds);
+    assert_eq!(250, output[0].milliseconds);
+    // names stay on the portal for anonymous participants
+    assert_eq!("11", output[1].bib);
+    assert_eq!("", output[1].first);
+    assert_eq!("", output[1].last);
+    assert!(output[1].anonymous);
+}
@@ -1,4 +1,5 @@
 use std::{net::TcpStream, sync::{Arc, Mutex}, thread::{self, JoinHandle}, time::Duration};
+use log::{error, info, warn};
 
 #[cfg(target_os = "linux")]
 use std::time::SystemTime;
@@ -11,7 +12,7 @@ use i2c_character_display::{AdafruitLCDBackpack, LcdDisplayType};
 #[cfg(target_os = "linux")]
 use rppal::{hal, i2c::I2c};
 
-use crate::{control::{socket::{self, CONNECTION_CHANGE_PAUSE, MAX_CONNECTED}, sound::{SoundNotifier, SoundType}, Control, SETTING_AUTO_REMOTE, SETTING_CHIP_TYPE, SETTING_ENABLE_NTFY, SETTING_PLAY_SOUND, SETTING_READ_WINDOW, SETTING_SIGHTING_PERIOD, SETTING_UPLOAD_INTERVAL, SETTING_VOICE, SETTING_VOLUME}, database::{sqlite, Database}, notifier, objects::setting::Setting, processor::{self, SightingsProcessor}, reader::{self, auto_connect, reconnector::Reconnector}, remote::uploader::{self, Status}, sound_board::Voice, types::{TYPE_CHIP_DEC, TYPE_CHIP_HEX}};
+use crate::{control::{socket::{self, CONNECTION_CHANGE_PAUSE}, sound::{SoundNotifier, SoundType}, Control, SETTING_AUTO_REMOTE, SETTING_CHIP_TYPE, SETTING_ENABLE_NTFY, SETTING_PLAY_SOUND, SETTING_READ_WINDOW, SETTING_SIGHTING_PERIOD, SETTING_UPLOAD_INTERVAL, SETTING_VOICE, SETTING_VOLUME}, database::{self, sqlite, Database}, events::{self, EventBus}, network::connectivity, notifier, objects::{setting::Setting, system_event::{self, SystemEvent}}, processor::{self, SightingsProcessor}, reader::{self, auto_connect, reconnector::Reconnector}, remote::uploader::{self, Status}, sound_board::Voice, types::{TYPE_CHIP_DEC, TYPE_CHIP_HEX}};
 
 pub const EMPTY_STRING: &str = "                    ";
 
@@ -21,12 +22,14 @@ pub const READING_MENU: u8 = 2;
 pub const ABOUT_MENU: u8 = 3;
 pub const SHUTDOWN_MENU: u8 = 4;
 pub const STARTUP_MENU: u8 = 5;
+pub const EVENTS_MENU: u8 = 6;
 pub const SCREEN_OFF: u8 = 15;
 
 pub const MAIN_START_READING: u8 = 0;
 pub const MAIN_SETTINGS: u8 = 1;
-pub const MAIN_ABOUT: u8 = 2;
-pub const MAIN_SHUTDOWN: u8 = 3;
+pub const MAIN_EVENTS: u8 = 2;
+pub const MAIN_ABOUT: u8 = 3;
+pub const MAIN_SHUTDOWN: u8 = 4;
 
 pub const SETTINGS_SIGHTING_PERIOD: u8 = 0;
 pub const SETTINGS_READ_WINDOW: u8 = 1;
@@ -39,14 +42,16 @@ pub const SETTINGS_UPLOAD_INTERVAL: u8 = 7;
 pub const SETTINGS_ENABLE_NTFY: u8 = 8;
 pub const SETTINGS_SET_TIME: u8 = 9;
 
+// How many entries from the event log to show on the recent events screen.
+pub const RECENT_EVENTS: u32 = 20;
+
 #[derive(Clone)]
 pub struct CharacterDisplay {
     keepalive: Arc<Mutex<bool>>,
     control: Arc<Mutex<Control>>,
     readers: Arc<Mutex<Vec<reader::Reader>>>,
     sqlite: Arc<Mutex<sqlite::SQLite>>,
-    control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
-    read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+    event_bus: EventBus,
     sight_processor: Arc<SightingsProcessor>,
     button_presses: Arc<Mutex<Vec<ButtonPress>>>,
     ac_state: Arc<Mutex<auto_connect::State>>,
@@ -62,8 +67,11 @@ pub struct CharacterDisplay {
 pub struct DisplayInfo {
     title_bar: String,
     reader_info: Vec<String>,
+    // reader and antenna of every antenna that has stopped reading
+    antenna_alerts: Vec<(String, u32)>,
     main_menu: Vec<String>,
     settings_menu: Vec<String>,
+    event_lines: Vec<String>,
 }
 
 pub enum ButtonPress {
@@ -80,8 +88,7 @@ impl CharacterDisplay {
         control: Arc<Mutex<Control>>,
         readers: Arc<Mutex<Vec<reader::Reader>>>,
         sqlite: Arc<Mutex<sqlite::SQLite>>,
-        control_sockets: Arc<Mutex<[Option<TcpStream>;MAX_CONNECTED + 1]>>,
-        read_repeaters: Arc<Mutex<[bool;MAX_CONNECTED]>>,
+        event_bus: EventBus,
         sight_processor: Arc<SightingsProcessor>,
         ac_state: Arc<Mutex<auto_connect::State>>,
         read_saver: Arc<processor::ReadSaver>,
@@ -95,20 +102,22 @@ impl CharacterDisplay {
             control,
             readers,
             sqlite,
-            control_sockets,
-            read_repeaters,
+            event_bus,
             sight_processor,
             button_presses: Arc::new(Mutex::new(Vec::new())),
             info: Arc::new(Mutex::new(DisplayInfo {
                 title_bar: format!("{:<20}", "Chronokeep"),
                 reader_info: Vec::new(),
+                antenna_alerts: Vec::new(),
                 main_menu: vec![
                     " > Start Reading    ".to_string(),
                     "   Settings         ".to_string(),
+                    "   Recent Events    ".to_string(),
                     "   About            ".to_string(),
                     "   Shutdown         ".to_string(),
                 ],
                 settings_menu: Vec::new(),
+                event_lines: Vec::new(),
             })),
             current_menu: [0, 0, 0],
             ac_state,
@@ -142,6 +151,18 @@ impl CharacterDisplay {
         }
     }
 
+    pub fn update_connectivity(&mut self, state: connectivity::State) {
+        if let Ok(mut info) = self.info.lock() {
+            let connectivity_status = match state {
+                connectivity::State::Online => "*",
+                connectivity::State::Degraded => "~",
+                connectivity::State::Offline => "x",
+                connectivity::State::Unknown => " ",
+            };
+            info.title_bar.replace_range(12..13, connectivity_status);
+        }
+    }
+
     pub fn update_battery(&mut self) {
         if let Ok(mut info) = self.info.lock() {
             if let Ok(control) = self.control.lock() {
@@ -158,6 +179,13 @@ impl CharacterDisplay {
         }
     }
 
+    pub fn update_antenna_alerts(&mut self, alerts: Vec<(String, u32)>) {
+        if let Ok(mut info) = self.info.lock() {
+            info.antenna_alerts = alerts;
+        }
+        self.update_readers();
+    }
+
     pub fn update_readers(&mut self) {
         if let Ok(mut info) = self.info.lock() {
             info.reader_info.clear();
@@ -167,18 +195,26 @@ impl CharacterDisplay {
                     if let Some(is_con) = read.is_connected() {
                         if is_con {
                             if let Ok(ants) = read.antennas.lock() {
-                                info.reader_info.push(
-                                    format!("{} {}{}{}{}{}{}{}{}",
-                                        read.nickname(),
-                                        reader::helpers::antenna_status_str(ants[0]),
-                                        reader::helpers::antenna_status_str(ants[1]),
-                                        reader::helpers::antenna_status_str(ants[2]),
-                                        reader::helpers::antenna_status_str(ants[3]),
-                                        reader::helpers::antenna_status_str(ants[4]),
-                                        reader::helpers::antenna_status_str(ants[5]),
-                                        reader::helpers::antenna_status_str(ants[6]),
-                                        reader::helpers::antenna_status_str(ants[7]),
-                                    ));
+                                // antennas that stopped reading get flagged so they stand out
+                                let status = |ix: usize| {
+                                    if info.antenna_alerts.iter().any(|(r, a)| r == read.nickname() && *a == ix as u32 + 1) {
+                                        "!"
+                                    } else {
+                                        reader::helpers::antenna_status_str(ants[ix])
+                                    }
+                                };
+                                let line = format!("{} {}{}{}{}{}{}{}{}",
+                                    read.nickname(),
+                                    status(0),
+                                    status(1),
+                                    status(2),
+                                    status(3),
+                                    status(4),
+                                    status(5),
+                                    status(6),
+                                    status(7),
+                                );
+                                info.reader_info.push(line);
                             }
                         }
                     }
@@ -187,6 +223,26 @@ impl CharacterDisplay {
         }
     }
 
+    pub fn update_events(&mut self) {
+        let query = system_event::EventLogQuery {
+            limit: Some(RECENT_EVENTS),
+            ..Default::default()
+        };
+        let events = match self.sqlite.lock() {
+            Ok(sq) => match sq.get_event_log(&query) {
+                Ok(events) => events,
+                Err(e) => {
+                    error!("Error getting recent events. {e}");
+                    Vec::new()
+                }
+            },
+            Err(_) => Vec::new(),
+        };
+        if let Ok(mut info) = self.info.lock() {
+            info.event_lines = events.iter().map(event_line).collect();
+        }
+    }
+
     pub fn update_menu(&mut self) {
         if let Ok(mut info) = self.info.lock() {
             match self.current_menu[0] {
@@ -242,14 +298,14 @@ impl CharacterDisplay {
     }
 
     pub fn run(&mut self, bus: u8) {
-        println!("Screen bus set to {bus}");
+        info!("Screen bus set to {bus}");
         #[cfg(target_os = "linux")]
-        println!("Attempting to connect to screen on i2c{bus}.");
+        info!("Attempting to connect to screen on i2c{bus}.");
         #[cfg(target_os = "linux")]
         let i2c_res = I2c::with_bus(bus);
         #[cfg(target_os = "linux")]
         if let Err(ref e) = i2c_res {
-            println!("Error connecting to screen on bus {bus}. {e}");
+            error!("Error connecting to screen on bus {bus}. {e}");
         }
         #[cfg(target_os = "linux")]
         let i2c = i2c_res.unwrap();
@@ -259,18 +315,18 @@ impl CharacterDisplay {
         let mut lcd = AdafruitLCDBackpack::new(i2c, LcdDisplayType::Lcd20x4, delay);
         #[cfg(target_os = "linux")]
         {
-            println!("Initializing the lcd.");
+            info!("Initializing the lcd.");
             if let Err(e) = lcd.init() {
-                println!("Error initializing lcd. {e}");
+                error!("Error initializing lcd. {e}");
             }
             if let Err(e) = lcd.backlight(true) {
-                println!("Error setting lcd backlight. {e}");
+                error!("Error setting lcd backlight. {e}");
             }
             if let Err(e) = lcd.clear() {
-                println!("Error clearing lcd. {e}");
+                error!("Error clearing lcd. {e}");
             }
             if let Err(e) = lcd.home() {
-                println!("Error homing cursor. {e}");
+                error!("Error homing cursor. {e}");
             }
             if let Ok(mut info) = self.info.lock() {
                 let sys_time = SystemTime::now();
@@ -288,7 +344,7 @@ impl CharacterDisplay {
         loop {
             if let Ok(keepalive) = self.keepalive.try_lock() {
                 if *keepalive == false {
-                    println!("LCD thread stopping.");
+                    info!("LCD thread stopping.");
                     break;
                 }
             }
@@ -304,6 +360,17 @@ impl CharacterDisplay {
                                         self.current_menu[1] = MAIN_SHUTDOWN;
                                     }
                                 }
+                                EVENTS_MENU => {
+                                    if let Ok(info) = self.info.lock() {
+                                        if info.event_lines.len() > 3 {
+                                            if self.current_menu[1] > 0 {
+                                                self.current_menu[1] -= 1;
+                                            } else {
+                                                self.current_menu[1] = (info.event_lines.len() - 1) as u8;
+                                            }
+                                        }
+                                    }
+                                }
                                 READING_MENU => {
                                     if let Ok(info) = self.info.lock() {
                                         if info.reader_info.len() > 3 {
@@ -344,6 +411,17 @@ impl CharacterDisplay {
                                         self.current_menu[1] = MAIN_START_READING;
                                     }
                                 },
+                                EVENTS_MENU => {
+                                    if let Ok(info) = self.info.lock() {
+                                        if info.event_lines.len() > 3 {
+                                            if self.current_menu[1] < (info.event_lines.len() - 1) as u8 {
+                                                self.current_menu[1] += 1;
+                                            } else {
+                                                self.current_menu[1] = 0;
+                                            }
+                                        }
+                                    }
+                                }
                                 READING_MENU => {
                                     if let Ok(info) = self.info.lock() {
                                         if info.reader_info.len() > 3 {
@@ -385,7 +463,7 @@ impl CharacterDisplay {
                                                     if let Ok(sq) = self.sqlite.lock() {
                                                         control.sighting_period -= 30;
                                                         if let Err(e) = sq.set_setting(&Setting::new(SETTING_SIGHTING_PERIOD.to_string(), control.sighting_period.to_string())) {
-                                                            println!("Error saving setting: {e}");
+                                                            error!("Error saving setting: {e}");
                                                         }
                                                     }
                                                 }
@@ -395,7 +473,7 @@ impl CharacterDisplay {
                                                     if let Ok(sq) = self.sqlite.lock() {
                                                         control.read_window -= 1;
                                                         if let Err(e) = sq.set_setting(&Setting::new(SETTING_READ_WINDOW.to_string(), control.read_window.to_string())) {
-                                                            println!("Error saving setting: {e}");
+                                                            error!("Error saving setting: {e}");
                                                         }
                                                     }
                                                 }
@@ -408,7 +486,7 @@ impl CharacterDisplay {
                                                         control.chip_type = TYPE_CHIP_HEX.to_string();
                                                     }
                                                     if let Err(e) = sq.set_setting(&Setting::new(SETTING_CHIP_TYPE.to_string(), control.chip_type.to_string())) {
-                                                        println!("Error saving setting: {e}");
+                                                        error!("Error saving setting: {e}");
                                                     }
                                                 }
                                             }
@@ -416,7 +494,7 @@ impl CharacterDisplay {
                                                 if let Ok(sq) = self.sqlite.lock() {
                                                     control.play_sound = !control.play_sound;
                                                     if let Err(e) = sq.set_setting(&Setting::new(SETTING_PLAY_SOUND.to_string(), control.play_sound.to_string())) {
-                                                        println!("Error saving setting: {e}");
+                                                        error!("Error saving setting: {e}");
                                                     }
                                                 }
                                             }
@@ -425,7 +503,7 @@ impl CharacterDisplay {
                                                     if let Ok(sq) = self.sqlite.lock() {
                                                         control.volume -= 0.1;
                                                         if let Err(e) = sq.set_setting(&Setting::new(SETTING_VOLUME.to_string(), control.volume.to_string())) {
-                                                            println!("Error saving setting: {e}");
+                                                            error!("Error saving setting: {e}");
                                                         }
                                                     }
                                                 }
@@ -435,7 +513,7 @@ impl CharacterDisplay {
                                                     match control.sound_board.get_voice() {
                                                         Voice::Emily => {
                                                             if let Err(_) = control.sound_board.change_voice(Voice::Custom) {
-                                                                println!("Error changing voice to Custom");
+                                                                error!("Error changing voice to Custom");
                                                                 control.sound_board.play_custom_not_available(control.volume);
                                                             } else {
                                                                 control.sound_board.play_introduction(control.volume);
@@ -443,21 +521,21 @@ impl CharacterDisplay {
                                                         },
                                                         Voice::Michael => {
                                                             if let Err(_) = control.sound_board.change_voice(Voice::Emily) {
-                                                                println!("Error changing voice to Emily");
+                                                                error!("Error changing voice to Emily");
                                                             } else {
                                                                 control.sound_board.play_introduction(control.volume);
                                                             }
                                                         },
                                                         Voice::Custom => {
                                                             if let Err(_) = control.sound_board.change_voice(Voice::Michael) {
-                                                                println!("Error changing voice to Michael");
+                                                                error!("Error changing voice to Michael");
                                                             } else {
                                                                 control.sound_board.play_introduction(control.volume);
                                                             }
                                                         },
                                                     }
                                                     if let Err(e) = sq.set_setting(&Setting::new(SETTING_VOICE.to_string(), control.sound_board.get_voice().as_str().to_string())) {
-                                                        println!("Error saving setting: {e}");
+                                                        error!("Error saving setting: {e}");
                                                     }
                                                 }
                                             }
@@ -465,7 +543,7 @@ impl CharacterDisplay {
                                                 if let Ok(sq) = self.sqlite.lock() {
                                                     control.auto_remote = !control.auto_remote;
                                                     if let Err(e) = sq.set_setting(&Setting::new(SETTING_AUTO_REMOTE.to_string(), control.auto_remote.to_string())) {
-                                                        println!("Error saving setting: {e}");
+                                                        error!("Error saving setting: {e}");
                                                     }
                                                 }
                                             }
@@ -474,7 +552,7 @@ impl CharacterDisplay {
                                                     if let Ok(sq) = self.sqlite.lock() {
                                                         control.upload_interval -= 1;
                                                         if let Err(e) = sq.set_setting(&Setting::new(SETTING_UPLOAD_INTERVAL.to_string(), control.upload_interval.to_string())) {
-                                                            println!("Error saving setting: {e}");
+                                                            error!("Error saving setting: {e}");
                                                         }
                                                     }
                                                 }
@@ -483,7 +561,7 @@ impl CharacterDisplay {
                                                 if let Ok(sq) = self.sqlite.lock() {
                                                     control.enable_ntfy = !control.enable_ntfy;
                                                     if let Err(e) = sq.set_setting(&Setting::new(SETTING_ENABLE_NTFY.to_string(), control.enable_ntfy.to_string())) {
-                                                        println!("Error saving setting: {e}");
+                                                        error!("Error saving setting: {e}");
                                                     }
                                                 }
                                             }
@@ -497,6 +575,11 @@ impl CharacterDisplay {
                                     self.current_menu[1] = MAIN_START_READING;
                                     self.update_menu();
                                 },
+                                EVENTS_MENU => {
+                                    self.current_menu[0] = MAIN_MENU;
+                                    self.current_menu[1] = MAIN_EVENTS;
+                                    self.update_menu();
+                                },
                                 SHUTDOWN_MENU => {
                                     self.current_menu[1] = (self.current_menu[1] + 1) % 2;
                                 },
@@ -527,15 +610,13 @@ impl CharacterDisplay {
                                                             for ix in (0..u_readers.len()).rev() {
                                                                 let mut reader = u_readers.remove(ix);
                                                                 if reader.is_connected() != Some(true) {
-                                                                    reader.set_control_sockets(self.control_sockets.clone());
+                                                                    reader.set_event_bus(self.event_bus.clone());
                                                                     reader.set_readers(self.readers.clone());
-                                                                    reader.set_read_repeaters(self.read_repeaters.clone());
                                                                     reader.set_sight_processor(self.sight_processor.clone());
                                                                     let reconnector = Reconnector::new(
                                                                         self.readers.clone(),
                                                                         self.joiners.clone(),
-                                                                        self.control_sockets.clone(),
-                                                                        self.read_repeaters.clone(),
+                                                                        self.event_bus.clone(),
                                                                         self.sight_processor.clone(),
                                                                         self.control.clone(),
                                                                         self.sqlite.clone(),
@@ -559,7 +640,7 @@ impl CharacterDisplay {
                                                                             }
                                                                         },
                                                                         Err(e) => {
-                                                                            println!("Error connecting to reader: {e}");
+                                                                            error!("Error connecting to reader: {e}");
                                                                         }
                                                                     }
                                                                 }
@@ -568,14 +649,14 @@ impl CharacterDisplay {
                                                         }
                                                     }
                                                     _ => {
-                                                        println!("Auto connect is working right now.");
+                                                        info!("Auto connect is working right now.");
                                                         self.sound.notify_custom(SoundType::StartupInProgress);
                                                         self.current_menu[0] = STARTUP_MENU;
                                                         self.current_menu[1] = 0;
                                                     }
                                                 }
                                             } else {
-                                                println!("Auto connect is working right now.");
+                                                info!("Auto connect is working right now.");
                                                 self.sound.notify_custom(SoundType::StartupInProgress);
                                                 self.current_menu[0] = STARTUP_MENU;
                                                 self.current_menu[1] = 0;
@@ -587,6 +668,11 @@ impl CharacterDisplay {
                                             self.update_settings();
                                             self.update_menu();
                                         }
+                                        MAIN_EVENTS => { // Recent Events
+                                            self.current_menu[0] = EVENTS_MENU;
+                                            self.current_menu[1] = 0;
+                                            self.update_events();
+                                        },
                                         MAIN_ABOUT => { // About
                                             self.current_menu[0] = ABOUT_MENU;
                                             self.current_menu[1] = 0;
@@ -620,7 +706,7 @@ impl CharacterDisplay {
                                                                 match reader.stop() {
                                                                     Ok(_) => {},
                                                                     Err(e) => {
-                                                                        println!("Error stopping reader: {e}");
+                                                                        error!("Error stopping reader: {e}");
                                                                     }
                                                                 }
                                                             }
@@ -628,7 +714,7 @@ impl CharacterDisplay {
                                                                 match reader.disconnect() {
                                                                     Ok(_) => {},
                                                                     Err(e) => {
-                                                                        println!("Error disconnecting reader: {e}");
+                                                                        error!("Error disconnecting reader: {e}");
                                                                     }
                                                                 }
                                                             }
@@ -637,14 +723,14 @@ impl CharacterDisplay {
                                                     }
                                                 },
                                                 _ => {
-                                                    println!("Auto connect is working right now.");
+                                                    info!("Auto connect is working right now.");
                                                     self.sound.notify_custom(SoundType::StartupInProgress);
                                                     self.current_menu[0] = STARTUP_MENU;
                                                     self.current_menu[1] = 0;
                                                 },
                                             }
                                         } else {
-                                            println!("Auto connect is working right now.");
+                                            info!("Auto connect is working right now.");
                                             self.sound.notify_custom(SoundType::StartupInProgress);
                                             self.current_menu[0] = STARTUP_MENU;
                                             self.current_menu[1] = 0;
@@ -659,7 +745,7 @@ impl CharacterDisplay {
                                                     if let Ok(sq) = self.sqlite.lock() {
                                                         control.sighting_period += 30;
                                                         if let Err(e) = sq.set_setting(&Setting::new(SETTING_SIGHTING_PERIOD.to_string(), control.sighting_period.to_string())) {
-                                                            println!("Error saving setting: {e}");
+                                                            error!("Error saving setting: {e}");
                                                         }
                                                     }
                                                 }
@@ -669,7 +755,7 @@ impl CharacterDisplay {
                                                     if let Ok(sq) = self.sqlite.lock() {
                                                         control.read_window += 1;
                                                         if let Err(e) = sq.set_setting(&Setting::new(SETTING_READ_WINDOW.to_string(), control.read_window.to_string())) {
-                                                            println!("Error saving setting: {e}");
+                                                            error!("Error saving setting: {e}");
                                                         }
                                                     }
                                                 }
@@ -682,7 +768,7 @@ impl CharacterDisplay {
                                                         control.chip_type = TYPE_CHIP_HEX.to_string();
                                                     }
                                                     if let Err(e) = sq.set_setting(&Setting::new(SETTING_CHIP_TYPE.to_string(), control.chip_type.to_string())) {
-                                                        println!("Error saving setting: {e}");
+                                                        error!("Error saving setting: {e}");
                                                     }
                                                 }
                                             }
@@ -690,7 +776,7 @@ impl CharacterDisplay {
                                                 if let Ok(sq) = self.sqlite.lock() {
                                                     control.play_sound = !control.play_sound;
                                                     if let Err(e) = sq.set_setting(&Setting::new(SETTING_PLAY_SOUND.to_string(), control.play_sound.to_string())) {
-                                                        println!("Error saving setting: {e}");
+                                                        error!("Error saving setting: {e}");
                                                     }
                                                 }
                                             }
@@ -699,7 +785,7 @@ impl CharacterDisplay {
                                                     if let Ok(sq) = self.sqlite.lock() {
                                                         control.volume += 0.1;
                                                         if let Err(e) = sq.set_setting(&Setting::new(SETTING_VOLUME.to_string(), control.volume.to_string())) {
-                                                            println!("Error saving setting: {e}");
+                                                            error!("Error saving setting: {e}");
                                                         }
                                                     }
                                                 }
@@ -709,14 +795,14 @@ impl CharacterDisplay {
                                                     match control.sound_board.get_voice() {
                                                         Voice::Emily => {
                                                             if let Err(_) = control.sound_board.change_voice(Voice::Michael) {
-                                                                println!("Error changing voice to Michael");
+                                                                error!("Error changing voice to Michael");
                                                             } else {
                                                                 control.sound_board.play_introduction(control.volume);
                                                             }
                                                         },
                                                         Voice::Michael => {
                                                             if let Err(_) = control.sound_board.change_voice(Voice::Custom) {
-                                                                println!("Error changing voice to Custom");
+                                                                error!("Error changing voice to Custom");
                                                                 control.sound_board.play_custom_not_available(control.volume);
                                                             } else {
                                                                 control.sound_board.play_introduction(control.volume);
@@ -724,14 +810,14 @@ impl CharacterDisplay {
                                                         },
                                                         Voice::Custom => {
                                                             if let Err(_) = control.sound_board.change_voice(Voice::Emily) {
-                                                                println!("Error changing voice to Emily");
+                                                                error!("Error changing voice to Emily");
                                                             } else {
                                                                 control.sound_board.play_introduction(control.volume);
                                                             }
                                                         },
                                                     }
                                                     if let Err(e) = sq.set_setting(&Setting::new(SETTING_VOICE.to_string(), control.sound_board.get_voice().as_str().to_string())) {
-                                                        println!("Error saving setting: {e}");
+                                                        error!("Error saving setting: {e}");
                                                     }
                                                 }
                                             }
@@ -739,7 +825,7 @@ impl CharacterDisplay {
                                                 if let Ok(sq) = self.sqlite.lock() {
                                                     control.auto_remote = !control.auto_remote;
                                                     if let Err(e) = sq.set_setting(&Setting::new(SETTING_AUTO_REMOTE.to_string(), control.auto_remote.to_string())) {
-                                                        println!("Error saving setting: {e}");
+                                                        error!("Error saving setting: {e}");
                                                     }
                                                 }
                                             }
@@ -748,7 +834,7 @@ impl CharacterDisplay {
                                                     if let Ok(sq) = self.sqlite.lock() {
                                                         control.upload_interval += 1;
                                                         if let Err(e) = sq.set_setting(&Setting::new(SETTING_UPLOAD_INTERVAL.to_string(), control.upload_interval.to_string())) {
-                                                            println!("Error saving setting: {e}");
+                                                            error!("Error saving setting: {e}");
                                                         }
                                                     }
                                                 }
@@ -757,7 +843,7 @@ impl CharacterDisplay {
                                                 if let Ok(sq) = self.sqlite.lock() {
                                                     control.enable_ntfy = !control.enable_ntfy;
                                                     if let Err(e) = sq.set_setting(&Setting::new(SETTING_ENABLE_NTFY.to_string(), control.enable_ntfy.to_string())) {
-                                                        println!("Error saving setting: {e}");
+                                                        error!("Error saving setting: {e}");
                                                     }
                                                 }
                                             }
@@ -801,15 +887,13 @@ impl CharacterDisplay {
                                                             for ix in (0..u_readers.len()).rev() {
                                                                 let mut reader = u_readers.remove(ix);
                                                                 if reader.is_connected() != Some(true) {
-                                                                    reader.set_control_sockets(self.control_sockets.clone());
+                                                                    reader.set_event_bus(self.event_bus.clone());
                                                                     reader.set_readers(self.readers.clone());
-                                                                    reader.set_read_repeaters(self.read_repeaters.clone());
                                                                     reader.set_sight_processor(self.sight_processor.clone());
                                                                     let reconnector = Reconnector::new(
                                                                         self.readers.clone(),
                                                                         self.joiners.clone(),
-                                                                        self.control_sockets.clone(),
-                                                                        self.read_repeaters.clone(),
+                                                                        self.event_bus.clone(),
                                                                         self.sight_processor.clone(),
                                                                         self.control.clone(),
                                                                         self.sqlite.clone(),
@@ -833,7 +917,7 @@ impl CharacterDisplay {
                                                                             }
                                                                         },
                                                                         Err(e) => {
-                                                                            println!("Error connecting to reader: {e}");
+                                                                            error!("Error connecting to reader: {e}");
                                                                         }
                                                                     }
                                                                     thread::sleep(Duration::from_millis(CONNECTION_CHANGE_PAUSE));
@@ -843,14 +927,14 @@ impl CharacterDisplay {
                                                         }
                                                     }
                                                     _ => {
-                                                        println!("Auto connect is working right now.");
+                                                        info!("Auto connect is working right now.");
                                                         self.sound.notify_custom(SoundType::StartupInProgress);
                                                         self.current_menu[0] = STARTUP_MENU;
                                                         self.current_menu[1] = 0;
                                                     }
                                                 }
                                             } else {
-                                                println!("Auto connect is working right now.");
+                                                info!("Auto connect is working right now.");
                                                 self.sound.notify_custom(SoundType::StartupInProgress);
                                                 self.current_menu[0] = STARTUP_MENU;
                                                 self.current_menu[1] = 0;
@@ -862,6 +946,11 @@ impl CharacterDisplay {
                                             self.update_settings();
                                             self.update_menu();
                                         }
+                                        MAIN_EVENTS => { // Recent Events
+                                            self.current_menu[0] = EVENTS_MENU;
+                                            self.current_menu[1] = 0;
+                                            self.update_events();
+                                        },
                                         MAIN_ABOUT => { // About
                                             self.current_menu[0] = ABOUT_MENU;
                                             self.current_menu[1] = 0;
@@ -884,32 +973,33 @@ impl CharacterDisplay {
                                             let _ = write!(lcd, "{:^20}", "Setting Time . . .");
                                             let _ = write!(lcd, "{:<20}", "");
                                         }
+                                        let previous = chrono::Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string();
                                         match std::process::Command::new("sudo").arg("ntpd").arg(format!("-q")).arg(format!("-g")).arg(format!("--configfile=/etc/ntpsec/ntp-get.conf")).status() {
                                             Ok(_) => {
                                                 match std::process::Command::new("sudo").arg("hwclock").arg("-w").status() {
                                                     Ok(_) => {
+                                                        database::log_event(
+                                                            &self.sqlite,
+                                                            system_event::SEVERITY_WARNING,
+                                                            system_event::SOURCE_SCREEN,
+                                                            system_event::SYSTEM_EVENT_TIME_CHANGED,
+                                                            serde_json::json!({ "from": previous, "to": chrono::Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string() }),
+                                                        );
                                                         self.current_menu[0] = MAIN_MENU;
                                                         self.current_menu[1] = MAIN_START_READING;
                                                         self.update_menu();
                                                         // notify of settings changes
                                                         if let Ok(sq) = self.sqlite.try_lock() {
-                                                            let settings = socket::get_settings(&sq);
-                                                            if let Ok(socks) = self.control_sockets.try_lock() {
-                                                                for sock_opt in &*socks {
-                                                                    if let Some(sock) = sock_opt {
-                                                                        _ = socket::write_settings(&sock, &settings);
-                                                                    }
-                                                                }
-                                                            }
+                                                            self.event_bus.publish(events::Event::Settings(socket::get_settings(&sq)));
                                                         }
                                                     },
                                                     Err(e) => {
-                                                        println!("error setting hardware clock: {e}");
+                                                        error!("error setting hardware clock: {e}");
                                                     }
                                                 }
                                             },
                                             Err(e) => {
-                                                println!("error setting time: {e}");
+                                                error!("error setting time: {e}");
                                             }
                                         }
                                     } else {
@@ -918,20 +1008,18 @@ impl CharacterDisplay {
                                         self.update_menu();
                                         // notify of settings changes
                                         if let Ok(sq) = self.sqlite.try_lock() {
-                                            let settings = socket::get_settings(&sq);
-                                            if let Ok(socks) = self.control_sockets.try_lock() {
-                                                for sock_opt in &*socks {
-                                                    if let Some(sock) = sock_opt {
-                                                        _ = socket::write_settings(&sock, &settings);
-                                                    }
-                                                }
-                                            }
+                                            self.event_bus.publish(events::Event::Settings(socket::get_settings(&sq)));
                                         }
                                     }
                                 },
                                 READING_MENU => { // currently reading
                                     self.current_menu[2] = 1; // used to allow readers to stop
                                 },
+                                EVENTS_MENU => {
+                                    self.current_menu[0] = MAIN_MENU;
+                                    self.current_menu[1] = MAIN_EVENTS;
+                                    self.update_menu();
+                                },
                                 ABOUT_MENU | STARTUP_MENU => {
                                     self.current_menu[0] = MAIN_MENU;
                                     self.current_menu[1] = MAIN_START_READING;
@@ -945,8 +1033,15 @@ impl CharacterDisplay {
                                             //let _ = lcd.backlight(false);
                                             let _ = lcd.show_display(false);
                                         }
+                                        database::log_event(
+                                            &self.sqlite,
+                                            system_event::SEVERITY_INFO,
+                                            system_event::SOURCE_SCREEN,
+                                            system_event::SYSTEM_EVENT_SHUTDOWN,
+                                            serde_json::json!({ "reason": "shutdown" }),
+                                        );
                                         if let Ok(mut ka) = self.keepalive.lock() {
-                                            println!("Starting program stop sequence.");
+                                            info!("Starting program stop sequence.");
                                             *ka = false;
                                         }
                                         // play a shutdown command since the shutdown 
@@ -956,20 +1051,20 @@ impl CharacterDisplay {
                                             }
                                         }
                                         // send shutdown command to the OS
-                                        println!("Sending OS shutdown command if on Linux.");
+                                        info!("Sending OS shutdown command if on Linux.");
                                         match std::env::consts::OS {
                                             "linux" => {
                                                 match std::process::Command::new("sudo").arg("shutdown").arg("-h").arg("now").spawn() {
                                                     Ok(_) => {
-                                                        println!("Shutdown command sent to OS successfully.");
+                                                        info!("Shutdown command sent to OS successfully.");
                                                     },
                                                     Err(e) => {
-                                                        println!("Error sending shutdown command: {e}");
+                                                        error!("Error sending shutdown command: {e}");
                                                     }
                                                 }
                                             },
                                             other => {
-                                                println!("Shutdown not supported on this platform ({other})");
+                                                warn!("Shutdown not supported on this platform ({other})");
                                             }
                                         }
                                         // connect to ensure the spawning thread will exit the accept call
@@ -1089,6 +1184,35 @@ impl CharacterDisplay {
                             }
                         }
                     },
+                    EVENTS_MENU => { // recent events, newest first
+                        if let Ok(info) = self.info.lock() {
+                            let lines = &info.event_lines;
+                            match lines.len() {
+                                0 => {
+                                    messages.push(format!("{:^20}", "No events"));
+                                    messages.push(format!("{:^20}", ""));
+                                    messages.push(format!("{:^20}", ""));
+                                },
+                                1 => {
+                                    messages.push(format!("{:^20}", ""));
+                                    messages.push(lines[0].clone());
+                                    messages.push(format!("{:^20}", ""));
+                                },
+                                2 => {
+                                    messages.push(lines[1].clone());
+                                    messages.push(lines[0].clone());
+                                    messages.push(format!("{:^20}", ""));
+                                },
+                                _ => {
+                                    let max_ix = lines.len() - 1;
+                                    let first = (self.current_menu[1] as usize).min(max_ix - 2);
+                                    messages.push(lines[first + 1].clone());
+                                    messages.push(lines[first].clone());
+                                    messages.push(lines[first + 2].clone());
+                                }
+                            }
+                        }
+                    },
                     ABOUT_MENU => { // about menu
                         messages.clear();
                         if let Ok(control) = self.control.try_lock() {
@@ -1140,7 +1264,7 @@ impl CharacterDisplay {
             //let _ = lcd.backlight(false);
             let _ = lcd.show_display(false);
         }
-        println!("LCD thread terminated.");
+        info!("LCD thread terminated.");
     }
 
     pub fn register_button(&self, button: ButtonPress) {
@@ -1148,4 +1272,16 @@ impl CharacterDisplay {
             presses.push(button);
         }
     }
-}
\ No newline at end of file
+}
+
+// Fits an event log entry on one line of the screen as time, severity letter and kind.
+fn event_line(event: &SystemEvent) -> String {
+    use chrono::TimeZone;
+    let time = match chrono::Local.timestamp_opt(event.time(), 0).single() {
+        Some(t) => t.format("%H:%M").to_string(),
+        None => String::from("--:--"),
+    };
+    let severity = event.severity().chars().next().unwrap_or(' ').to_ascii_uppercase();
+    let line = format!("{time} {severity} {}", event.kind());
+    format!("{:<20.20}", line)
+}
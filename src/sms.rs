@@ -0,0 +1,246 @@
+use std::{collections::{HashMap, VecDeque}, sync::{mpsc::{Receiver, RecvTimeoutError}, Arc, Mutex}, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use chrono::{Local, TimeZone};
+use reqwest::blocking::Client;
+use serde::Serialize;
+use log::{error, info, warn};
+
+use crate::{control::Control, database::{sqlite, Database}, events::{Event, EventBus}, network::http::ClientFactory, objects::{participant::Participant, sighting::Sighting, sms_delivery::{self, SmsDelivery}, sms_supporter::SmsSupporter}};
+
+#[cfg(test)]
+pub mod test;
+
+pub const WAKE_SECONDS: u64 = 1;
+pub const HOUR_SECONDS: u64 = 60 * 60;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub url: String,
+    pub token: String,
+    pub from: String,
+}
+
+// What we post to the gateway, most of them take something close to this.
+#[derive(Serialize, Debug)]
+struct Request<'a> {
+    #[serde(skip_serializing_if="str::is_empty")]
+    from: &'a str,
+    to: &'a str,
+    message: &'a str,
+}
+
+pub fn send(http_client: &Client, config: &Config, to: &str, message: &str) -> Result<(), String> {
+    let mut request = http_client.post(config.url.trim()).json(&Request {
+        from: config.from.trim(),
+        to,
+        message,
+    });
+    if !config.token.is_empty() {
+        request = request.bearer_auth(&config.token);
+    }
+    match request.send() {
+        Ok(resp) if resp.status().is_success() => Ok(()),
+        Ok(resp) => Err(format!("unexpected status code {}", resp.status())),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Fills in {first}, {last}, {bib}, {distance}, {portal} and {time}.
+pub fn render(template: &str, participant: &Participant, portal: &str, time: &str) -> String {
+    template
+        .replace("{first}", participant.first())
+        .replace("{last}", participant.last())
+        .replace("{bib}", participant.bib())
+        .replace("{distance}", participant.distance())
+        .replace("{portal}", portal)
+        .replace("{time}", time)
+}
+
+// Everyone who should get a text about the participant, the participant only if they opted in.
+pub fn recipients(participant: &Participant, supporters: &[SmsSupporter]) -> Vec<String> {
+    let mut output: Vec<String> = Vec::new();
+    if participant.sms_enabled() && !participant.mobile().trim().is_empty() {
+        output.push(String::from(participant.mobile().trim()));
+    }
+    for supporter in supporters.iter().filter(|s| s.bib() == participant.bib()) {
+        let mobile = supporter.mobile().trim();
+        if !mobile.is_empty() && !output.iter().any(|m| m == mobile) {
+            output.push(String::from(mobile));
+        }
+    }
+    output
+}
+
+// Keeps a number from getting the same participant more than once per interval, and the whole
+// portal from sending more than the hourly limit.  Zero turns either off.
+#[derive(Default)]
+pub struct Limiter {
+    last_sent: HashMap<(String, String), u64>,
+    sent: VecDeque<u64>,
+}
+
+impl Limiter {
+    pub fn allow(&mut self, bib: &str, mobile: &str, now: u64, interval: u64, hourly_limit: u32) -> bool {
+        while self.sent.front().is_some_and(|t| t + HOUR_SECONDS <= now) {
+            self.sent.pop_front();
+        }
+        if hourly_limit > 0 && self.sent.len() >= hourly_limit as usize {
+            return false
+        }
+        let key = (String::from(bib), String::from(mobile));
+        if let Some(last) = self.last_sent.get(&key) {
+            if interval > 0 && last + interval > now {
+                return false
+            }
+        }
+        self.last_sent.insert(key, now);
+        self.sent.push_back(now);
+        true
+    }
+}
+
+// Texts participants, and anyone following them, when they're seen at this portal.
+pub struct Texter {
+    keepalive: Arc<Mutex<bool>>,
+    control: Arc<Mutex<Control>>,
+    sqlite: Arc<Mutex<sqlite::SQLite>>,
+    receiver: Receiver<Arc<Event>>,
+    http: Arc<ClientFactory>,
+    limiter: Limiter,
+}
+
+impl Texter {
+    pub fn new(
+        keepalive: Arc<Mutex<bool>>,
+        control: Arc<Mutex<Control>>,
+        sqlite: Arc<Mutex<sqlite::SQLite>>,
+        event_bus: &EventBus,
+        http: Arc<ClientFactory>,
+    ) -> Self {
+        Self {
+            keepalive,
+            control,
+            sqlite,
+            receiver: event_bus.listen(),
+            http,
+            limiter: Limiter::default(),
+        }
+    }
+
+    pub fn run(&mut self) {
+        loop {
+            match self.keepalive.lock() {
+                Ok(ka) if *ka => {},
+                _ => break,
+            }
+            let mut sightings: Vec<Sighting> = Vec::new();
+            match self.receiver.recv_timeout(Duration::from_secs(WAKE_SECONDS)) {
+                Ok(event) => add_sightings(&mut sightings, &event),
+                Err(RecvTimeoutError::Timeout) => {},
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            while let Ok(event) = self.receiver.try_recv() {
+                add_sightings(&mut sightings, &event);
+            }
+            if sightings.is_empty() {
+                continue;
+            }
+            let (enabled, config, template, interval, hourly_limit, portal) = match self.control.lock() {
+                Ok(control) => (
+                    control.enable_sms && !control.sms_url.is_empty(),
+                    Config {
+                        url: control.sms_url.clone(),
+                        token: control.sms_token.clone(),
+                        from: control.sms_from.clone(),
+                    },
+                    control.sms_template.clone(),
+                    control.sms_interval,
+                    control.sms_hourly_limit,
+                    control.name.clone(),
+                ),
+                Err(_) => break,
+            };
+            if !enabled {
+                continue;
+            }
+            let supporters = match self.sqlite.lock() {
+                Ok(sq) => match sq.get_sms_supporters() {
+                    Ok(s) => s,
+                    Err(e) => {
+                        error!("Error getting sms supporters. {e}");
+                        continue;
+                    }
+                },
+                Err(_) => break,
+            };
+            let http_client = match self.http.client() {
+                Ok(client) => client,
+                Err(e) => {
+                    warn!("Unable to get http client for texts. {e}");
+                    continue;
+                }
+            };
+            for sighting in sightings.iter() {
+                let time = match Local.timestamp_opt(sighting.read.seconds() as i64, 0).single() {
+                    Some(t) => t.format("%H:%M:%S").to_string(),
+                    None => String::new(),
+                };
+                let message = render(&template, &sighting.participant, &portal, &time);
+                let read_seconds = sighting.read.seconds() as i64;
+                let read_milliseconds = sighting.read.milliseconds();
+                for mobile in recipients(&sighting.participant, &supporters) {
+                    // sightings are worked out again on boot and when participants change, anyone
+                    // who already got a text about this read doesn't need another
+                    let delivered = match self.sqlite.lock() {
+                        Ok(sq) => sq.sms_delivered(sighting.participant.bib(), &mobile, read_seconds, read_milliseconds),
+                        Err(_) => break,
+                    };
+                    match delivered {
+                        Ok(false) => {},
+                        Ok(true) => continue,
+                        Err(e) => {
+                            error!("Error checking sms deliveries. {e}");
+                            continue;
+                        }
+                    }
+                    if !self.limiter.allow(sighting.participant.bib(), &mobile, now(), interval, hourly_limit) {
+                        info!("Not texting about bib {}, one was sent too recently.", sighting.participant.bib());
+                        continue;
+                    }
+                    let (status, err) = match send(&http_client, &config, &mobile, &message) {
+                        Ok(_) => (sms_delivery::SMS_STATUS_SENT, String::new()),
+                        Err(e) => {
+                            error!("Error sending text about bib {}. {e}", sighting.participant.bib());
+                            (sms_delivery::SMS_STATUS_FAILED, e)
+                        }
+                    };
+                    if let Ok(sq) = self.sqlite.lock() {
+                        if let Err(e) = sq.save_sms_delivery(&SmsDelivery::new(
+                            0,
+                            String::from(sighting.participant.bib()),
+                            mobile,
+                            message.clone(),
+                            now() as i64,
+                            String::from(status),
+                            err,
+                        ).with_read(read_seconds, read_milliseconds)) {
+                            error!("Error saving sms delivery. {e}");
+                        }
+                    }
+                }
+            }
+        }
+        info!("SMS thread stopping.");
+    }
+}
+
+// Only sightings from our own readers, relayed ones get texted by the portal that saw them.
+fn add_sightings(sightings: &mut Vec<Sighting>, event: &Event) {
+    if let Event::Sightings { sightings: new, .. } = event {
+        sightings.extend(new.iter().filter(|s| s.read.source().is_empty()).cloned());
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
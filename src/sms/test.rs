@@ -0,0 +1,119 @@
+use std::{io::{Read, Write}, net::TcpListener, thread::{self, JoinHandle}};
+
+use reqwest::blocking::Client;
+
+use crate::objects::{participant::Participant, sms_supporter::SmsSupporter};
+
+use super::{recipients, render, send, Config, Limiter, HOUR_SECONDS};
+
+fn make_participant(sms_enabled: bool, mobile: &str) -> Participant {
+    Participant::new(
+        0,
+        String::from("104"),
+        String::from("Jane"),
+        String::from("Smith"),
+        String::from("1/1/1990"),
+        String::from("F"),
+        String::from("30-39"),
+        String::from("Marathon"),
+        false,
+    ).with_sms(sms_enabled, String::from(mobile))
+}
+
+// A stand in for an sms gateway that answers one request with the status given and hands back
+// what it was sent.
+fn gateway(status: &'static str) -> (String, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/send", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request: Vec<u8> = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            let read = stream.read(&mut buf).unwrap();
+            request.extend_from_slice(&buf[..read]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text.to_lowercase().lines()
+                    .find_map(|l| l.strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + length || read == 0 {
+                    break;
+                }
+            }
+        }
+        stream.write_all(format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").as_bytes()).unwrap();
+        String::from_utf8_lossy(&request).to_string()
+    });
+    (url, handle)
+}
+
+#[test]
+fn test_render() {
+    let participant = make_participant(true, "+15555550100");
+    assert_eq!(
+        "Jane Smith (bib 104) was seen at Finish at 10:42:07.",
+        render(crate::defaults::DEFAULT_SMS_TEMPLATE, &participant, "Finish", "10:42:07")
+    );
+    assert_eq!("Marathon {unknown}", render("{distance} {unknown}", &participant, "Finish", ""));
+}
+
+#[test]
+fn test_recipients() {
+    let supporters = vec![
+        SmsSupporter::new(String::from("104"), String::from("+15555550101")),
+        SmsSupporter::new(String::from("104"), String::from(" +15555550100 ")),
+        SmsSupporter::new(String::from("7"), String::from("+15555550102")),
+    ];
+    assert_eq!(
+        vec![String::from("+15555550100"), String::from("+15555550101")],
+        recipients(&make_participant(true, "+15555550100"), &supporters)
+    );
+    // supporters still get texts when the participant didn't opt in
+    assert_eq!(
+        vec![String::from("+15555550101"), String::from("+15555550100")],
+        recipients(&make_participant(false, "+15555550100"), &supporters)
+    );
+    assert!(recipients(&make_participant(true, " "), &[]).is_empty());
+}
+
+#[test]
+fn test_limiter() {
+    let mut limiter = Limiter::default();
+    assert!(limiter.allow("104", "+15555550100", 1000, 600, 3));
+    assert!(!limiter.allow("104", "+15555550100", 1300, 600, 3));
+    assert!(limiter.allow("104", "+15555550101", 1300, 600, 3));
+    assert!(limiter.allow("104", "+15555550100", 1600, 600, 3));
+    // the hourly limit covers everyone
+    assert!(!limiter.allow("7", "+15555550102", 1700, 600, 3));
+    assert!(limiter.allow("7", "+15555550102", 1000 + HOUR_SECONDS, 600, 3));
+    assert!(limiter.allow("7", "+15555550103", 1000 + HOUR_SECONDS, 0, 0));
+}
+
+#[test]
+fn test_send() {
+    let (url, handle) = gateway("200 OK");
+    let config = Config {
+        url,
+        token: String::from("secret"),
+        from: String::from("+15555550199"),
+    };
+    assert!(send(&Client::new(), &config, "+15555550100", "Jane Smith was seen.").is_ok());
+    let request = handle.join().unwrap();
+    assert!(request.starts_with("POST /send "));
+    assert!(request.to_lowercase().contains("authorization: bearer secret"));
+    assert!(request.contains(r#""from":"+15555550199""#));
+    assert!(request.contains(r#""to":"+15555550100""#));
+    assert!(request.contains(r#""message":"Jane Smith was seen.""#));
+    let (url, handle) = gateway("500 Internal Server Error");
+    let config = Config {
+        url,
+        token: String::new(),
+        from: String::new(),
+    };
+    let result = send(&Client::new(), &config, "+15555550100", "Jane Smith was seen.");
+    let request = handle.join().unwrap();
+    assert!(result.unwrap_err().contains("500"));
+    assert!(!request.to_lowercase().contains("authorization"));
+    assert!(!request.contains(r#""from""#));
+}
@@ -1,7 +1,9 @@
 use std::{fs::File, io::{BufReader, Cursor}, ops::Deref, path::Path, sync::{Arc, Mutex}};
 
 use rand::Rng;
+use rodio::Source;
 use serde::{Deserialize, Serialize};
+use log::warn;
 
 pub const EMILY_START:                  &'static [u8] = include_bytes!("sounds/emily-started.mp3");
 pub const EMILY_SHUTDOWN:               &'static [u8] = include_bytes!("sounds/emily-shutdown.mp3");
@@ -68,6 +70,15 @@ pub const BEEP_RAND_MAX: u64 = 350; //350
 pub const BEEP_SEPARATION: u64 = 50; //50
 pub const BEEP_SEP_RAND_MAX: u64 = 450; //450
 
+// Alternating tones so an alarm can't be mistaken for reads.
+pub const ALARM_FREQUENCIES: [f32; 2] = [880.0, 660.0];
+pub const ALARM_TONE_DURATION: u64 = 250;
+pub const ALARM_REPEATS: usize = 3;
+// A rising chime so a watched bib can't be mistaken for a dead mat.
+pub const WATCHED_BIB_FREQUENCIES: [f32; 3] = [1046.5, 1318.5, 1568.0];
+pub const WATCHED_BIB_TONE_DURATION: u64 = 150;
+pub const WATCHED_BIB_REPEATS: usize = 2;
+
 #[derive(Clone)]
 pub struct SoundBoard {
     current_voice: Arc<Mutex<Voice>>,
@@ -103,7 +114,7 @@ impl SoundBoard {
 
     pub fn change_voice(&mut self, new_voice: Voice) -> Result<(), ()> {
         if new_voice == Voice::Custom && !self.custom_available {
-            println!("Custom voice is not available at this time.");
+            warn!("Custom voice is not available at this time.");
             return Err(())
         }
         if let Ok(mut voice) = self.current_voice.lock() {
@@ -369,6 +380,32 @@ impl SoundBoard {
             }
         }
     }
+
+    pub fn play_alarm(&self, volume: f32) {
+        self.play_tones(volume, &ALARM_FREQUENCIES, ALARM_TONE_DURATION, ALARM_REPEATS);
+    }
+
+    pub fn play_watched_bib(&self, volume: f32) {
+        self.play_tones(volume, &WATCHED_BIB_FREQUENCIES, WATCHED_BIB_TONE_DURATION, WATCHED_BIB_REPEATS);
+    }
+
+    fn play_tones(&self, volume: f32, frequencies: &[f32], duration: u64, repeats: usize) {
+        if let Ok(_voice) = self.current_voice.lock() {
+            if let Ok((_source, source_handle)) = rodio::OutputStream::try_default() {
+                if let Ok(sink) = rodio::Sink::try_new(&source_handle) {
+                    sink.set_volume(volume);
+                    for _ in 0..repeats {
+                        for frequency in frequencies {
+                            let source = rodio::source::SineWave::new(*frequency)
+                                .take_duration(std::time::Duration::from_millis(duration));
+                            sink.append(source);
+                        }
+                    }
+                    sink.sleep_until_end();
+                }
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]